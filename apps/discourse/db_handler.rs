@@ -5,15 +5,23 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use once_cell::sync::OnceCell;
 use proposalsapp_db::models::{
-    discourse_category, discourse_post, discourse_post_like, discourse_post_revision,
-    discourse_topic, discourse_user,
+    dao_discourse, discourse_category, discourse_post, discourse_post_like,
+    discourse_post_revision, discourse_topic, discourse_user,
 };
 use sea_orm::{
-    ActiveValue::NotSet, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait,
-    QueryFilter, Set, TryInsertResult, prelude::Uuid, sea_query::OnConflict,
+    ActiveValue::NotSet,
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, Set, TransactionTrait, TryInsertResult,
+    prelude::{Expr, Uuid},
+    sea_query::{OnConflict, Query},
 };
 use std::time::Duration;
 use tracing::{debug, info, instrument};
+use utils::outbox::{GovernanceEvent, publish};
+
+/// Discourse's `post_type` of regular posts. The others are staff whispers,
+/// moderator actions and small actions, which aren't public content.
+const REGULAR_POST_TYPE: i32 = 1;
 
 // Use a OnceCell for safe, one-time initialization.
 pub static DB: OnceCell<DatabaseConnection> = OnceCell::new();
@@ -145,6 +153,7 @@ pub async fn upsert_user(user: &User, dao_discourse_id: Uuid) -> Result<()> {
 }
 
 /// Inserts or updates a category record in the database.
+/// A change to the category's read restriction is carried down to its topics and posts.
 #[instrument(skip(category), fields(category_id = category.id, category_name = %category.name, dao_discourse_id = %dao_discourse_id))]
 pub async fn upsert_category(category: &Category, dao_discourse_id: Uuid) -> Result<()> {
     let category_model = discourse_category::ActiveModel {
@@ -163,6 +172,7 @@ pub async fn upsert_category(category: &Category, dao_discourse_id: Uuid) -> Res
         topics_year: Set(category.topics_year),
         topics_all_time: Set(category.topics_all_time),
         dao_discourse_id: Set(dao_discourse_id),
+        restricted: Set(category.read_restricted),
         ..Default::default()
     };
 
@@ -184,23 +194,88 @@ pub async fn upsert_category(category: &Category, dao_discourse_id: Uuid) -> Res
         discourse_category::Column::TopicsMonth,
         discourse_category::Column::TopicsYear,
         discourse_category::Column::TopicsAllTime,
+        discourse_category::Column::Restricted,
     ])
     .to_owned();
 
+    let txn = db().begin().await?;
     discourse_category::Entity::insert(category_model)
         .on_conflict(on_conflict)
-        .exec(db())
+        .exec(&txn)
         .await
         .with_context(|| format!("Failed to upsert category with external_id {}", category.id))?;
 
+    // Unlisted topics, whispers and the like stay restricted in public categories
+    let topic_restricted = if category.read_restricted {
+        Expr::value(true)
+    } else {
+        Expr::col(discourse_topic::Column::Visible).not()
+    };
+    let topics = discourse_topic::Entity::update_many()
+        .col_expr(
+            discourse_topic::Column::Restricted,
+            topic_restricted.clone(),
+        )
+        .filter(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .filter(discourse_topic::Column::CategoryId.eq(category.id))
+        .filter(Expr::col(discourse_topic::Column::Restricted).ne(topic_restricted))
+        .exec(&txn)
+        .await
+        .context("Failed to update restriction of category topics")?;
+    let category_topics = |restricted_only: bool| {
+        let mut query = Query::select();
+        query
+            .column(discourse_topic::Column::ExternalId)
+            .from(discourse_topic::Entity)
+            .and_where(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+            .and_where(discourse_topic::Column::CategoryId.eq(category.id));
+        if restricted_only {
+            query.and_where(discourse_topic::Column::Restricted.eq(true));
+        }
+        query
+    };
+    let post_restricted = if category.read_restricted {
+        Expr::value(true)
+    } else {
+        Expr::col(discourse_post::Column::PostType)
+            .ne(REGULAR_POST_TYPE)
+            .or(discourse_post::Column::TopicId.in_subquery(category_topics(true)))
+    };
+    let posts = discourse_post::Entity::update_many()
+        .col_expr(discourse_post::Column::Restricted, post_restricted.clone())
+        .filter(discourse_post::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .filter(discourse_post::Column::TopicId.in_subquery(category_topics(false)))
+        .filter(Expr::col(discourse_post::Column::Restricted).ne(post_restricted))
+        .exec(&txn)
+        .await
+        .context("Failed to update restriction of category posts")?;
+    txn.commit().await?;
+
+    if topics.rows_affected > 0 || posts.rows_affected > 0 {
+        info!(
+            restricted = category.read_restricted,
+            topics = topics.rows_affected,
+            posts = posts.rows_affected,
+            "Category restriction changed"
+        );
+    }
+
     debug!("Category upserted successfully.");
     Ok(())
 }
 
-/// Inserts or updates a topic record.
+/// Inserts or updates a topic record, publishing `topic_created` for new topics.
+/// Topics take the read restriction of their category, and pass it on to their posts;
+/// restricted topics publish nothing. Unlisted topics are restricted, and so
+/// are topics of unknown categories on `authenticated` forums, which return
+/// what anonymous visitors can't see.
 #[instrument(skip(topic), fields(topic_id = topic.id, topic_title = %topic.title, dao_discourse_id = %dao_discourse_id))]
-pub async fn upsert_topic(topic: &Topic, dao_discourse_id: Uuid) -> Result<()> {
-    let topic_model = discourse_topic::ActiveModel {
+pub async fn upsert_topic(
+    topic: &Topic,
+    dao_discourse_id: Uuid,
+    authenticated: bool,
+) -> Result<()> {
+    let mut topic_model = discourse_topic::ActiveModel {
         external_id: Set(topic.id),
         title: Set(topic.title.clone()),
         fancy_title: Set(topic.fancy_title.clone()),
@@ -244,22 +319,79 @@ pub async fn upsert_topic(topic: &Topic, dao_discourse_id: Uuid) -> Result<()> {
         discourse_topic::Column::LikeCount,
         discourse_topic::Column::CategoryId,
         discourse_topic::Column::PinnedGlobally,
+        discourse_topic::Column::Restricted,
     ])
     .to_owned();
 
-    discourse_topic::Entity::insert(topic_model)
+    let txn = db().begin().await?;
+    let existing = discourse_topic::Entity::find()
+        .filter(discourse_topic::Column::ExternalId.eq(topic.id))
+        .filter(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .one(&txn)
+        .await
+        .context("Failed to fetch existing topic")?;
+    let restricted = !topic.visible
+        || discourse_category::Entity::find()
+            .filter(discourse_category::Column::ExternalId.eq(topic.category_id))
+            .filter(discourse_category::Column::DaoDiscourseId.eq(dao_discourse_id))
+            .one(&txn)
+            .await
+            .context("Failed to fetch topic category")?
+            .map_or(authenticated, |category| category.restricted);
+    topic_model.restricted = Set(restricted);
+
+    let stored = discourse_topic::Entity::insert(topic_model)
         .on_conflict(on_conflict)
-        .exec(db())
+        .exec_with_returning(&txn)
         .await
         .with_context(|| format!("Failed to upsert topic with external_id {}", topic.id))?;
 
+    // Posts can be stored before their topic, from the latest posts feed
+    if existing
+        .as_ref()
+        .is_none_or(|existing| existing.restricted != restricted)
+    {
+        let post_restricted = if restricted {
+            Expr::value(true)
+        } else {
+            Expr::col(discourse_post::Column::PostType).ne(REGULAR_POST_TYPE)
+        };
+        discourse_post::Entity::update_many()
+            .col_expr(discourse_post::Column::Restricted, post_restricted.clone())
+            .filter(discourse_post::Column::DaoDiscourseId.eq(dao_discourse_id))
+            .filter(discourse_post::Column::TopicId.eq(topic.id))
+            .filter(Expr::col(discourse_post::Column::Restricted).ne(post_restricted))
+            .exec(&txn)
+            .await
+            .context("Failed to update restriction of topic posts")?;
+    }
+
+    if existing.is_none() && !restricted {
+        let dao_id = forum_dao_id(&txn, dao_discourse_id).await?;
+        publish(
+            &txn,
+            dao_id,
+            [GovernanceEvent::TopicCreated {
+                topic_id: stored.id,
+                dao_discourse_id,
+                external_id: stored.external_id,
+            }],
+        )
+        .await?;
+    }
+    txn.commit().await?;
+
     debug!(topic_id = topic.id, "Topic upserted successfully.");
     Ok(())
 }
 
 /// Inserts or updates a post record. Handles potential deletion flags based on raw content.
+/// Posts stored with a higher version than before are published as `post_edited`,
+/// unless they're restricted: not regular posts, like staff whispers, or in a
+/// restricted topic. Posts of unknown topics on `authenticated` forums are
+/// restricted until their topic is stored.
 #[instrument(skip(post), fields(post_id = post.id, post_username = %post.username, dao_discourse_id = %dao_discourse_id))]
-pub async fn upsert_post(post: &Post, dao_discourse_id: Uuid) -> Result<()> {
+pub async fn upsert_post(post: &Post, dao_discourse_id: Uuid, authenticated: bool) -> Result<()> {
     // Determine if the post is considered deleted based on specific raw content patterns.
     let mut is_deleted = post.raw.as_ref().is_some_and(|raw| {
         raw == "(post deleted by author)"
@@ -284,7 +416,7 @@ pub async fn upsert_post(post: &Post, dao_discourse_id: Uuid) -> Result<()> {
         }
     };
 
-    let post_model = discourse_post::ActiveModel {
+    let mut post_model = discourse_post::ActiveModel {
         external_id: Set(post.id),
         name: Set(post.name.clone()),
         username: Set(post.username.clone()),
@@ -347,19 +479,64 @@ pub async fn upsert_post(post: &Post, dao_discourse_id: Uuid) -> Result<()> {
         discourse_post::Column::UserId,
         discourse_post::Column::CanViewEditHistory,
         discourse_post::Column::Deleted,
+        discourse_post::Column::Restricted,
     ])
     .to_owned();
 
-    discourse_post::Entity::insert(post_model)
+    let txn = db().begin().await?;
+    let existing = discourse_post::Entity::find()
+        .filter(discourse_post::Column::ExternalId.eq(post.id))
+        .filter(discourse_post::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .one(&txn)
+        .await
+        .context("Failed to fetch existing post")?;
+    let restricted = post.post_type != REGULAR_POST_TYPE
+        || discourse_topic::Entity::find()
+            .filter(discourse_topic::Column::ExternalId.eq(post.topic_id))
+            .filter(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+            .one(&txn)
+            .await
+            .context("Failed to fetch post topic")?
+            .map_or(authenticated, |topic| topic.restricted);
+    post_model.restricted = Set(restricted);
+
+    let stored = discourse_post::Entity::insert(post_model)
         .on_conflict(on_conflict)
-        .exec(db())
+        .exec_with_returning(&txn)
         .await
         .with_context(|| format!("Failed to upsert post with external_id {}", post.id))?;
 
+    if !restricted && existing.is_some_and(|existing| existing.version < stored.version) {
+        let dao_id = forum_dao_id(&txn, dao_discourse_id).await?;
+        publish(
+            &txn,
+            dao_id,
+            [GovernanceEvent::PostEdited {
+                post_id: stored.id,
+                dao_discourse_id,
+                external_id: stored.external_id,
+                topic_external_id: stored.topic_id,
+                version: stored.version,
+            }],
+        )
+        .await?;
+    }
+    txn.commit().await?;
+
     debug!("Post upserted successfully.");
     Ok(())
 }
 
+/// The DAO a forum belongs to, which its events are published under.
+async fn forum_dao_id<C: ConnectionTrait>(conn: &C, dao_discourse_id: Uuid) -> Result<Uuid> {
+    dao_discourse::Entity::find_by_id(dao_discourse_id)
+        .one(conn)
+        .await
+        .context("Failed to fetch DAO forum")?
+        .map(|forum| forum.dao_id)
+        .with_context(|| format!("DAO forum {dao_discourse_id} not found"))
+}
+
 /// Inserts or updates a post revision record.
 #[instrument(skip(revision), fields(revision_version = revision.current_version, post_id = revision.post_id, dao_discourse_id = %dao_discourse_id, discourse_post_id = %discourse_post_id))]
 pub async fn upsert_revision(
@@ -525,3 +702,35 @@ pub async fn get_post_like_count(post_external_id: i32, dao_discourse_id: Uuid)
     );
     Ok(count)
 }
+
+/// Fetches the newest post id taken from the forum's latest posts feed, if any.
+#[instrument(fields(dao_discourse_id = %dao_discourse_id))]
+pub async fn get_post_high_water_mark(dao_discourse_id: Uuid) -> Result<Option<i32>> {
+    let forum = dao_discourse::Entity::find_by_id(dao_discourse_id)
+        .one(db())
+        .await
+        .context("Failed to fetch DAO forum")?
+        .with_context(|| format!("DAO forum {dao_discourse_id} not found"))?;
+
+    Ok(forum.post_high_water_id)
+}
+
+/// Moves the forum's post high-water mark up to `post_id`. Never moves it back,
+/// so overlapping runs can't make the next run re-read the feed.
+#[instrument(fields(dao_discourse_id = %dao_discourse_id, post_id = post_id))]
+pub async fn advance_post_high_water_mark(dao_discourse_id: Uuid, post_id: i32) -> Result<()> {
+    dao_discourse::Entity::update_many()
+        .col_expr(dao_discourse::Column::PostHighWaterId, Expr::value(post_id))
+        .filter(dao_discourse::Column::Id.eq(dao_discourse_id))
+        .filter(
+            Condition::any()
+                .add(dao_discourse::Column::PostHighWaterId.is_null())
+                .add(dao_discourse::Column::PostHighWaterId.lt(post_id)),
+        )
+        .exec(db())
+        .await
+        .context("Failed to advance post high-water mark")?;
+
+    debug!(post_id, "Post high-water mark advanced");
+    Ok(())
+}
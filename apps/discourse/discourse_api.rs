@@ -10,6 +10,7 @@ use reqwest::{
 use serde::de::DeserializeOwned;
 use std::{
     collections::{HashMap, VecDeque},
+    fmt,
     sync::{Arc, Mutex},
     time::{Duration, Instant, SystemTime},
 };
@@ -27,6 +28,9 @@ const MAX_BACKOFF: Duration = Duration::from_secs(60); // Cap backoff to prevent
 const FORBIDDEN_CACHE_DURATION: Duration = Duration::from_secs(3600); // 1 hour
 const NORMAL_JOBS_BATCH_SIZE: usize = 10; // Process normal jobs in batches
 const WORKER_YIELD_DURATION: Duration = Duration::from_millis(10); // Small sleep to yield control
+// Discourse's default per-key limits for authenticated requests
+const ADMIN_API_REQUESTS_PER_MINUTE: u32 = 60;
+const USER_API_REQUESTS_PER_MINUTE: u32 = 20;
 
 const USER_AGENTS: [&str; 5] = [
     "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/114.0.0.0 Safari/537.36",
@@ -44,6 +48,184 @@ static RE_UPLOAD_URL: Lazy<Regex> = Lazy::new(|| {
 
 // --- Structs ---
 
+/// Credentials for reading a forum as a Discourse user instead of anonymously,
+/// which opens up read-restricted categories and per-key rate limits.
+#[derive(Clone, PartialEq, Eq)]
+pub enum DiscourseCredentials {
+    /// An admin API key, acting as `username`.
+    ApiKey { key: String, username: String },
+    /// A user API key, limited to what its user can read.
+    UserApiKey { key: String },
+}
+
+impl fmt::Debug for DiscourseCredentials {
+    // Keys stay out of logs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ApiKey { username, .. } => f
+                .debug_struct("ApiKey")
+                .field("username", username)
+                .finish_non_exhaustive(),
+            Self::UserApiKey { .. } => f.debug_struct("UserApiKey").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl DiscourseCredentials {
+    /// Loads the credentials for the forum of `dao_slug`, if it has any.
+    ///
+    /// Reads `DISCOURSE_API_KEY_<SLUG>` with `DISCOURSE_API_USERNAME_<SLUG>`, or
+    /// `DISCOURSE_USER_API_KEY_<SLUG>`. Each can also be read from a mounted secret
+    /// named by the same variable with a `_FILE` suffix.
+    pub fn from_env(dao_slug: &str) -> Result<Option<Self>> {
+        Self::from_lookup(dao_slug, env_secret)
+    }
+
+    fn from_lookup(
+        dao_slug: &str,
+        lookup: impl Fn(&str) -> Result<Option<String>>,
+    ) -> Result<Option<Self>> {
+        let suffix = env_suffix(dao_slug);
+        let api_key = lookup(&format!("DISCOURSE_API_KEY_{suffix}"))?;
+        let username = lookup(&format!("DISCOURSE_API_USERNAME_{suffix}"))?;
+        let user_api_key = lookup(&format!("DISCOURSE_USER_API_KEY_{suffix}"))?;
+
+        let credentials = match (api_key, username, user_api_key) {
+            (None, None, None) => return Ok(None),
+            (Some(key), Some(username), None) => Self::ApiKey { key, username },
+            (None, None, Some(key)) => Self::UserApiKey { key },
+            (Some(_), None, None) => {
+                return Err(anyhow!(
+                    "DISCOURSE_API_KEY_{suffix} is set without DISCOURSE_API_USERNAME_{suffix}"
+                ));
+            }
+            (None, Some(_), None) => {
+                return Err(anyhow!(
+                    "DISCOURSE_API_USERNAME_{suffix} is set without DISCOURSE_API_KEY_{suffix}"
+                ));
+            }
+            (_, _, Some(_)) => {
+                return Err(anyhow!(
+                    "Set either DISCOURSE_API_KEY_{suffix} or DISCOURSE_USER_API_KEY_{suffix}, not both"
+                ));
+            }
+        };
+        Ok(Some(credentials))
+    }
+
+    /// The headers that authenticate a request, marked sensitive.
+    fn headers(&self) -> Result<HeaderMap> {
+        let fields = match self {
+            Self::ApiKey { key, username } => {
+                vec![
+                    ("Api-Key", key.as_str()),
+                    ("Api-Username", username.as_str()),
+                ]
+            }
+            Self::UserApiKey { key } => vec![("User-Api-Key", key.as_str())],
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in fields {
+            let mut value = HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid {name} header value"))?;
+            value.set_sensitive(true);
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+}
+
+/// Pacing and retry settings for the requests to one forum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Least time between the starts of two requests.
+    pub min_interval: Duration,
+    pub max_retries: usize,
+    pub initial_backoff: Duration,
+}
+
+impl RateLimit {
+    /// Anonymous access is not paced, and backs off only once the forum pushes back.
+    pub const ANONYMOUS: Self = Self {
+        min_interval: Duration::ZERO,
+        max_retries: DEFAULT_MAX_RETRIES,
+        initial_backoff: DEFAULT_INITIAL_BACKOFF,
+    };
+
+    /// The defaults for `credentials`: authenticated access stays under the
+    /// forum's per-key limit instead of running into it.
+    pub fn for_credentials(credentials: Option<&DiscourseCredentials>) -> Self {
+        let requests_per_minute = match credentials {
+            None => return Self::ANONYMOUS,
+            Some(DiscourseCredentials::ApiKey { .. }) => ADMIN_API_REQUESTS_PER_MINUTE,
+            Some(DiscourseCredentials::UserApiKey { .. }) => USER_API_REQUESTS_PER_MINUTE,
+        };
+        Self {
+            min_interval: interval_for(requests_per_minute),
+            ..Self::ANONYMOUS
+        }
+    }
+
+    /// The defaults for `credentials`, paced by `DISCOURSE_REQUESTS_PER_MINUTE_<SLUG>`
+    /// instead when the forum has a different limit.
+    pub fn from_env(dao_slug: &str, credentials: Option<&DiscourseCredentials>) -> Result<Self> {
+        let defaults = Self::for_credentials(credentials);
+        let name = format!("DISCOURSE_REQUESTS_PER_MINUTE_{}", env_suffix(dao_slug));
+        let Ok(value) = std::env::var(&name) else {
+            return Ok(defaults);
+        };
+
+        let requests_per_minute = value
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|requests| *requests > 0)
+            .with_context(|| format!("{name} must be a positive number, got {value:?}"))?;
+        Ok(Self {
+            min_interval: interval_for(requests_per_minute),
+            ..defaults
+        })
+    }
+}
+
+fn interval_for(requests_per_minute: u32) -> Duration {
+    Duration::from_secs(60) / requests_per_minute
+}
+
+/// When the next request may start, given when the last one was allowed to.
+fn next_request_at(last: Option<Instant>, now: Instant, min_interval: Duration) -> Instant {
+    last.map_or(now, |last| (last + min_interval).max(now))
+}
+
+/// The per-forum suffix of an environment variable: `arbitrum-one` is `ARBITRUM_ONE`.
+fn env_suffix(dao_slug: &str) -> String {
+    dao_slug
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Reads `name` from the environment, or from the file that `<name>_FILE` points to.
+fn env_secret(name: &str) -> Result<Option<String>> {
+    let value = match std::env::var(name) {
+        Ok(value) => value,
+        Err(_) => match std::env::var(format!("{name}_FILE")) {
+            Ok(path) => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {name}_FILE at {path}"))?,
+            Err(_) => return Ok(None),
+        },
+    };
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
 /// Represents a job to be processed by the API worker.
 struct Job {
     url: String, // Full URL to fetch
@@ -62,23 +244,40 @@ struct PendingRequest {
 #[derive(Clone)]
 pub struct DiscourseApi {
     client: Client,
-    max_retries: usize,
+    rate_limit: RateLimit,
+    authenticated: bool,
     sender: mpsc::Sender<Job>,
     pub base_url: String,
     // Cache for URLs that returned 403 Forbidden. Uses SystemTime for expiry.
     forbidden_urls: Arc<Mutex<HashMap<String, SystemTime>>>,
     // Tracks ongoing requests to prevent duplicate fetches for the same endpoint.
     pending_requests: Arc<Mutex<HashMap<String, PendingRequest>>>,
+    // When the most recent request was allowed to start, for pacing.
+    last_request: Arc<Mutex<Option<Instant>>>,
 }
 
 // --- Implementation ---
 
 impl DiscourseApi {
-    /// Creates a new Discourse API client and starts its background worker task.
-    #[instrument(skip(base_url), fields(base_url = %base_url))]
+    /// Creates a new anonymous Discourse API client and starts its background worker task.
     pub fn new(base_url: String) -> Self {
-        info!("Initializing Discourse API client");
-        let (headers, cookie_jar) = Self::default_headers_with_cookies();
+        Self::with_credentials(base_url, None, RateLimit::ANONYMOUS)
+            .expect("Failed to build HTTP client") // Panic is acceptable during initialization
+    }
+
+    /// Creates a Discourse API client that sends `credentials`, if any, with every
+    /// request and paces them by `rate_limit`, and starts its background worker task.
+    #[instrument(skip(base_url, credentials), fields(base_url = %base_url, authenticated = credentials.is_some()))]
+    pub fn with_credentials(
+        base_url: String,
+        credentials: Option<DiscourseCredentials>,
+        rate_limit: RateLimit,
+    ) -> Result<Self> {
+        info!(?rate_limit, "Initializing Discourse API client");
+        let (mut headers, cookie_jar) = Self::default_headers_with_cookies();
+        if let Some(credentials) = &credentials {
+            headers.extend(credentials.headers()?);
+        }
 
         let client = Client::builder()
             .default_headers(headers)
@@ -86,18 +285,20 @@ impl DiscourseApi {
             .timeout(Duration::from_secs(60)) // Add a general request timeout
             .connect_timeout(Duration::from_secs(20)) // Timeout for establishing connection
             .build()
-            .expect("Failed to build HTTP client"); // Panic is acceptable during initialization
+            .context("Failed to build HTTP client")?;
 
         // Use a bounded channel to prevent unbounded memory growth if the worker falls behind.
         let (sender, receiver) = mpsc::channel(DEFAULT_QUEUE_SIZE);
 
         let api_handler = Self {
             client,
-            max_retries: DEFAULT_MAX_RETRIES,
+            rate_limit,
+            authenticated: credentials.is_some(),
             sender,
             base_url: base_url.clone(),
             forbidden_urls: Arc::new(Mutex::new(HashMap::new())),
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            last_request: Arc::new(Mutex::new(None)),
         };
 
         // Spawn the queue runner task.
@@ -109,7 +310,33 @@ impl DiscourseApi {
         );
 
         info!("Discourse API client initialized and worker task started.");
-        api_handler
+        Ok(api_handler)
+    }
+
+    /// Whether requests carry credentials, so may see read-restricted content.
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    /// Waits until the rate limit allows the next request to start.
+    async fn wait_for_turn(&self) {
+        if self.rate_limit.min_interval.is_zero() {
+            return;
+        }
+        let wait = {
+            let mut last_request = self
+                .last_request
+                .lock()
+                .expect("Last request lock poisoned");
+            let now = Instant::now();
+            let start = next_request_at(*last_request, now, self.rate_limit.min_interval);
+            *last_request = Some(start);
+            start - now
+        };
+        if !wait.is_zero() {
+            debug!(?wait, "Pacing request");
+            sleep(wait).await;
+        }
     }
 
     /// Selects a random user agent string.
@@ -440,10 +667,11 @@ impl DiscourseApi {
 
         // --- Retry Loop ---
         let mut attempt = 0;
-        let mut delay = DEFAULT_INITIAL_BACKOFF;
+        let mut delay = self.rate_limit.initial_backoff;
 
         loop {
             attempt += 1;
+            self.wait_for_turn().await;
             let request_start = Instant::now(); // Use Instant for duration measurement
             debug!(url, attempt, "Sending HTTP GET request attempt.");
 
@@ -466,16 +694,16 @@ impl DiscourseApi {
                             });
                         }
                         StatusCode::TOO_MANY_REQUESTS => {
-                            if attempt > self.max_retries {
+                            if attempt > self.rate_limit.max_retries {
                                 error!(
                                     url,
                                     attempt,
-                                    max_retries = self.max_retries,
+                                    max_retries = self.rate_limit.max_retries,
                                     "Max retries reached after 429 Too Many Requests"
                                 );
                                 return Err(anyhow!(
                                     "Max retries ({}) reached for {}: Last error HTTP 429",
-                                    self.max_retries,
+                                    self.rate_limit.max_retries,
                                     url
                                 ));
                             }
@@ -488,11 +716,11 @@ impl DiscourseApi {
                         }
                         s if s.is_server_error() => {
                             // 5xx errors
-                            if attempt > self.max_retries {
-                                error!(url, status = %s, attempt, max_retries = self.max_retries, "Max retries reached after server error");
+                            if attempt > self.rate_limit.max_retries {
+                                error!(url, status = %s, attempt, max_retries = self.rate_limit.max_retries, "Max retries reached after server error");
                                 return Err(anyhow!(
                                     "Max retries ({}) reached for {}: Last error HTTP {}",
-                                    self.max_retries,
+                                    self.rate_limit.max_retries,
                                     url,
                                     s
                                 ));
@@ -508,7 +736,7 @@ impl DiscourseApi {
                                 Ok(text) => text.chars().take(100).collect::<String>(),
                                 Err(_) => "[failed to read body]".to_string(),
                             };
-                            error!(url, status = %status, body_preview, "Request forbidden (403), caching URL and failing permanently.");
+                            error!(url, status = %status, body_preview, authenticated = self.authenticated, "Request forbidden (403), caching URL and failing permanently.");
 
                             // Add to forbidden cache
                             {
@@ -560,11 +788,11 @@ impl DiscourseApi {
                 // Handle network or reqwest-level errors
                 Err(e) => {
                     let error_string = e.to_string();
-                    if attempt > self.max_retries {
-                        error!(url, error = %error_string, attempt, max_retries = self.max_retries, "Max retries reached after request error.");
+                    if attempt > self.rate_limit.max_retries {
+                        error!(url, error = %error_string, attempt, max_retries = self.rate_limit.max_retries, "Max retries reached after request error.");
                         return Err(anyhow!(
                             "Max retries ({}) reached for {}. Last error: {}",
-                            self.max_retries,
+                            self.rate_limit.max_retries,
                             url,
                             e
                         ))
@@ -649,6 +877,99 @@ mod tests {
         assert_eq!(processed, expected);
     }
 
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Result<Option<String>> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| Ok(vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_credentials_from_lookup() {
+        assert_eq!(
+            DiscourseCredentials::from_lookup("arbitrum-one", lookup(&[])).unwrap(),
+            None
+        );
+        assert_eq!(
+            DiscourseCredentials::from_lookup(
+                "arbitrum-one",
+                lookup(&[
+                    ("DISCOURSE_API_KEY_ARBITRUM_ONE", "secret"),
+                    ("DISCOURSE_API_USERNAME_ARBITRUM_ONE", "indexer"),
+                ])
+            )
+            .unwrap(),
+            Some(DiscourseCredentials::ApiKey {
+                key: "secret".to_string(),
+                username: "indexer".to_string(),
+            })
+        );
+        assert_eq!(
+            DiscourseCredentials::from_lookup(
+                "uniswap",
+                lookup(&[("DISCOURSE_USER_API_KEY_UNISWAP", "secret")])
+            )
+            .unwrap(),
+            Some(DiscourseCredentials::UserApiKey {
+                key: "secret".to_string(),
+            })
+        );
+
+        // Half-configured or ambiguous credentials are refused rather than ignored
+        for vars in [
+            &[("DISCOURSE_API_KEY_UNISWAP", "secret")][..],
+            &[
+                ("DISCOURSE_API_KEY_UNISWAP", "secret"),
+                ("DISCOURSE_API_USERNAME_UNISWAP", "indexer"),
+                ("DISCOURSE_USER_API_KEY_UNISWAP", "secret"),
+            ],
+        ] {
+            assert!(DiscourseCredentials::from_lookup("uniswap", lookup(vars)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_credential_headers_stay_out_of_logs() {
+        let credentials = DiscourseCredentials::ApiKey {
+            key: "secret".to_string(),
+            username: "indexer".to_string(),
+        };
+
+        let headers = credentials.headers().unwrap();
+        assert_eq!(headers["Api-Key"], "secret");
+        assert_eq!(headers["Api-Username"], "indexer");
+        assert!(headers.values().all(HeaderValue::is_sensitive));
+        assert!(!format!("{credentials:?}").contains("secret"));
+
+        let user_key = DiscourseCredentials::UserApiKey {
+            key: "secret".to_string(),
+        };
+        assert_eq!(user_key.headers().unwrap()["User-Api-Key"], "secret");
+    }
+
+    #[test]
+    fn test_authenticated_requests_are_paced() {
+        assert_eq!(RateLimit::for_credentials(None), RateLimit::ANONYMOUS);
+        let user_key = DiscourseCredentials::UserApiKey {
+            key: "secret".to_string(),
+        };
+        let rate_limit = RateLimit::for_credentials(Some(&user_key));
+        assert_eq!(rate_limit.min_interval, Duration::from_secs(3));
+
+        let now = Instant::now();
+        assert_eq!(next_request_at(None, now, rate_limit.min_interval), now);
+        assert_eq!(
+            next_request_at(Some(now), now, rate_limit.min_interval),
+            now + Duration::from_secs(3)
+        );
+        let later = now + Duration::from_secs(10);
+        assert_eq!(
+            next_request_at(Some(now), later, rate_limit.min_interval),
+            later
+        );
+    }
+
     // --- Keep Existing Tests ---
 
     #[tokio::test]
@@ -1,6 +1,8 @@
 pub mod categories;
 pub mod likes;
+pub mod ownership;
 pub mod posts;
+pub mod proposal_links;
 pub mod revisions;
 pub mod topics;
 pub mod users;
@@ -0,0 +1,457 @@
+use crate::{
+    db_handler::db,
+    discourse_api::DiscourseApi,
+    models::{
+        posts::{PostResponse, TopicPostsResponse},
+        users::UserDetailResponse,
+    },
+};
+use alloy::primitives::{Address, Signature};
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use proposalsapp_db::models::{
+    dao_discourse, delegate_to_discourse_user, delegate_to_discourse_user_verification,
+    delegate_to_voter, discourse_user, voter,
+};
+use regex::Regex;
+use sea_orm::{
+    ActiveValue::NotSet, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+    prelude::Uuid, sea_query::Expr,
+};
+use serde::Serialize;
+use std::{str::FromStr, sync::Arc};
+use tracing::{debug, info, instrument, warn};
+
+/// Prefix of challenges, so they stand out in a bio or a post.
+const CHALLENGE_PREFIX: &str = "proposalsapp-verify-";
+
+/// Links verified before they had a challenge keep their status this long,
+/// for their owner to post the challenge.
+const CHALLENGE_GRACE_PERIOD: Duration = Duration::days(7);
+
+/// How many of an account's latest posts in the verification topic are
+/// searched for the challenge.
+const RECENT_TOPIC_POSTS: usize = 20;
+
+static RE_SIGNATURE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"0x[0-9a-fA-F]{130}").expect("Failed to compile signature regex"));
+
+/// Where the owner of an account put the challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofLocation {
+    ProfileBio,
+    TopicPost,
+}
+
+impl ProofLocation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofLocation::ProfileBio => "PROFILE_BIO",
+            ProofLocation::TopicPost => "TOPIC_POST",
+        }
+    }
+}
+
+/// What proved ownership, stored with the check.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Evidence {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<i32>,
+    /// Voter address of the delegate that signed [`ownership_message`]
+    /// alongside the challenge.
+    pub signed_by: Option<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnershipCheck {
+    Verified {
+        location: ProofLocation,
+        evidence: Evidence,
+    },
+    Rejected(String),
+}
+
+/// Text the delegate's voter address can sign with `personal_sign`, and post
+/// next to the challenge, to tie the account to the address as well.
+pub fn ownership_message(forum_url: &str, username: &str, challenge: &str) -> String {
+    format!("I own the {username} account on {forum_url} for proposals.app\nChallenge: {challenge}")
+}
+
+/// Whether `text` holds the challenge. When it does, also returns which of
+/// `voters` signed `message` in it, if any.
+pub fn find_proof(
+    text: &str,
+    challenge: &str,
+    message: &str,
+    voters: &[Address],
+) -> Option<Option<Address>> {
+    if !text.contains(challenge) {
+        return None;
+    }
+
+    let signed_by = RE_SIGNATURE.find_iter(text).find_map(|m| {
+        let bytes = alloy::hex::decode(m.as_str()).ok()?;
+        let signature = Signature::try_from(bytes.as_slice()).ok()?;
+        let signer = signature.recover_address_from_msg(message).ok()?;
+        voters.contains(&signer).then_some(signer)
+    });
+    Some(signed_by)
+}
+
+/// The challenge of a link, issuing one if it has none yet.
+#[instrument(skip_all, fields(delegate_to_discourse_user_id = %link_id))]
+pub async fn issue_challenge(link_id: Uuid) -> Result<String> {
+    let challenge = format!("{CHALLENGE_PREFIX}{:032x}", rand::random::<u128>());
+    delegate_to_discourse_user::Entity::update_many()
+        .col_expr(
+            delegate_to_discourse_user::Column::Challenge,
+            Expr::value(challenge),
+        )
+        .col_expr(
+            delegate_to_discourse_user::Column::ChallengeIssuedAt,
+            // The UTC clock the grace period is measured against
+            Expr::cust("timezone('utc', now())"),
+        )
+        .filter(delegate_to_discourse_user::Column::Id.eq(link_id))
+        .filter(delegate_to_discourse_user::Column::Challenge.is_null())
+        .exec(db())
+        .await
+        .context("Failed to issue challenge")?;
+
+    delegate_to_discourse_user::Entity::find_by_id(link_id)
+        .one(db())
+        .await
+        .context("Failed to fetch link")?
+        .and_then(|link| link.challenge)
+        .context("Link not found")
+}
+
+/// Record the outcome of a check of `link` against `challenge`, and mark the
+/// link verified or not accordingly. Checks are only recorded when their
+/// outcome changes. Returns whether anything changed.
+#[instrument(skip_all, fields(delegate_to_discourse_user_id = %link.id))]
+pub async fn record_ownership_check(
+    link: &delegate_to_discourse_user::Model,
+    challenge: &str,
+    check: &OwnershipCheck,
+) -> Result<bool> {
+    let (verified, method, evidence, error) = match check {
+        OwnershipCheck::Verified { location, evidence } => (
+            true,
+            Some(location.as_str().to_string()),
+            Some(serde_json::to_value(evidence).context("Failed to serialize evidence")?),
+            None,
+        ),
+        OwnershipCheck::Rejected(reason) => (false, None, None, Some(reason.clone())),
+    };
+
+    let latest = latest_check(link.id).await?;
+    let outcome_changed = latest.is_none_or(|latest| {
+        latest.verified != verified || latest.method != method || latest.challenge != challenge
+    });
+    if !outcome_changed && link.verified == verified {
+        return Ok(false);
+    }
+
+    let txn = db()
+        .begin()
+        .await
+        .context("Failed to start ownership check transaction")?;
+    if outcome_changed {
+        delegate_to_discourse_user_verification::Entity::insert(
+            delegate_to_discourse_user_verification::ActiveModel {
+                id: NotSet,
+                delegate_to_discourse_user_id: Set(link.id),
+                challenge: Set(challenge.to_string()),
+                verified: Set(verified),
+                method: Set(method),
+                evidence: Set(evidence),
+                error: Set(error.clone()),
+                checked_at: NotSet,
+            },
+        )
+        .exec_without_returning(&txn)
+        .await
+        .context("Failed to record ownership check")?;
+    }
+    if link.verified != verified {
+        delegate_to_discourse_user::Entity::update_many()
+            .col_expr(
+                delegate_to_discourse_user::Column::Verified,
+                Expr::value(verified),
+            )
+            .filter(delegate_to_discourse_user::Column::Id.eq(link.id))
+            .exec(&txn)
+            .await
+            .context("Failed to update link")?;
+    }
+    txn.commit()
+        .await
+        .context("Failed to commit ownership check")?;
+
+    if link.verified != verified {
+        info!(verified, error = ?error, "Discourse account ownership changed");
+    }
+    Ok(true)
+}
+
+async fn latest_check(
+    link_id: Uuid,
+) -> Result<Option<delegate_to_discourse_user_verification::Model>> {
+    delegate_to_discourse_user_verification::Entity::find()
+        .filter(
+            delegate_to_discourse_user_verification::Column::DelegateToDiscourseUserId.eq(link_id),
+        )
+        .order_by_desc(delegate_to_discourse_user_verification::Column::CheckedAt)
+        .one(db())
+        .await
+        .context("Failed to fetch latest ownership check")
+}
+
+/// Verifies that delegates own the Discourse accounts they link to, by
+/// finding each link's challenge in the account's profile bio or in its posts
+/// in the forum's verification topic. Links are re-checked on every run, so
+/// they're revoked once the challenge is gone.
+#[derive(Clone)]
+pub struct OwnershipVerifier {
+    discourse_api: Arc<DiscourseApi>,
+}
+
+impl OwnershipVerifier {
+    pub fn new(discourse_api: Arc<DiscourseApi>) -> Self {
+        Self { discourse_api }
+    }
+
+    /// Check every link to an account of the forum, issuing challenges to
+    /// links that have none.
+    #[instrument(skip_all, fields(dao_discourse_id = %forum.id))]
+    pub async fn verify_links(&self, forum: &dao_discourse::Model) -> Result<()> {
+        let links = delegate_to_discourse_user::Entity::find()
+            .find_also_related(discourse_user::Entity)
+            .filter(discourse_user::Column::DaoDiscourseId.eq(forum.id))
+            .all(db())
+            .await
+            .context("Failed to fetch delegate links")?;
+
+        let now = Utc::now().naive_utc();
+        let mut changed = 0;
+        for (link, user) in links {
+            let Some(user) = user else { continue };
+            let challenge = match &link.challenge {
+                Some(challenge) => challenge.clone(),
+                None => issue_challenge(link.id).await?,
+            };
+
+            let check = match self.check_link(forum, &link, &user, &challenge, now).await {
+                Ok(check) => check,
+                Err(e) => {
+                    warn!(delegate_to_discourse_user_id = %link.id, error = ?e, "Failed to check account ownership, will retry");
+                    continue;
+                }
+            };
+
+            if matches!(check, OwnershipCheck::Rejected(_))
+                && link.verified
+                && link
+                    .challenge_issued_at
+                    .is_none_or(|issued_at| issued_at + CHALLENGE_GRACE_PERIOD > now)
+                && latest_check(link.id).await?.is_none()
+            {
+                debug!(delegate_to_discourse_user_id = %link.id, "Waiting for the challenge to be posted");
+                continue;
+            }
+
+            if record_ownership_check(&link, &challenge, &check).await? {
+                changed += 1;
+            }
+        }
+
+        info!(changed, "Discourse account ownership checked");
+        Ok(())
+    }
+
+    async fn check_link(
+        &self,
+        forum: &dao_discourse::Model,
+        link: &delegate_to_discourse_user::Model,
+        user: &discourse_user::Model,
+        challenge: &str,
+        now: NaiveDateTime,
+    ) -> Result<OwnershipCheck> {
+        if now < link.period_start {
+            return Ok(OwnershipCheck::Rejected(
+                "Link period hasn't started".to_string(),
+            ));
+        }
+        if now >= link.period_end {
+            return Ok(OwnershipCheck::Rejected("Link period ended".to_string()));
+        }
+
+        let voters = delegate_voters(link.delegate_id, now).await?;
+        let message = ownership_message(&forum.discourse_base_url, &user.username, challenge);
+
+        let username =
+            url::form_urlencoded::byte_serialize(user.username.as_bytes()).collect::<String>();
+        let profile: UserDetailResponse = self
+            .discourse_api
+            .queue(&format!("/u/{username}.json"), false)
+            .await
+            .with_context(|| format!("Failed to fetch profile of {}", user.username))?;
+        let bio = profile
+            .user
+            .bio_raw
+            .or(profile.user.bio_cooked)
+            .unwrap_or_default();
+        if let Some(signed_by) = find_proof(&bio, challenge, &message, &voters) {
+            return Ok(OwnershipCheck::Verified {
+                location: ProofLocation::ProfileBio,
+                evidence: Evidence {
+                    url: format!("{}/u/{}", forum.discourse_base_url, user.username),
+                    post_id: None,
+                    signed_by,
+                },
+            });
+        }
+
+        if let Some(topic_id) = forum.verification_topic_id {
+            // The account's posts in the topic, oldest first, of which only
+            // the latest are fetched: that's where a new challenge is
+            let response: PostResponse = self
+                .discourse_api
+                .queue(
+                    &format!("/t/{topic_id}.json?username_filters={username}"),
+                    false,
+                )
+                .await
+                .with_context(|| format!("Failed to fetch verification topic {topic_id}"))?;
+            let stream = &response.post_stream.stream;
+            let post_ids = stream[stream.len().saturating_sub(RECENT_TOPIC_POSTS)..]
+                .iter()
+                .map(|post_id| format!("post_ids[]={post_id}"))
+                .collect::<Vec<_>>();
+            if post_ids.is_empty() {
+                return Ok(OwnershipCheck::Rejected("Challenge not found".to_string()));
+            }
+            let response: TopicPostsResponse = self
+                .discourse_api
+                .queue(
+                    &format!(
+                        "/t/{topic_id}/posts.json?include_raw=true&{}",
+                        post_ids.join("&")
+                    ),
+                    false,
+                )
+                .await
+                .with_context(|| {
+                    format!("Failed to fetch posts of verification topic {topic_id}")
+                })?;
+            let proof = response
+                .post_stream
+                .posts
+                .iter()
+                .rev()
+                .filter(|post| post.user_id == user.external_id)
+                .find_map(|post| {
+                    let signed_by = find_proof(post.raw.as_deref()?, challenge, &message, &voters)?;
+                    Some((post, signed_by))
+                });
+            if let Some((post, signed_by)) = proof {
+                return Ok(OwnershipCheck::Verified {
+                    location: ProofLocation::TopicPost,
+                    evidence: Evidence {
+                        url: format!(
+                            "{}/t/{}/{}",
+                            forum.discourse_base_url, topic_id, post.post_number
+                        ),
+                        post_id: Some(post.id),
+                        signed_by,
+                    },
+                });
+            }
+        }
+
+        Ok(OwnershipCheck::Rejected("Challenge not found".to_string()))
+    }
+}
+
+/// Verified voter addresses linked to the delegate at `now`.
+async fn delegate_voters(delegate_id: Uuid, now: NaiveDateTime) -> Result<Vec<Address>> {
+    let voters = voter::Entity::find()
+        .inner_join(delegate_to_voter::Entity)
+        .filter(delegate_to_voter::Column::DelegateId.eq(delegate_id))
+        .filter(delegate_to_voter::Column::Verified.eq(true))
+        .filter(delegate_to_voter::Column::PeriodStart.lte(now))
+        .filter(delegate_to_voter::Column::PeriodEnd.gt(now))
+        .all(db())
+        .await
+        .context("Failed to fetch delegate voters")?;
+    Ok(voters
+        .iter()
+        .filter_map(|voter| Address::from_str(&voter.address).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        primitives::b256,
+        signers::{SignerSync, local::PrivateKeySigner},
+    };
+
+    const FORUM: &str = "https://forum.arbitrum.foundation";
+    const CHALLENGE: &str = "proposalsapp-verify-0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_find_proof_challenge() {
+        let message = ownership_message(FORUM, "alice", CHALLENGE);
+
+        assert_eq!(
+            find_proof(
+                &format!("Delegate for the Arbitrum DAO. {CHALLENGE}"),
+                CHALLENGE,
+                &message,
+                &[]
+            ),
+            Some(None)
+        );
+        assert_eq!(
+            find_proof(
+                "<p>Delegate for the Arbitrum DAO. proposalsapp-verify-0123</p>",
+                CHALLENGE,
+                &message,
+                &[]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_proof_signature() {
+        let signer = PrivateKeySigner::from_bytes(&b256!(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        ))
+        .unwrap();
+        let message = ownership_message(FORUM, "alice", CHALLENGE);
+        let signature = signer.sign_message_sync(message.as_bytes()).unwrap();
+        let text = format!("{CHALLENGE}\n\nSigned: {signature}");
+
+        assert_eq!(
+            find_proof(&text, CHALLENGE, &message, &[signer.address()]),
+            Some(Some(signer.address()))
+        );
+        // Signatures by anyone but the delegate's voters don't count, nor do
+        // signatures of another account's message
+        assert_eq!(
+            find_proof(&text, CHALLENGE, &message, &[Address::ZERO]),
+            Some(None)
+        );
+        let other_message = ownership_message(FORUM, "mallory", CHALLENGE);
+        assert_eq!(
+            find_proof(&text, CHALLENGE, &other_message, &[signer.address()]),
+            Some(None)
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{
     MAX_PAGES_PER_RUN,
-    db_handler::{db, get_or_create_unknown_user, get_post_like_count, upsert_post},
+    db_handler::{
+        advance_post_high_water_mark, db, get_or_create_unknown_user, get_post_high_water_mark,
+        get_post_like_count, upsert_post,
+    },
     discourse_api::{DiscourseApi, process_upload_urls},
     indexers::{PageCursor, likes::LikesIndexer, users::UserIndexer},
-    models::posts::{Post, PostResponse},
+    models::posts::{LatestPostsResponse, Post, PostResponse},
 };
 use anyhow::{Context, Result};
 use futures::stream::{self, StreamExt};
@@ -21,6 +24,20 @@ use std::{
 use tokio::{sync::Mutex, task};
 use tracing::{debug, error, info, instrument, warn};
 
+/// Pages of the latest posts feed read per run. A forum further behind than
+/// this gets its bumped topics re-crawled instead.
+const MAX_FEED_PAGES_PER_RUN: u32 = 20;
+
+/// New posts taken from the latest posts feed in one run.
+#[derive(Debug, Default)]
+pub struct LatestPosts {
+    /// New posts per topic external id.
+    pub new_posts_by_topic: HashMap<i32, i32>,
+    /// Whether every post since the last run was stored. When not, posts may
+    /// be missing and bumped topics need their posts re-crawled.
+    pub complete: bool,
+}
+
 #[derive(Clone)] // Add Clone derive
 pub struct PostIndexer {
     discourse_api: Arc<DiscourseApi>,
@@ -88,6 +105,7 @@ impl PostIndexer {
         let mut pager = PageCursor::new(MAX_PAGES_PER_RUN);
         let mut total_api_posts_count: Option<i32> = None; // Initialize lazily from first response
         let mut total_processed_posts: i32 = 0;
+        let mut failed_posts = 0;
         let mut seen_in_api_post_ids: HashSet<i32> = HashSet::new();
 
         loop {
@@ -133,63 +151,16 @@ impl PostIndexer {
                         posts_on_page.iter().map(|p| p.id).collect();
                     seen_in_api_post_ids.extend(current_page_ids);
 
-                    // Process posts concurrently using buffer_unordered
-                    // Wrap the processing in a task to handle potential errors per post
-                    let processing_results = stream::iter(posts_on_page)
-                        .map(|post| {
-                            let user_indexer = self.user_indexer.clone(); // Clone Arcs for the task
-                            let likes_indexer = self.likes_indexer.clone();
-                            let user_cache = Arc::clone(&self.user_cache);
-                            let api_handler = Arc::clone(&self.discourse_api); // Clone API handler Arc
-
-                            task::spawn(async move {
-                                Self::process_single_post(
-                                    post, // Takes ownership of post
-                                    dao_discourse_id,
-                                    api_handler,
-                                    user_indexer,
-                                    likes_indexer,
-                                    user_cache,
-                                    priority,
-                                )
-                                .await
-                            })
-                        })
-                        .buffer_unordered(10) // Limit concurrency
-                        .collect::<Vec<_>>() // Collect join handles
+                    let num_errors = self
+                        .process_posts(posts_on_page, dao_discourse_id, priority)
                         .await;
-
-                    // Check results from concurrent processing
-                    let mut post_errors = Vec::new();
-                    for result in processing_results {
-                        match result {
-                            Ok(Ok(_)) => { /* Post processed successfully */ }
-                            Ok(Err(e)) => {
-                                // Error occurred within process_single_post
-                                error!(error = ?e, "Error processing individual post");
-                                post_errors.push(e);
-                            }
-                            Err(join_err) => {
-                                // Task panicked or was cancelled
-                                error!(error = ?join_err, "Post processing task failed");
-                                post_errors.push(
-                                    anyhow::Error::new(join_err)
-                                        .context("Post processing task failed"),
-                                );
-                            }
-                        }
-                    }
-
-                    // Optionally aggregate or handle post_errors (e.g., return first error or log summary)
-                    if !post_errors.is_empty() {
+                    if num_errors > 0 {
                         warn!(
                             topic_id,
-                            num_errors = post_errors.len(),
-                            "Encountered errors processing some posts for topic"
+                            num_errors, "Encountered errors processing some posts for topic"
                         );
-                        // Could return the first error if needed: return
-                        // Err(post_errors.remove(0));
                     }
+                    failed_posts += num_errors;
 
                     total_processed_posts += num_posts_on_page;
 
@@ -291,9 +262,183 @@ impl PostIndexer {
             duration = ?duration,
             "Finished updating posts for topic"
         );
+        if failed_posts > 0 {
+            anyhow::bail!("Failed to store {failed_posts} posts of topic {topic_id}");
+        }
+        Ok(())
+    }
+
+    /// Fetches and updates the first page of a topic's posts. Edits of the
+    /// opening post and likes on the posts around it don't show in the latest
+    /// posts feed, so topics with new activity get their first page refreshed.
+    #[instrument(skip(self), fields(dao_discourse_id = %dao_discourse_id, topic_id = topic_id, priority = priority))]
+    pub async fn update_first_posts_page(
+        &self,
+        dao_discourse_id: Uuid,
+        topic_id: i32,
+        priority: bool,
+    ) -> Result<()> {
+        let url = format!("/t/{topic_id}.json?include_raw=true&page=1");
+        let response = self
+            .discourse_api
+            .queue::<PostResponse>(&url, priority)
+            .await
+            .with_context(|| format!("Failed to fetch first posts page of topic {topic_id}"))?;
+
+        let num_errors = self
+            .process_posts(response.post_stream.posts, dao_discourse_id, priority)
+            .await;
+        if num_errors > 0 {
+            anyhow::bail!(
+                "Failed to store {num_errors} posts of the first page of topic {topic_id}"
+            );
+        }
         Ok(())
     }
 
+    /// Fetches the forum's latest posts feed down to the stored high-water mark
+    /// and upserts the new posts, skipping ones a topic crawl already stored
+    /// unchanged. The mark only moves once every new post is stored.
+    #[instrument(skip(self), fields(dao_discourse_id = %dao_discourse_id, priority = priority))]
+    pub async fn update_latest_posts(
+        &self,
+        dao_discourse_id: Uuid,
+        priority: bool,
+    ) -> Result<LatestPosts> {
+        let start_time = Instant::now();
+        let high_water = get_post_high_water_mark(dao_discourse_id).await?;
+        info!(?high_water, "Starting latest posts update");
+
+        let mut new_posts = Vec::new();
+        let mut complete = false;
+        let mut before: Option<i32> = None;
+        let mut pager = PageCursor::new(MAX_FEED_PAGES_PER_RUN);
+
+        loop {
+            let url = match before {
+                Some(before) => format!("/posts.json?before={before}"),
+                None => "/posts.json".to_string(),
+            };
+            debug!(%url, "Fetching latest posts page");
+
+            let response = self
+                .discourse_api
+                .queue::<LatestPostsResponse>(&url, priority)
+                .await
+                .with_context(|| format!("Failed to fetch latest posts page {url}"))?;
+            let oldest_post_id = response.latest_posts.iter().map(|post| post.id).min();
+
+            let Some(high_water) = high_water else {
+                // First run: the mark starts at the newest posts, older ones are
+                // left to the full refresh
+                new_posts.extend(response.latest_posts);
+                break;
+            };
+
+            let (posts, reached) = posts_above_high_water(response.latest_posts, high_water);
+            new_posts.extend(posts);
+            if reached {
+                complete = true;
+                break;
+            }
+
+            before = oldest_post_id;
+            if !pager.advance("latest posts pages") {
+                break;
+            }
+        }
+
+        let new_high_water = new_posts.iter().map(|post| post.id).max();
+        let mut new_posts_by_topic: HashMap<i32, i32> = HashMap::new();
+        for post in &new_posts {
+            *new_posts_by_topic.entry(post.topic_id).or_default() += 1;
+        }
+
+        let unchanged_post_ids = unchanged_post_ids(dao_discourse_id, &new_posts).await?;
+        let num_new_posts = new_posts.len();
+        let changed_posts: Vec<Post> = new_posts
+            .into_iter()
+            .filter(|post| !unchanged_post_ids.contains(&post.id))
+            .collect();
+        let num_upserted_posts = changed_posts.len();
+
+        let num_errors = self
+            .process_posts(changed_posts, dao_discourse_id, priority)
+            .await;
+        if num_errors > 0 {
+            // Left below the mark so the next run picks them up again
+            warn!(
+                num_errors,
+                "Encountered errors processing latest posts, keeping high-water mark"
+            );
+            complete = false;
+        } else if let Some(new_high_water) = new_high_water {
+            advance_post_high_water_mark(dao_discourse_id, new_high_water).await?;
+        }
+
+        info!(
+            num_new_posts,
+            num_upserted_posts,
+            complete,
+            duration = ?start_time.elapsed(),
+            "Finished latest posts update"
+        );
+        Ok(LatestPosts {
+            new_posts_by_topic,
+            complete,
+        })
+    }
+
+    /// Processes posts concurrently, logging failures. Returns how many failed.
+    async fn process_posts(
+        &self,
+        posts: Vec<Post>,
+        dao_discourse_id: Uuid,
+        priority: bool,
+    ) -> usize {
+        let processing_results = stream::iter(posts)
+            .map(|post| {
+                let user_indexer = self.user_indexer.clone(); // Clone Arcs for the task
+                let likes_indexer = self.likes_indexer.clone();
+                let user_cache = Arc::clone(&self.user_cache);
+                let api_handler = Arc::clone(&self.discourse_api); // Clone API handler Arc
+
+                task::spawn(async move {
+                    Self::process_single_post(
+                        post, // Takes ownership of post
+                        dao_discourse_id,
+                        api_handler,
+                        user_indexer,
+                        likes_indexer,
+                        user_cache,
+                        priority,
+                    )
+                    .await
+                })
+            })
+            .buffer_unordered(10) // Limit concurrency
+            .collect::<Vec<_>>() // Collect join handles
+            .await;
+
+        let mut num_errors = 0;
+        for result in processing_results {
+            match result {
+                Ok(Ok(_)) => { /* Post processed successfully */ }
+                Ok(Err(e)) => {
+                    // Error occurred within process_single_post
+                    error!(error = ?e, "Error processing individual post");
+                    num_errors += 1;
+                }
+                Err(join_err) => {
+                    // Task panicked or was cancelled
+                    error!(error = ?join_err, "Post processing task failed");
+                    num_errors += 1;
+                }
+            }
+        }
+        num_errors
+    }
+
     /// Processes a single post: ensures user exists, processes content, upserts post, fetches likes
     /// if needed.
     #[instrument(skip(post, discourse_api, user_indexer, likes_indexer, user_cache), fields(post_id = %post.id, username = %post.username, priority = priority))]
@@ -357,6 +502,8 @@ impl PostIndexer {
             }
         };
 
+        let authenticated = discourse_api.is_authenticated();
+
         // 2. Process Raw Content (e.g., replace upload URLs)
         // This function is now sync, no need for spawn_blocking
         if let Some(raw_content) = &post.raw {
@@ -366,7 +513,7 @@ impl PostIndexer {
 
         // 3. Upsert Post to Database
         let post_external_id = post.id;
-        upsert_post(&post, dao_discourse_id)
+        upsert_post(&post, dao_discourse_id, authenticated)
             .await
             .with_context(|| format!("Failed upsert_post for {post_external_id}"))?;
         debug!(post_id = post_external_id, "Post upserted successfully.");
@@ -419,3 +566,122 @@ impl PostIndexer {
         Ok(()) // Indicate successful processing of this post
     }
 }
+
+/// Ids of `posts` already stored with the same version and update time.
+async fn unchanged_post_ids(dao_discourse_id: Uuid, posts: &[Post]) -> Result<HashSet<i32>> {
+    #[derive(FromQueryResult)]
+    struct StoredPost {
+        external_id: i32,
+        version: i32,
+        updated_at: chrono::NaiveDateTime,
+    }
+
+    if posts.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let stored: HashMap<i32, StoredPost> = discourse_post::Entity::find()
+        .select_only()
+        .column(discourse_post::Column::ExternalId)
+        .column(discourse_post::Column::Version)
+        .column(discourse_post::Column::UpdatedAt)
+        .filter(discourse_post::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .filter(discourse_post::Column::ExternalId.is_in(posts.iter().map(|post| post.id)))
+        .into_model::<StoredPost>()
+        .all(db())
+        .await
+        .context("Failed to fetch stored latest posts")?
+        .into_iter()
+        .map(|post| (post.external_id, post))
+        .collect();
+
+    Ok(posts
+        .iter()
+        .filter(|post| {
+            stored.get(&post.id).is_some_and(|stored| {
+                stored.version == post.version && stored.updated_at == post.updated_at.naive_utc()
+            })
+        })
+        .map(|post| post.id)
+        .collect())
+}
+
+/// Splits a latest posts page into the posts above the high-water mark, and
+/// whether the page reached the mark (or the end of the feed).
+fn posts_above_high_water(page: Vec<Post>, high_water: i32) -> (Vec<Post>, bool) {
+    let reached = page.is_empty() || page.iter().any(|post| post.id <= high_water);
+    let posts = page
+        .into_iter()
+        .filter(|post| post.id > high_water)
+        .collect();
+    (posts, reached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn latest_posts_page(ids: &[i32]) -> Vec<Post> {
+        let posts: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "id": id,
+                    "name": null,
+                    "username": "alice",
+                    "avatar_template": "/user_avatar/alice/{size}/1.png",
+                    "created_at": "2025-01-01T00:00:00.000Z",
+                    "cooked": "<p>hello</p>",
+                    "raw": "hello",
+                    "post_number": 2,
+                    "post_type": 1,
+                    "updated_at": "2025-01-01T00:00:00.000Z",
+                    "reply_count": 0,
+                    "reply_to_post_number": null,
+                    "quote_count": 0,
+                    "incoming_link_count": 0,
+                    "reads": 1,
+                    "readers_count": 0,
+                    "score": 0.2,
+                    "topic_id": 10,
+                    "topic_slug": "welcome",
+                    "topic_title": "Welcome",
+                    "display_username": null,
+                    "primary_group_name": null,
+                    "flair_name": null,
+                    "flair_url": null,
+                    "flair_bg_color": null,
+                    "flair_color": null,
+                    "version": 1,
+                    "can_view_edit_history": true,
+                    "user_id": 1,
+                    "actions_summary": [],
+                })
+            })
+            .collect();
+
+        serde_json::from_value::<LatestPostsResponse>(json!({ "latest_posts": posts }))
+            .unwrap()
+            .latest_posts
+    }
+
+    fn ids(posts: &[Post]) -> Vec<i32> {
+        posts.iter().map(|post| post.id).collect()
+    }
+
+    #[test]
+    fn test_posts_above_high_water() {
+        let (posts, reached) = posts_above_high_water(latest_posts_page(&[105, 104, 101]), 100);
+        assert_eq!(ids(&posts), vec![105, 104, 101]);
+        assert!(!reached, "the mark may be on an older page");
+
+        let (posts, reached) = posts_above_high_water(latest_posts_page(&[103, 101, 99]), 101);
+        assert_eq!(ids(&posts), vec![103]);
+        assert!(reached);
+
+        let (posts, reached) = posts_above_high_water(latest_posts_page(&[]), 101);
+        assert!(posts.is_empty());
+        assert!(reached, "an empty page is the end of the feed");
+    }
+}
@@ -0,0 +1,486 @@
+use crate::db_handler::db;
+use anyhow::{Context, Result};
+use proposalsapp_db::models::{
+    dao_discourse, discourse_post, discourse_topic, mapping_proposal_decision, proposal,
+    proposal_group,
+};
+use reqwest::{Client, Url};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait, prelude::Uuid,
+};
+use serde_json::{Value, json};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{debug, info, instrument, warn};
+use utils::{
+    public_http::{is_public_web_url, public_client},
+    types::{ProposalGroupItem, ProposalItem, TopicItem},
+};
+
+/// A proposal's own discussion URL pointing at a topic leaves no doubt about
+/// where it belongs.
+const URL_LINK_CONFIDENCE: f64 = 1.0;
+
+/// How many redirects of a discussion URL are followed.
+const MAX_REDIRECTS: usize = 5;
+
+/// How long following the redirects of a discussion URL may take.
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a discussion URL refers to a topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicReference {
+    /// `/t/<id>` or `/t/<slug>/<id>`, possibly anchored to a post.
+    Id(i32),
+    /// `/t/<slug>`, without the id.
+    Slug(String),
+    /// `/p/<post-id>`, the short link of a post.
+    Post(i32),
+}
+
+/// Links proposals to the Discourse topic their discussion URL points at,
+/// grouping both. Proposals that are already grouped are left alone, and
+/// URLs that don't resolve to a single topic are left to the mapping agent.
+#[derive(Clone)]
+pub struct ProposalLinker {
+    /// Only follows redirects to public websites, see [`redirect_client`].
+    http_client: Client,
+    /// Where discussion URLs redirect to, so each is only followed once.
+    redirects: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+struct ResolvedTopic {
+    topic: discourse_topic::Model,
+    resolved_by: &'static str,
+}
+
+impl ProposalLinker {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            http_client: redirect_client()?,
+            redirects: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Link the ungrouped proposals of the forum's DAO that have a discussion
+    /// URL on the forum.
+    #[instrument(skip_all, fields(dao_discourse_id = %forum.id))]
+    pub async fn link_proposals(&self, forum: &dao_discourse::Model) -> Result<()> {
+        let groups = proposal_group::Entity::find()
+            .filter(proposal_group::Column::DaoId.eq(forum.dao_id))
+            .all(db())
+            .await
+            .context("Failed to fetch proposal groups")?;
+        let grouped: HashSet<(Uuid, String)> = groups
+            .iter()
+            .flat_map(|group| group_items(&group.items))
+            .filter_map(|item| match item {
+                ProposalGroupItem::Proposal(item) => Some((item.governor_id, item.external_id)),
+                ProposalGroupItem::Topic(_) => None,
+            })
+            .collect();
+
+        let proposals = proposal::Entity::find()
+            .filter(proposal::Column::DaoId.eq(forum.dao_id))
+            .filter(proposal::Column::DiscussionUrl.is_not_null())
+            .filter(proposal::Column::MarkedSpam.eq(false))
+            .all(db())
+            .await
+            .context("Failed to fetch proposals with a discussion URL")?;
+
+        let mut linked = 0;
+        for proposal in proposals {
+            if grouped.contains(&(proposal.governor_id, proposal.external_id.clone())) {
+                continue;
+            }
+            if self.link(forum, &proposal).await?.is_some() {
+                linked += 1;
+            }
+        }
+
+        info!(linked, "Proposals linked to their discussion topics");
+        Ok(())
+    }
+
+    /// Link a newly indexed proposal right away, rather than on the next
+    /// refresh cycle of its DAO's forum.
+    #[instrument(skip(self))]
+    pub async fn link_new_proposal(&self, proposal_id: Uuid) -> Result<()> {
+        let Some(proposal) = proposal::Entity::find_by_id(proposal_id)
+            .one(db())
+            .await
+            .context("Failed to fetch proposal")?
+        else {
+            debug!("Proposal not found, nothing to link");
+            return Ok(());
+        };
+        if proposal.discussion_url.is_none() || proposal.marked_spam {
+            return Ok(());
+        }
+
+        // A DAO may have several forums, the URL points at one of them
+        let forums = dao_discourse::Entity::find()
+            .filter(dao_discourse::Column::DaoId.eq(proposal.dao_id))
+            .all(db())
+            .await
+            .context("Failed to fetch DAO forums")?;
+        for forum in forums {
+            if self.link(&forum, &proposal).await?.is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn link(
+        &self,
+        forum: &dao_discourse::Model,
+        proposal: &proposal::Model,
+    ) -> Result<Option<Uuid>> {
+        let Some(url) = proposal.discussion_url.as_deref() else {
+            return Ok(None);
+        };
+        let Some(resolved) = self
+            .resolve_url(forum, url)
+            .await
+            .with_context(|| format!("Failed to resolve discussion URL {url}"))?
+        else {
+            debug!(proposal_id = %proposal.id, url, "Discussion URL doesn't point at a known topic");
+            return Ok(None);
+        };
+
+        link_proposal(forum, proposal, &resolved).await
+    }
+
+    async fn resolve_url(
+        &self,
+        forum: &dao_discourse::Model,
+        url: &str,
+    ) -> Result<Option<ResolvedTopic>> {
+        if let Some(reference) = parse_topic_reference(url, &forum.discourse_base_url) {
+            let resolved_by = match reference {
+                TopicReference::Id(_) => "topic_id",
+                TopicReference::Slug(_) => "slug",
+                TopicReference::Post(_) => "post",
+            };
+            if let Some(topic) = resolve_reference(forum.id, &reference).await? {
+                return Ok(Some(ResolvedTopic { topic, resolved_by }));
+            }
+        }
+
+        // Short links, renamed slugs and links to an old forum domain all
+        // redirect to the topic
+        let Some(redirected) = self.follow_redirects(url).await else {
+            return Ok(None);
+        };
+        let Some(reference) = parse_topic_reference(&redirected, &forum.discourse_base_url) else {
+            return Ok(None);
+        };
+        Ok(resolve_reference(forum.id, &reference)
+            .await?
+            .map(|topic| ResolvedTopic {
+                topic,
+                resolved_by: "redirect",
+            }))
+    }
+
+    /// Final URL of `url` when it redirects elsewhere.
+    async fn follow_redirects(&self, url: &str) -> Option<String> {
+        if !is_public_web_url(url) {
+            return None;
+        }
+        if let Some(redirected) = self.redirects.lock().unwrap().get(url) {
+            return redirected.clone();
+        }
+
+        let redirected = match self.http_client.head(url).send().await {
+            Ok(response) => Some(response.url().to_string()).filter(|final_url| final_url != url),
+            Err(e) => {
+                warn!(url, error = %e, "Failed to follow discussion URL redirects");
+                None
+            }
+        };
+        self.redirects
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), redirected.clone());
+        redirected
+    }
+}
+
+/// The topic a discussion URL refers to, when it's on the forum at
+/// `forum_base_url`.
+pub fn parse_topic_reference(url: &str, forum_base_url: &str) -> Option<TopicReference> {
+    let url = Url::parse(url.trim()).ok()?;
+    let forum = Url::parse(forum_base_url).ok()?;
+    let host = |url: &Url| {
+        url.host_str()
+            .map(|host| host.trim_start_matches("www.").to_lowercase())
+    };
+    if host(&url)? != host(&forum)? {
+        return None;
+    }
+
+    let segments: Vec<&str> = url
+        .path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let kind = segments
+        .iter()
+        .position(|segment| *segment == "t" || *segment == "p")?;
+    let number = |segment: &&str| segment.trim_end_matches(".json").parse::<i32>().ok();
+
+    match (segments[kind], &segments[kind + 1..]) {
+        ("p", [post, ..]) => number(post).map(TopicReference::Post),
+        ("t", [first, rest @ ..]) => number(first)
+            .or_else(|| rest.first().and_then(number))
+            .map(TopicReference::Id)
+            .or_else(|| Some(TopicReference::Slug(first.to_string()))),
+        _ => None,
+    }
+}
+
+/// Client for following discussion URLs. They come from proposal authors, so
+/// only redirects to public websites are followed.
+fn redirect_client() -> Result<Client> {
+    public_client(REDIRECT_TIMEOUT, MAX_REDIRECTS)
+}
+
+async fn resolve_reference(
+    dao_discourse_id: Uuid,
+    reference: &TopicReference,
+) -> Result<Option<discourse_topic::Model>> {
+    let topic_id = match reference {
+        TopicReference::Id(id) => *id,
+        TopicReference::Slug(slug) => {
+            // Slugs aren't unique, so only an unambiguous one resolves
+            let mut topics = discourse_topic::Entity::find()
+                .filter(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+                .filter(discourse_topic::Column::Slug.eq(slug.as_str()))
+                .limit(2)
+                .all(db())
+                .await
+                .context("Failed to fetch topics by slug")?;
+            return Ok((topics.len() == 1).then(|| topics.remove(0)));
+        }
+        TopicReference::Post(post_id) => {
+            let post = discourse_post::Entity::find()
+                .filter(discourse_post::Column::DaoDiscourseId.eq(dao_discourse_id))
+                .filter(discourse_post::Column::ExternalId.eq(*post_id))
+                .one(db())
+                .await
+                .context("Failed to fetch post")?;
+            match post {
+                Some(post) => post.topic_id,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    discourse_topic::Entity::find()
+        .filter(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .filter(discourse_topic::Column::ExternalId.eq(topic_id))
+        .one(db())
+        .await
+        .context("Failed to fetch topic")
+}
+
+/// Add the proposal to the group of its topic, creating the group when the
+/// topic has none, and record the decision. Returns the group, or nothing
+/// when the proposal was grouped in the meantime.
+async fn link_proposal(
+    forum: &dao_discourse::Model,
+    proposal: &proposal::Model,
+    resolved: &ResolvedTopic,
+) -> Result<Option<Uuid>> {
+    let topic = &resolved.topic;
+    let topic_item = ProposalGroupItem::Topic(TopicItem {
+        name: topic.title.clone(),
+        external_id: topic.external_id.to_string(),
+        dao_discourse_id: forum.id,
+    });
+    let proposal_item = ProposalGroupItem::Proposal(ProposalItem {
+        name: proposal.name.clone(),
+        governor_id: proposal.governor_id,
+        external_id: proposal.external_id.clone(),
+    });
+
+    let txn = db().begin().await?;
+    // Locked so concurrent groupings by the mapping agent can't interleave
+    let groups = proposal_group::Entity::find()
+        .filter(proposal_group::Column::DaoId.eq(forum.dao_id))
+        .lock_exclusive()
+        .all(&txn)
+        .await
+        .context("Failed to lock proposal groups")?;
+
+    if groups
+        .iter()
+        .any(|group| contains_item(&group.items, &proposal_item))
+    {
+        return Ok(None);
+    }
+
+    let group_id = match groups
+        .into_iter()
+        .find(|group| contains_item(&group.items, &topic_item))
+    {
+        Some(group) => {
+            let mut items = match group.items.clone() {
+                Value::Array(items) => items,
+                _ => Vec::new(),
+            };
+            items.push(serde_json::to_value(&proposal_item)?);
+
+            let mut group: proposal_group::ActiveModel = group.into();
+            group.items = Set(Value::Array(items));
+            group
+                .update(&txn)
+                .await
+                .context("Failed to add proposal to group")?
+                .id
+        }
+        None => {
+            proposal_group::ActiveModel {
+                id: NotSet,
+                name: Set(topic.title.clone()),
+                items: Set(json!([topic_item, proposal_item])),
+                created_at: Set(topic.created_at),
+                dao_id: Set(forum.dao_id),
+            }
+            .insert(&txn)
+            .await
+            .context("Failed to create proposal group")?
+            .id
+        }
+    };
+
+    mapping_proposal_decision::ActiveModel {
+        id: NotSet,
+        dao_id: Set(forum.dao_id),
+        proposal_id: Set(proposal.id),
+        target_group_id: Set(Some(group_id)),
+        decision_source: Set("deterministic".to_string()),
+        status: Set("accepted".to_string()),
+        confidence: Set(Some(URL_LINK_CONFIDENCE)),
+        reason: Set("The proposal's discussion URL points at the group's topic".to_string()),
+        evidence_ids: Set(json!([topic.id])),
+        metadata: Set(json!({
+            "discussion_url": proposal.discussion_url,
+            "topic_external_id": topic.external_id,
+            "resolved_by": resolved.resolved_by,
+        })),
+        created_at: NotSet,
+        session_trace: NotSet,
+        session_stats: NotSet,
+    }
+    .insert(&txn)
+    .await
+    .context("Failed to record proposal link decision")?;
+
+    txn.commit().await?;
+    info!(proposal_id = %proposal.id, topic_id = topic.external_id, group_id = %group_id, "Proposal linked to its discussion topic");
+    Ok(Some(group_id))
+}
+
+/// Items of a group that parse, skipping any this version doesn't know.
+/// Legacy rows may hold `{}` instead of an empty array.
+fn group_items(items: &Value) -> Vec<ProposalGroupItem> {
+    match items {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| serde_json::from_value(item.clone()).ok())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn contains_item(items: &Value, wanted: &ProposalGroupItem) -> bool {
+    group_items(items).iter().any(|item| match (item, wanted) {
+        (ProposalGroupItem::Topic(a), ProposalGroupItem::Topic(b)) => {
+            a.dao_discourse_id == b.dao_discourse_id && a.external_id == b.external_id
+        }
+        (ProposalGroupItem::Proposal(a), ProposalGroupItem::Proposal(b)) => {
+            a.governor_id == b.governor_id && a.external_id == b.external_id
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORUM: &str = "https://forum.arbitrum.foundation";
+
+    #[test]
+    fn test_parse_topic_reference() {
+        let parse = |url| parse_topic_reference(url, FORUM);
+
+        assert_eq!(
+            parse("https://forum.arbitrum.foundation/t/aip-arbos-version-11/19695"),
+            Some(TopicReference::Id(19695))
+        );
+        assert_eq!(
+            parse("https://forum.arbitrum.foundation/t/19695"),
+            Some(TopicReference::Id(19695))
+        );
+        assert_eq!(
+            parse("https://www.forum.arbitrum.foundation/t/aip-arbos-version-11/19695/12?u=alice"),
+            Some(TopicReference::Id(19695))
+        );
+        assert_eq!(
+            parse("https://forum.arbitrum.foundation/t/aip-arbos-version-11/19695.json#post_3"),
+            Some(TopicReference::Id(19695))
+        );
+        assert_eq!(
+            parse(" https://forum.arbitrum.foundation/t/aip-arbos-version-11 "),
+            Some(TopicReference::Slug("aip-arbos-version-11".to_string()))
+        );
+        assert_eq!(
+            parse("https://forum.arbitrum.foundation/p/40123"),
+            Some(TopicReference::Post(40123))
+        );
+
+        assert_eq!(parse("https://gov.uniswap.org/t/topic/19695"), None);
+        assert_eq!(
+            parse("https://forum.arbitrum.foundation/c/proposals/7"),
+            None
+        );
+        assert_eq!(parse("https://forum.arbitrum.foundation/t/"), None);
+        assert_eq!(parse("not a url"), None);
+    }
+
+    #[test]
+    fn test_contains_item() {
+        let forum_id = Uuid::from_u128(1);
+        let governor_id = Uuid::from_u128(2);
+        let topic = ProposalGroupItem::Topic(TopicItem {
+            name: "AIP: ArbOS Version 11".to_string(),
+            external_id: "19695".to_string(),
+            dao_discourse_id: forum_id,
+        });
+        let proposal = ProposalGroupItem::Proposal(ProposalItem {
+            name: "Constitutional AIP: ArbOS Version 11".to_string(),
+            governor_id,
+            external_id: "1".to_string(),
+        });
+
+        // Legacy snake_case keys and unknown items are tolerated
+        let items = json!([
+            {"type": "topic", "name": "Renamed", "external_id": "19695", "dao_discourse_id": forum_id},
+            {"type": "other"},
+        ]);
+        assert!(contains_item(&items, &topic));
+        assert!(!contains_item(&items, &proposal));
+
+        let items =
+            json!([{"type": "proposal", "name": "", "externalId": "1", "governorId": governor_id}]);
+        assert!(contains_item(&items, &proposal));
+        assert!(!contains_item(&json!({}), &topic));
+    }
+}
@@ -1,17 +1,18 @@
 use crate::{
     MAX_PAGES_PER_RUN, RECENT_LOOKBACK_HOURS,
-    db_handler::upsert_topic,
+    db_handler::{db, upsert_topic},
     discourse_api::DiscourseApi,
     indexers::{PageCursor, handle_join_result, posts::PostIndexer},
-    models::topics::TopicResponse,
+    models::topics::{Topic, TopicResponse},
 };
 use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
+use chrono::{Duration, NaiveDateTime, Utc};
+use proposalsapp_db::models::discourse_topic;
 use reqwest::Client;
-use sea_orm::prelude::Uuid;
-use std::{sync::Arc, time::Instant};
+use sea_orm::{ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QuerySelect, prelude::Uuid};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 use tokio::task::JoinSet;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 // Derive Clone for TopicIndexer
 #[derive(Clone)]
@@ -31,15 +32,35 @@ impl TopicIndexer {
     }
 
     /// Fetches and updates topics based on recent activity (e.g., new posts, bumps).
+    /// New posts come from the latest posts feed; bumped topics whose posts
+    /// changed in ways the feed doesn't show, like deletions, moves, edits or
+    /// likes, are re-crawled, and the first page of the others refreshed.
     /// Uses high priority for API requests.
     #[instrument(skip(self), fields(dao_discourse_id = %dao_discourse_id))]
     pub async fn update_recent_topics(&self, dao_discourse_id: Uuid) -> Result<()> {
         info!("Starting update of recent topics (high priority)");
+        let new_posts_by_topic = match self
+            .post_indexer
+            .update_latest_posts(dao_discourse_id, true)
+            .await
+        {
+            Ok(latest_posts) if latest_posts.complete => Some(latest_posts.new_posts_by_topic),
+            Ok(_) => {
+                info!("Latest posts feed incomplete, re-crawling all bumped topics");
+                None
+            }
+            Err(e) => {
+                warn!(error = ?e, "Failed to update latest posts, re-crawling all bumped topics");
+                None
+            }
+        };
+
         self.update_topics_internal(
             dao_discourse_id,
             false,                                        /* recent: uses activity descending */
             true,                                         /* priority */
             Some(Duration::hours(RECENT_LOOKBACK_HOURS)), /* lookback duration */
+            new_posts_by_topic.as_ref(),
         )
         .await
     }
@@ -55,18 +76,22 @@ impl TopicIndexer {
             true,  /* all: uses created ascending */
             false, /* priority */
             None,  /* no lookback duration */
+            None,  /* re-crawl every topic */
         )
         .await
     }
 
     /// Internal helper to fetch and process topics based on parameters.
-    #[instrument(skip(self), fields(dao_discourse_id = %dao_discourse_id, fetch_all = fetch_all, priority = priority, ?lookback))]
+    #[instrument(skip(self, new_posts_by_topic), fields(dao_discourse_id = %dao_discourse_id, fetch_all = fetch_all, priority = priority, ?lookback))]
     async fn update_topics_internal(
         &self,
         dao_discourse_id: Uuid,
         fetch_all: bool, // True for full refresh (created asc), false for recent (activity desc)
         priority: bool,
         lookback: Option<Duration>, // Used only when fetch_all is false
+        // New posts per topic already stored from the latest posts feed; when
+        // set, only topics with changes the feed doesn't show are re-crawled
+        new_posts_by_topic: Option<&HashMap<i32, i32>>,
     ) -> Result<()> {
         let start_time = Instant::now();
         info!("Starting topic update process");
@@ -119,6 +144,12 @@ impl TopicIndexer {
                         topics_on_page.sort_by_key(|topic| std::cmp::Reverse(topic.bumped_at));
                     }
 
+                    let stored_topics = stored_topics(dao_discourse_id, &topics_on_page)
+                        .await
+                        .inspect_err(|e| {
+                            warn!(error = ?e, page, "Failed to fetch stored topics, re-crawling topics on page");
+                        })
+                        .ok();
                     let mut topics_to_process_posts = Vec::new();
 
                     for topic in topics_on_page {
@@ -135,11 +166,40 @@ impl TopicIndexer {
                         total_processed_topics += 1;
                         let topic_external_id = topic.id; // Store for logging/potential use
 
-                        // Upsert the topic information first
-                        match upsert_topic(&topic, dao_discourse_id).await {
+                        let stored = stored_topics
+                            .as_ref()
+                            .and_then(|stored_topics| stored_topics.get(&topic.id));
+                        let new_posts = new_posts_by_topic
+                            .and_then(|new_posts_by_topic| new_posts_by_topic.get(&topic.id))
+                            .copied()
+                            .unwrap_or(0);
+                        let refresh = match (new_posts_by_topic, &stored_topics) {
+                            (Some(_), Some(_)) => posts_refresh(&topic, stored, new_posts),
+                            _ => PostsRefresh::Recrawl,
+                        };
+
+                        // Upsert the topic information first, keeping the
+                        // activity of the posts already stored until they're
+                        // refreshed
+                        let synced = match refresh {
+                            PostsRefresh::UpToDate => None,
+                            _ => Some(with_synced_activity(&topic, stored, new_posts)),
+                        };
+                        match upsert_topic(
+                            synced.as_ref().unwrap_or(&topic),
+                            dao_discourse_id,
+                            self.discourse_api.is_authenticated(),
+                        )
+                        .await
+                        {
+                            Ok(_) if refresh == PostsRefresh::UpToDate => {
+                                debug!(
+                                    topic_id = topic_external_id,
+                                    "Topic posts up to date from latest posts feed"
+                                );
+                            }
                             Ok(_) => {
-                                // Always queue post update for now (both recent and full)
-                                topics_to_process_posts.push(topic);
+                                topics_to_process_posts.push((topic, refresh));
                             }
                             Err(e) => {
                                 error!(error = ?e, topic_id = topic_external_id, "Failed to upsert topic, skipping post update for this topic.");
@@ -149,7 +209,7 @@ impl TopicIndexer {
                     } // End loop through topics on page
 
                     // Spawn tasks to update posts for the selected topics
-                    for topic_to_update in topics_to_process_posts {
+                    for (topic_to_update, refresh) in topics_to_process_posts {
                         // Limit concurrency
                         while join_set.len() >= max_concurrent_post_updates {
                             if let Some(res) = join_set.join_next().await {
@@ -163,12 +223,40 @@ impl TopicIndexer {
                         let post_fetcher = self.post_indexer.clone(); // Clone the PostIndexer
                         let dao_id_clone = dao_discourse_id;
                         let topic_id_clone = topic_to_update.id;
+                        let authenticated = self.discourse_api.is_authenticated();
 
                         debug!(topic_id = topic_id_clone, "Spawning post update task");
                         join_set.spawn(async move {
-                            post_fetcher
-                                .update_posts_for_topic(dao_id_clone, topic_id_clone, priority)
-                                .await
+                            let result = match refresh {
+                                PostsRefresh::FirstPage => {
+                                    post_fetcher
+                                        .update_first_posts_page(
+                                            dao_id_clone,
+                                            topic_id_clone,
+                                            priority,
+                                        )
+                                        .await
+                                }
+                                _ => {
+                                    post_fetcher
+                                        .update_posts_for_topic(
+                                            dao_id_clone,
+                                            topic_id_clone,
+                                            priority,
+                                        )
+                                        .await
+                                }
+                            };
+                            let result = match result {
+                                // The posts match the listing now, so its
+                                // activity can be stored
+                                Ok(()) => {
+                                    upsert_topic(&topic_to_update, dao_id_clone, authenticated)
+                                        .await
+                                }
+                                Err(e) => Err(e),
+                            };
+                            result
                                 // Wrap result for joinset handling
                                 .map_err(|e| {
                                     e.context(format!(
@@ -235,3 +323,227 @@ impl TopicIndexer {
         Ok(())
     }
 }
+
+/// What a topic's posts looked like when last stored, to tell what changed
+/// since from its listing.
+#[derive(Debug, Clone, FromQueryResult)]
+struct StoredTopic {
+    external_id: i32,
+    posts_count: i32,
+    like_count: i32,
+    last_posted_at: NaiveDateTime,
+    bumped_at: NaiveDateTime,
+}
+
+/// Topics stored for `topics` before this run updates them.
+async fn stored_topics(
+    dao_discourse_id: Uuid,
+    topics: &[Topic],
+) -> Result<HashMap<i32, StoredTopic>> {
+    let stored = discourse_topic::Entity::find()
+        .select_only()
+        .column(discourse_topic::Column::ExternalId)
+        .column(discourse_topic::Column::PostsCount)
+        .column(discourse_topic::Column::LikeCount)
+        .column(discourse_topic::Column::LastPostedAt)
+        .column(discourse_topic::Column::BumpedAt)
+        .filter(discourse_topic::Column::DaoDiscourseId.eq(dao_discourse_id))
+        .filter(discourse_topic::Column::ExternalId.is_in(topics.iter().map(|topic| topic.id)))
+        .into_model::<StoredTopic>()
+        .all(db())
+        .await
+        .context("Failed to fetch stored topics")?;
+
+    Ok(stored
+        .into_iter()
+        .map(|topic| (topic.external_id, topic))
+        .collect())
+}
+
+/// `listed` with the activity of its posts already stored: as stored before,
+/// or for a new topic only its posts from the feed. A topic is stored this
+/// way until its posts are refreshed, so a refresh that fails is tried again
+/// on the next run.
+fn with_synced_activity(listed: &Topic, stored: Option<&StoredTopic>, new_posts: i32) -> Topic {
+    let mut topic = listed.clone();
+    match stored {
+        Some(stored) => {
+            topic.posts_count = stored.posts_count;
+            topic.like_count = stored.like_count;
+            topic.last_posted_at = stored.last_posted_at.and_utc();
+            topic.bumped_at = stored.bumped_at.and_utc();
+        }
+        None => topic.posts_count = new_posts,
+    }
+    topic
+}
+
+/// How much of a topic's posts to fetch again besides the new posts taken
+/// from the latest posts feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PostsRefresh {
+    UpToDate,
+    /// Only the first page, where the opening post is. Editing it doesn't
+    /// bump the topic, so it's refreshed whenever the topic has new posts.
+    FirstPage,
+    /// Every page, as posts changed in ways the feed doesn't show.
+    Recrawl,
+}
+
+/// What to refresh of a listed topic's posts, given how it was stored and how
+/// many new posts of it the feed had.
+fn posts_refresh(listed: &Topic, stored: Option<&StoredTopic>, new_posts: i32) -> PostsRefresh {
+    let Some(stored) = stored else {
+        return if new_posts == listed.posts_count {
+            PostsRefresh::UpToDate
+        } else {
+            PostsRefresh::Recrawl
+        };
+    };
+
+    // Deleted or moved posts, or posts moved in
+    if stored.posts_count + new_posts != listed.posts_count {
+        return PostsRefresh::Recrawl;
+    }
+
+    let active = listed.bumped_at.naive_utc() != stored.bumped_at
+        || listed.last_posted_at.naive_utc() != stored.last_posted_at
+        || listed.like_count != stored.like_count;
+    match (active, new_posts) {
+        (false, _) => PostsRefresh::UpToDate,
+        // Activity without new posts, like edits that bumped the topic or likes
+        (true, 0) => PostsRefresh::Recrawl,
+        (true, _) => PostsRefresh::FirstPage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone};
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn listed(posts_count: i32, like_count: i32, bumped_at: DateTime<Utc>) -> Topic {
+        Topic {
+            id: 1,
+            title: "Grants program".to_string(),
+            fancy_title: "Grants program".to_string(),
+            slug: "grants-program".to_string(),
+            posts_count,
+            reply_count: 0,
+            created_at: at(0),
+            last_posted_at: bumped_at,
+            bumped: true,
+            bumped_at,
+            pinned: false,
+            visible: true,
+            closed: false,
+            archived: false,
+            liked: None,
+            views: 100,
+            like_count,
+            category_id: 1,
+            pinned_globally: false,
+        }
+    }
+
+    fn stored(posts_count: i32, like_count: i32, bumped_at: DateTime<Utc>) -> StoredTopic {
+        StoredTopic {
+            external_id: 1,
+            posts_count,
+            like_count,
+            last_posted_at: bumped_at.naive_utc(),
+            bumped_at: bumped_at.naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_posts_refresh() {
+        // Nothing happened
+        assert_eq!(
+            posts_refresh(&listed(10, 5, at(1)), Some(&stored(10, 5, at(1))), 0),
+            PostsRefresh::UpToDate
+        );
+        // New posts from the feed account for the change, the opening post
+        // may have been edited alongside
+        assert_eq!(
+            posts_refresh(&listed(12, 5, at(2)), Some(&stored(10, 5, at(1))), 2),
+            PostsRefresh::FirstPage
+        );
+        assert_eq!(
+            posts_refresh(&listed(1, 0, at(1)), None, 1),
+            PostsRefresh::UpToDate
+        );
+        // A post was deleted or moved out, possibly alongside a new one
+        assert_eq!(
+            posts_refresh(&listed(9, 5, at(1)), Some(&stored(10, 5, at(1))), 0),
+            PostsRefresh::Recrawl
+        );
+        assert_eq!(
+            posts_refresh(&listed(10, 5, at(2)), Some(&stored(10, 5, at(1))), 1),
+            PostsRefresh::Recrawl
+        );
+        // Posts appeared that the feed didn't show, like posts moved in
+        assert_eq!(
+            posts_refresh(&listed(14, 5, at(2)), Some(&stored(10, 5, at(1))), 1),
+            PostsRefresh::Recrawl
+        );
+        assert_eq!(
+            posts_refresh(&listed(5, 0, at(1)), None, 0),
+            PostsRefresh::Recrawl
+        );
+    }
+
+    #[test]
+    fn test_posts_refresh_without_new_posts() {
+        // The posts count is unchanged but the opening post was edited, which
+        // bumped the topic
+        assert_eq!(
+            posts_refresh(&listed(1, 0, at(2)), Some(&stored(1, 0, at(1))), 0),
+            PostsRefresh::Recrawl
+        );
+        // A post was liked
+        assert_eq!(
+            posts_refresh(&listed(10, 6, at(1)), Some(&stored(10, 5, at(1))), 0),
+            PostsRefresh::Recrawl
+        );
+    }
+
+    #[test]
+    fn test_failed_refresh_is_retried() {
+        // What the next run finds stored after a refresh that failed
+        let stored_after_failure = |listed: &Topic, stored: Option<&StoredTopic>, new_posts| {
+            let topic = with_synced_activity(listed, stored, new_posts);
+            StoredTopic {
+                external_id: topic.id,
+                posts_count: topic.posts_count,
+                like_count: topic.like_count,
+                last_posted_at: topic.last_posted_at.naive_utc(),
+                bumped_at: topic.bumped_at.naive_utc(),
+            }
+        };
+
+        for (listed, stored, new_posts) in [
+            // New posts and maybe an edited opening post
+            (listed(12, 5, at(2)), Some(stored(10, 5, at(1))), 2),
+            // An edit that bumped the topic
+            (listed(10, 5, at(2)), Some(stored(10, 5, at(1))), 0),
+            // A new topic the feed only had some posts of
+            (listed(5, 0, at(1)), None, 2),
+        ] {
+            assert_ne!(
+                posts_refresh(&listed, stored.as_ref(), new_posts),
+                PostsRefresh::UpToDate
+            );
+            let stored = stored_after_failure(&listed, stored.as_ref(), new_posts);
+            // The feed's posts were stored, so it has none for the topic now
+            assert_eq!(
+                posts_refresh(&listed, Some(&stored), 0),
+                PostsRefresh::Recrawl
+            );
+        }
+    }
+}
@@ -4,10 +4,10 @@ use anyhow::{Context, Error, Result};
 use axum::Router;
 use discourse::{
     db_handler::{db, initialize_db},
-    discourse_api::DiscourseApi,
+    discourse_api::{DiscourseApi, DiscourseCredentials, RateLimit},
     indexers::{
-        categories::CategoryIndexer, revisions::RevisionIndexer, topics::TopicIndexer,
-        users::UserIndexer,
+        categories::CategoryIndexer, ownership::OwnershipVerifier, proposal_links::ProposalLinker,
+        revisions::RevisionIndexer, topics::TopicIndexer, users::UserIndexer,
     },
 };
 use dotenv::dotenv;
@@ -22,6 +22,10 @@ use std::{
 use tokio::{task::JoinSet, time::interval_at};
 use tracing::{Instrument, error, info, instrument, warn};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use utils::{
+    job_queue::{WorkerConfig, run_worker},
+    types::ProposalJobData,
+};
 
 // --- Configuration Constants ---
 const FULL_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
@@ -30,6 +34,11 @@ const INITIAL_FULL_REFRESH_TASK_DELAY: Duration = Duration::from_secs(60);
 const RECENT_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
 const INITIAL_RECENT_UPDATE_TASK_DELAY: Duration = Duration::from_secs(5);
 
+const OWNERSHIP_VERIFICATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const INITIAL_OWNERSHIP_VERIFICATION_TASK_DELAY: Duration = Duration::from_secs(120);
+
+const JOB_WORKER_RESTART_DELAY: Duration = Duration::from_secs(5);
+
 #[tokio::main]
 #[instrument]
 async fn main() -> Result<()> {
@@ -137,10 +146,26 @@ async fn main() -> Result<()> {
                 warn!(dao_discourse_id = %dao_config.id, "Missing DAO details for dao_discourse record. Using ID as name.");
                 dao_config.id.to_string()
             });
-
-        info!(%dao_name, dao_id = %dao_config.id, base_url = %dao_config.discourse_base_url, "Initializing indexer tasks for DAO");
-
-        let api_client = Arc::new(DiscourseApi::new(dao_config.discourse_base_url.clone()));
+        let dao_slug = dao_details_vec
+            .first()
+            .map(|d| d.slug.clone())
+            .unwrap_or_default();
+
+        let credentials = DiscourseCredentials::from_env(&dao_slug)
+            .with_context(|| format!("Invalid Discourse credentials for {dao_name}"))?;
+        let rate_limit = RateLimit::from_env(&dao_slug, credentials.as_ref())
+            .with_context(|| format!("Invalid Discourse rate limit for {dao_name}"))?;
+
+        info!(%dao_name, dao_id = %dao_config.id, base_url = %dao_config.discourse_base_url, authenticated = credentials.is_some(), "Initializing indexer tasks for DAO");
+
+        let api_client = Arc::new(
+            DiscourseApi::with_credentials(
+                dao_config.discourse_base_url.clone(),
+                credentials,
+                rate_limit,
+            )
+            .with_context(|| format!("Failed to create Discourse API client for {dao_name}"))?,
+        );
 
         // Create indexer instances
         let category_indexer = CategoryIndexer::new(Arc::clone(&api_client));
@@ -149,6 +174,8 @@ async fn main() -> Result<()> {
         let topic_indexer =
             TopicIndexer::new(Arc::clone(&api_client), Arc::clone(&shared_http_client));
         let revision_indexer = RevisionIndexer::new(Arc::clone(&api_client));
+        let proposal_linker = ProposalLinker::new()?;
+        let ownership_verifier = OwnershipVerifier::new(Arc::clone(&api_client));
 
         // --- Spawn Full Refresh Task ---
         let dao_id_full = dao_config.id;
@@ -171,17 +198,14 @@ async fn main() -> Result<()> {
                 let rev_idx_full = rev_idx_full.clone();
 
                 async move {
-                    let cat_fut = cat_idx_full.update_all_categories(dao_id_full);
+                    // Categories first, so topics and posts pick up their read restriction
+                    let cat_res = cat_idx_full.update_all_categories(dao_id_full).await;
                     let user_fut = user_idx_full.update_all_users(dao_id_full);
                     let topic_fut = topic_idx_full.update_all_topics(dao_id_full);
                     let rev_fut = rev_idx_full.update_all_revisions(dao_id_full);
 
-                    let (cat_res, user_res, topic_res, rev_res): (
-                        Result<()>,
-                        Result<()>,
-                        Result<()>,
-                        Result<()>,
-                    ) = tokio::join!(cat_fut, user_fut, topic_fut, rev_fut);
+                    let (user_res, topic_res, rev_res): (Result<()>, Result<()>, Result<()>) =
+                        tokio::join!(user_fut, topic_fut, rev_fut);
 
                     log_indexer_result("Full Categories", &cat_res);
                     log_indexer_result("Full Users", &user_res);
@@ -199,6 +223,8 @@ async fn main() -> Result<()> {
         let user_idx_recent = user_indexer.clone();
         let topic_idx_recent = topic_indexer.clone();
         let rev_idx_recent = revision_indexer.clone();
+        let linker_recent = proposal_linker.clone();
+        let forum_recent = dao_config.clone();
 
         spawn_refresh_loop(
             &mut indexer_tasks,
@@ -212,31 +238,74 @@ async fn main() -> Result<()> {
                 let user_idx_recent = user_idx_recent.clone();
                 let topic_idx_recent = topic_idx_recent.clone();
                 let rev_idx_recent = rev_idx_recent.clone();
+                let linker_recent = linker_recent.clone();
+                let forum_recent = forum_recent.clone();
 
                 async move {
-                    let cat_fut = cat_idx_recent.update_all_categories(dao_id_recent);
+                    let cat_res = cat_idx_recent.update_all_categories(dao_id_recent).await;
                     let user_fut = user_idx_recent.update_recent_users(dao_id_recent);
                     let topic_fut = topic_idx_recent.update_recent_topics(dao_id_recent);
                     let rev_fut = rev_idx_recent.update_recent_revisions(dao_id_recent);
 
-                    let (cat_res, user_res, topic_res, rev_res): (
-                        Result<()>,
-                        Result<()>,
-                        Result<()>,
-                        Result<()>,
-                    ) = tokio::join!(cat_fut, user_fut, topic_fut, rev_fut);
+                    let (user_res, topic_res, rev_res): (Result<()>, Result<()>, Result<()>) =
+                        tokio::join!(user_fut, topic_fut, rev_fut);
 
                     log_indexer_result("Full Categories", &cat_res);
                     log_indexer_result("Recent Users", &user_res);
                     log_indexer_result("Recent Topics/Posts", &topic_res);
                     log_indexer_result("Recent Revisions", &rev_res);
 
+                    // Topics are indexed by now, so discussion URLs can resolve
+                    let link_res = linker_recent.link_proposals(&forum_recent).await;
+                    log_indexer_result("Proposal Links", &link_res);
+
+                    Ok(())
+                }
+            },
+        );
+
+        // --- Spawn Ownership Verification Task ---
+        let forum_ownership = dao_config.clone();
+
+        spawn_refresh_loop(
+            &mut indexer_tasks,
+            dao_name.clone(),
+            "ownership_verification",
+            INITIAL_OWNERSHIP_VERIFICATION_TASK_DELAY,
+            OWNERSHIP_VERIFICATION_INTERVAL,
+            "ownership verification",
+            move || {
+                let ownership_verifier = ownership_verifier.clone();
+                let forum_ownership = forum_ownership.clone();
+
+                async move {
+                    let res = ownership_verifier.verify_links(&forum_ownership).await;
+                    log_indexer_result("Ownership Verification", &res);
                     Ok(())
                 }
             },
         );
     }
 
+    // --- Spawn Job Worker ---
+    // New proposals are linked as soon as the indexer stores them
+    let job_linker = ProposalLinker::new()?;
+    indexer_tasks.spawn(
+        async move {
+            let config = WorkerConfig::new(format!("discourse-{}", std::process::id()));
+            loop {
+                let worker_res = run_worker(db(), &config, |job: ProposalJobData| {
+                    let job_linker = job_linker.clone();
+                    async move { job_linker.link_new_proposal(job.proposal_id).await }
+                })
+                .await;
+                log_indexer_result("Job Worker", &worker_res);
+                tokio::time::sleep(JOB_WORKER_RESTART_DELAY).await;
+            }
+        }
+        .instrument(tracing::info_span!("job_worker")),
+    );
+
     info!("All indexer tasks started, application running indefinitely");
 
     // Wait for any task to complete or for shutdown signal
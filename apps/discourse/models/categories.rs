@@ -26,5 +26,8 @@ pub struct Category {
     pub topics_month: Option<i32>,
     pub topics_year: Option<i32>,
     pub topics_all_time: Option<i32>,
+    // Only present for users who can see restricted categories
+    #[serde(default)]
+    pub read_restricted: bool,
     pub subcategory_list: Option<Vec<Category>>,
 }
@@ -51,6 +51,22 @@ pub struct PostResponse {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PostStream {
     pub posts: Vec<Post>,
+    /// IDs of every post of the topic, oldest first, the first page of which
+    /// is in `posts`.
+    #[serde(default)]
+    pub stream: Vec<i32>,
+}
+
+/// Posts of a topic picked by ID, from `/t/{id}/posts.json`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TopicPostsResponse {
+    pub post_stream: PostStream,
+}
+
+/// A page of `/posts.json`, the forum's posts newest first.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LatestPostsResponse {
+    pub latest_posts: Vec<Post>,
 }
 
 mod date_format {
@@ -1,20 +1,7 @@
 use anyhow::Result;
 use scraper::{Html, Selector};
 use thiserror::Error;
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum DiffType {
-    Deletion,
-    Insertion,
-    Unchanged,
-}
-
-#[derive(Debug, Clone)]
-pub struct DiffNode {
-    pub content: String,
-    pub diff_type: DiffType,
-    pub children: Vec<DiffNode>,
-}
+use utils::diff::{DiffNode, DiffType};
 
 #[derive(Debug, Error)]
 pub enum MarkdownDiffError {
@@ -22,46 +9,6 @@ pub enum MarkdownDiffError {
     SelectorError(String),
 }
 
-impl DiffNode {
-    pub fn new(content: String, diff_type: DiffType) -> Self {
-        Self {
-            content,
-            diff_type,
-            children: Vec::new(),
-        }
-    }
-
-    pub fn add_child(&mut self, child: DiffNode) {
-        self.children.push(child);
-    }
-
-    pub fn get_before_content(&self) -> String {
-        match self.diff_type {
-            DiffType::Deletion | DiffType::Unchanged => {
-                let mut content = self.content.clone();
-                for child in &self.children {
-                    content.push_str(&child.get_before_content());
-                }
-                content
-            }
-            DiffType::Insertion => String::new(),
-        }
-    }
-
-    pub fn get_after_content(&self) -> String {
-        match self.diff_type {
-            DiffType::Insertion | DiffType::Unchanged => {
-                let mut content = self.content.clone();
-                for child in &self.children {
-                    content.push_str(&child.get_after_content());
-                }
-                content
-            }
-            DiffType::Deletion => String::new(),
-        }
-    }
-}
-
 pub fn parse_markdown_diff(content: &str) -> Result<DiffNode> {
     let html = Html::parse_fragment(content);
     let table_selector = Selector::parse("table.markdown").map_err(|e| {
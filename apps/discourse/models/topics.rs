@@ -12,7 +12,7 @@ pub struct TopicList {
     pub per_page: i32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Topic {
     pub id: i32,
     pub title: String,
@@ -12,6 +12,8 @@ pub struct UserDetail {
     pub name: Option<String>,
     pub avatar_template: String,
     pub title: Option<String>,
+    pub bio_raw: Option<String>,
+    pub bio_cooked: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -1,8 +1,12 @@
 use anyhow::{Context, Result, anyhow};
 use chrono::{TimeZone, Utc};
 use discourse::db_handler::{
-    get_or_create_unknown_user, get_post_like_count, initialize_db, upsert_category, upsert_post,
-    upsert_post_likes_batch, upsert_revision, upsert_topic, upsert_user,
+    advance_post_high_water_mark, get_or_create_unknown_user, get_post_high_water_mark,
+    get_post_like_count, initialize_db, upsert_category, upsert_post, upsert_post_likes_batch,
+    upsert_revision, upsert_topic, upsert_user,
+};
+use discourse::indexers::ownership::{
+    Evidence, OwnershipCheck, ProofLocation, issue_challenge, record_ownership_check,
 };
 use discourse::models::{
     categories::Category,
@@ -13,10 +17,15 @@ use discourse::models::{
 };
 use once_cell::sync::Lazy;
 use proposalsapp_db::models::{
-    dao, dao_discourse, discourse_category, discourse_post, discourse_post_revision,
-    discourse_topic, discourse_user,
+    dao, dao_discourse, delegate, delegate_to_discourse_user,
+    delegate_to_discourse_user_verification, discourse_category, discourse_post,
+    discourse_post_revision, discourse_topic, discourse_user, governance_event,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set, prelude::Uuid,
 };
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, prelude::Uuid};
+use serde_json::json;
 use serial_test::serial;
 use std::{
     fs,
@@ -191,6 +200,8 @@ async fn seed_discourse(db: &DatabaseConnection) -> Result<SeedData> {
         id: Set(dao_discourse_id),
         dao_id: Set(dao_id),
         discourse_base_url: Set("https://forum.example.com".to_string()),
+        verification_topic_id: Set(None),
+        post_high_water_id: Set(None),
     };
 
     dao_discourse::Entity::insert(dao_discourse_model)
@@ -255,6 +266,7 @@ fn test_upsert_user_and_category() -> Result<()> {
             topics_month: Some(3),
             topics_year: Some(4),
             topics_all_time: Some(5),
+            read_restricted: false,
             subcategory_list: None,
         };
 
@@ -306,6 +318,45 @@ fn test_get_or_create_unknown_user() -> Result<()> {
     })
 }
 
+#[test]
+#[serial]
+fn test_post_high_water_mark_only_advances() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let context = test_context().await?;
+
+        assert_eq!(
+            get_post_high_water_mark(context.dao_discourse_id).await?,
+            None
+        );
+
+        advance_post_high_water_mark(context.dao_discourse_id, 120).await?;
+        assert_eq!(
+            get_post_high_water_mark(context.dao_discourse_id).await?,
+            Some(120)
+        );
+
+        // A slower, overlapping run finishing later doesn't move it back
+        advance_post_high_water_mark(context.dao_discourse_id, 110).await?;
+        assert_eq!(
+            get_post_high_water_mark(context.dao_discourse_id).await?,
+            Some(120)
+        );
+
+        advance_post_high_water_mark(context.dao_discourse_id, 130).await?;
+        assert_eq!(
+            get_post_high_water_mark(context.dao_discourse_id).await?,
+            Some(130)
+        );
+
+        Ok(())
+    })
+}
+
 #[test]
 #[serial]
 fn test_upsert_topic_and_post() -> Result<()> {
@@ -339,7 +390,7 @@ fn test_upsert_topic_and_post() -> Result<()> {
             pinned_globally: false,
         };
 
-        upsert_topic(&topic, context.dao_discourse_id).await?;
+        upsert_topic(&topic, context.dao_discourse_id, false).await?;
 
         let post = Post {
             id: 9001,
@@ -372,7 +423,7 @@ fn test_upsert_topic_and_post() -> Result<()> {
             actions_summary: vec![ActionSummary { id: 2, count: 1 }],
         };
 
-        upsert_post(&post, context.dao_discourse_id).await?;
+        upsert_post(&post, context.dao_discourse_id, false).await?;
 
         let stored_topic = discourse_topic::Entity::find()
             .filter(discourse_topic::Column::ExternalId.eq(topic.id))
@@ -395,6 +446,188 @@ fn test_upsert_topic_and_post() -> Result<()> {
         assert_eq!(stored_post.topic_id, topic.id);
         assert!(!stored_post.deleted);
 
+        // New topics and edited posts are published, re-indexing alone isn't
+        upsert_topic(&topic, context.dao_discourse_id, false).await?;
+        upsert_post(&post, context.dao_discourse_id, false).await?;
+        let edited = Post {
+            version: 2,
+            raw: Some("Hello edited world".to_string()),
+            ..post
+        };
+        upsert_post(&edited, context.dao_discourse_id, false).await?;
+
+        let events: Vec<_> = governance_event::Entity::find()
+            .filter(governance_event::Column::DaoId.eq(Uuid::from_u128(1)))
+            .order_by_asc(governance_event::Column::Id)
+            .all(&context.db)
+            .await
+            .context("failed to query governance_event")?
+            .into_iter()
+            .filter_map(|event| match event.event_type.as_str() {
+                "topic_created" if event.payload["external_id"] == topic.id => {
+                    Some(("topic_created", event.payload["topic_id"].clone()))
+                }
+                "post_edited" if event.payload["external_id"] == edited.id => {
+                    Some(("post_edited", event.payload["version"].clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("topic_created", json!(stored_topic.id)),
+                ("post_edited", json!(2)),
+            ]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_restricted_category_tags_topics_and_posts() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let context = test_context().await?;
+
+        let mut category = Category {
+            id: 511,
+            name: "Delegates Only".to_string(),
+            color: "FF0000".to_string(),
+            text_color: "FFFFFF".to_string(),
+            slug: "delegates-only".to_string(),
+            topic_count: 1,
+            post_count: 1,
+            description: None,
+            description_text: None,
+            topics_day: None,
+            topics_week: None,
+            topics_month: None,
+            topics_year: None,
+            topics_all_time: None,
+            read_restricted: true,
+            subcategory_list: None,
+        };
+        let topic = Topic {
+            id: 711,
+            title: "Private Topic".to_string(),
+            fancy_title: "Private Topic".to_string(),
+            slug: "private-topic".to_string(),
+            posts_count: 1,
+            reply_count: 0,
+            created_at: Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+            last_posted_at: Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+            bumped: true,
+            bumped_at: Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+            pinned: false,
+            visible: true,
+            closed: false,
+            archived: false,
+            liked: None,
+            views: 1,
+            like_count: 0,
+            category_id: category.id,
+            pinned_globally: false,
+        };
+        let post = Post {
+            id: 9011,
+            name: None,
+            username: "bob".to_string(),
+            avatar_template: "https://example.com/avatar-bob.png".to_string(),
+            created_at: Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+            raw: Some("Delegates only".to_string()),
+            post_number: 1,
+            post_type: 1,
+            updated_at: Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+            reply_count: 0,
+            reply_to_post_number: None,
+            quote_count: 0,
+            incoming_link_count: 0,
+            reads: 1,
+            readers_count: 1,
+            score: 0.0,
+            topic_id: topic.id,
+            topic_slug: topic.slug.clone(),
+            display_username: None,
+            primary_group_name: None,
+            flair_name: None,
+            flair_url: None,
+            flair_bg_color: None,
+            flair_color: None,
+            version: 1,
+            can_view_edit_history: true,
+            user_id: 102,
+            actions_summary: vec![],
+        };
+
+        let restriction = || async {
+            let topic = discourse_topic::Entity::find()
+                .filter(discourse_topic::Column::ExternalId.eq(topic.id))
+                .filter(discourse_topic::Column::DaoDiscourseId.eq(context.dao_discourse_id))
+                .one(&context.db)
+                .await?
+                .context("missing discourse_topic record")?;
+            let post = discourse_post::Entity::find()
+                .filter(discourse_post::Column::ExternalId.eq(post.id))
+                .filter(discourse_post::Column::DaoDiscourseId.eq(context.dao_discourse_id))
+                .one(&context.db)
+                .await?
+                .context("missing discourse_post record")?;
+            Ok::<_, anyhow::Error>((topic.restricted, post.restricted))
+        };
+        let whisper = Post {
+            id: 9012,
+            post_number: 2,
+            post_type: 4,
+            raw: Some("Staff only".to_string()),
+            ..post.clone()
+        };
+        let whisper_restricted = || async {
+            Ok::<_, anyhow::Error>(
+                discourse_post::Entity::find()
+                    .filter(discourse_post::Column::ExternalId.eq(whisper.id))
+                    .filter(discourse_post::Column::DaoDiscourseId.eq(context.dao_discourse_id))
+                    .one(&context.db)
+                    .await?
+                    .context("missing whisper discourse_post record")?
+                    .restricted,
+            )
+        };
+
+        // The feed of an authenticated forum can deliver a post before its
+        // topic and category are stored, which keeps it restricted
+        upsert_post(&post, context.dao_discourse_id, true).await?;
+        upsert_post(&whisper, context.dao_discourse_id, true).await?;
+        upsert_topic(&topic, context.dao_discourse_id, true).await?;
+        assert_eq!(restriction().await?, (true, true));
+        upsert_category(&category, context.dao_discourse_id).await?;
+        assert_eq!(restriction().await?, (true, true));
+
+        let topic_events = governance_event::Entity::find()
+            .filter(governance_event::Column::EventType.eq("topic_created"))
+            .all(&context.db)
+            .await?
+            .into_iter()
+            .filter(|event| event.payload["external_id"] == topic.id)
+            .count();
+        assert_eq!(topic_events, 0);
+
+        // Opening the category up releases what was indexed under it, but
+        // staff whispers
+        category.read_restricted = false;
+        upsert_category(&category, context.dao_discourse_id).await?;
+        assert_eq!(restriction().await?, (false, false));
+        assert!(whisper_restricted().await?);
+        upsert_topic(&topic, context.dao_discourse_id, true).await?;
+        assert_eq!(restriction().await?, (false, false));
+        assert!(whisper_restricted().await?);
+
         Ok(())
     })
 }
@@ -441,7 +674,7 @@ fn test_upsert_deleted_post_marks_deleted() -> Result<()> {
             actions_summary: vec![],
         };
 
-        upsert_post(&post, context.dao_discourse_id).await?;
+        upsert_post(&post, context.dao_discourse_id, false).await?;
 
         let stored_post = discourse_post::Entity::find()
             .filter(discourse_post::Column::ExternalId.eq(post.id))
@@ -500,7 +733,7 @@ fn test_upsert_revision_and_likes() -> Result<()> {
             actions_summary: vec![ActionSummary { id: 2, count: 2 }],
         };
 
-        upsert_post(&post, context.dao_discourse_id).await?;
+        upsert_post(&post, context.dao_discourse_id, false).await?;
 
         let stored_post = discourse_post::Entity::find()
             .filter(discourse_post::Column::ExternalId.eq(post.id))
@@ -561,3 +794,113 @@ fn test_upsert_revision_and_likes() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+#[serial]
+fn test_ownership_checks_verify_and_revoke_links() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let context = test_context().await?;
+        let forum = dao_discourse::Entity::find_by_id(context.dao_discourse_id)
+            .one(&context.db)
+            .await?
+            .context("missing dao_discourse record")?;
+
+        let user = User {
+            id: 401,
+            username: "carol".to_string(),
+            name: None,
+            avatar_template: "https://example.com/avatar.png".to_string(),
+            title: None,
+            likes_received: None,
+            likes_given: None,
+            topics_entered: None,
+            topic_count: None,
+            post_count: None,
+            posts_read: None,
+            days_visited: None,
+        };
+        upsert_user(&user, forum.id).await?;
+        let stored_user = discourse_user::Entity::find()
+            .filter(discourse_user::Column::ExternalId.eq(user.id))
+            .filter(discourse_user::Column::DaoDiscourseId.eq(forum.id))
+            .one(&context.db)
+            .await?
+            .context("missing discourse_user record")?;
+
+        let delegate = delegate::ActiveModel {
+            id: Set(Uuid::from_u128(40)),
+            dao_id: Set(forum.dao_id),
+        }
+        .insert(&context.db)
+        .await
+        .context("failed to insert delegate")?;
+        let now = Utc::now().naive_utc();
+        let link = delegate_to_discourse_user::ActiveModel {
+            id: Set(Uuid::from_u128(41)),
+            delegate_id: Set(delegate.id),
+            discourse_user_id: Set(stored_user.id),
+            period_start: Set(now - chrono::Duration::days(1)),
+            period_end: Set(now + chrono::Duration::days(30)),
+            proof: Set(None),
+            verified: Set(false),
+            created_at: Set(now),
+            challenge: NotSet,
+            challenge_issued_at: NotSet,
+        }
+        .insert(&context.db)
+        .await
+        .context("failed to insert delegate_to_discourse_user")?;
+
+        // Challenges are issued once per link
+        let challenge = issue_challenge(link.id).await?;
+        assert!(challenge.starts_with("proposalsapp-verify-"));
+        assert_eq!(issue_challenge(link.id).await?, challenge);
+
+        let refetch = || async {
+            delegate_to_discourse_user::Entity::find_by_id(link.id)
+                .one(&context.db)
+                .await?
+                .context("missing delegate_to_discourse_user record")
+        };
+        let verified = OwnershipCheck::Verified {
+            location: ProofLocation::ProfileBio,
+            evidence: Evidence {
+                url: "https://forum.example.com/u/carol".to_string(),
+                post_id: None,
+                signed_by: None,
+            },
+        };
+        assert!(record_ownership_check(&refetch().await?, &challenge, &verified).await?);
+        assert!(refetch().await?.verified);
+        // Unchanged outcomes aren't recorded again
+        assert!(!record_ownership_check(&refetch().await?, &challenge, &verified).await?);
+
+        // The challenge disappearing revokes the link
+        let rejected = OwnershipCheck::Rejected("Challenge not found".to_string());
+        assert!(record_ownership_check(&refetch().await?, &challenge, &rejected).await?);
+        assert!(!refetch().await?.verified);
+
+        let checks = delegate_to_discourse_user_verification::Entity::find()
+            .filter(
+                delegate_to_discourse_user_verification::Column::DelegateToDiscourseUserId
+                    .eq(link.id),
+            )
+            .order_by_asc(delegate_to_discourse_user_verification::Column::CheckedAt)
+            .all(&context.db)
+            .await?;
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].method.as_deref(), Some("PROFILE_BIO"));
+        assert_eq!(
+            checks[0].evidence,
+            Some(json!({ "url": "https://forum.example.com/u/carol", "signed_by": null }))
+        );
+        assert_eq!(checks[1].error.as_deref(), Some("Challenge not found"));
+
+        Ok(())
+    })
+}
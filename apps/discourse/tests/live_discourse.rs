@@ -7,7 +7,7 @@ use discourse::discourse_api::DiscourseApi;
 use discourse::models::{
     categories::CategoryResponse,
     likes::PostLikeResponse,
-    posts::{Post, PostResponse},
+    posts::{LatestPostsResponse, Post, PostResponse},
     revisions::Revision,
     topics::TopicResponse,
     users::{User, UserDetailResponse, UserResponse},
@@ -223,6 +223,8 @@ async fn seed_discourse(db: &DatabaseConnection, base_url: &str) -> Result<SeedD
         id: Set(dao_discourse_id),
         dao_id: Set(dao_id),
         discourse_base_url: Set(base_url.to_string()),
+        verification_topic_id: Set(None),
+        post_high_water_id: Set(None),
     };
 
     dao_discourse::Entity::insert(dao_discourse_model)
@@ -313,16 +315,13 @@ fn live_discourse_endpoints() -> Result<()> {
             "category topics response missing topic_list"
         );
 
-        let latest_posts: Value = api
-            .queue(LATEST_POSTS_ENDPOINT, true)
+        let latest_posts = api
+            .queue::<LatestPostsResponse>(LATEST_POSTS_ENDPOINT, true)
             .await
             .context("fetch latest posts")?;
         ensure!(
-            latest_posts
-                .get("latest_posts")
-                .and_then(Value::as_array)
-                .is_some(),
-            "latest posts response missing latest_posts"
+            !latest_posts.latest_posts.is_empty(),
+            "latest posts response was empty"
         );
 
         let topics = api
@@ -342,7 +341,7 @@ fn live_discourse_endpoints() -> Result<()> {
         let mut revision_post: Option<Post> = None;
 
         for topic in topics.topic_list.topics.iter().take(MAX_TOPIC_SCAN) {
-            upsert_topic(topic, dao_discourse_id).await?;
+            upsert_topic(topic, dao_discourse_id, false).await?;
             topic_ids.push(topic.id);
 
             let endpoint = format!("/t/{}.json?include_raw=true&page=1", topic.id);
@@ -353,7 +352,7 @@ fn live_discourse_endpoints() -> Result<()> {
             ensure!(posts.posts_count > 0, "topic {} has no posts", topic.id);
 
             for post in posts.post_stream.posts.iter().take(MAX_POSTS_PER_TOPIC) {
-                upsert_post(post, dao_discourse_id).await?;
+                upsert_post(post, dao_discourse_id, false).await?;
                 post_ids.push(post.id);
 
                 if sample_post.is_none() {
@@ -8,8 +8,18 @@ import {
   NewProposalEmailTemplate,
   NewDiscussionEmailTemplate,
   EndingProposalEmailTemplate,
+  VotingStartedEmailTemplate,
+  ProposalEndedEmailTemplate,
 } from '@proposalsapp/emails';
 import { deliverNotification } from './notification-delivery';
+import {
+  NOTIFICATION_JOB_TYPES,
+  claimNotificationJob,
+  completeNotificationJob,
+  failNotificationJob,
+  type NotificationJob,
+  type NotificationType,
+} from './notification-jobs';
 import { createServer } from 'http';
 import pino from 'pino';
 
@@ -44,108 +54,158 @@ const BETTERSTACK_KEY = process.env.BETTERSTACK_KEY;
 
 const FROM_EMAIL =
   process.env.FROM_EMAIL || 'Proposals.app <no-reply@proposals.app>';
-const NEW_PROPOSAL_MINUTES = 5;
-const ENDING_PROPOSAL_MINUTES = 60;
-const NEW_DISCUSSION_MINUTES = 5;
-const COOLDOWN_HOURS = 24;
-
 const ENABLED_DAOS = ['arbitrum', 'uniswap'];
 
-// DAO-specific Discourse category filters
-const DAO_DISCOURSE_CATEGORIES: Record<string, number[]> = {
-  arbitrum: [7, 8, 9],
-  uniswap: [5, 8, 9, 10],
-};
+// Notification jobs are leased while their email is sent
+const WORKER_NAME = 'email-service';
+const JOB_LEASE_SECONDS = 5 * 60;
 
 // Initialize Resend
 const resend = new Resend(RESEND_API_KEY);
 
+interface NotificationEmail {
+  subject: string;
+  html: string;
+}
+
 // ============================================
 // Main Process Entry Points
 // ============================================
 
-// Main process
+// Main process: send the notifications the indexers queued, one job at a time
 async function processNotifications(): Promise<void> {
   logger.info('Starting notification processing...');
 
-  try {
-    const daos = await db
-      .selectFrom('dao')
-      .selectAll()
-      .where('dao.slug', 'in', ENABLED_DAOS)
-      .execute();
+  let processed = 0;
+  for (;;) {
+    const job = await claimNotificationJob(WORKER_NAME, JOB_LEASE_SECONDS);
+    if (!job) break;
+    processed++;
 
-    for (const dao of daos) {
-      try {
-        await processDao(dao);
-      } catch (error) {
-        logger.error({ err: error }, `Error processing ${dao.name}`);
+    let settled: boolean;
+    try {
+      await processNotificationJob(job);
+      settled = await completeNotificationJob(job);
+    } catch (error) {
+      if (job.attempts >= job.maxAttempts) {
+        logger.error(
+          { err: error, jobId: job.id, attempts: job.attempts },
+          'Notification job failed, dead-lettered'
+        );
+      } else {
+        logger.warn(
+          { err: error, jobId: job.id, attempts: job.attempts },
+          'Notification job failed, will retry'
+        );
       }
+      settled = await failNotificationJob(job, error);
+    }
+    if (!settled) {
+      logger.warn(
+        { jobId: job.id },
+        'Notification job lease expired before it was settled, it may run again'
+      );
     }
-
-    logger.info('Notification processing completed');
-  } catch (error) {
-    logger.error({ err: error }, 'Error during notification processing');
   }
+
+  logger.info(`Notification processing completed, ${processed} jobs`);
 }
 
-// Process notifications for a DAO
-async function processDao(dao: Selectable<Dao>): Promise<void> {
-  logger.info(`Processing ${dao.name}`);
+// Send the email of a notification job, unless the user opted out or what
+// it's about no longer warrants it since it was queued
+async function processNotificationJob(job: NotificationJob): Promise<void> {
+  const type = NOTIFICATION_JOB_TYPES[job.type];
 
-  try {
-    await processNewProposals(dao);
-    await processEndingProposals(dao);
-    await processNewDiscussions(dao);
-  } catch (error) {
-    logger.error(
-      { err: error },
-      `Error processing notifications for ${dao.name}`
-    );
+  const user = await db
+    .selectFrom('user')
+    .select([
+      'id',
+      'email',
+      'emailSettingsNewProposals',
+      'emailSettingsEndingProposals',
+      'emailSettingsNewDiscussions',
+    ])
+    .where('id', '=', job.userId)
+    .executeTakeFirst();
+  if (!user) {
+    logger.debug({ jobId: job.id }, 'User no longer exists');
+    return;
+  }
+  const optedIn = {
+    new_proposal: user.emailSettingsNewProposals,
+    voting_started: user.emailSettingsNewProposals,
+    ending_proposal: user.emailSettingsEndingProposals,
+    proposal_ended: user.emailSettingsEndingProposals,
+    new_discussion: user.emailSettingsNewDiscussions,
+  }[type];
+  if (!optedIn) {
+    logger.debug({ jobId: job.id, type }, 'User opted out since');
+    return;
+  }
+
+  const dao = await db
+    .selectFrom('dao')
+    .selectAll()
+    .where('id', '=', job.daoId)
+    .executeTakeFirst();
+  if (!dao || !ENABLED_DAOS.includes(dao.slug)) {
+    logger.debug({ jobId: job.id }, 'Notifications not enabled for DAO');
+    return;
   }
+
+  const email =
+    type === 'new_discussion'
+      ? await newDiscussionEmail(dao, job.targetId)
+      : await proposalEmail(dao, job);
+  if (!email) return;
+
+  await deliverNotification(
+    {
+      sendEmail,
+      recordNotification,
+    },
+    {
+      userId: user.id,
+      targetId: job.targetId,
+      type,
+      daoId: dao.id,
+      to: user.email,
+      subject: email.subject,
+      html: email.html,
+      idempotencyKey: generateIdempotencyKey(user.id, job.targetId, type),
+    }
+  );
 }
 
 // ============================================
-// Notification Type Processors
+// Notification Emails
 // ============================================
 
-// Process new proposal notifications
-async function processNewProposals(dao: Selectable<Dao>): Promise<void> {
-  // Get new proposals
-  const newProposals = await db
+// Email for a proposal notification
+async function proposalEmail(
+  dao: Selectable<Dao>,
+  job: NotificationJob
+): Promise<NotificationEmail | undefined> {
+  const proposal = await db
     .selectFrom('proposal')
     .selectAll()
-    .where('daoId', '=', dao.id)
-    .where('proposalState', '=', ProposalState.ACTIVE)
+    .where('id', '=', job.targetId)
     .where('markedSpam', '=', false)
-    .where(
-      'createdAt',
-      '>=',
-      new Date(Date.now() - NEW_PROPOSAL_MINUTES * 60 * 1000)
-    )
-    .execute();
-
-  if (newProposals.length === 0) {
-    logger.debug(`No new proposals found for ${dao.name}`);
-    return;
+    .executeTakeFirst();
+  if (!proposal) {
+    logger.debug({ jobId: job.id }, 'Proposal not found or marked as spam');
+    return undefined;
   }
 
-  // Get users who want new proposal notifications
-  const users = await db
-    .selectFrom('user')
-    .select(['id', 'email'])
-    .where('emailSettingsNewProposals', '=', true)
-    .execute();
-
-  logger.info(
-    `Found ${newProposals.length} new proposals and ${users.length} users for ${dao.name}`
-  );
-
-  for (const proposal of newProposals) {
-    for (const user of users) {
-      if (await alreadySent(user.id, proposal.id, 'new_proposal', dao.id))
-        continue;
+  const votingOpen =
+    proposal.proposalState === ProposalState.ACTIVE &&
+    new Date(proposal.endAt) > new Date();
+  const endTime = formatDistanceStrict(new Date(proposal.endAt), new Date(), {
+    addSuffix: false,
+  });
 
+  switch (job.type) {
+    case 'NOTIFICATION_NEW_PROPOSAL': {
       // Get author ENS if available
       const voter = proposal.author
         ? await db
@@ -155,144 +215,80 @@ async function processNewProposals(dao: Selectable<Dao>): Promise<void> {
             .executeTakeFirst()
         : null;
 
-      const html = await render(
-        NewProposalEmailTemplate({
-          proposalName: proposal.name,
-          proposalUrl: proposal.url,
-          daoName: dao.name,
-          daoSlug: dao.slug,
-          authorAddress:
-            proposal.author || '0x0000000000000000000000000000000000000000',
-          authorEns: voter?.ens || undefined,
-        })
-      );
-
-      const idempotencyKey = generateIdempotencyKey(
-        user.id,
-        proposal.id,
-        'new_proposal'
-      );
-
-      await deliverNotification(
-        {
-          sendEmail,
-          recordNotification,
-        },
-        {
-          userId: user.id,
-          targetId: proposal.id,
-          type: 'new_proposal',
-          daoId: dao.id,
-          to: user.email,
-          subject: `New proposal in ${dao.name}`,
-          html,
-          idempotencyKey,
-        }
-      );
-    }
-  }
-}
-
-// Process ending proposal notifications
-async function processEndingProposals(dao: Selectable<Dao>): Promise<void> {
-  // Get ending proposals
-  const endingProposals = await db
-    .selectFrom('proposal')
-    .selectAll()
-    .where('daoId', '=', dao.id)
-    .where('proposalState', '=', ProposalState.ACTIVE)
-    .where('markedSpam', '=', false)
-    .where(
-      'endAt',
-      '<=',
-      new Date(Date.now() + ENDING_PROPOSAL_MINUTES * 60 * 1000)
-    )
-    .where('endAt', '>', new Date())
-    .execute();
-
-  if (endingProposals.length === 0) {
-    logger.debug(`No ending proposals found for ${dao.name}`);
-    return;
-  }
-
-  // Get users who want ending proposal notifications
-  const users = await db
-    .selectFrom('user')
-    .select(['id', 'email'])
-    .where('emailSettingsEndingProposals', '=', true)
-    .execute();
-
-  logger.info(
-    `Found ${endingProposals.length} ending proposals and ${users.length} users for ${dao.name}`
-  );
-
-  for (const proposal of endingProposals) {
-    for (const user of users) {
-      if (await alreadySent(user.id, proposal.id, 'ending_proposal', dao.id))
-        continue;
-
-      const endTime = formatDistanceStrict(
-        new Date(proposal.endAt),
-        new Date(),
-        { addSuffix: false }
-      );
-
-      const html = await render(
-        EndingProposalEmailTemplate({
-          proposalName: proposal.name,
-          proposalUrl: proposal.url,
-          daoName: dao.name,
-          daoSlug: dao.slug,
-          endTime,
-        })
-      );
-
-      const idempotencyKey = generateIdempotencyKey(
-        user.id,
-        proposal.id,
-        'ending_proposal'
-      );
-
-      await deliverNotification(
-        {
-          sendEmail,
-          recordNotification,
-        },
-        {
-          userId: user.id,
-          targetId: proposal.id,
-          type: 'ending_proposal',
-          daoId: dao.id,
-          to: user.email,
-          subject: `Proposal ending soon in ${dao.name}`,
-          html,
-          idempotencyKey,
-        }
-      );
+      return {
+        subject: `New proposal in ${dao.name}`,
+        html: await render(
+          NewProposalEmailTemplate({
+            proposalName: proposal.name,
+            proposalUrl: proposal.url,
+            daoName: dao.name,
+            daoSlug: dao.slug,
+            authorAddress:
+              proposal.author || '0x0000000000000000000000000000000000000000',
+            authorEns: voter?.ens || undefined,
+          })
+        ),
+      };
     }
+    case 'NOTIFICATION_VOTING_STARTED':
+      if (!votingOpen) return undefined;
+      return {
+        subject: `Voting started in ${dao.name}`,
+        html: await render(
+          VotingStartedEmailTemplate({
+            proposalName: proposal.name,
+            proposalUrl: proposal.url,
+            daoName: dao.name,
+            daoSlug: dao.slug,
+            endTime,
+          })
+        ),
+      };
+    case 'NOTIFICATION_ENDING_PROPOSAL':
+      if (!votingOpen) return undefined;
+      return {
+        subject: `Proposal ending soon in ${dao.name}`,
+        html: await render(
+          EndingProposalEmailTemplate({
+            proposalName: proposal.name,
+            proposalUrl: proposal.url,
+            daoName: dao.name,
+            daoSlug: dao.slug,
+            endTime,
+          })
+        ),
+      };
+    case 'NOTIFICATION_PROPOSAL_ENDED':
+      return {
+        subject: `Proposal ended in ${dao.name}`,
+        html: await render(
+          ProposalEndedEmailTemplate({
+            proposalName: proposal.name,
+            proposalUrl: proposal.url,
+            daoName: dao.name,
+            daoSlug: dao.slug,
+            result: describeResult(job.result),
+          })
+        ),
+      };
+    default:
+      return undefined;
   }
 }
 
-// Process new discussion notifications
-async function processNewDiscussions(dao: Selectable<Dao>): Promise<void> {
-  // Check if Discourse is enabled for this DAO
-  const daoDiscourse = await db
-    .selectFrom('daoDiscourse')
-    .selectAll()
-    .where('daoId', '=', dao.id)
-    .executeTakeFirst();
-
-  if (!daoDiscourse) {
-    logger.debug(`Discourse not enabled for ${dao.name}`);
-    return;
-  }
-
-  // Get allowed category IDs for filtering
-  const allowedCategories = DAO_DISCOURSE_CATEGORIES[dao.slug];
-
-  let query = db
+// Email for a new discussion notification
+async function newDiscussionEmail(
+  dao: Selectable<Dao>,
+  topicId: string
+): Promise<NotificationEmail | undefined> {
+  const topic = await db
     .selectFrom('discourseTopic')
-    .innerJoin('discoursePost', (join) =>
+    .innerJoin(
+      'daoDiscourse',
+      'daoDiscourse.id',
+      'discourseTopic.daoDiscourseId'
+    )
+    .leftJoin('discoursePost', (join) =>
       join
         .onRef('discoursePost.topicId', '=', 'discourseTopic.externalId')
         .on('discoursePost.postNumber', '=', 1)
@@ -302,7 +298,7 @@ async function processNewDiscussions(dao: Selectable<Dao>): Promise<void> {
           'discourseTopic.daoDiscourseId'
         )
     )
-    .innerJoin('discourseUser', (join) =>
+    .leftJoin('discourseUser', (join) =>
       join
         .onRef('discourseUser.externalId', '=', 'discoursePost.userId')
         .onRef(
@@ -312,84 +308,54 @@ async function processNewDiscussions(dao: Selectable<Dao>): Promise<void> {
         )
     )
     .select([
-      'discourseTopic.id',
       'discourseTopic.title',
       'discourseTopic.slug',
       'discourseTopic.externalId',
+      'daoDiscourse.discourseBaseUrl',
       'discourseUser.username',
       'discourseUser.avatarTemplate',
     ])
-    .where('discourseTopic.daoDiscourseId', '=', daoDiscourse.id)
-    .where(
-      'discourseTopic.createdAt',
-      '>=',
-      new Date(Date.now() - NEW_DISCUSSION_MINUTES * 60 * 1000)
-    );
-
-  // Apply category filter if configured
-  if (allowedCategories && allowedCategories.length > 0) {
-    query = query.where('discourseTopic.categoryId', 'in', allowedCategories);
-  }
-
-  const newTopics = await query.execute();
-
-  if (newTopics.length === 0) {
-    logger.debug(`No new discussions found for ${dao.name}`);
-    return;
+    .where('discourseTopic.id', '=', topicId)
+    .where('discourseTopic.restricted', '=', false)
+    .executeTakeFirst();
+  if (!topic) {
+    logger.debug({ topicId }, 'Topic not found or restricted');
+    return undefined;
   }
 
-  // Get users who want new discussion notifications
-  const users = await db
-    .selectFrom('user')
-    .select(['id', 'email'])
-    .where('emailSettingsNewDiscussions', '=', true)
-    .execute();
-
-  logger.info(
-    `Found ${newTopics.length} new discussions and ${users.length} users for ${dao.name}`
-  );
-
-  for (const topic of newTopics) {
-    const topicUrl = `${daoDiscourse.discourseBaseUrl}/t/${topic.slug}/${topic.externalId}`;
-
-    for (const user of users) {
-      if (await alreadySent(user.id, topic.id, 'new_discussion', dao.id))
-        continue;
-
-      const html = await render(
-        NewDiscussionEmailTemplate({
-          discussionTitle: topic.title,
-          discussionUrl: topicUrl,
-          daoName: dao.name,
-          daoSlug: dao.slug,
-          authorUsername: topic.username || '',
-          authorProfilePicture: topic.avatarTemplate || '',
-        })
-      );
-
-      const idempotencyKey = generateIdempotencyKey(
-        user.id,
-        topic.id,
-        'new_discussion'
-      );
+  const topicUrl = `${topic.discourseBaseUrl}/t/${topic.slug}/${topic.externalId}`;
+  return {
+    subject: `New discussion in ${dao.name}`,
+    html: await render(
+      NewDiscussionEmailTemplate({
+        discussionTitle: topic.title,
+        discussionUrl: topicUrl,
+        daoName: dao.name,
+        daoSlug: dao.slug,
+        authorUsername: topic.username || '',
+        authorProfilePicture: topic.avatarTemplate || '',
+      })
+    ),
+  };
+}
 
-      await deliverNotification(
-        {
-          sendEmail,
-          recordNotification,
-        },
-        {
-          userId: user.id,
-          targetId: topic.id,
-          type: 'new_discussion',
-          daoId: dao.id,
-          to: user.email,
-          subject: `New discussion in ${dao.name}`,
-          html,
-          idempotencyKey,
-        }
-      );
-    }
+// How a proposal ended, from the state the indexers queued it with
+function describeResult(result: string | null): string {
+  switch (result) {
+    case 'Succeeded':
+      return 'succeeded';
+    case 'Queued':
+      return 'passed and was queued';
+    case 'Executed':
+      return 'passed and was executed';
+    case 'Defeated':
+      return 'was defeated';
+    case 'Canceled':
+      return 'was canceled';
+    case 'Expired':
+      return 'expired';
+    default:
+      return 'ended';
   }
 }
 
@@ -420,38 +386,14 @@ async function sendEmail(
 function generateIdempotencyKey(
   userId: string,
   targetId: string,
-  type: 'new_proposal' | 'ending_proposal' | 'new_discussion'
+  type: NotificationType
 ): string {
-  // Use a stable key based on user, target, and type only.
-  // The alreadySent() function handles the 24-hour cooldown period.
-  // Note: Resend idempotency keys expire after 24 hours, which aligns
-  // with our cooldown period, so duplicate sends are prevented both
-  // by our database check and by Resend's idempotency.
+  // Jobs are queued once per user, target and type, but one whose lease
+  // expired after its email was sent runs again. Resend's idempotency keys
+  // last 24 hours, which covers the retries.
   return `${userId}-${targetId}-${type}`;
 }
 
-// Check if we already sent this notification
-async function alreadySent(
-  userId: string,
-  targetId: string,
-  type: string,
-  daoId: string
-): Promise<boolean> {
-  const cutoff = new Date(Date.now() - COOLDOWN_HOURS * 60 * 60 * 1000);
-
-  const existing = await db
-    .selectFrom('userNotification')
-    .select('id')
-    .where('userId', '=', userId)
-    .where('targetId', '=', targetId)
-    .where('type', '=', type)
-    .where('daoId', '=', daoId)
-    .where('sentAt', '>=', cutoff)
-    .executeTakeFirst();
-
-  return !!existing;
-}
-
 // Record notification sent
 async function recordNotification(
   userId: string,
@@ -548,7 +490,7 @@ cron.schedule('* * * * *', async () => {
 });
 
 // Start the service
-logger.info('Email service started - checking for notifications every minute');
+logger.info('Email service started - sending queued notifications every minute');
 
 // Handle shutdown
 process.on('SIGINT', async () => {
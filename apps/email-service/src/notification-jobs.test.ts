@@ -0,0 +1,15 @@
+import { describe, expect, it, vi } from 'vitest';
+import { retryDelaySeconds } from './notification-jobs';
+
+vi.mock('@proposalsapp/db', () => ({ db: {}, sql: {} }));
+
+describe('retryDelaySeconds', () => {
+  it('doubles per attempt up to an hour', () => {
+    expect(retryDelaySeconds(1)).toBe(30);
+    expect(retryDelaySeconds(2)).toBe(60);
+    expect(retryDelaySeconds(5)).toBe(8 * 60);
+    expect(retryDelaySeconds(8)).toBe(60 * 60);
+    expect(retryDelaySeconds(Number.MAX_SAFE_INTEGER)).toBe(60 * 60);
+    expect(retryDelaySeconds(0)).toBe(30);
+  });
+});
@@ -0,0 +1,156 @@
+import { db, sql } from '@proposalsapp/db';
+
+// Notification job types the indexers enqueue, and the userNotification type
+// each is recorded as once sent
+export const NOTIFICATION_JOB_TYPES = {
+  NOTIFICATION_NEW_PROPOSAL: 'new_proposal',
+  NOTIFICATION_VOTING_STARTED: 'voting_started',
+  NOTIFICATION_ENDING_PROPOSAL: 'ending_proposal',
+  NOTIFICATION_PROPOSAL_ENDED: 'proposal_ended',
+  NOTIFICATION_NEW_DISCUSSION: 'new_discussion',
+} as const;
+
+export type NotificationJobType = keyof typeof NOTIFICATION_JOB_TYPES;
+export type NotificationType =
+  (typeof NOTIFICATION_JOB_TYPES)[NotificationJobType];
+
+// A claimed notification job. It's leased to this worker until it completes,
+// fails, or the lease runs out and another worker claims it.
+export interface NotificationJob {
+  id: number;
+  type: NotificationJobType;
+  // Claims so far, including this one
+  attempts: number;
+  maxAttempts: number;
+  userId: string;
+  // A proposal, or a topic for new discussions
+  targetId: string;
+  daoId: string;
+  // The state the proposal ended in, for NOTIFICATION_PROPOSAL_ENDED
+  result: string | null;
+}
+
+const FIRST_RETRY_DELAY_SECONDS = 30;
+const MAX_RETRY_DELAY_SECONDS = 60 * 60;
+
+const JOB_TYPES = Object.keys(NOTIFICATION_JOB_TYPES);
+
+// Delay before retrying a job after its `attempts`-th failure: 30 seconds,
+// doubling per attempt, capped at an hour. Same as the Rust job queue.
+export function retryDelaySeconds(attempts: number): number {
+  const exponent = Math.min(Math.max(attempts, 1), 16) - 1;
+  return Math.min(
+    FIRST_RETRY_DELAY_SECONDS * 2 ** exponent,
+    MAX_RETRY_DELAY_SECONDS
+  );
+}
+
+// Claim the next due notification job for `leaseSeconds`. Pending jobs and
+// running jobs whose lease expired are due; jobs locked by a concurrent claim
+// are skipped rather than waited on.
+export async function claimNotificationJob(
+  worker: string,
+  leaseSeconds: number
+): Promise<NotificationJob | undefined> {
+  // Expired leases of jobs out of attempts mean their worker died mid-run
+  // every time
+  await sql`
+    UPDATE job_queue SET
+      status = 'DEAD',
+      last_error = COALESCE(last_error, 'Lease expired'),
+      locked_until = NULL,
+      updated_at = NOW()
+    WHERE type IN (${sql.join(JOB_TYPES)})
+      AND status = 'RUNNING'
+      AND locked_until <= NOW()
+      AND attempts >= max_attempts
+  `.execute(db);
+
+  for (;;) {
+    // Data fields are read one by one, as the camel case plugin would rename
+    // the keys of the whole object
+    const { rows } = await sql<NotificationJob>`
+      WITH next AS (
+        SELECT id FROM job_queue
+        WHERE type IN (${sql.join(JOB_TYPES)})
+          AND (
+            (status = 'PENDING' AND run_at <= NOW())
+            OR (status = 'RUNNING' AND locked_until <= NOW() AND attempts < max_attempts)
+          )
+        ORDER BY run_at, id
+        LIMIT 1
+        FOR UPDATE SKIP LOCKED
+      )
+      UPDATE job_queue SET
+        status = 'RUNNING',
+        attempts = job_queue.attempts + 1,
+        locked_until = NOW() + make_interval(secs => ${leaseSeconds}::float8),
+        locked_by = ${worker},
+        updated_at = NOW()
+      FROM next
+      WHERE job_queue.id = next.id
+      RETURNING
+        job_queue.id,
+        job_queue.type,
+        job_queue.attempts,
+        job_queue.max_attempts,
+        job_queue.data->>'user_id' AS user_id,
+        job_queue.data->>'target_id' AS target_id,
+        job_queue.data->>'dao_id' AS dao_id,
+        job_queue.data->>'result' AS result
+    `.execute(db);
+
+    const job = rows[0];
+    if (!job) return undefined;
+    if (job.userId && job.targetId && job.daoId) return job;
+
+    await settle(job, 'DEAD', null, 'Failed to parse job data');
+  }
+}
+
+// Mark a job done. Returns false when its lease expired and another worker
+// claimed it in the meantime.
+export async function completeNotificationJob(
+  job: NotificationJob
+): Promise<boolean> {
+  const result = await sql`
+    UPDATE job_queue SET
+      status = 'COMPLETED',
+      locked_until = NULL,
+      updated_at = NOW()
+    WHERE id = ${job.id} AND status = 'RUNNING' AND attempts = ${job.attempts}
+  `.execute(db);
+  return Number(result.numAffectedRows ?? 0) > 0;
+}
+
+// Record a failed attempt: the job is retried after retryDelaySeconds, or
+// dead-lettered once it's out of attempts. Returns false when its lease
+// expired and another worker claimed it in the meantime.
+export async function failNotificationJob(
+  job: NotificationJob,
+  error: unknown
+): Promise<boolean> {
+  const message = error instanceof Error ? error.message : String(error);
+  if (job.attempts >= job.maxAttempts) {
+    return settle(job, 'DEAD', null, message);
+  }
+  return settle(job, 'PENDING', retryDelaySeconds(job.attempts), message);
+}
+
+async function settle(
+  job: NotificationJob,
+  status: 'PENDING' | 'DEAD',
+  retryInSeconds: number | null,
+  error: string
+): Promise<boolean> {
+  const result = await sql`
+    UPDATE job_queue SET
+      status = ${status},
+      run_at = COALESCE(NOW() + make_interval(secs => ${retryInSeconds}::float8), run_at),
+      last_error = ${error},
+      locked_until = NULL,
+      updated_at = NOW()
+    WHERE id = ${job.id} AND status = 'RUNNING' AND attempts = ${job.attempts}
+  `.execute(db);
+  return Number(result.numAffectedRows ?? 0) > 0;
+}
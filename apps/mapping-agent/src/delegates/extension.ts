@@ -50,7 +50,7 @@ export function createDelegateExtension(
       name: 'query_delegate_mapping_data',
       label: 'Query Delegate Mapping Data',
       description:
-        'Run a single read-only SQL SELECT/CTE statement against the delegate-mapping helper relations and the raw public tables exposed in the schema export. In addition to current_case, discourse_users, voters, delegate_to_discourse_users, delegate_to_voters, current_delegate_discourse_users, current_delegate_voters, votes, and voting_power_timeseries, you may inspect raw public discourse_topic, discourse_post, dao_discourse, vote, and voter rows when you need first-post, communication-thread, or vote-reason evidence. Reads may span any DAO. For delegate_to_voter writes, the propose callback resolves exact targets against the global voters table, then validates claim conflicts within the current DAO.',
+        'Run a single read-only SQL SELECT/CTE statement against the delegate-mapping helper relations and the raw public tables exposed in the schema export. In addition to current_case, discourse_users, voters, delegate_to_discourse_users, delegate_to_voters, current_delegate_discourse_users, current_delegate_voters, votes, and voting_power_timeseries, you may inspect raw public discourse_topic, discourse_post, dao_discourse, vote, and voter rows when you need first-post, communication-thread, or vote-reason evidence. Name discourse_topic and discourse_post without the public schema prefix; restricted forum content is left out of them. Reads may span any DAO. For delegate_to_voter writes, the propose callback resolves exact targets against the global voters table, then validates claim conflicts within the current DAO.',
       parameters: Type.Object({
         sql: Type.String(),
       }),
@@ -197,7 +197,7 @@ export function buildDelegateSystemPrompt(params: {
     '- current_delegate_discourse_users(delegate_id, discourse_user_id, dao_id, username, name, title), current_delegate_voters(delegate_id, voter_id, address, ens)',
     '- proposal_category_topics(id, external_id, dao_discourse_id, dao_id, title, category_id), proposal_category_post_counts(discourse_user_id, proposal_category_post_count), current_delegate_proposal_category_posts(external_id, topic_id, created_at, post_number, username, user_id)',
     '- votes(id, dao_id, governor_id, proposal_id, proposal_external_id, voter_address, created_at, voting_power, reason), voting_power_timeseries(id, dao_id, voter, timestamp, voting_power, block, txid)',
-    '- the read tool can also inspect the raw public tables in the schema export, including discourse_topic, discourse_post, dao_discourse, vote, and voter.',
+    '- the read tool can also inspect the raw public tables in the schema export, including discourse_topic, discourse_post, dao_discourse, vote, and voter. Name discourse_topic and discourse_post without the public schema prefix; they only hold unrestricted forum content.',
     'Workflow:',
     '- Start with current_case.',
     '- Query the exact already-linked row early. When source_discourse_user_id is present, query that exact discourse_users row early so you have the canonical username, name, dao_discourse_id, and external_id before touching raw forum tables.',
@@ -23,6 +23,13 @@ import {
   type DiscourseUserRecord,
   type VoterRecord,
 } from './deterministic';
+import {
+  getPairKey,
+  suggestIdentityLinks,
+  type AddressMention,
+  type SuggestionDiscourseUser,
+  type SuggestionVoter,
+} from './suggestions';
 import {
   executeReadOnlySqlQuery,
   type ReadOnlySqlQueryResult,
@@ -318,6 +325,7 @@ export async function loadDelegateWorkerContext(
           .select(['externalId'])
           .where('daoDiscourseId', '=', daoDiscourse.id)
           .where('categoryId', 'in', allowedCategoryIds)
+          .where('restricted', '=', false)
           .execute()
       : Promise.resolve([] as Array<{ externalId: number }>),
     daoDiscourse && allowedCategoryIds.length > 0
@@ -325,6 +333,7 @@ export async function loadDelegateWorkerContext(
           .selectFrom('discoursePost')
           .select(['userId'])
           .where('daoDiscourseId', '=', daoDiscourse.id)
+          .where('restricted', '=', false)
           .where(
             'topicId',
             'in',
@@ -1004,6 +1013,163 @@ export async function runDeterministicDelegateMappings(input: {
   return { context: activeContext, unresolvedCases, seedResults };
 }
 
+const POSTED_ADDRESS_PATTERN = '\\m(0x[0-9a-fA-F]{40})\\M';
+
+/**
+ * Score voter and forum account pairings from indexed identity data and keep
+ * them as pending suggestions for review on the delegate mapping page.
+ * Suggestions someone already accepted or rejected are left as they are.
+ */
+export async function runIdentitySuggestions(
+  context: DelegateWorkerContext
+): Promise<{ suggested: number }> {
+  if (!context.daoDiscourse) {
+    return { suggested: 0 };
+  }
+
+  const daoDiscourseId = context.daoDiscourse.id;
+  const [voters, discourseUsers, mentions] = await Promise.all([
+    sql<SuggestionVoter>`
+      SELECT v.id, v.address, v.ens, v.discourse
+      FROM (${sql.raw(buildRelevantVoterLookupSql(context.dao.id))}) v
+    `
+      .execute(db)
+      .then((result) => result.rows),
+    sql<SuggestionDiscourseUser>`
+      SELECT
+        du.id,
+        du.username,
+        du.name,
+        du.title,
+        flair.flair_name AS flair
+      FROM public.discourse_user du
+      LEFT JOIN (
+        SELECT DISTINCT ON (user_id) user_id, flair_name
+        FROM public.discourse_post
+        WHERE dao_discourse_id = ${daoDiscourseId}
+          AND flair_name IS NOT NULL
+          AND NOT restricted
+        ORDER BY user_id, created_at DESC
+      ) flair ON flair.user_id = du.external_id
+      WHERE du.dao_discourse_id = ${daoDiscourseId}
+    `
+      .execute(db)
+      .then((result) => result.rows),
+    sql<AddressMention>`
+      SELECT
+        du.id AS "discourseUserId",
+        mention.address,
+        p.external_id AS "postId",
+        p.post_number AS "postNumber",
+        p.topic_id AS "topicId",
+        t.title AS "topicTitle"
+      FROM public.discourse_post p
+      JOIN public.discourse_user du
+        ON du.dao_discourse_id = p.dao_discourse_id
+        AND du.external_id = p.user_id
+      JOIN public.discourse_topic t
+        ON t.dao_discourse_id = p.dao_discourse_id
+        AND t.external_id = p.topic_id
+      CROSS JOIN LATERAL (
+        SELECT DISTINCT lower(match[1]) AS address
+        FROM regexp_matches(p.cooked, ${POSTED_ADDRESS_PATTERN}, 'g') AS match
+      ) mention
+      WHERE p.dao_discourse_id = ${daoDiscourseId}
+        AND NOT p.deleted
+        AND NOT p.restricted
+    `
+      .execute(db)
+      .then((result) => result.rows),
+  ]);
+
+  const linkedPairs = new Set<string>();
+  for (const delegate of context.delegates) {
+    for (const voterId of delegate.voterIds) {
+      for (const discourseUserId of delegate.discourseUserIds) {
+        linkedPairs.add(getPairKey(voterId, discourseUserId));
+      }
+    }
+  }
+
+  const systemUserIds = new Set(
+    discourseUsers
+      .filter((user) => isSystemMetaDiscourseIdentity(user))
+      .map((user) => user.id)
+  );
+  const suggestions = suggestIdentityLinks({
+    voters,
+    discourseUsers: discourseUsers.filter(
+      (user) => !systemUserIds.has(user.id)
+    ),
+    mentions: mentions.filter(
+      (mention) => !systemUserIds.has(mention.discourseUserId)
+    ),
+    forumUrl: context.daoDiscourse.discourseBaseUrl,
+    linkedPairs,
+  });
+
+  if (isDryRunEnabled()) {
+    return { suggested: suggestions.length };
+  }
+
+  const rows = JSON.stringify(
+    suggestions.map((suggestion) => ({
+      voter_id: suggestion.voterId,
+      discourse_user_id: suggestion.discourseUserId,
+      score: suggestion.score,
+      evidence: suggestion.evidence,
+    }))
+  );
+
+  await db.transaction().execute(async (trx) => {
+    await sql`
+      INSERT INTO public.mapping_identity_suggestion (
+        dao_id,
+        voter_id,
+        discourse_user_id,
+        score,
+        evidence
+      )
+      SELECT
+        ${context.dao.id},
+        suggestion.voter_id,
+        suggestion.discourse_user_id,
+        suggestion.score,
+        suggestion.evidence
+      FROM jsonb_to_recordset(CAST(${rows} AS jsonb)) AS suggestion(
+        voter_id UUID,
+        discourse_user_id UUID,
+        score DOUBLE PRECISION,
+        evidence JSONB
+      )
+      ON CONFLICT (dao_id, voter_id, discourse_user_id)
+      DO UPDATE SET
+        score = EXCLUDED.score,
+        evidence = EXCLUDED.evidence,
+        updated_at = timezone('utc', now())
+      WHERE public.mapping_identity_suggestion.status = 'pending'
+    `.execute(trx);
+
+    // Pending pairings that got linked or lost their evidence since last run
+    await sql`
+      DELETE FROM public.mapping_identity_suggestion existing
+      WHERE existing.dao_id = ${context.dao.id}
+        AND existing.status = 'pending'
+        AND NOT EXISTS (
+          SELECT 1
+          FROM jsonb_to_recordset(CAST(${rows} AS jsonb)) AS suggestion(
+            voter_id UUID,
+            discourse_user_id UUID
+          )
+          WHERE suggestion.voter_id = existing.voter_id
+            AND suggestion.discourse_user_id = existing.discourse_user_id
+        )
+    `.execute(trx);
+  });
+
+  return { suggested: suggestions.length };
+}
+
 function quoteSqlLiteral(value: string): string {
   return `'${value.replaceAll("'", "''")}'`;
 }
@@ -1025,6 +1191,11 @@ JOIN (
   ON relevant_addresses.address = v.address`;
 }
 
+// The helper relations shadow these tables with their unrestricted rows, which
+// a schema-qualified name would bypass
+const SCHEMA_QUALIFIED_DISCOURSE_CONTENT_PATTERN =
+  /"?public"?\s*\.\s*"?discourse_(?:topic|post)\b/i;
+
 function buildDelegateQueryRelations(params: {
   delegateId: string;
   allowedCategoryIds: number[];
@@ -1035,6 +1206,19 @@ function buildDelegateQueryRelations(params: {
       : null;
 
   return [
+    // Shadow the raw tables so reads never see restricted forum content
+    {
+      name: 'discourse_topic',
+      sql: `SELECT *
+FROM public.discourse_topic
+WHERE NOT restricted`,
+    },
+    {
+      name: 'discourse_post',
+      sql: `SELECT *
+FROM public.discourse_post
+WHERE NOT restricted`,
+    },
     {
       name: 'current_case',
       sql: `SELECT
@@ -1274,6 +1458,12 @@ export async function queryDelegateMappingData(params: {
   allowedCategoryIds: number[];
   input: DelegateQueryInput;
 }): Promise<ReadOnlySqlQueryResult> {
+  if (SCHEMA_QUALIFIED_DISCOURSE_CONTENT_PATTERN.test(params.input.sql)) {
+    throw new Error(
+      'Read discourse_topic and discourse_post without the public schema prefix; restricted forum content is left out of them'
+    );
+  }
+
   const result = await executeReadOnlySqlQuery({
     query: params.input.sql,
     relations: buildDelegateQueryRelations({
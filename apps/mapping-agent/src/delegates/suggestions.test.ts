@@ -0,0 +1,194 @@
+import { describe, expect, it } from 'vitest';
+import {
+  getPairKey,
+  parseDiscourseRecord,
+  suggestIdentityLinks,
+  type AddressMention,
+  type SuggestionDiscourseUser,
+  type SuggestionVoter,
+} from './suggestions';
+
+const FORUM_URL = 'https://forum.arbitrum.foundation';
+
+function makeVoter(
+  overrides: Partial<SuggestionVoter> & Pick<SuggestionVoter, 'id'>
+): SuggestionVoter {
+  return {
+    id: overrides.id,
+    address: overrides.address ?? '0x0000000000000000000000000000000000000000',
+    ens: overrides.ens ?? null,
+    discourse: overrides.discourse ?? null,
+  };
+}
+
+function makeDiscourseUser(
+  overrides: Partial<SuggestionDiscourseUser> &
+    Pick<SuggestionDiscourseUser, 'id'>
+): SuggestionDiscourseUser {
+  return {
+    id: overrides.id,
+    username: overrides.username ?? 'delegate',
+    name: overrides.name ?? null,
+    title: overrides.title ?? null,
+    flair: overrides.flair ?? null,
+  };
+}
+
+function makeMention(
+  overrides: Partial<AddressMention> &
+    Pick<AddressMention, 'discourseUserId' | 'address'>
+): AddressMention {
+  return {
+    discourseUserId: overrides.discourseUserId,
+    address: overrides.address,
+    postId: overrides.postId ?? 1,
+    postNumber: overrides.postNumber ?? 1,
+    topicId: overrides.topicId ?? 10,
+    topicTitle: overrides.topicTitle ?? 'General discussion',
+  };
+}
+
+describe('parseDiscourseRecord', () => {
+  it('reads usernames from bare names, handles and profile URLs on the forum', () => {
+    expect(parseDiscourseRecord('Alice', FORUM_URL)).toBe('alice');
+    expect(parseDiscourseRecord(' @alice ', FORUM_URL)).toBe('alice');
+    expect(
+      parseDiscourseRecord(
+        'https://www.forum.arbitrum.foundation/u/alice/summary',
+        FORUM_URL
+      )
+    ).toBe('alice');
+    expect(
+      parseDiscourseRecord('https://gov.uniswap.org/u/alice', FORUM_URL)
+    ).toBeNull();
+    expect(parseDiscourseRecord('alice on the forum', FORUM_URL)).toBeNull();
+    expect(parseDiscourseRecord(null, FORUM_URL)).toBeNull();
+  });
+});
+
+describe('suggestIdentityLinks', () => {
+  it('scores ENS records, names and profiles, strongest first', () => {
+    const suggestions = suggestIdentityLinks({
+      voters: [
+        makeVoter({ id: 'voter-1', ens: 'alice.eth', discourse: '@alice' }),
+        makeVoter({ id: 'voter-2', ens: 'bob.eth' }),
+        makeVoter({ id: 'voter-3', ens: 'carol.eth' }),
+        makeVoter({ id: 'voter-4', ens: 'dave.eth' }),
+      ],
+      discourseUsers: [
+        makeDiscourseUser({ id: 'discourse-1', username: 'alice' }),
+        makeDiscourseUser({
+          id: 'discourse-2',
+          username: 'robert',
+          title: 'Delegate | bob.eth',
+        }),
+        makeDiscourseUser({ id: 'discourse-3', username: 'carol' }),
+        makeDiscourseUser({
+          id: 'discourse-4',
+          username: 'd4v3',
+          name: 'Dave',
+        }),
+      ],
+      mentions: [],
+      forumUrl: FORUM_URL,
+      linkedPairs: new Set(),
+    });
+
+    expect(
+      suggestions.map(({ voterId, discourseUserId, score }) => ({
+        voterId,
+        discourseUserId,
+        score,
+      }))
+    ).toEqual([
+      { voterId: 'voter-1', discourseUserId: 'discourse-1', score: 0.95 },
+      { voterId: 'voter-2', discourseUserId: 'discourse-2', score: 0.7 },
+      { voterId: 'voter-3', discourseUserId: 'discourse-3', score: 0.5 },
+    ]);
+    expect(suggestions[1]?.evidence).toEqual([
+      {
+        kind: 'ens_in_profile',
+        ens: 'bob.eth',
+        field: 'title',
+        value: 'Delegate | bob.eth',
+      },
+    ]);
+  });
+
+  it('counts posted addresses, discounting replies and widely posted ones', () => {
+    const delegateAddress = '0x1111111111111111111111111111111111111111';
+    const popularAddress = '0x2222222222222222222222222222222222222222';
+
+    const suggestions = suggestIdentityLinks({
+      voters: [
+        makeVoter({ id: 'voter-1', address: delegateAddress }),
+        makeVoter({ id: 'voter-2', address: popularAddress }),
+      ],
+      discourseUsers: [],
+      mentions: [
+        makeMention({
+          discourseUserId: 'discourse-1',
+          address: delegateAddress.toUpperCase().replace('0X', '0x'),
+          topicTitle: 'Delegate Statement Template',
+          postId: 7,
+          topicId: 70,
+        }),
+        makeMention({
+          discourseUserId: 'discourse-2',
+          address: delegateAddress,
+          topicTitle: 'Delegate Statement Template',
+          postNumber: 4,
+        }),
+        ...['discourse-2', 'discourse-3', 'discourse-4', 'discourse-5'].map(
+          (discourseUserId) =>
+            makeMention({ discourseUserId, address: popularAddress })
+        ),
+      ],
+      forumUrl: FORUM_URL,
+      linkedPairs: new Set(),
+    });
+
+    expect(suggestions).toEqual([
+      {
+        voterId: 'voter-1',
+        discourseUserId: 'discourse-1',
+        score: 0.85,
+        evidence: [
+          {
+            kind: 'delegate_statement',
+            postId: 7,
+            topicId: 70,
+            topicTitle: 'Delegate Statement Template',
+          },
+        ],
+      },
+      {
+        voterId: 'voter-1',
+        discourseUserId: 'discourse-2',
+        score: 0.6,
+        evidence: [
+          {
+            kind: 'address_in_post',
+            postId: 1,
+            topicId: 10,
+            topicTitle: 'Delegate Statement Template',
+          },
+        ],
+      },
+    ]);
+  });
+
+  it('leaves out pairings already linked through a delegate', () => {
+    const suggestions = suggestIdentityLinks({
+      voters: [makeVoter({ id: 'voter-1', discourse: 'alice' })],
+      discourseUsers: [
+        makeDiscourseUser({ id: 'discourse-1', username: 'alice' }),
+      ],
+      mentions: [],
+      forumUrl: FORUM_URL,
+      linkedPairs: new Set([getPairKey('voter-1', 'discourse-1')]),
+    });
+
+    expect(suggestions).toEqual([]);
+  });
+});
@@ -0,0 +1,302 @@
+import { getEnsStem, normalizeText } from '../shared/normalize';
+
+export interface SuggestionVoter {
+  id: string;
+  address: string;
+  ens: string | null;
+  discourse: string | null;
+}
+
+export interface SuggestionDiscourseUser {
+  id: string;
+  username: string;
+  name: string | null;
+  title: string | null;
+  flair: string | null;
+}
+
+export interface AddressMention {
+  discourseUserId: string;
+  address: string;
+  postId: number;
+  postNumber: number;
+  topicId: number;
+  topicTitle: string;
+}
+
+export type SuggestionEvidence =
+  | { kind: 'ens_discourse_record'; ens: string | null; record: string }
+  | { kind: 'ens_stem_matches_username'; ens: string; username: string }
+  | { kind: 'ens_stem_matches_display_name'; ens: string; name: string }
+  | {
+      kind: 'ens_in_profile';
+      ens: string;
+      field: 'name' | 'title' | 'flair';
+      value: string;
+    }
+  | {
+      kind: 'address_in_post' | 'delegate_statement';
+      postId: number;
+      topicId: number;
+      topicTitle: string;
+    };
+
+export interface IdentitySuggestion {
+  voterId: string;
+  discourseUserId: string;
+  score: number;
+  evidence: SuggestionEvidence[];
+}
+
+export const MIN_SUGGESTION_SCORE = 0.5;
+
+/**
+ * How likely each piece of evidence alone makes a pairing. A voter naming the
+ * account in their own ENS records, or an account posting the address in its
+ * delegate statement, is close to a claim; matching names are only hints.
+ */
+const EVIDENCE_WEIGHTS: Record<SuggestionEvidence['kind'], number> = {
+  ens_discourse_record: 0.9,
+  delegate_statement: 0.85,
+  ens_in_profile: 0.7,
+  address_in_post: 0.6,
+  ens_stem_matches_username: 0.5,
+  ens_stem_matches_display_name: 0.35,
+};
+
+/** Posts kept as evidence per pairing. */
+const MAX_POST_EVIDENCE = 5;
+
+/**
+ * Addresses posted by more accounts than this are mostly other people's, like
+ * a popular delegate's, so only delegate statements count for them.
+ */
+const MAX_ADDRESS_POSTERS = 3;
+
+const DELEGATE_STATEMENT_TOPIC =
+  /delegate\s+(statement|application|platform|thread|communication)/i;
+
+export function getPairKey(voterId: string, discourseUserId: string): string {
+  return `${voterId}:${discourseUserId}`;
+}
+
+/**
+ * Username a `com.discourse` ENS record points at on the forum: a bare
+ * username, `@username`, or a profile URL on the forum itself.
+ */
+export function parseDiscourseRecord(
+  record: string | null,
+  forumUrl: string | null
+): string | null {
+  const value = record?.trim();
+  if (!value) {
+    return null;
+  }
+
+  if (/^https?:\/\//i.test(value)) {
+    let url: URL;
+    let forum: URL;
+    try {
+      url = new URL(value);
+      forum = new URL(forumUrl ?? '');
+    } catch {
+      return null;
+    }
+
+    const host = (hostname: string) => hostname.replace(/^www\./, '');
+    if (host(url.hostname) !== host(forum.hostname)) {
+      return null;
+    }
+
+    const match = url.pathname.match(/^\/u(?:sers)?\/([^/]+)/);
+    return match ? decodeURIComponent(match[1]!).toLowerCase() : null;
+  }
+
+  const username = value.replace(/^@/, '');
+  return /^[\w.-]+$/.test(username) ? username.toLowerCase() : null;
+}
+
+export function isDelegateStatementTopic(title: string): boolean {
+  return DELEGATE_STATEMENT_TOPIC.test(title);
+}
+
+export function combineEvidenceScore(evidence: SuggestionEvidence[]): number {
+  const kinds = new Set(evidence.map((item) => item.kind));
+  let miss = 1;
+  for (const kind of kinds) {
+    miss *= 1 - EVIDENCE_WEIGHTS[kind];
+  }
+
+  return Math.round((1 - miss) * 1000) / 1000;
+}
+
+/**
+ * Score pairings of voters and forum accounts from their ENS names and
+ * records, their forum profiles, and addresses posted on the forum. Pairings
+ * already linked through a delegate, or scoring below `minScore`, are left out.
+ */
+export function suggestIdentityLinks(input: {
+  voters: SuggestionVoter[];
+  discourseUsers: SuggestionDiscourseUser[];
+  mentions: AddressMention[];
+  forumUrl: string | null;
+  linkedPairs: Set<string>;
+  minScore?: number;
+}): IdentitySuggestion[] {
+  const usersByUsername = new Map<string, SuggestionDiscourseUser>();
+  const usersByNormalizedUsername = new Map<string, SuggestionDiscourseUser[]>();
+  const usersByNormalizedName = new Map<string, SuggestionDiscourseUser[]>();
+  const push = <K, V>(map: Map<K, V[]>, key: K, value: V) => {
+    map.set(key, [...(map.get(key) ?? []), value]);
+  };
+
+  for (const user of input.discourseUsers) {
+    usersByUsername.set(user.username.toLowerCase(), user);
+    push(usersByNormalizedUsername, normalizeText(user.username), user);
+    if (user.name) {
+      push(usersByNormalizedName, normalizeText(user.name), user);
+    }
+  }
+
+  const evidenceByPair = new Map<
+    string,
+    { voterId: string; discourseUserId: string; evidence: SuggestionEvidence[] }
+  >();
+  const addEvidence = (
+    voterId: string,
+    discourseUserId: string,
+    evidence: SuggestionEvidence
+  ) => {
+    const key = getPairKey(voterId, discourseUserId);
+    const pair = evidenceByPair.get(key) ?? {
+      voterId,
+      discourseUserId,
+      evidence: [],
+    };
+    const postEvidenceCount = pair.evidence.filter(
+      (item) => 'postId' in item
+    ).length;
+    if ('postId' in evidence && postEvidenceCount >= MAX_POST_EVIDENCE) {
+      return;
+    }
+
+    pair.evidence.push(evidence);
+    evidenceByPair.set(key, pair);
+  };
+
+  const votersByAddress = new Map<string, SuggestionVoter>();
+  for (const voter of input.voters) {
+    votersByAddress.set(voter.address.toLowerCase(), voter);
+
+    const recordUsername = parseDiscourseRecord(
+      voter.discourse,
+      input.forumUrl
+    );
+    const recordUser = recordUsername
+      ? usersByUsername.get(recordUsername)
+      : undefined;
+    if (recordUser && voter.discourse) {
+      addEvidence(voter.id, recordUser.id, {
+        kind: 'ens_discourse_record',
+        ens: voter.ens,
+        record: voter.discourse,
+      });
+    }
+
+    const ens = voter.ens?.toLowerCase();
+    const ensStem = getEnsStem(ens);
+    if (!ens || !ensStem) {
+      continue;
+    }
+
+    for (const user of usersByNormalizedUsername.get(ensStem) ?? []) {
+      addEvidence(voter.id, user.id, {
+        kind: 'ens_stem_matches_username',
+        ens,
+        username: user.username,
+      });
+    }
+    for (const user of usersByNormalizedName.get(ensStem) ?? []) {
+      addEvidence(voter.id, user.id, {
+        kind: 'ens_stem_matches_display_name',
+        ens,
+        name: user.name!,
+      });
+    }
+  }
+
+  // Full ENS names spelled out in a profile are rarer than stems, so they're
+  // looked up per account rather than per voter
+  const votersByEns = new Map<string, SuggestionVoter>();
+  for (const voter of input.voters) {
+    if (voter.ens) {
+      votersByEns.set(voter.ens.toLowerCase(), voter);
+    }
+  }
+  for (const user of input.discourseUsers) {
+    for (const field of ['name', 'title', 'flair'] as const) {
+      const value = user[field];
+      for (const ens of value?.toLowerCase().match(/[\w-]+(\.[\w-]+)*\.eth\b/g) ??
+        []) {
+        const voter = votersByEns.get(ens);
+        if (voter) {
+          addEvidence(voter.id, user.id, {
+            kind: 'ens_in_profile',
+            ens,
+            field,
+            value: value!,
+          });
+        }
+      }
+    }
+  }
+
+  const postersByAddress = new Map<string, Set<string>>();
+  for (const mention of input.mentions) {
+    const address = mention.address.toLowerCase();
+    const posters = postersByAddress.get(address) ?? new Set<string>();
+    posters.add(mention.discourseUserId);
+    postersByAddress.set(address, posters);
+  }
+
+  for (const mention of input.mentions) {
+    const address = mention.address.toLowerCase();
+    const voter = votersByAddress.get(address);
+    if (!voter) {
+      continue;
+    }
+
+    // Replies in someone else's statement thread are ordinary posts
+    const isStatement =
+      mention.postNumber === 1 &&
+      isDelegateStatementTopic(mention.topicTitle);
+    if (
+      !isStatement &&
+      (postersByAddress.get(address)?.size ?? 0) > MAX_ADDRESS_POSTERS
+    ) {
+      continue;
+    }
+
+    addEvidence(voter.id, mention.discourseUserId, {
+      kind: isStatement ? 'delegate_statement' : 'address_in_post',
+      postId: mention.postId,
+      topicId: mention.topicId,
+      topicTitle: mention.topicTitle,
+    });
+  }
+
+  const minScore = input.minScore ?? MIN_SUGGESTION_SCORE;
+  return [...evidenceByPair.entries()]
+    .filter(([key]) => !input.linkedPairs.has(key))
+    .map(([, pair]) => ({
+      ...pair,
+      score: combineEvidenceScore(pair.evidence),
+    }))
+    .filter((suggestion) => suggestion.score >= minScore)
+    .sort(
+      (left, right) =>
+        right.score - left.score ||
+        left.voterId.localeCompare(right.voterId) ||
+        left.discourseUserId.localeCompare(right.discourseUserId)
+    );
+}
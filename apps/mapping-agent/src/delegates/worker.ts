@@ -8,7 +8,10 @@ import { getPublicSchemaExport } from '../shared/schema-export';
 import { createDelegateExtension } from './extension';
 import { buildDelegatePrompt, buildDelegateSystemPrompt } from './prompt';
 import { attachLatestDelegateDecisionSession } from '../shared/audit';
-import { runDeterministicDelegateMappings } from './repository';
+import {
+  runDeterministicDelegateMappings,
+  runIdentitySuggestions,
+} from './repository';
 
 export async function runDelegateMappingWorker(
   config: MappingAgentConfig,
@@ -49,6 +52,12 @@ export async function runDelegateMappingWorker(
       'Completed deterministic delegate mapping pass'
     );
 
+    const { suggested } = await runIdentitySuggestions(context);
+    logger.info(
+      { daoSlug: dao.slug, suggested },
+      'Refreshed voter and discourse user link suggestions'
+    );
+
     if (unresolvedCases.length === 0) {
       continue;
     }
@@ -119,6 +119,7 @@ export async function loadProposalWorkerContext(
         .where('closed', '=', false)
         .where('archived', '=', false)
         .where('visible', '=', true)
+        .where('restricted', '=', false)
         .orderBy('createdAt', 'asc')
         .execute()
     : [];
@@ -436,7 +437,8 @@ FROM proposal`,
   posts_count,
   last_posted_at,
   bumped_at
-FROM discourse_topic`,
+FROM discourse_topic
+WHERE NOT restricted`,
     },
     {
       name: 'proposal_groups',
@@ -0,0 +1,269 @@
+use crate::{
+    extensions::{
+        db_extension::{DAO_SLUG_ID_MAP, DB},
+        failed_events::{delete_failed_event, find_failed_event, list_failed_events},
+        governor_backfill::backfill_governors,
+        proposal_revisions::{diff_revisions, find_proposal_revision, proposal_revisions},
+        proposal_transitions::{
+            StateChange, TransitionSource, correct_proposal_state, proposal_state_history,
+        },
+        tally_audit::{audit_tallies, open_discrepancies},
+        voting_power::{
+            rebuild_delegated_voting_power_snapshots, rebuild_voting_power_latest,
+            verify_delegated_voting_power_snapshots, verify_voting_power_latest,
+        },
+        voting_power_gaps::{find_voting_power_gaps, heal_voting_power_gaps},
+    },
+    tasks::failed_event_replay::replay_and_resolve,
+};
+use anyhow::{Context, Result, bail};
+use proposalsapp_db::models::{proposal, sea_orm_active_enums::ProposalState};
+use sea_orm::{ActiveEnum, EntityTrait, prelude::Uuid};
+use tracing::info;
+
+/// Run a one-off operator command instead of the indexer, e.g.
+/// `proposalsapp-rindexer rebuild-voting-power-latest arbitrum`.
+pub async fn run_command(command: &str, args: &[String]) -> Result<()> {
+    match command {
+        "rebuild-voting-power-latest" => {
+            let dao_id = dao_id_arg(args)?;
+            let written = rebuild_voting_power_latest(dao_id).await?;
+            let report = verify_voting_power_latest(dao_id).await?;
+            if !report.is_consistent() {
+                bail!("voting_power_latest is still inconsistent after rebuild: {report:?}");
+            }
+            info!(
+                written_rows = written,
+                "voting_power_latest rebuilt and verified"
+            );
+        }
+        "verify-voting-power-latest" => {
+            let report = verify_voting_power_latest(dao_id_arg(args)?).await?;
+            if !report.is_consistent() {
+                bail!("voting_power_latest is inconsistent: {report:?}");
+            }
+        }
+        "rebuild-delegated-vp-snapshots" => {
+            let written = rebuild_delegated_voting_power_snapshots(dao_id_arg(args)?).await?;
+            info!(
+                written_snapshots = written,
+                "Delegated voting power snapshots rebuilt"
+            );
+        }
+        "verify-delegated-vp-snapshots" => {
+            let dao_id = dao_id_arg(args)?.context("A DAO slug is required")?;
+            if !verify_delegated_voting_power_snapshots(dao_id).await? {
+                bail!("Delegated voting power snapshots are inconsistent with voting_power_latest");
+            }
+        }
+        "find-voting-power-gaps" => {
+            let dao_slug = args.first().context("A DAO slug is required")?;
+            let gaps = find_voting_power_gaps(dao_slug).await?;
+            if !gaps.is_empty() {
+                bail!(
+                    "{} voting power gaps found for {dao_slug}; run heal-voting-power-gaps {dao_slug}",
+                    gaps.len()
+                );
+            }
+        }
+        "heal-voting-power-gaps" => {
+            let dao_slug = args.first().context("A DAO slug is required")?;
+            let remaining = heal_voting_power_gaps(dao_slug).await?;
+            if !remaining.is_empty() {
+                bail!(
+                    "{} voting power gaps left after healing: {remaining:?}",
+                    remaining.len()
+                );
+            }
+            info!("Voting power gaps healed");
+        }
+        "list-failed-events" => {
+            let failed_events = list_failed_events(args.first().map(String::as_str)).await?;
+            for failed_event in &failed_events {
+                info!(
+                    id = %failed_event.id,
+                    handler = %failed_event.handler,
+                    block_number = failed_event.block_number,
+                    transaction_hash = %failed_event.transaction_hash,
+                    log_index = failed_event.log_index,
+                    attempts = failed_event.attempts,
+                    next_attempt_at = ?failed_event.next_attempt_at,
+                    error = %failed_event.error,
+                    "Failed event"
+                );
+            }
+            info!(
+                failed_event_count = failed_events.len(),
+                "Listed failed events"
+            );
+        }
+        "replay-failed-events" => {
+            // Replays right away, regardless of the backoff
+            let failed_events = match args.first() {
+                Some(id) => {
+                    let id = id.parse::<Uuid>().context("Invalid failed event id")?;
+                    vec![
+                        find_failed_event(id)
+                            .await?
+                            .with_context(|| format!("Failed event {id} not found"))?,
+                    ]
+                }
+                None => list_failed_events(None).await?,
+            };
+
+            let mut remaining = 0;
+            for failed_event in &failed_events {
+                if !replay_and_resolve(failed_event).await? {
+                    remaining += 1;
+                }
+            }
+            if remaining > 0 {
+                bail!(
+                    "{remaining} of {} failed events could not be replayed",
+                    failed_events.len()
+                );
+            }
+            info!(
+                replayed_count = failed_events.len(),
+                "Failed events replayed"
+            );
+        }
+        "discard-failed-event" => {
+            let id = args
+                .first()
+                .context("A failed event id is required")?
+                .parse::<Uuid>()
+                .context("Invalid failed event id")?;
+            if !delete_failed_event(id).await? {
+                bail!("Failed event {id} not found");
+            }
+            info!(failed_event_id = %id, "Failed event discarded");
+        }
+        "backfill-governors" => {
+            backfill_governors(args.first().map(String::as_str)).await?;
+            info!("Governors backfilled");
+        }
+        "audit-tallies" => {
+            let report = audit_tallies(args.first().map(String::as_str)).await?;
+            for discrepancy in open_discrepancies().await? {
+                info!(
+                    proposal_id = %discrepancy.proposal_id,
+                    field = %discrepancy.field,
+                    severity = %discrepancy.severity,
+                    stored_value = %discrepancy.stored_value,
+                    onchain_value = %discrepancy.onchain_value,
+                    first_seen_at = ?discrepancy.first_seen_at,
+                    "Open tally discrepancy"
+                );
+            }
+            if report.critical > 0 {
+                bail!(
+                    "{} critical tally discrepancies found: {report:?}",
+                    report.critical
+                );
+            }
+            info!(report = ?report, "Tallies audited");
+        }
+        "set-proposal-state" => {
+            let (Some(id), Some(state)) = (args.first(), args.get(1)) else {
+                bail!("A proposal id and a state are required");
+            };
+            let id = id.parse::<Uuid>().context("Invalid proposal id")?;
+            let state = ProposalState::try_from_value(&state.to_uppercase())
+                .map_err(|_| anyhow::anyhow!("Unknown proposal state: {state}"))?;
+            let db = DB.get().context("DB not initialized")?;
+            let proposal = proposal::Entity::find_by_id(id)
+                .one(db)
+                .await?
+                .with_context(|| format!("Proposal {id} not found"))?;
+
+            correct_proposal_state(
+                &proposal,
+                state.clone(),
+                &StateChange::new(TransitionSource::Manual),
+                "operator correction",
+            )
+            .await?;
+            info!(proposal_id = %id, state = ?state, "Proposal state set");
+        }
+        "proposal-state-history" => {
+            let id = args
+                .first()
+                .context("A proposal id is required")?
+                .parse::<Uuid>()
+                .context("Invalid proposal id")?;
+            for change in proposal_state_history(id).await? {
+                info!(
+                    from = ?change.from_state,
+                    to = ?change.to_state,
+                    source = %change.source,
+                    correction = change.correction,
+                    block_number = change.block_number,
+                    txid = change.txid.as_deref(),
+                    created_at = ?change.created_at,
+                    "Proposal state change"
+                );
+            }
+        }
+        "proposal-revisions" => {
+            let id = args
+                .first()
+                .context("A proposal id is required")?
+                .parse::<Uuid>()
+                .context("Invalid proposal id")?;
+            for revision in proposal_revisions(id).await? {
+                info!(
+                    revision_id = %revision.id,
+                    revision = revision.revision,
+                    name = %revision.name,
+                    content_hash = %revision.content_hash,
+                    created_at = ?revision.created_at,
+                    "Proposal revision"
+                );
+            }
+        }
+        "diff-proposal-revisions" => {
+            let (Some(before), Some(after)) = (args.first(), args.get(1)) else {
+                bail!("Two proposal revision ids are required");
+            };
+            let mut revisions = Vec::new();
+            for id in [before, after] {
+                let id = id.parse::<Uuid>().context("Invalid proposal revision id")?;
+                revisions.push(
+                    find_proposal_revision(id)
+                        .await?
+                        .with_context(|| format!("Proposal revision {id} not found"))?,
+                );
+            }
+
+            let diff = diff_revisions(&revisions[0], &revisions[1]);
+            info!(
+                changed = diff.has_changes(),
+                diff = %serde_json::to_string(&diff)?,
+                "Proposal revisions diffed"
+            );
+        }
+        _ => bail!(
+            "Unknown command: {command}. Available commands: rebuild-voting-power-latest [dao-slug], verify-voting-power-latest [dao-slug], rebuild-delegated-vp-snapshots [dao-slug], verify-delegated-vp-snapshots <dao-slug>, find-voting-power-gaps <dao-slug>, heal-voting-power-gaps <dao-slug>, list-failed-events [handler], replay-failed-events [id], discard-failed-event <id>, backfill-governors [dao-slug], audit-tallies [dao-slug], set-proposal-state <proposal-id> <state>, proposal-state-history <proposal-id>, proposal-revisions <proposal-id>, diff-proposal-revisions <revision-id> <revision-id>"
+        ),
+    }
+
+    Ok(())
+}
+
+/// Optional DAO slug as the first argument; no argument means all DAOs.
+fn dao_id_arg(args: &[String]) -> Result<Option<Uuid>> {
+    let Some(slug) = args.first() else {
+        return Ok(None);
+    };
+
+    let dao_id = DAO_SLUG_ID_MAP
+        .get()
+        .context("DAO map not initialized")?
+        .lock()
+        .unwrap()
+        .get(slug)
+        .copied()
+        .with_context(|| format!("Unknown DAO slug: {slug}"))?;
+    Ok(Some(dao_id))
+}
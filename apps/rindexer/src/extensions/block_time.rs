@@ -286,6 +286,13 @@ async fn get_provider_cache_for_network(
     }
 }
 
+/// Latest block number of a network, through the cached provider.
+#[instrument(name = "block_time_current_block", skip(network), fields(network = network))]
+pub async fn current_block(network: &str) -> Result<u64> {
+    let provider = get_provider_cache_for_network(network).await;
+    get_current_block_number(&provider).await
+}
+
 // Get current block number from provider
 #[instrument(name = "block_time_get_current_block", skip_all)]
 async fn get_current_block_number(
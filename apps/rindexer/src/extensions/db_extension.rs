@@ -1,30 +1,47 @@
 use crate::{
-    extensions::snapshot_api::SnapshotProposal,
+    extensions::{
+        ens_identity::{
+            EnsIdentity, EnsRefreshPolicy, ProviderEnsResolver, UnsupportedResolution,
+            resolve_identities,
+        },
+        proposal_revisions::{content_hash, record_revision},
+        proposal_transitions::{
+            StateChange, TransitionSource, is_allowed_transition, notify_schedule_changed,
+            record_state_history,
+        },
+        snapshot_api::SnapshotProposal,
+        voting_power::{
+            total_delegated_voting_power_at, update_delegated_voting_power_snapshots,
+            upsert_voting_power_latest,
+        },
+    },
     rindexer_lib::typings::networks::get_ethereum_provider,
 };
 use alloy::primitives::Address;
-use alloy_ens::ProviderEnsExt;
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use once_cell::sync::{Lazy, OnceCell};
 use proposalsapp_db::models::{
     dao, dao_governor, delegation, proposal, vote, voter, voting_power_timeseries,
 };
-use rindexer::provider::RindexerProvider;
 use sea_orm::{
     ActiveValue::NotSet,
-    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait,
-    QueryFilter, Set, Statement,
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, IdenStatic, QueryFilter, Set,
+    TransactionTrait,
     prelude::Uuid,
-    sea_query::{Expr, OnConflict},
+    sea_query::{Expr, OnConflict, SimpleExpr},
 };
 use std::{
     collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
     time::Duration,
 };
-use tokio::sync::Semaphore;
 use tracing::{debug, error, info, instrument, warn};
+use utils::{
+    job_queue::enqueue,
+    outbox::{GovernanceEvent, publish},
+    types::ProposalJobData,
+};
 
 pub static DB: OnceCell<DatabaseConnection> = OnceCell::new();
 pub static DAO_SLUG_ID_MAP: OnceCell<Mutex<HashMap<String, Uuid>>> = OnceCell::new();
@@ -139,8 +156,11 @@ pub async fn initialize_db() -> Result<()> {
     Ok(())
 }
 
-#[instrument(name = "db_store_proposal", skip(proposal))]
-pub async fn store_proposal(proposal: proposal::ActiveModel) -> Result<()> {
+/// Insert or update a proposal. An incoming state only replaces the stored
+/// one when `change`'s source may make that transition, and every state
+/// change is recorded in the proposal's history.
+#[instrument(name = "db_store_proposal", skip(proposal, change), fields(source = change.source.as_str()))]
+pub async fn store_proposal(proposal: proposal::ActiveModel, change: &StateChange) -> Result<()> {
     let db = DB
         .get()
         .ok_or_else(|| anyhow::anyhow!("DB not initialized"))?;
@@ -166,7 +186,36 @@ pub async fn store_proposal(proposal: proposal::ActiveModel) -> Result<()> {
         .one(db)
         .await?;
 
+    let txn = db.begin().await?;
+
     if let Some(existing) = existing_proposal {
+        let proposal_state = match proposal.proposal_state.clone().take() {
+            Some(state) if state != existing.proposal_state => {
+                if is_allowed_transition(change.source, &existing.proposal_state, &state) {
+                    record_state_history(
+                        &txn,
+                        &existing,
+                        Some(existing.proposal_state.clone()),
+                        state.clone(),
+                        change,
+                        false,
+                    )
+                    .await?;
+                    state
+                } else {
+                    warn!(
+                        proposal_id = %existing.id,
+                        external_id = %external_id,
+                        from = ?existing.proposal_state,
+                        to = ?state,
+                        "Rejected invalid proposal state transition, keeping the stored state"
+                    );
+                    existing.proposal_state.clone()
+                }
+            }
+            _ => existing.proposal_state.clone(),
+        };
+
         // Update existing proposal
         debug!(proposal_id = %existing.id, external_id = %external_id, "Updating existing proposal");
         let active_model = proposal::ActiveModel {
@@ -198,11 +247,7 @@ pub async fn store_proposal(proposal: proposal::ActiveModel) -> Result<()> {
                 .take()
                 .unwrap_or(existing.choices.clone())),
             quorum: Set(proposal.quorum.clone().take().unwrap_or(existing.quorum)),
-            proposal_state: Set(proposal
-                .proposal_state
-                .clone()
-                .take()
-                .unwrap_or(existing.proposal_state.clone())),
+            proposal_state: Set(proposal_state),
             marked_spam: Set(proposal
                 .marked_spam
                 .clone()
@@ -257,20 +302,99 @@ pub async fn store_proposal(proposal: proposal::ActiveModel) -> Result<()> {
                 .unwrap_or(existing.governor_id)),
         };
 
-        proposal::Entity::update(active_model).exec(db).await?;
+        let updated = proposal::Entity::update(active_model).exec(&txn).await?;
+
+        // Proposals stored before revisions were tracked get the content
+        // they had as their first revision
+        if content_hash(&updated) != content_hash(&existing) {
+            record_revision(&txn, &existing).await?;
+            record_revision(&txn, &updated).await?;
+        }
         info!(proposal_id = %existing.id, external_id = %external_id, "Proposal updated successfully");
     } else {
         // Insert new proposal
         debug!(external_id = %external_id, "Inserting new proposal");
-        let inserted_proposal = proposal::Entity::insert(proposal.clone()).exec(db).await?;
-        info!(proposal_id = %inserted_proposal.last_insert_id, external_id = %external_id, "Proposal inserted successfully");
-
-        // No longer creating jobs for new proposals - mapping-agent handles grouping.
+        let inserted_proposal = proposal::Entity::insert(proposal)
+            .exec_with_returning(&txn)
+            .await?;
+        record_state_history(
+            &txn,
+            &inserted_proposal,
+            None,
+            inserted_proposal.proposal_state.clone(),
+            change,
+            false,
+        )
+        .await?;
+        record_revision(&txn, &inserted_proposal).await?;
+        publish(
+            &txn,
+            inserted_proposal.dao_id,
+            [GovernanceEvent::ProposalCreated {
+                proposal_id: inserted_proposal.id,
+                governor_id: inserted_proposal.governor_id,
+                external_id: inserted_proposal.external_id.clone(),
+            }],
+        )
+        .await?;
+        enqueue(
+            &txn,
+            &ProposalJobData {
+                proposal_id: inserted_proposal.id,
+            },
+        )
+        .await?;
+        info!(proposal_id = %inserted_proposal.id, external_id = %external_id, "Proposal inserted successfully");
     }
 
+    txn.commit().await?;
+
+    // New or extended proposals may move the next state transition
+    notify_schedule_changed();
+
     Ok(())
 }
 
+/// Columns a re-indexed vote, delegation or voting power row overwrites.
+const VOTE_UPDATE_COLUMNS: [vote::Column; 5] = [
+    vote::Column::Choice,
+    vote::Column::VotingPower,
+    vote::Column::Reason,
+    vote::Column::CreatedAt,
+    vote::Column::BlockCreatedAt,
+];
+const DELEGATION_UPDATE_COLUMNS: [delegation::Column; 5] = [
+    delegation::Column::Delegate,
+    delegation::Column::Timestamp,
+    delegation::Column::Block,
+    delegation::Column::FromDelegate,
+    delegation::Column::LogIndex,
+];
+const VOTING_POWER_UPDATE_COLUMNS: [voting_power_timeseries::Column; 5] = [
+    voting_power_timeseries::Column::VotingPower,
+    voting_power_timeseries::Column::Timestamp,
+    voting_power_timeseries::Column::Block,
+    voting_power_timeseries::Column::LogIndex,
+    voting_power_timeseries::Column::PreviousVotingPower,
+];
+
+/// Condition of an upsert's update that skips rows already holding the
+/// incoming `columns`, so only inserted and changed rows are returned.
+/// Re-indexing, backfills, gap healing and replays store history again, and
+/// only what actually changed is published.
+fn any_changed<C: IdenStatic>(table: &str, columns: &[C]) -> SimpleExpr {
+    Expr::cust(
+        columns
+            .iter()
+            .map(|column| {
+                let column = column.as_str();
+                format!("{table}.{column} IS DISTINCT FROM EXCLUDED.{column}")
+            })
+            .collect::<Vec<_>>()
+            .join(" OR "),
+    )
+}
+
 #[instrument(name = "db_store_votes", skip(votes), fields(vote_count = votes.len()))]
 pub async fn store_votes(votes: Vec<vote::ActiveModel>, governor_id: Uuid) -> Result<()> {
     let db = DB
@@ -354,6 +478,8 @@ pub async fn store_votes(votes: Vec<vote::ActiveModel>, governor_id: Uuid) -> Re
 
     // Process votes in chunks
     for chunk in vote_active_models.chunks(BATCH_SIZE) {
+        let txn = db.begin().await?;
+
         // Use SeaORM's insert_many with on_conflict
         let insert_result = vote::Entity::insert_many(chunk.to_vec())
             .on_conflict(
@@ -363,20 +489,26 @@ pub async fn store_votes(votes: Vec<vote::ActiveModel>, governor_id: Uuid) -> Re
                     vote::Column::Txid,
                 ])
                 .target_and_where(Expr::col(vote::Column::Txid).is_not_null())
-                .update_columns([
-                    vote::Column::Choice,
-                    vote::Column::VotingPower,
-                    vote::Column::Reason,
-                    vote::Column::CreatedAt,
-                    vote::Column::BlockCreatedAt,
-                ])
+                .update_columns(VOTE_UPDATE_COLUMNS)
+                .action_and_where(any_changed("vote", &VOTE_UPDATE_COLUMNS))
                 .to_owned(),
             )
-            .exec(db)
+            .exec_with_returning_many(&txn)
             .await;
 
         match insert_result {
-            Ok(_) => {
+            Ok(stored) => {
+                for dao_id in stored.iter().map(|v| v.dao_id).collect::<HashSet<_>>() {
+                    let events = stored.iter().filter(|v| v.dao_id == dao_id).map(|v| {
+                        GovernanceEvent::VoteCast {
+                            vote_id: v.id,
+                            proposal_id: v.proposal_id,
+                            voter_address: v.voter_address.clone(),
+                        }
+                    });
+                    publish(&txn, dao_id, events).await?;
+                }
+                txn.commit().await?;
                 debug!(chunk_size = chunk.len(), "Successfully upserted vote chunk");
             }
             Err(err) => {
@@ -407,6 +539,8 @@ pub async fn store_delegations(delegations: Vec<delegation::ActiveModel>) -> Res
 
     // Process delegations in chunks
     for chunk in delegations.chunks(BATCH_SIZE) {
+        let txn = db.begin().await?;
+
         // Use SeaORM's insert_many with on_conflict
         let insert_result = delegation::Entity::insert_many(chunk.to_vec())
             .on_conflict(
@@ -416,18 +550,27 @@ pub async fn store_delegations(delegations: Vec<delegation::ActiveModel>) -> Res
                     delegation::Column::Txid,
                 ])
                 .target_and_where(Expr::col(delegation::Column::Txid).is_not_null())
-                .update_columns([
-                    delegation::Column::Delegate,
-                    delegation::Column::Timestamp,
-                    delegation::Column::Block,
-                ])
+                .update_columns(DELEGATION_UPDATE_COLUMNS)
+                .action_and_where(any_changed("delegation", &DELEGATION_UPDATE_COLUMNS))
                 .to_owned(),
             )
-            .exec(db)
+            .exec_with_returning_many(&txn)
             .await;
 
         match insert_result {
-            Ok(_) => {
+            Ok(stored) => {
+                for dao_id in stored.iter().map(|d| d.dao_id).collect::<HashSet<_>>() {
+                    let events = stored.iter().filter(|d| d.dao_id == dao_id).map(|d| {
+                        GovernanceEvent::DelegationChanged {
+                            delegation_id: d.id,
+                            delegator: d.delegator.clone(),
+                            delegate: d.delegate.clone(),
+                            from_delegate: d.from_delegate.clone(),
+                        }
+                    });
+                    publish(&txn, dao_id, events).await?;
+                }
+                txn.commit().await?;
                 debug!(
                     chunk_size = chunk.len(),
                     "Successfully upserted delegation chunk"
@@ -464,8 +607,15 @@ pub async fn store_voting_powers(
         .get()
         .ok_or_else(|| anyhow::anyhow!("DB not initialized"))?;
 
-    // Process voting powers in chunks
+    // Process voting powers in chunks, keeping voting_power_latest and the
+    // delegated voting power snapshots in step with the timeseries by writing
+    // all of them in one transaction per chunk
     for chunk in voting_powers.chunks(BATCH_SIZE) {
+        let txn = db
+            .begin()
+            .await
+            .context("Failed to start voting power transaction")?;
+
         // Use SeaORM's insert_many with on_conflict
         let insert_result = voting_power_timeseries::Entity::insert_many(chunk.to_vec())
             .on_conflict(
@@ -475,23 +625,18 @@ pub async fn store_voting_powers(
                     voting_power_timeseries::Column::Txid,
                 ])
                 .target_and_where(Expr::col(voting_power_timeseries::Column::Txid).is_not_null())
-                .update_columns([
-                    voting_power_timeseries::Column::VotingPower,
-                    voting_power_timeseries::Column::Timestamp,
-                    voting_power_timeseries::Column::Block,
-                ])
+                .update_columns(VOTING_POWER_UPDATE_COLUMNS)
+                .action_and_where(any_changed(
+                    "voting_power_timeseries",
+                    &VOTING_POWER_UPDATE_COLUMNS,
+                ))
                 .to_owned(),
             )
-            .exec(db)
+            .exec_with_returning_many(&txn)
             .await;
 
-        match insert_result {
-            Ok(_) => {
-                debug!(
-                    chunk_size = chunk.len(),
-                    "Successfully upserted voting power chunk"
-                );
-            }
+        let stored = match insert_result {
+            Ok(stored) => stored,
             Err(err) => {
                 error!(error = %err, chunk_size = chunk.len(), "Failed to bulk upsert voting powers");
                 return Err(anyhow::anyhow!(
@@ -499,7 +644,29 @@ pub async fn store_voting_powers(
                     err
                 ));
             }
+        };
+        for dao_id in stored.iter().map(|v| v.dao_id).collect::<HashSet<_>>() {
+            let events = stored.iter().filter(|v| v.dao_id == dao_id).map(|v| {
+                GovernanceEvent::VotingPowerChanged {
+                    timeseries_id: v.id,
+                    voter: v.voter.clone(),
+                    block: v.block,
+                }
+            });
+            publish(&txn, dao_id, events).await?;
         }
+
+        upsert_voting_power_latest(&txn, chunk).await?;
+        update_delegated_voting_power_snapshots(&txn, chunk).await?;
+
+        txn.commit()
+            .await
+            .context("Failed to commit voting power chunk")?;
+
+        debug!(
+            chunk_size = chunk.len(),
+            "Successfully upserted voting power chunk"
+        );
     }
 
     info!(
@@ -509,17 +676,26 @@ pub async fn store_voting_powers(
     Ok(())
 }
 
-/// Maximum concurrent ENS lookups to avoid rate limiting
-const ENS_CONCURRENCY_LIMIT: usize = 5;
+/// Upper bound for resolving the identities of one voter chunk (batched
+/// reverse and forward lookups plus text records)
+const ENS_LOOKUP_TIMEOUT: Duration = Duration::from_secs(60);
 
-/// Result of an ENS lookup operation
+/// Result of an ENS lookup operation.
 struct EnsLookupResult {
     address: String,
-    ens: Option<String>,
-    is_new_voter: bool,
+    lookup: EnsLookup,
     existing_voter_id: Option<Uuid>,
 }
 
+enum EnsLookup {
+    Resolved(EnsIdentity),
+    /// The name can't be verified here, see [`UnsupportedResolution`]. The
+    /// stored identity is kept and looked up again once it's due.
+    Unsupported,
+    /// The voter is left untouched and retried on the next run.
+    Failed,
+}
+
 #[instrument(name = "db_store_voters", skip(voter_addresses), fields(voter_address_count = voter_addresses.len()))]
 async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
     let db = DB
@@ -527,7 +703,8 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!("DB not initialized"))?;
 
     // Get the provider once at the beginning to reuse throughout the function
-    let provider = get_ethereum_provider().await;
+    let resolver = Arc::new(ProviderEnsResolver::new(get_ethereum_provider().await)?);
+    let refresh_policy = EnsRefreshPolicy::default();
 
     let voter_list: Vec<String> = voter_addresses.into_iter().collect();
     let total_voters = voter_list.len();
@@ -536,9 +713,6 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
         "Starting voter storage with ENS lookups"
     );
 
-    // Semaphore to limit concurrent ENS lookups to avoid rate limiting
-    let semaphore = Arc::new(Semaphore::new(ENS_CONCURRENCY_LIMIT));
-
     for (chunk_index, addresses_chunk) in voter_list.chunks(BATCH_SIZE).enumerate() {
         let chunk_start = chunk_index * BATCH_SIZE;
         let chunk_end = std::cmp::min(chunk_start + BATCH_SIZE, total_voters);
@@ -549,7 +723,7 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
             "Processing voter chunk with ENS lookups"
         );
 
-        // Fetch existing voters with address and ens in a single query
+        // Fetch existing voters in a single query
         let existing_voters_models: Vec<voter::Model> = voter::Entity::find()
             .filter(voter::Column::Address.is_in(addresses_chunk.to_vec()))
             .all(db)
@@ -561,105 +735,92 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
             .map(|v| (v.address.clone(), v))
             .collect();
 
-        // Collect addresses that need ENS lookup
-        let twenty_four_hours_ago = Utc::now() - Duration::from_secs(24 * 60 * 60);
-        let mut ens_lookup_tasks = Vec::new();
+        let now = Utc::now().naive_utc();
+        let mut lookup_results: Vec<EnsLookupResult> = Vec::new();
+        let mut pending_lookups: Vec<(String, Address, Option<Uuid>)> = Vec::new();
 
         for address in addresses_chunk {
             let existing_voter = existing_voters_map.get(address);
 
-            // Skip if voter was updated recently
+            // Skip if the stored identity is still fresh
             if let Some(voter) = existing_voter
-                && voter.updated_at > twenty_four_hours_ago.naive_utc()
+                && !refresh_policy.is_due(voter, now)
             {
                 debug!(
                     address = address,
-                    "Voter updated recently, skipping ENS lookup"
+                    "Voter identity refreshed recently, skipping ENS lookup"
                 );
                 continue;
             }
 
-            // Parse address
-            let eth_address = match address.parse::<Address>() {
-                Ok(addr) => addr,
+            match address.parse::<Address>() {
+                Ok(eth_address) => pending_lookups.push((
+                    address.clone(),
+                    eth_address,
+                    existing_voter.map(|v| v.id),
+                )),
                 Err(e) => {
                     debug!(address = address, error = %e, "Failed to parse address");
                     // For new voters with invalid addresses, still insert them without ENS
                     if existing_voter.is_none() {
-                        let address_owned = address.to_string();
-                        ens_lookup_tasks.push(tokio::spawn(async move {
-                            EnsLookupResult {
-                                address: address_owned,
-                                ens: None,
-                                is_new_voter: true,
-                                existing_voter_id: None,
-                            }
-                        }));
+                        lookup_results.push(EnsLookupResult {
+                            address: address.clone(),
+                            lookup: EnsLookup::Failed,
+                            existing_voter_id: None,
+                        });
                     }
-                    continue;
                 }
-            };
+            }
+        }
 
-            // Prepare ENS lookup task with rate limiting via semaphore
-            let sem = semaphore.clone();
-            let prov = provider.clone();
-            let addr_clone = address.clone();
-            let is_new = existing_voter.is_none();
-            let voter_id = existing_voter.map(|v| v.id);
-            let old_ens = existing_voter.and_then(|v| v.ens.clone());
-
-            let task = tokio::spawn(async move {
-                // Acquire semaphore permit to limit concurrency
-                let _permit = sem.acquire().await.expect("Semaphore closed");
-
-                // Try ENS lookup with timeout
-                let ens_result = match tokio::time::timeout(
-                    std::time::Duration::from_secs(5),
-                    isolated_ens_lookup(eth_address, addr_clone.clone(), prov),
-                )
-                .await
-                {
-                    Ok(result) => result.ok(),
-                    Err(_) => {
-                        debug!(address = addr_clone, "ENS lookup timed out");
-                        None
-                    }
-                };
+        // Resolve the whole chunk at once so reverse and forward lookups are
+        // batched through multicall. Spawned so a panic only loses this chunk.
+        let eth_addresses: Vec<Address> = pending_lookups.iter().map(|(_, a, _)| *a).collect();
+        let chunk_resolver = resolver.clone();
+        let identities = match tokio::spawn(async move {
+            tokio::time::timeout(
+                ENS_LOOKUP_TIMEOUT,
+                resolve_identities(chunk_resolver.as_ref(), &eth_addresses),
+            )
+            .await
+        })
+        .await
+        {
+            Ok(Ok(identities)) => identities,
+            Ok(Err(_)) => {
+                warn!(
+                    lookup_count = pending_lookups.len(),
+                    "ENS lookups timed out for chunk"
+                );
+                Vec::new()
+            }
+            Err(e) => {
+                warn!(error = %e, "Task error during ENS lookup");
+                Vec::new()
+            }
+        };
 
-                // Only update if ENS changed (for existing voters)
-                let should_update = if is_new {
-                    true
-                } else {
-                    ens_result.is_some() && ens_result != old_ens
-                };
-
-                if should_update {
-                    EnsLookupResult {
-                        address: addr_clone,
-                        ens: ens_result,
-                        is_new_voter: is_new,
-                        existing_voter_id: voter_id,
-                    }
-                } else {
-                    EnsLookupResult {
-                        address: addr_clone,
-                        ens: None,
-                        is_new_voter: false,
-                        existing_voter_id: None, // Signal no update needed
-                    }
+        let mut identities = identities.into_iter();
+        for (address, _, existing_voter_id) in pending_lookups {
+            let lookup = match identities.next() {
+                Some(Ok(identity)) => EnsLookup::Resolved(identity),
+                Some(Err(e)) if e.downcast_ref::<UnsupportedResolution>().is_some() => {
+                    debug!(address = address, error = %e, "ENS name can't be verified");
+                    EnsLookup::Unsupported
+                }
+                Some(Err(e)) => {
+                    debug!(address = address, error = %e, "ENS lookup failed");
+                    EnsLookup::Failed
                 }
+                None => EnsLookup::Failed,
+            };
+            lookup_results.push(EnsLookupResult {
+                address,
+                lookup,
+                existing_voter_id,
             });
-
-            ens_lookup_tasks.push(task);
         }
 
-        // Wait for all ENS lookups to complete (with controlled concurrency)
-        let lookup_results: Vec<EnsLookupResult> = futures::future::join_all(ens_lookup_tasks)
-            .await
-            .into_iter()
-            .filter_map(|r| r.ok())
-            .collect();
-
         info!(
             completed_lookups = lookup_results.len(),
             "Completed ENS lookups for chunk"
@@ -670,34 +831,47 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
         let mut voters_to_update: Vec<voter::ActiveModel> = Vec::new();
 
         for result in lookup_results {
-            if result.is_new_voter {
-                voters_to_insert.push(voter::ActiveModel {
-                    id: NotSet,
-                    address: Set(result.address),
-                    ens: if result.ens.is_some() {
-                        Set(result.ens)
-                    } else {
-                        NotSet
-                    },
-                    avatar: NotSet,
-                    updated_at: Set(Utc::now().naive_utc()),
-                });
-            } else if let Some(voter_id) = result.existing_voter_id {
-                // Only update if we have a voter_id (means ENS changed)
-                if let Some(ens) = result.ens {
+            match (result.existing_voter_id, result.lookup) {
+                (None, lookup) => {
+                    let mut model = voter::ActiveModel {
+                        address: Set(result.address),
+                        updated_at: Set(now),
+                        ..Default::default()
+                    };
+                    match lookup {
+                        EnsLookup::Resolved(identity) => {
+                            apply_ens_identity(&mut model, identity, now)
+                        }
+                        EnsLookup::Unsupported => model.ens_refreshed_at = Set(Some(now)),
+                        EnsLookup::Failed => {}
+                    }
+                    voters_to_insert.push(model);
+                }
+                (Some(voter_id), EnsLookup::Resolved(identity)) => {
                     debug!(
                         address = result.address,
-                        new_ens = ens,
-                        "Updating ENS for existing voter"
+                        ens = ?identity.ens,
+                        "Refreshing ENS identity for existing voter"
                     );
+                    let mut model = voter::ActiveModel {
+                        id: Set(voter_id),
+                        updated_at: Set(now),
+                        ..Default::default()
+                    };
+                    apply_ens_identity(&mut model, identity, now);
+                    voters_to_update.push(model);
+                }
+                // Keep the stored identity, but don't look it up again
+                // before it's due
+                (Some(voter_id), EnsLookup::Unsupported) => {
                     voters_to_update.push(voter::ActiveModel {
                         id: Set(voter_id),
-                        address: NotSet,
-                        ens: Set(Some(ens)),
-                        avatar: NotSet,
-                        updated_at: Set(Utc::now().naive_utc()),
+                        ens_refreshed_at: Set(Some(now)),
+                        ..Default::default()
                     });
                 }
+                // Lookup failed for an existing voter, keep what we have
+                (Some(_), EnsLookup::Failed) => {}
             }
         }
 
@@ -715,18 +889,18 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
             }
         }
 
-        // Perform bulk update for existing voters with new ENS
+        // Perform bulk update for existing voters with refreshed identities
         if !voters_to_update.is_empty() {
             info!(
                 update_count = voters_to_update.len(),
-                "Updating existing voters with new ENS"
+                "Updating existing voters with refreshed ENS identity"
             );
             for voter_update in voters_to_update {
                 let update_result = voter::Entity::update(voter_update).exec(db).await;
                 if let Err(e) = update_result {
-                    error!(error = %e, "Failed to update voter ENS");
+                    error!(error = %e, "Failed to update voter ENS identity");
                 } else {
-                    debug!("Voter ENS updated");
+                    debug!("Voter ENS identity updated");
                 }
             }
         }
@@ -735,65 +909,20 @@ async fn store_voters(voter_addresses: HashSet<String>) -> Result<()> {
     Ok(())
 }
 
-#[instrument(name = "db_isolated_ens_lookup", skip(address, provider, addr_string), fields(address = %address))]
-async fn isolated_ens_lookup(
-    address: Address,
-    addr_string: String,
-    provider: Arc<RindexerProvider>,
-) -> Result<String> {
-    // Spawn a task to isolate potential panics
-    match tokio::task::spawn(async move { provider.lookup_address(&address).await }).await {
-        Ok(result) => match result {
-            Ok(ens) => {
-                debug!(ens_name = ens, "ENS lookup successful");
-                Ok(ens)
-            }
-            Err(e) => {
-                debug!(error = %e, "ENS lookup error");
-                Err(anyhow::anyhow!("ENS lookup error: {}", e))
-            }
-        },
-        Err(e) => {
-            warn!(
-                address = addr_string,
-                error = %e,
-                "Task error during ENS lookup"
-            );
-            // This happens if the task panicked
-            Err(anyhow::anyhow!("Task error during ENS lookup: {}", e))
-        }
-    }
+/// Copy a resolved identity onto a voter. Every field is written, so records
+/// removed from ENS (or a name that no longer verifies) are cleared too.
+/// Names that can't be verified never get here, see [`EnsLookup::Unsupported`].
+fn apply_ens_identity(model: &mut voter::ActiveModel, identity: EnsIdentity, now: NaiveDateTime) {
+    model.ens_verified = Set(identity.is_verified());
+    model.ens = Set(identity.ens);
+    model.avatar = Set(identity.avatar);
+    model.url = Set(identity.url);
+    model.twitter = Set(identity.twitter);
+    model.discourse = Set(identity.discourse);
+    model.description = Set(identity.description);
+    model.ens_refreshed_at = Set(Some(now));
 }
 
-// #[instrument(name = "db_isolated_avatar_resolve", skip(ens, provider, addr_string),
-// fields(ens_name = ens))] async fn isolated_avatar_resolve(ens: String, addr_string: String,
-// provider: Arc<RindexerProvider>) -> Result<String> {     // Spawn a task to isolate potential
-// panics     match tokio::task::spawn(async move { provider.resolve_avatar(&ens).await }).await {
-//         Ok(result) => match result {
-//             Ok(avatar) => {
-//                 debug!(avatar_url = %avatar, "Avatar resolution successful");
-//                 Ok(avatar.to_string())
-//             }
-//             Err(e) => {
-//                 debug!(error = %e, "Avatar resolution error");
-//                 Err(anyhow::anyhow!("Avatar resolution error: {}", e))
-//             }
-//         },
-//         Err(e) => {
-//             warn!(
-//                 address = addr_string,
-//                 error = %e,
-//                 "Task error during avatar resolution"
-//             );
-//             // This happens if the task panicked
-//             Err(anyhow::anyhow!(
-//                 "Task error during avatar resolution: {}",
-//                 e
-//             ))
-//         }
-//     }
-// }
-
 /// Store a Snapshot proposal (wrapper around store_proposal for SnapshotProposal)
 #[instrument(name = "store_snapshot_proposal", skip(proposal))]
 pub async fn store_snapshot_proposal(
@@ -875,81 +1004,31 @@ pub async fn store_snapshot_proposal(
         governor_id: Set(governor_id),
     };
 
-    store_proposal(proposal_active_model).await
+    store_proposal(
+        proposal_active_model,
+        &StateChange::new(TransitionSource::Snapshot),
+    )
+    .await
 }
 
-/// Calculate total delegated voting power at a specific timestamp using type-safe SeaQuery CTE.
+/// Calculate total delegated voting power at a specific timestamp.
 ///
-/// This function uses SeaQuery's CommonTableExpression to build a type-safe query
-/// that calculates the sum of the latest voting power for each voter up to the given timestamp.
-///
-/// The table name is derived from the SeaORM entity, providing compile-time safety
-/// against table name mismatches after migrations.
+/// Reads the newest `delegated_voting_power_snapshot` at or before
+/// `timestamp`, which the indexer maintains alongside the timeseries with
+/// each DAO's excluded voters (e.g. timelock contracts) left out.
 ///
 /// # Arguments
+/// * `dao_id` - The DAO whose voting power is summed
 /// * `timestamp` - The point in time to calculate voting power for
-/// * `excluded_voter` - Address to exclude from the calculation (e.g., timelock contract)
 ///
 /// # Returns
 /// The total delegated voting power as f64
-#[instrument(name = "db_calculate_total_delegated_voting_power", skip(timestamp), fields(timestamp = ?timestamp, excluded_voter = %excluded_voter))]
+#[instrument(name = "db_calculate_total_delegated_voting_power", skip(timestamp), fields(dao_id = %dao_id, timestamp = ?timestamp))]
 pub async fn calculate_total_delegated_voting_power(
+    dao_id: Uuid,
     timestamp: chrono::NaiveDateTime,
-    excluded_voter: &str,
 ) -> Result<f64> {
-    use sea_orm::EntityName;
-
-    let db = DB
-        .get()
-        .ok_or_else(|| anyhow::anyhow!("DB not initialized"))?;
-
-    // Get the table name from the entity - this provides compile-time safety
-    // If the table is renamed in a migration and the entity is regenerated,
-    // this will automatically use the new name
-    let table_name = voting_power_timeseries::Entity.table_name();
-
-    // Build the SQL query using the type-safe table name
-    // We use raw SQL for the complex window function since SeaQuery's window function
-    // support doesn't handle PARTITION BY with multiple ORDER BY columns well
-    let sql = format!(
-        r#"
-        WITH latest_voting_power AS (
-            SELECT
-                voter,
-                voting_power,
-                ROW_NUMBER() OVER (
-                    PARTITION BY voter
-                    ORDER BY timestamp DESC, block DESC
-                ) AS rn
-            FROM {table_name}
-            WHERE
-                voter != $1
-                AND timestamp <= $2
-        )
-        SELECT COALESCE(SUM(voting_power), 0.0) as total_voting_power
-        FROM latest_voting_power
-        WHERE rn = 1
-        "#
-    );
-
-    debug!(sql = %sql, table_name = %table_name, "Executing type-safe voting power query");
-
-    // Execute the query with parameterized values
-    let result = db
-        .query_one(Statement::from_sql_and_values(
-            DbBackend::Postgres,
-            sql,
-            vec![excluded_voter.into(), timestamp.into()],
-        ))
-        .await
-        .context("Failed to execute SQL query")?;
-
-    // Extract the total voting power from the result
-    let total_vp: f64 = result
-        .map(|qr| qr.try_get::<f64>("", "total_voting_power"))
-        .transpose()
-        .context("Failed to get total_voting_power from query result")?
-        .unwrap_or(0.0);
+    let total_vp = total_delegated_voting_power_at(dao_id, timestamp).await?;
 
     debug!(
         total_voting_power = total_vp,
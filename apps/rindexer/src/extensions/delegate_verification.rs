@@ -0,0 +1,582 @@
+use crate::{
+    extensions::db_extension::DB,
+    rindexer_lib::typings::networks::{get_arbitrum_provider, get_ethereum_provider},
+};
+use alloy::{
+    primitives::{Address, B256, Bytes, FixedBytes, Signature, eip191_hash_message},
+    providers::Provider,
+    sol,
+    sol_types::{SolStruct, eip712_domain},
+};
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use proposalsapp_db::models::{delegate_to_voter, delegate_to_voter_verification, voter};
+use rindexer::provider::RindexerProvider;
+use sea_orm::{
+    ActiveValue::NotSet, ConnectionTrait, DbBackend, EntityTrait, Set, Statement, TransactionTrait,
+    prelude::Uuid,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{future::Future, str::FromStr, sync::Arc};
+use tracing::{info, instrument, warn};
+
+sol! {
+    /// What a voter signs to claim a delegate profile, as EIP-712 typed data.
+    struct DelegateClaim {
+        string delegateId;
+        address voter;
+        uint64 periodStart;
+        uint64 periodEnd;
+    }
+
+    #[sol(rpc)]
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4);
+    }
+}
+
+/// Returned by `isValidSignature` when a wallet accepts a signature.
+const ERC1271_MAGIC_VALUE: FixedBytes<4> = FixedBytes([0x16, 0x26, 0xba, 0x7e]);
+
+const ETHEREUM_CHAIN_ID: u64 = 1;
+const ARBITRUM_CHAIN_ID: u64 = 42161;
+
+const EIP712_DOMAIN_NAME: &str = "proposals.app";
+const EIP712_DOMAIN_VERSION: &str = "1";
+
+const VERIFICATION_BATCH_SIZE: u64 = 100;
+
+/// Signed proof stored in `delegate_to_voter.proof`. The signed claim isn't
+/// part of it: it's rebuilt from the row, so a signature only proves the
+/// delegate, voter and period the row claims.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum DelegateProof {
+    /// `personal_sign` of [`claim_message`]. `chain_id` is where the voter's
+    /// wallet lives, for smart contract wallets.
+    #[serde(rename = "eip191")]
+    Eip191 {
+        signature: Bytes,
+        #[serde(default = "default_chain_id")]
+        chain_id: u64,
+    },
+    /// `eth_signTypedData_v4` of a [`DelegateClaim`] in the proposals.app
+    /// domain of `chain_id`.
+    #[serde(rename = "eip712")]
+    Eip712 { signature: Bytes, chain_id: u64 },
+}
+
+fn default_chain_id() -> u64 {
+    ETHEREUM_CHAIN_ID
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationMethod {
+    Eip191,
+    Eip712,
+    /// A smart contract wallet accepted an EIP-191 or EIP-712 signature.
+    Eip1271,
+}
+
+impl VerificationMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationMethod::Eip191 => "EIP191",
+            VerificationMethod::Eip712 => "EIP712",
+            VerificationMethod::Eip1271 => "EIP1271",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    Verified(VerificationMethod),
+    Rejected(String),
+}
+
+/// A voter's claim to a delegate profile, as stored in `delegate_to_voter`.
+#[derive(Debug, Clone)]
+pub struct Claim {
+    pub delegate_id: Uuid,
+    pub voter: Address,
+    pub period_start: NaiveDateTime,
+    pub period_end: NaiveDateTime,
+}
+
+impl Claim {
+    fn typed_data(&self) -> DelegateClaim {
+        DelegateClaim {
+            delegateId: self.delegate_id.to_string(),
+            voter: self.voter,
+            periodStart: self.period_start.and_utc().timestamp().max(0) as u64,
+            periodEnd: self.period_end.and_utc().timestamp().max(0) as u64,
+        }
+    }
+}
+
+/// Text a voter signs with `personal_sign` to claim a delegate profile.
+pub fn claim_message(claim: &Claim) -> String {
+    let claim = claim.typed_data();
+    format!(
+        "I claim the proposals.app delegate profile {}\nVoter: {}\nPeriod start: {}\nPeriod end: {}",
+        claim.delegateId,
+        claim.voter.to_checksum(None),
+        claim.periodStart,
+        claim.periodEnd,
+    )
+}
+
+/// Hash signed for `claim` in the proposals.app EIP-712 domain of `chain_id`.
+pub fn claim_typed_data_hash(claim: &Claim, chain_id: u64) -> B256 {
+    let domain = eip712_domain! {
+        name: EIP712_DOMAIN_NAME,
+        version: EIP712_DOMAIN_VERSION,
+        chain_id: chain_id,
+    };
+    claim.typed_data().eip712_signing_hash(&domain)
+}
+
+/// Checks signatures of smart contract wallets. The production implementation
+/// calls `isValidSignature` on chain; tests plug in a stub.
+pub trait SignatureValidator: Send + Sync {
+    /// Whether the contract at `wallet` on `chain_id` accepts `signature` of
+    /// `hash`. False when there's no contract at `wallet`.
+    fn is_valid_signature(
+        &self,
+        chain_id: u64,
+        wallet: Address,
+        hash: B256,
+        signature: &Bytes,
+    ) -> impl Future<Output = Result<bool>> + Send;
+}
+
+/// Check `proof` of `claim` as of `now`. Errors are left for transient
+/// failures, like an unreachable provider, so the proof is checked again;
+/// proofs that don't hold are rejected with the reason.
+#[instrument(name = "delegate_verification_verify_claim", skip_all, fields(delegate_id = %claim.delegate_id, voter = %claim.voter))]
+pub async fn verify_claim<V: SignatureValidator>(
+    validator: &V,
+    claim: &Claim,
+    proof: Option<&Value>,
+    now: NaiveDateTime,
+) -> Result<Verification> {
+    if claim.period_start >= claim.period_end {
+        return Ok(Verification::Rejected(
+            "Claimed period ends before it starts".to_string(),
+        ));
+    }
+    if now < claim.period_start {
+        return Ok(Verification::Rejected(
+            "Claimed period hasn't started".to_string(),
+        ));
+    }
+    if now >= claim.period_end {
+        return Ok(Verification::Rejected("Claimed period ended".to_string()));
+    }
+
+    let Some(proof) = proof else {
+        return Ok(Verification::Rejected("No proof".to_string()));
+    };
+    let proof = match DelegateProof::deserialize(proof) {
+        Ok(proof) => proof,
+        Err(e) => return Ok(Verification::Rejected(format!("Malformed proof: {e}"))),
+    };
+
+    let (method, hash, signature, chain_id) = match proof {
+        DelegateProof::Eip191 {
+            signature,
+            chain_id,
+        } => (
+            VerificationMethod::Eip191,
+            eip191_hash_message(claim_message(claim)),
+            signature,
+            chain_id,
+        ),
+        DelegateProof::Eip712 {
+            signature,
+            chain_id,
+        } => (
+            VerificationMethod::Eip712,
+            claim_typed_data_hash(claim, chain_id),
+            signature,
+            chain_id,
+        ),
+    };
+
+    // Externally owned accounts sign with their key
+    if let Ok(parsed) = Signature::try_from(signature.as_ref())
+        && parsed.recover_address_from_prehash(&hash).ok() == Some(claim.voter)
+    {
+        return Ok(Verification::Verified(method));
+    }
+
+    // Smart contract wallets decide for themselves
+    if ![ETHEREUM_CHAIN_ID, ARBITRUM_CHAIN_ID].contains(&chain_id) {
+        return Ok(Verification::Rejected(format!(
+            "Signature not from the voter, and chain {chain_id} isn't supported for smart contract wallets"
+        )));
+    }
+    if validator
+        .is_valid_signature(chain_id, claim.voter, hash, &signature)
+        .await
+        .context("Failed to check smart contract wallet signature")?
+    {
+        return Ok(Verification::Verified(VerificationMethod::Eip1271));
+    }
+
+    Ok(Verification::Rejected(
+        "Signature not from the voter".to_string(),
+    ))
+}
+
+/// [`SignatureValidator`] calling wallets on Ethereum and Arbitrum.
+#[derive(Clone)]
+pub struct ProviderSignatureValidator {
+    ethereum: Arc<RindexerProvider>,
+    arbitrum: Arc<RindexerProvider>,
+}
+
+impl ProviderSignatureValidator {
+    pub async fn new() -> Self {
+        Self {
+            ethereum: get_ethereum_provider().await,
+            arbitrum: get_arbitrum_provider().await,
+        }
+    }
+}
+
+impl SignatureValidator for ProviderSignatureValidator {
+    async fn is_valid_signature(
+        &self,
+        chain_id: u64,
+        wallet: Address,
+        hash: B256,
+        signature: &Bytes,
+    ) -> Result<bool> {
+        let provider = match chain_id {
+            ETHEREUM_CHAIN_ID => self.ethereum.clone(),
+            ARBITRUM_CHAIN_ID => self.arbitrum.clone(),
+            _ => anyhow::bail!("Unsupported chain {chain_id}"),
+        };
+
+        let code = provider
+            .get_code_at(wallet)
+            .await
+            .context("Failed to fetch wallet code")?;
+        if code.is_empty() {
+            return Ok(false);
+        }
+
+        // Wallets revert on signatures they reject as often as they return
+        // something other than the magic value
+        let result = IERC1271::new(wallet, provider)
+            .isValidSignature(hash, signature.clone())
+            .call()
+            .await;
+        Ok(matches!(result, Ok(value) if value == ERC1271_MAGIC_VALUE))
+    }
+}
+
+/// Check proofs of claims that were never checked, whose proof changed, whose
+/// verified period ended, or whose period started since they were checked.
+/// Only rows holding a [`DelegateProof`] are checked: links without one were
+/// confirmed by a person (e.g. an accepted mapping) and are left alone.
+/// Returns how many were checked.
+#[instrument(name = "delegate_verification_verify_pending", skip_all)]
+pub async fn verify_pending_claims<V: SignatureValidator>(validator: &V) -> Result<usize> {
+    let db = DB.get().context("DB not initialized")?;
+    let now = Utc::now().naive_utc();
+
+    let pending = delegate_to_voter::Entity::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT d.* FROM delegate_to_voter d
+            LEFT JOIN LATERAL (
+                SELECT v.proof, v.checked_at FROM delegate_to_voter_verification v
+                WHERE v.delegate_to_voter_id = d.id
+                ORDER BY v.checked_at DESC
+                LIMIT 1
+            ) last ON TRUE
+            WHERE d.proof->>'type' IN ('eip191', 'eip712')
+                AND (
+                    last.checked_at IS NULL
+                    OR last.proof IS DISTINCT FROM d.proof
+                    OR (d.verified AND d.period_end <= $1)
+                    OR (NOT d.verified AND last.checked_at < d.period_start AND d.period_start <= $1)
+                )
+            ORDER BY d.created_at
+            LIMIT $2
+            "#,
+            vec![now.into(), VERIFICATION_BATCH_SIZE.into()],
+        ))
+        .all(db)
+        .await
+        .context("Failed to fetch claims to verify")?;
+
+    let mut checked = 0;
+    for row in pending {
+        let voter = voter::Entity::find_by_id(row.voter_id)
+            .one(db)
+            .await
+            .context("Failed to fetch voter")?;
+        let verification = match voter.map(|voter| Address::from_str(&voter.address)) {
+            Some(Ok(address)) => {
+                let claim = Claim {
+                    delegate_id: row.delegate_id,
+                    voter: address,
+                    period_start: row.period_start,
+                    period_end: row.period_end,
+                };
+                match verify_claim(validator, &claim, row.proof.as_ref(), now).await {
+                    Ok(verification) => verification,
+                    Err(e) => {
+                        warn!(delegate_to_voter_id = %row.id, error = ?e, "Failed to verify claim, will retry");
+                        continue;
+                    }
+                }
+            }
+            Some(Err(_)) => Verification::Rejected("Voter address is invalid".to_string()),
+            None => Verification::Rejected("Voter not found".to_string()),
+        };
+
+        record_verification(db, &row, &verification, now).await?;
+        checked += 1;
+    }
+
+    Ok(checked)
+}
+
+async fn record_verification<C: TransactionTrait>(
+    db: &C,
+    row: &delegate_to_voter::Model,
+    verification: &Verification,
+    checked_at: NaiveDateTime,
+) -> Result<()> {
+    let (verified, method, error) = match verification {
+        Verification::Verified(method) => (true, Some(method.as_str().to_string()), None),
+        Verification::Rejected(reason) => (false, None, Some(reason.clone())),
+    };
+
+    let txn = db
+        .begin()
+        .await
+        .context("Failed to start verification transaction")?;
+    delegate_to_voter_verification::Entity::insert(delegate_to_voter_verification::ActiveModel {
+        id: NotSet,
+        delegate_to_voter_id: Set(row.id),
+        proof: Set(row.proof.clone()),
+        verified: Set(verified),
+        method: Set(method.clone()),
+        error: Set(error.clone()),
+        // The same UTC clock the claim period was checked against
+        checked_at: Set(checked_at),
+    })
+    .exec_without_returning(&txn)
+    .await
+    .context("Failed to record verification")?;
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "UPDATE delegate_to_voter SET verified = $2 WHERE id = $1",
+        vec![row.id.into(), verified.into()],
+    ))
+    .await
+    .context("Failed to update delegate_to_voter")?;
+    txn.commit()
+        .await
+        .context("Failed to commit verification")?;
+
+    if verified != row.verified {
+        info!(
+            delegate_to_voter_id = %row.id,
+            verified,
+            method = ?method,
+            error = ?error,
+            "Delegate claim verification changed"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        primitives::b256,
+        signers::{SignerSync, local::PrivateKeySigner},
+    };
+    use chrono::Duration;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    const SAFE: Address = alloy::primitives::address!("0x5afe5afe5afe5afe5afe5afe5afe5afe5afe5afe");
+
+    /// Contract wallets accepting the (wallet, hash) pairs listed.
+    #[derive(Default)]
+    struct StubValidator {
+        accepted: HashSet<(Address, B256)>,
+    }
+
+    impl SignatureValidator for StubValidator {
+        async fn is_valid_signature(
+            &self,
+            _chain_id: u64,
+            wallet: Address,
+            hash: B256,
+            _signature: &Bytes,
+        ) -> Result<bool> {
+            Ok(self.accepted.contains(&(wallet, hash)))
+        }
+    }
+
+    fn signer() -> PrivateKeySigner {
+        PrivateKeySigner::from_bytes(&b256!(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        ))
+        .unwrap()
+    }
+
+    fn claim(voter: Address, now: NaiveDateTime) -> Claim {
+        Claim {
+            delegate_id: Uuid::from_u128(1),
+            voter,
+            period_start: now - Duration::days(1),
+            period_end: now + Duration::days(30),
+        }
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2026-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_claim_eip191_and_eip712() {
+        let signer = signer();
+        let claim = claim(signer.address(), now());
+        let validator = StubValidator::default();
+
+        let signature = signer
+            .sign_message_sync(claim_message(&claim).as_bytes())
+            .unwrap();
+        let proof = json!({ "type": "eip191", "signature": signature.to_string() });
+        assert_eq!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Verified(VerificationMethod::Eip191)
+        );
+
+        let signature = signer
+            .sign_hash_sync(&claim_typed_data_hash(&claim, ARBITRUM_CHAIN_ID))
+            .unwrap();
+        let proof =
+            json!({ "type": "eip712", "signature": signature.to_string(), "chain_id": 42161 });
+        assert_eq!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Verified(VerificationMethod::Eip712)
+        );
+
+        // Signed for another chain's domain
+        let proof = json!({ "type": "eip712", "signature": signature.to_string(), "chain_id": 1 });
+        assert!(matches!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Rejected(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_claim_rejects_other_signers_and_periods() {
+        let signer = signer();
+        let mut claim = claim(SAFE, now());
+        let validator = StubValidator::default();
+
+        // A key that isn't the voter's, for a wallet that doesn't accept it
+        let signature = signer
+            .sign_message_sync(claim_message(&claim).as_bytes())
+            .unwrap();
+        let proof = json!({ "type": "eip191", "signature": signature.to_string() });
+        assert_eq!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Rejected("Signature not from the voter".to_string())
+        );
+
+        // Signatures only cover the period they were made for
+        let signer_claim = Claim {
+            voter: signer.address(),
+            ..claim.clone()
+        };
+        let signature = signer
+            .sign_message_sync(claim_message(&signer_claim).as_bytes())
+            .unwrap();
+        let proof = json!({ "type": "eip191", "signature": signature.to_string() });
+        let extended = Claim {
+            period_end: signer_claim.period_end + Duration::days(365),
+            ..signer_claim.clone()
+        };
+        assert!(matches!(
+            verify_claim(&validator, &extended, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Rejected(_)
+        ));
+        assert_eq!(
+            verify_claim(
+                &validator,
+                &signer_claim,
+                Some(&proof),
+                signer_claim.period_end
+            )
+            .await
+            .unwrap(),
+            Verification::Rejected("Claimed period ended".to_string())
+        );
+
+        claim.period_start = claim.period_end;
+        assert!(matches!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Rejected(_)
+        ));
+        assert!(matches!(
+            verify_claim(&validator, &signer_claim, Some(&json!({ "type": "eip191" })), now())
+                .await
+                .unwrap(),
+            Verification::Rejected(reason) if reason.starts_with("Malformed proof")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_claim_smart_contract_wallet() {
+        let claim = claim(SAFE, now());
+        let hash = claim_typed_data_hash(&claim, ETHEREUM_CHAIN_ID);
+        let validator = StubValidator {
+            accepted: HashSet::from([(SAFE, hash)]),
+        };
+
+        // Wallets may use signatures that aren't ECDSA, like Safe's
+        // concatenated owner signatures
+        let proof = json!({ "type": "eip712", "signature": "0xdeadbeef", "chain_id": 1 });
+        assert_eq!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Verified(VerificationMethod::Eip1271)
+        );
+
+        let proof = json!({ "type": "eip712", "signature": "0xdeadbeef", "chain_id": 10 });
+        assert!(matches!(
+            verify_claim(&validator, &claim, Some(&proof), now())
+                .await
+                .unwrap(),
+            Verification::Rejected(_)
+        ));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::extensions::db_extension::DB;
+use anyhow::{Context, Result};
+use proposalsapp_db::models::{token_balance, token_transfer};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ConnectionTrait, DbBackend, EntityTrait, Statement, TransactionTrait,
+    prelude::Uuid,
+    sea_query::{Expr, OnConflict},
+};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info, instrument};
+
+/// Mints come from and burns go to the zero address, which has no balance
+/// and stands for "not delegated" in DelegateChanged events.
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+const BATCH_SIZE: usize = 1000;
+
+/// Blocks turned into flows per transaction by [`sync_delegation_flows`].
+const FLOW_SYNC_BLOCK_RANGE: i32 = 50_000;
+
+/// Net balance change per account in a batch of transfers, with the last
+/// block that touched it. The zero address is skipped.
+pub fn balance_deltas(transfers: &[token_transfer::Model]) -> HashMap<String, (f64, i32)> {
+    let mut deltas: HashMap<String, (f64, i32)> = HashMap::new();
+
+    for transfer in transfers {
+        for (account, amount) in [
+            (&transfer.from_address, -transfer.amount),
+            (&transfer.to_address, transfer.amount),
+        ] {
+            if account == ZERO_ADDRESS {
+                continue;
+            }
+            let entry = deltas
+                .entry(account.clone())
+                .or_insert((0.0, transfer.block));
+            entry.0 += amount;
+            entry.1 = entry.1.max(transfer.block);
+        }
+    }
+
+    deltas
+}
+
+/// Store token transfers and apply them to `token_balance`. Only transfers
+/// that weren't stored before change balances, so replayed batches are
+/// harmless.
+#[instrument(name = "delegation_flows_store_token_transfers", skip(transfers), fields(transfer_count = transfers.len()))]
+pub async fn store_token_transfers(transfers: Vec<token_transfer::ActiveModel>) -> Result<()> {
+    if transfers.is_empty() {
+        return Ok(());
+    }
+
+    let db = DB.get().context("DB not initialized")?;
+
+    for chunk in transfers.chunks(BATCH_SIZE) {
+        let txn = db
+            .begin()
+            .await
+            .context("Failed to start token transfer transaction")?;
+
+        let inserted = token_transfer::Entity::insert_many(chunk.to_vec())
+            .on_conflict(
+                OnConflict::columns([
+                    token_transfer::Column::DaoId,
+                    token_transfer::Column::Txid,
+                    token_transfer::Column::LogIndex,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_with_returning_many(&txn)
+            .await
+            .context("Failed to insert token transfers")?;
+
+        let mut balances = Vec::new();
+        for dao_id in inserted.iter().map(|t| t.dao_id).collect::<HashSet<_>>() {
+            let dao_transfers: Vec<_> = inserted
+                .iter()
+                .filter(|t| t.dao_id == dao_id)
+                .cloned()
+                .collect();
+            for (account, (delta, block)) in balance_deltas(&dao_transfers) {
+                balances.push(token_balance::ActiveModel {
+                    id: NotSet,
+                    dao_id: Set(dao_id),
+                    account: Set(account),
+                    balance: Set(delta),
+                    block: Set(block),
+                    updated_at: NotSet,
+                });
+            }
+        }
+
+        if !balances.is_empty() {
+            token_balance::Entity::insert_many(balances)
+                .on_conflict(
+                    OnConflict::columns([
+                        token_balance::Column::DaoId,
+                        token_balance::Column::Account,
+                    ])
+                    .value(
+                        token_balance::Column::Balance,
+                        Expr::cust("token_balance.balance + EXCLUDED.balance"),
+                    )
+                    .value(
+                        token_balance::Column::Block,
+                        Expr::cust("GREATEST(token_balance.block, EXCLUDED.block)"),
+                    )
+                    .value(token_balance::Column::UpdatedAt, Expr::cust("NOW()"))
+                    .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await
+                .context("Failed to update token balances")?;
+        }
+
+        txn.commit()
+            .await
+            .context("Failed to commit token transfer chunk")?;
+
+        debug!(
+            chunk_size = chunk.len(),
+            new_transfers = inserted.len(),
+            "Stored token transfer chunk"
+        );
+    }
+
+    Ok(())
+}
+
+/// Record `delegate` flows for DelegateChanged events up to block `$3` that
+/// have no weight yet: the delegator's balance right before the event moves
+/// from the previous delegate to the new one. Also stores that balance as the
+/// delegation's weight, which marks the event as processed.
+const DELEGATE_FLOWS_SQL: &str = r#"
+    WITH moved AS (
+        SELECT
+            d.id, d.dao_id, d.delegator, d.delegate, d.block, d.log_index, d.timestamp, d.txid,
+            COALESCE(d.from_delegate, prev.delegate) AS from_delegate,
+            (
+                SELECT COALESCE(SUM(t.amount), 0) FROM token_transfer t
+                WHERE t.dao_id = d.dao_id AND t.to_address = d.delegator
+                  AND (t.block, t.log_index) < (d.block, d.log_index)
+            ) - (
+                SELECT COALESCE(SUM(t.amount), 0) FROM token_transfer t
+                WHERE t.dao_id = d.dao_id AND t.from_address = d.delegator
+                  AND (t.block, t.log_index) < (d.block, d.log_index)
+            ) AS weight
+        FROM delegation d
+        LEFT JOIN LATERAL (
+            SELECT p.delegate FROM delegation p
+            WHERE p.dao_id = d.dao_id AND p.delegator = d.delegator
+              AND (p.block, p.log_index) < (d.block, d.log_index)
+            ORDER BY p.block DESC, p.log_index DESC
+            LIMIT 1
+        ) prev ON TRUE
+        WHERE d.dao_id = $1 AND d.weight IS NULL AND d.block <= $2
+    ),
+    weighted AS (
+        UPDATE delegation SET weight = moved.weight
+        FROM moved
+        WHERE delegation.id = moved.id
+    )
+    INSERT INTO delegation_flow
+        (dao_id, kind, delegator, from_delegate, to_delegate, amount, block, log_index, timestamp, txid)
+    SELECT
+        dao_id, 'delegate', delegator, NULLIF(from_delegate, $3), NULLIF(delegate, $3),
+        weight, block, log_index, timestamp, txid
+    FROM moved
+    WHERE weight > 0
+      AND NULLIF(from_delegate, $3) IS DISTINCT FROM NULLIF(delegate, $3)
+    ON CONFLICT (dao_id, txid, log_index, kind) DO NOTHING
+"#;
+
+/// Record `transfer` flows for unsynced transfers up to block `$2` between
+/// holders with different delegates: the amount moves from the sender's
+/// delegate to the recipient's. The transfers are marked as synced.
+const TRANSFER_FLOWS_SQL: &str = r#"
+    WITH pending AS (
+        UPDATE token_transfer SET flow_synced = true
+        WHERE dao_id = $1 AND NOT flow_synced AND block <= $2
+        RETURNING *
+    )
+    INSERT INTO delegation_flow
+        (dao_id, kind, delegator, from_delegate, to_delegate, amount, block, log_index, timestamp, txid)
+    SELECT
+        t.dao_id, 'transfer', t.from_address, sender.delegate, recipient.delegate,
+        t.amount, t.block, t.log_index, t.timestamp, t.txid
+    FROM pending t
+    LEFT JOIN LATERAL (
+        SELECT NULLIF(d.delegate, $3) AS delegate FROM delegation d
+        WHERE d.dao_id = t.dao_id AND d.delegator = t.from_address
+          AND (d.block, d.log_index) < (t.block, t.log_index)
+        ORDER BY d.block DESC, d.log_index DESC
+        LIMIT 1
+    ) sender ON TRUE
+    LEFT JOIN LATERAL (
+        SELECT NULLIF(d.delegate, $3) AS delegate FROM delegation d
+        WHERE d.dao_id = t.dao_id AND d.delegator = t.to_address
+          AND (d.block, d.log_index) < (t.block, t.log_index)
+        ORDER BY d.block DESC, d.log_index DESC
+        LIMIT 1
+    ) recipient ON TRUE
+    WHERE t.amount > 0
+      AND sender.delegate IS DISTINCT FROM recipient.delegate
+    ON CONFLICT (dao_id, txid, log_index, kind) DO NOTHING
+"#;
+
+/// Turn the delegations and transfers of a DAO that have no flows yet into
+/// `delegation_flow` rows, including ones indexed below the cursor by
+/// backfills or replays. Only blocks that both the DelegateChanged and the
+/// Transfer indexers have reached are processed, in ranges above the cursor
+/// in `delegation_flow_sync_state`. Returns the last processed block.
+#[instrument(name = "delegation_flows_sync", skip_all, fields(dao_id = %dao_id))]
+pub async fn sync_delegation_flows(dao_id: Uuid) -> Result<i32> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT
+                COALESCE((SELECT last_block FROM delegation_flow_sync_state WHERE dao_id = $1), 0) AS last_block,
+                LEAST(
+                    (SELECT MAX(block) FROM token_transfer WHERE dao_id = $1),
+                    (SELECT MAX(block) FROM delegation WHERE dao_id = $1)
+                ) AS safe_block
+            "#,
+            vec![dao_id.into()],
+        ))
+        .await
+        .context("Failed to read delegation flow sync state")?
+        .context("Sync state query returned no rows")?;
+
+    let mut last_block: i32 = row.try_get("", "last_block")?;
+    let Some(safe_block) = row.try_get::<Option<i32>>("", "safe_block")? else {
+        debug!("No delegations or transfers indexed yet");
+        return Ok(last_block);
+    };
+
+    // The first range also picks up late rows below the cursor, so it runs
+    // even when no new blocks were indexed
+    loop {
+        let to_block = safe_block
+            .min(last_block.saturating_add(FLOW_SYNC_BLOCK_RANGE))
+            .max(last_block);
+        let txn = db
+            .begin()
+            .await
+            .context("Failed to start delegation flow transaction")?;
+
+        let values = vec![dao_id.into(), to_block.into(), ZERO_ADDRESS.into()];
+        let delegate_flows = txn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                DELEGATE_FLOWS_SQL,
+                values.clone(),
+            ))
+            .await
+            .context("Failed to record delegation flows")?
+            .rows_affected();
+        let transfer_flows = txn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                TRANSFER_FLOWS_SQL,
+                values,
+            ))
+            .await
+            .context("Failed to record transfer flows")?
+            .rows_affected();
+
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO delegation_flow_sync_state (dao_id, last_block, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (dao_id) DO UPDATE SET
+                last_block = EXCLUDED.last_block,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            vec![dao_id.into(), to_block.into()],
+        ))
+        .await
+        .context("Failed to update delegation flow sync state")?;
+
+        txn.commit()
+            .await
+            .context("Failed to commit delegation flows")?;
+
+        if delegate_flows > 0 || transfer_flows > 0 {
+            info!(
+                from_block = last_block,
+                to_block, delegate_flows, transfer_flows, "Recorded delegation flows"
+            );
+        }
+        last_block = to_block;
+        if last_block >= safe_block {
+            break;
+        }
+    }
+
+    Ok(last_block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn transfer(from: &str, to: &str, amount: f64, block: i32) -> token_transfer::Model {
+        token_transfer::Model {
+            id: Uuid::from_u128(block as u128),
+            dao_id: Uuid::from_u128(1),
+            from_address: from.to_string(),
+            to_address: to.to_string(),
+            amount,
+            block,
+            log_index: 0,
+            timestamp: NaiveDateTime::default(),
+            txid: format!("0x{block:x}"),
+            flow_synced: false,
+        }
+    }
+
+    #[test]
+    fn test_balance_deltas_net_out_and_skip_zero_address() {
+        let deltas = balance_deltas(&[
+            transfer(ZERO_ADDRESS, "0xa", 100.0, 10),
+            transfer("0xa", "0xb", 40.0, 12),
+            transfer("0xb", "0xa", 5.0, 11),
+            transfer("0xb", ZERO_ADDRESS, 10.0, 13),
+        ]);
+
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas["0xa"], (65.0, 12));
+        assert_eq!(deltas["0xb"], (25.0, 13));
+    }
+}
@@ -0,0 +1,853 @@
+use crate::extensions::multicall::Multicall;
+use alloy::{
+    primitives::{Address, B256, U256, address, keccak256},
+    sol,
+};
+use anyhow::{Context, Result, anyhow};
+use chrono::NaiveDateTime;
+use futures::{StreamExt, stream};
+use proposalsapp_db::models::voter;
+use rindexer::provider::RindexerProvider;
+use serde::Deserialize;
+use std::{future::Future, sync::Arc, time::Duration};
+use tracing::{debug, instrument, warn};
+use utils::public_http::{is_public_web_url, public_client};
+
+/// ENS registry, deployed at the same address on mainnet and testnets.
+pub const ENS_REGISTRY_ADDRESS: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+const ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+const NFT_METADATA_TIMEOUT: Duration = Duration::from_secs(5);
+/// Redirects of an NFT metadata URL that are followed
+const NFT_METADATA_MAX_REDIRECTS: usize = 3;
+/// Larger metadata documents are refused rather than read into memory
+const NFT_METADATA_MAX_BYTES: usize = 256 * 1024;
+/// Verified names whose text records are fetched concurrently
+const PROFILE_CONCURRENCY_LIMIT: usize = 5;
+
+sol! {
+    #[sol(rpc)]
+    interface IEnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+
+    #[sol(rpc)]
+    interface IEnsResolver {
+        function addr(bytes32 node) external view returns (address);
+        function name(bytes32 node) external view returns (string memory);
+        function text(bytes32 node, string calldata key) external view returns (string memory);
+    }
+
+    #[sol(rpc)]
+    interface IErc721Avatar {
+        function tokenURI(uint256 tokenId) external view returns (string memory);
+        function ownerOf(uint256 tokenId) external view returns (address);
+    }
+
+    #[sol(rpc)]
+    interface IErc1155Avatar {
+        function uri(uint256 id) external view returns (string memory);
+        function balanceOf(address account, uint256 id) external view returns (uint256);
+    }
+}
+
+/// Identity data resolved for a voter address. `ens` is only set when the
+/// reverse record has been forward-verified, so an unverified name never
+/// leaks into the other fields either.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnsIdentity {
+    pub ens: Option<String>,
+    pub avatar: Option<String>,
+    pub url: Option<String>,
+    pub twitter: Option<String>,
+    pub discourse: Option<String>,
+    pub description: Option<String>,
+}
+
+impl EnsIdentity {
+    pub fn is_verified(&self) -> bool {
+        self.ens.is_some()
+    }
+}
+
+/// Forward lookup error for names without a resolver of their own. Such
+/// names can still resolve through a parent's wildcard (ENSIP-10) or offchain
+/// (CCIP-read) resolver, which aren't supported, so whether they verify is
+/// unknown rather than known to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedResolution;
+
+impl std::fmt::Display for UnsupportedResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Name only resolves through a wildcard or offchain resolver")
+    }
+}
+
+impl std::error::Error for UnsupportedResolution {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+/// An NFT avatar reference as described by ENSIP-12, e.g.
+/// `eip155:1/erc721:0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB/1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftAvatar {
+    pub chain_id: u64,
+    pub standard: NftStandard,
+    pub contract: Address,
+    pub token_id: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AvatarUri {
+    Url(String),
+    Nft(NftAvatar),
+}
+
+/// Source of ENS data. The production implementation talks to the ENS
+/// contracts through the Ethereum provider; tests plug in an in-memory stub.
+pub trait EnsResolver: Send + Sync {
+    /// Name from the reverse record of `address`, if any.
+    fn reverse_name(&self, address: Address)
+    -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// Address the name resolves to, if any. Fails with
+    /// [`UnsupportedResolution`] when that can't be told.
+    fn resolve_name(&self, name: &str) -> impl Future<Output = Result<Option<Address>>> + Send;
+
+    /// Raw text record `key` of `name`.
+    fn text(&self, name: &str, key: &str) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// Image URL of an NFT avatar, provided `owner` still holds the token.
+    fn nft_image(
+        &self,
+        nft: &NftAvatar,
+        owner: Address,
+    ) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// [`Self::reverse_name`] for many addresses. Results line up with
+    /// `addresses`; implementations can override this to batch requests.
+    fn reverse_names(
+        &self,
+        addresses: &[Address],
+    ) -> impl Future<Output = Vec<Result<Option<String>>>> + Send {
+        async move {
+            let mut names = Vec::with_capacity(addresses.len());
+            for address in addresses {
+                names.push(self.reverse_name(*address).await);
+            }
+            names
+        }
+    }
+
+    /// [`Self::resolve_name`] for many names. Results line up with `names`.
+    fn resolve_names(
+        &self,
+        names: &[String],
+    ) -> impl Future<Output = Vec<Result<Option<Address>>>> + Send {
+        async move {
+            let mut addresses = Vec::with_capacity(names.len());
+            for name in names {
+                addresses.push(self.resolve_name(name).await);
+            }
+            addresses
+        }
+    }
+}
+
+/// How long resolved data is trusted before the voter is looked up again.
+/// Addresses without a verified name are re-checked less often since most
+/// of them never set a reverse record.
+#[derive(Debug, Clone, Copy)]
+pub struct EnsRefreshPolicy {
+    pub resolved_ttl: chrono::Duration,
+    pub unresolved_ttl: chrono::Duration,
+}
+
+impl Default for EnsRefreshPolicy {
+    fn default() -> Self {
+        Self {
+            resolved_ttl: chrono::Duration::hours(24),
+            unresolved_ttl: chrono::Duration::days(7),
+        }
+    }
+}
+
+impl EnsRefreshPolicy {
+    pub fn is_due(&self, voter: &voter::Model, now: NaiveDateTime) -> bool {
+        let Some(refreshed_at) = voter.ens_refreshed_at else {
+            return true;
+        };
+        let ttl = if voter.ens_verified {
+            self.resolved_ttl
+        } else {
+            self.unresolved_ttl
+        };
+        now - refreshed_at >= ttl
+    }
+}
+
+/// Reverse-resolve `address`, forward-verify the name and fetch the profile
+/// records. Failing text records are skipped; only the reverse and forward
+/// lookups are fatal since without them nothing can be trusted.
+#[instrument(name = "ens_resolve_identity", skip(resolver), fields(address = %address))]
+pub async fn resolve_identity<R: EnsResolver>(
+    resolver: &R,
+    address: Address,
+) -> Result<EnsIdentity> {
+    resolve_identities(resolver, &[address])
+        .await
+        .pop()
+        .unwrap_or_else(|| Ok(EnsIdentity::default()))
+}
+
+/// [`resolve_identity`] for many addresses. Reverse and forward lookups go
+/// through the resolver's batch methods; profile records are only fetched
+/// for verified names. Results line up with `addresses`.
+#[instrument(name = "ens_resolve_identities", skip_all, fields(address_count = addresses.len()))]
+pub async fn resolve_identities<R: EnsResolver>(
+    resolver: &R,
+    addresses: &[Address],
+) -> Vec<Result<EnsIdentity>> {
+    let reverse = resolver.reverse_names(addresses).await;
+
+    let names: Vec<String> = reverse
+        .iter()
+        .filter_map(|r| r.as_ref().ok().and_then(|n| n.clone()))
+        .collect();
+    let mut forward = resolver.resolve_names(&names).await.into_iter();
+
+    // Verified name per address; `Ok(None)` means no usable name
+    let mut verified: Vec<Result<Option<String>>> = Vec::with_capacity(addresses.len());
+    for (address, reverse_result) in addresses.iter().zip(reverse) {
+        let name = match reverse_result.context("Reverse ENS lookup failed") {
+            Ok(Some(name)) => name,
+            Ok(None) => {
+                verified.push(Ok(None));
+                continue;
+            }
+            Err(e) => {
+                verified.push(Err(e));
+                continue;
+            }
+        };
+
+        let resolved = match forward
+            .next()
+            .unwrap_or_else(|| Err(anyhow!("Missing forward lookup result")))
+            .context("Forward ENS lookup failed")
+        {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                verified.push(Err(e));
+                continue;
+            }
+        };
+
+        if resolved != Some(*address) {
+            warn!(
+                address = %address,
+                ens_name = name,
+                resolved = ?resolved,
+                "Reverse ENS record does not resolve back to the address, ignoring"
+            );
+            verified.push(Ok(None));
+            continue;
+        }
+
+        verified.push(Ok(Some(name)));
+    }
+
+    stream::iter(addresses.iter().copied().zip(verified))
+        .map(|(address, name)| async move {
+            match name? {
+                Some(name) => Ok(resolve_profile(resolver, name, address).await),
+                None => Ok(EnsIdentity::default()),
+            }
+        })
+        .buffered(PROFILE_CONCURRENCY_LIMIT)
+        .collect()
+        .await
+}
+
+/// Profile records of a forward-verified name.
+async fn resolve_profile<R: EnsResolver>(
+    resolver: &R,
+    name: String,
+    address: Address,
+) -> EnsIdentity {
+    let avatar = match text_record(resolver, &name, "avatar").await {
+        Some(raw) => resolve_avatar(resolver, &raw, address).await,
+        None => None,
+    };
+
+    EnsIdentity {
+        avatar,
+        url: text_record(resolver, &name, "url").await,
+        twitter: text_record(resolver, &name, "com.twitter").await,
+        discourse: text_record(resolver, &name, "com.discourse").await,
+        description: text_record(resolver, &name, "description").await,
+        ens: Some(name),
+    }
+}
+
+async fn text_record<R: EnsResolver>(resolver: &R, name: &str, key: &str) -> Option<String> {
+    match resolver.text(name, key).await {
+        Ok(value) => value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        Err(e) => {
+            debug!(ens_name = name, key = key, error = %e, "Failed to fetch ENS text record");
+            None
+        }
+    }
+}
+
+async fn resolve_avatar<R: EnsResolver>(resolver: &R, raw: &str, owner: Address) -> Option<String> {
+    match parse_avatar_uri(raw)? {
+        AvatarUri::Url(url) => Some(url),
+        AvatarUri::Nft(nft) => match resolver.nft_image(&nft, owner).await {
+            Ok(image) => image,
+            Err(e) => {
+                debug!(avatar = raw, error = %e, "Failed to resolve NFT avatar");
+                None
+            }
+        },
+    }
+}
+
+/// Parse an ENS `avatar` record into either a fetchable URL or an NFT
+/// reference. Returns `None` for schemes we cannot display.
+pub fn parse_avatar_uri(raw: &str) -> Option<AvatarUri> {
+    let raw = raw.trim();
+    if raw.to_ascii_lowercase().starts_with("eip155:") {
+        return parse_nft_avatar(raw).map(AvatarUri::Nft);
+    }
+    normalize_media_url(raw).map(AvatarUri::Url)
+}
+
+fn parse_nft_avatar(raw: &str) -> Option<NftAvatar> {
+    let (chain, asset) = raw.split_once('/')?;
+    let chain_id = chain
+        .split_once(':')
+        .and_then(|(_, id)| id.parse::<u64>().ok())?;
+
+    let mut parts = asset.splitn(2, '/');
+    let (standard, contract) = parts.next()?.split_once(':')?;
+    let token_id = parts.next()?;
+
+    let standard = match standard.to_ascii_lowercase().as_str() {
+        "erc721" => NftStandard::Erc721,
+        "erc1155" => NftStandard::Erc1155,
+        _ => return None,
+    };
+
+    Some(NftAvatar {
+        chain_id,
+        standard,
+        contract: contract.parse().ok()?,
+        token_id: U256::from_str_radix(token_id, 10).ok()?,
+    })
+}
+
+/// Rewrite decentralized storage URIs to gateway URLs and drop anything that
+/// is not directly displayable.
+pub fn normalize_media_url(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let lower = raw.to_ascii_lowercase();
+
+    if lower.starts_with("https://")
+        || lower.starts_with("http://")
+        || lower.starts_with("data:image/")
+    {
+        Some(raw.to_string())
+    } else if lower.starts_with("ipfs://") {
+        let path = &raw["ipfs://".len()..];
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        (!path.is_empty()).then(|| format!("{IPFS_GATEWAY}{path}"))
+    } else if lower.starts_with("ar://") {
+        let path = &raw["ar://".len()..];
+        (!path.is_empty()).then(|| format!("{ARWEAVE_GATEWAY}{path}"))
+    } else {
+        None
+    }
+}
+
+/// ENS namehash (EIP-137). Names are lowercased as a cheap approximation of
+/// full ENSIP-15 normalization.
+pub fn namehash(name: &str) -> B256 {
+    let mut node = B256::ZERO;
+    if name.is_empty() {
+        return node;
+    }
+    for label in name.to_lowercase().rsplit('.') {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(node.as_slice());
+        buf[32..].copy_from_slice(keccak256(label.as_bytes()).as_slice());
+        node = keccak256(buf);
+    }
+    node
+}
+
+/// Node of the reverse record for `address` (`<hex>.addr.reverse`).
+pub fn reverse_node(address: Address) -> B256 {
+    namehash(&format!("{}.addr.reverse", hex_without_prefix(address)))
+}
+
+fn hex_without_prefix(address: Address) -> String {
+    format!("{address:x}").trim_start_matches("0x").to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct NftMetadata {
+    image: Option<String>,
+    image_url: Option<String>,
+}
+
+/// [`EnsResolver`] backed by the ENS contracts on Ethereum mainnet.
+#[derive(Clone)]
+pub struct ProviderEnsResolver {
+    provider: Arc<RindexerProvider>,
+    /// Token URIs are set by whoever deployed the NFT, so metadata is only
+    /// fetched from public websites.
+    http: reqwest::Client,
+}
+
+impl ProviderEnsResolver {
+    pub fn new(provider: Arc<RindexerProvider>) -> Result<Self> {
+        Ok(Self {
+            provider,
+            http: public_client(NFT_METADATA_TIMEOUT, NFT_METADATA_MAX_REDIRECTS)?,
+        })
+    }
+
+    async fn resolver_for(&self, node: B256) -> Result<Option<Address>> {
+        let registry = IEnsRegistry::new(ENS_REGISTRY_ADDRESS, self.provider.clone());
+        let resolver = registry.resolver(node).call().await?;
+        Ok((resolver != Address::ZERO).then_some(resolver))
+    }
+
+    /// Resolver contract of every node, in one multicall round trip.
+    async fn batch_resolvers(&self, nodes: &[B256]) -> Vec<Result<Option<Address>>> {
+        let mut batch = Multicall::new(self.provider.clone());
+        let calls: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                batch.add(
+                    ENS_REGISTRY_ADDRESS,
+                    &IEnsRegistry::resolverCall { node: *node },
+                )
+            })
+            .collect();
+
+        let results = batch.execute().await;
+        calls
+            .iter()
+            .map(|call| {
+                results
+                    .get(call)
+                    .map(|resolver| (resolver != Address::ZERO).then_some(resolver))
+            })
+            .collect()
+    }
+
+    async fn fetch_nft_image(&self, token_uri: &str) -> Result<Option<String>> {
+        // Inline base64 metadata is rare for avatars; not worth a decoder.
+        if token_uri.starts_with("data:application/json;base64,") {
+            return Ok(None);
+        }
+        if let Some(json) = token_uri.strip_prefix("data:application/json;utf8,") {
+            let metadata: NftMetadata = serde_json::from_str(json)?;
+            return Ok(metadata
+                .image
+                .or(metadata.image_url)
+                .and_then(|i| normalize_media_url(&i)));
+        }
+
+        let Some(url) = normalize_media_url(token_uri).filter(|url| is_public_web_url(url)) else {
+            return Ok(None);
+        };
+        let mut response = self.http.get(&url).send().await?.error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > NFT_METADATA_MAX_BYTES as u64)
+        {
+            anyhow::bail!("NFT metadata is larger than {NFT_METADATA_MAX_BYTES} bytes");
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > NFT_METADATA_MAX_BYTES {
+                anyhow::bail!("NFT metadata is larger than {NFT_METADATA_MAX_BYTES} bytes");
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let metadata: NftMetadata = serde_json::from_slice(&body)?;
+        Ok(metadata
+            .image
+            .or(metadata.image_url)
+            .and_then(|i| normalize_media_url(&i)))
+    }
+}
+
+impl EnsResolver for ProviderEnsResolver {
+    async fn reverse_name(&self, address: Address) -> Result<Option<String>> {
+        let node = reverse_node(address);
+        let Some(resolver) = self.resolver_for(node).await? else {
+            return Ok(None);
+        };
+        let name = IEnsResolver::new(resolver, self.provider.clone())
+            .name(node)
+            .call()
+            .await?;
+        Ok((!name.is_empty()).then_some(name))
+    }
+
+    async fn resolve_name(&self, name: &str) -> Result<Option<Address>> {
+        let node = namehash(name);
+        let Some(resolver) = self.resolver_for(node).await? else {
+            return Err(UnsupportedResolution.into());
+        };
+        let resolved = IEnsResolver::new(resolver, self.provider.clone())
+            .addr(node)
+            .call()
+            .await?;
+        Ok((resolved != Address::ZERO).then_some(resolved))
+    }
+
+    async fn text(&self, name: &str, key: &str) -> Result<Option<String>> {
+        let node = namehash(name);
+        let Some(resolver) = self.resolver_for(node).await? else {
+            return Ok(None);
+        };
+        let value = IEnsResolver::new(resolver, self.provider.clone())
+            .text(node, key.to_string())
+            .call()
+            .await?;
+        Ok((!value.is_empty()).then_some(value))
+    }
+
+    async fn reverse_names(&self, addresses: &[Address]) -> Vec<Result<Option<String>>> {
+        let nodes: Vec<B256> = addresses.iter().map(|a| reverse_node(*a)).collect();
+        let resolvers = self.batch_resolvers(&nodes).await;
+
+        let mut batch = Multicall::new(self.provider.clone());
+        let calls: Vec<_> = resolvers
+            .iter()
+            .zip(&nodes)
+            .map(|(resolver, node)| match resolver {
+                Ok(Some(resolver)) => {
+                    Some(batch.add(*resolver, &IEnsResolver::nameCall { node: *node }))
+                }
+                _ => None,
+            })
+            .collect();
+        let names = batch.execute().await;
+
+        resolvers
+            .into_iter()
+            .zip(calls)
+            .map(|(resolver, call)| match (resolver, call) {
+                (Err(e), _) => Err(e),
+                (Ok(_), Some(call)) => names.get(&call).map(|n| (!n.is_empty()).then_some(n)),
+                (Ok(_), None) => Ok(None),
+            })
+            .collect()
+    }
+
+    async fn resolve_names(&self, names: &[String]) -> Vec<Result<Option<Address>>> {
+        let nodes: Vec<B256> = names.iter().map(|n| namehash(n)).collect();
+        let resolvers = self.batch_resolvers(&nodes).await;
+
+        let mut batch = Multicall::new(self.provider.clone());
+        let calls: Vec<_> = resolvers
+            .iter()
+            .zip(&nodes)
+            .map(|(resolver, node)| match resolver {
+                Ok(Some(resolver)) => {
+                    Some(batch.add(*resolver, &IEnsResolver::addrCall { node: *node }))
+                }
+                _ => None,
+            })
+            .collect();
+        let addresses = batch.execute().await;
+
+        resolvers
+            .into_iter()
+            .zip(calls)
+            .map(|(resolver, call)| match (resolver, call) {
+                (Err(e), _) => Err(e),
+                (Ok(_), Some(call)) => addresses
+                    .get(&call)
+                    .map(|a| (a != Address::ZERO).then_some(a)),
+                (Ok(_), None) => Err(UnsupportedResolution.into()),
+            })
+            .collect()
+    }
+
+    async fn nft_image(&self, nft: &NftAvatar, owner: Address) -> Result<Option<String>> {
+        // Only mainnet NFTs are reachable through the Ethereum provider.
+        if nft.chain_id != 1 {
+            return Ok(None);
+        }
+
+        let token_uri = match nft.standard {
+            NftStandard::Erc721 => {
+                let token = IErc721Avatar::new(nft.contract, self.provider.clone());
+                if token.ownerOf(nft.token_id).call().await? != owner {
+                    debug!(owner = %owner, "NFT avatar is not owned by the voter");
+                    return Ok(None);
+                }
+                token.tokenURI(nft.token_id).call().await?
+            }
+            NftStandard::Erc1155 => {
+                let token = IErc1155Avatar::new(nft.contract, self.provider.clone());
+                if token.balanceOf(owner, nft.token_id).call().await?.is_zero() {
+                    debug!(owner = %owner, "NFT avatar is not owned by the voter");
+                    return Ok(None);
+                }
+                // ERC-1155 substitutes `{id}` with the zero-padded hex token id.
+                token
+                    .uri(nft.token_id)
+                    .call()
+                    .await?
+                    .replace("{id}", &format!("{:064x}", nft.token_id))
+            }
+        };
+
+        self.fetch_nft_image(&token_uri).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::b256;
+    use std::collections::HashMap;
+
+    const ALICE: Address = address!("0x1111111111111111111111111111111111111111");
+    const MALLORY: Address = address!("0x2222222222222222222222222222222222222222");
+
+    #[derive(Default)]
+    struct StubResolver {
+        reverse: HashMap<Address, String>,
+        forward: HashMap<String, Address>,
+        texts: HashMap<(String, String), String>,
+        nft_images: HashMap<Address, String>,
+        failing_keys: Vec<String>,
+        wildcard_names: Vec<String>,
+    }
+
+    impl EnsResolver for StubResolver {
+        async fn reverse_name(&self, address: Address) -> Result<Option<String>> {
+            Ok(self.reverse.get(&address).cloned())
+        }
+
+        async fn resolve_name(&self, name: &str) -> Result<Option<Address>> {
+            if self.wildcard_names.iter().any(|n| n == name) {
+                return Err(UnsupportedResolution.into());
+            }
+            Ok(self.forward.get(name).copied())
+        }
+
+        async fn text(&self, name: &str, key: &str) -> Result<Option<String>> {
+            if self.failing_keys.iter().any(|k| k == key) {
+                anyhow::bail!("rpc error");
+            }
+            Ok(self
+                .texts
+                .get(&(name.to_string(), key.to_string()))
+                .cloned())
+        }
+
+        async fn nft_image(&self, nft: &NftAvatar, _owner: Address) -> Result<Option<String>> {
+            Ok(self.nft_images.get(&nft.contract).cloned())
+        }
+    }
+
+    fn alice_stub() -> StubResolver {
+        let mut stub = StubResolver::default();
+        stub.reverse.insert(ALICE, "alice.eth".to_string());
+        stub.forward.insert("alice.eth".to_string(), ALICE);
+        for (key, value) in [
+            ("url", "https://alice.xyz"),
+            ("com.twitter", "alice"),
+            ("com.discourse", "alice_forum"),
+            ("description", "  Delegate  "),
+            ("avatar", "ipfs://QmAvatar"),
+        ] {
+            stub.texts.insert(
+                ("alice.eth".to_string(), key.to_string()),
+                value.to_string(),
+            );
+        }
+        stub
+    }
+
+    #[test]
+    fn test_namehash_known_vectors() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("foo.eth"),
+            b256!("0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+    }
+
+    #[test]
+    fn test_parse_avatar_uri() {
+        assert_eq!(
+            parse_avatar_uri("https://example.com/a.png"),
+            Some(AvatarUri::Url("https://example.com/a.png".to_string()))
+        );
+        assert_eq!(
+            parse_avatar_uri("ipfs://ipfs/QmHash"),
+            Some(AvatarUri::Url("https://ipfs.io/ipfs/QmHash".to_string()))
+        );
+        assert_eq!(
+            parse_avatar_uri("eip155:1/erc721:0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB/1"),
+            Some(AvatarUri::Nft(NftAvatar {
+                chain_id: 1,
+                standard: NftStandard::Erc721,
+                contract: address!("0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB"),
+                token_id: U256::from(1),
+            }))
+        );
+        assert_eq!(
+            parse_avatar_uri(
+                "eip155:1/erc1155:0x495f947276749ce646f68ac8c248420045cb7b5e/8112316025873927737505937898915153732580103913704334048512380490797008551937"
+            )
+            .map(|a| matches!(a, AvatarUri::Nft(NftAvatar { standard: NftStandard::Erc1155, .. }))),
+            Some(true)
+        );
+        assert_eq!(
+            parse_avatar_uri("eip155:1/erc20:0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB/1"),
+            None
+        );
+        assert_eq!(parse_avatar_uri("eip155:1/erc721:not-an-address/1"), None);
+        assert_eq!(parse_avatar_uri("ftp://example.com/a.png"), None);
+        assert_eq!(parse_avatar_uri(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identity_verified() {
+        let identity = resolve_identity(&alice_stub(), ALICE).await.unwrap();
+
+        assert_eq!(
+            identity,
+            EnsIdentity {
+                ens: Some("alice.eth".to_string()),
+                avatar: Some("https://ipfs.io/ipfs/QmAvatar".to_string()),
+                url: Some("https://alice.xyz".to_string()),
+                twitter: Some("alice".to_string()),
+                discourse: Some("alice_forum".to_string()),
+                description: Some("Delegate".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identity_rejects_spoofed_reverse_record() {
+        let mut stub = alice_stub();
+        // Mallory points their reverse record at alice.eth, which resolves to Alice.
+        stub.reverse.insert(MALLORY, "alice.eth".to_string());
+
+        let identity = resolve_identity(&stub, MALLORY).await.unwrap();
+        assert_eq!(identity, EnsIdentity::default());
+        assert!(!identity.is_verified());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identity_tolerates_failing_text_records() {
+        let mut stub = alice_stub();
+        stub.failing_keys.push("com.twitter".to_string());
+
+        let identity = resolve_identity(&stub, ALICE).await.unwrap();
+        assert_eq!(identity.ens.as_deref(), Some("alice.eth"));
+        assert_eq!(identity.twitter, None);
+        assert_eq!(identity.url.as_deref(), Some("https://alice.xyz"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identity_unsupported_resolution() {
+        let mut stub = alice_stub();
+        stub.reverse.insert(ALICE, "alice.base.eth".to_string());
+        stub.wildcard_names.push("alice.base.eth".to_string());
+
+        let err = resolve_identity(&stub, ALICE).await.unwrap_err();
+        assert!(err.downcast_ref::<UnsupportedResolution>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identities_batch_keeps_order() {
+        let mut stub = alice_stub();
+        stub.reverse.insert(MALLORY, "alice.eth".to_string());
+        let nobody = address!("0x3333333333333333333333333333333333333333");
+
+        let identities = resolve_identities(&stub, &[nobody, MALLORY, ALICE]).await;
+        assert_eq!(identities.len(), 3);
+        assert_eq!(identities[0].as_ref().unwrap(), &EnsIdentity::default());
+        assert_eq!(identities[1].as_ref().unwrap(), &EnsIdentity::default());
+        assert_eq!(
+            identities[2].as_ref().unwrap().ens.as_deref(),
+            Some("alice.eth")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identity_nft_avatar() {
+        let contract = address!("0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB");
+        let mut stub = alice_stub();
+        stub.texts.insert(
+            ("alice.eth".to_string(), "avatar".to_string()),
+            format!("eip155:1/erc721:{contract}/42"),
+        );
+        stub.nft_images
+            .insert(contract, "https://nft.example/42.png".to_string());
+
+        let identity = resolve_identity(&stub, ALICE).await.unwrap();
+        assert_eq!(
+            identity.avatar.as_deref(),
+            Some("https://nft.example/42.png")
+        );
+    }
+
+    #[test]
+    fn test_refresh_policy() {
+        let now = chrono::Utc::now().naive_utc();
+        let policy = EnsRefreshPolicy::default();
+        let mut voter = voter::Model {
+            id: Default::default(),
+            address: ALICE.to_string(),
+            ens: Some("alice.eth".to_string()),
+            avatar: None,
+            updated_at: now,
+            ens_verified: true,
+            url: None,
+            twitter: None,
+            discourse: None,
+            description: None,
+            ens_refreshed_at: None,
+        };
+        assert!(policy.is_due(&voter, now));
+
+        voter.ens_refreshed_at = Some(now - chrono::Duration::hours(2));
+        assert!(!policy.is_due(&voter, now));
+
+        voter.ens_refreshed_at = Some(now - chrono::Duration::hours(25));
+        assert!(policy.is_due(&voter, now));
+
+        voter.ens_verified = false;
+        voter.ens = None;
+        assert!(!policy.is_due(&voter, now));
+
+        voter.ens_refreshed_at = Some(now - chrono::Duration::days(8));
+        assert!(policy.is_due(&voter, now));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::extensions::db_extension::{DB, store_votes};
+use alloy::{
+    primitives::{B256, Bytes},
+    sol_types::SolEvent,
+};
+use anyhow::{Context, Result};
+use proposalsapp_db::models::{failed_event, vote};
+use rindexer::event::callback_registry::TxInformation;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    Statement,
+    prelude::{Expr, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, instrument, warn};
+
+/// Current time in UTC, matching the naive UTC timestamps the columns hold
+/// whatever the session time zone is.
+const NOW_UTC_SQL: &str = "timezone('utc', now())";
+
+/// Delay before the next replay after `failed_event.attempts` failures:
+/// one minute, doubling per attempt, capped at six hours.
+const NEXT_ATTEMPT_SQL: &str = "timezone('utc', now()) + LEAST(INTERVAL '1 minute' * POWER(2, LEAST(attempts, 16) - 1), INTERVAL '6 hours')";
+
+/// A raw event log and its position, enough to decode the event again and
+/// replay it through its handler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventPayload {
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_number: u64,
+    pub transaction_hash: String,
+    pub log_index: u64,
+}
+
+impl EventPayload {
+    pub fn new<E: SolEvent>(event: &E, tx_information: &TxInformation) -> Self {
+        let log_data = event.encode_log_data();
+        Self {
+            topics: log_data.topics().to_vec(),
+            data: log_data.data,
+            block_number: tx_information.block_number,
+            transaction_hash: tx_information.transaction_hash.to_string(),
+            log_index: tx_information.log_index.to::<u64>(),
+        }
+    }
+
+    pub fn decode<E: SolEvent>(&self) -> Result<E> {
+        E::decode_raw_log(self.topics.iter().copied(), &self.data)
+            .with_context(|| format!("Failed to decode {} from payload", E::SIGNATURE))
+    }
+}
+
+/// Dead-letter an event whose handler failed, keyed by handler (e.g.
+/// `ArbitrumCoreGovernor::VoteCast`) and log position. Failing again for an
+/// event that is already stored counts as another attempt. Errors are only
+/// logged, as this runs on the handlers' error paths.
+#[instrument(name = "failed_events_record", skip(payload, error), fields(block_number = payload.block_number, log_index = payload.log_index))]
+pub async fn record_failed_event(handler: &str, payload: &EventPayload, error: &anyhow::Error) {
+    if let Err(e) = try_record_failed_event(handler, payload, error).await {
+        error!(
+            handler,
+            transaction_hash = %payload.transaction_hash,
+            log_index = payload.log_index,
+            original_error = %error,
+            error = %e,
+            "Failed to record failed event, event is lost"
+        );
+    }
+}
+
+/// [`record_failed_event`] for every event of a batch that failed as a whole.
+pub async fn record_failed_events(handler: &str, payloads: &[EventPayload], error: &anyhow::Error) {
+    for payload in payloads {
+        record_failed_event(handler, payload, error).await;
+    }
+}
+
+/// Split the models built from a batch of events from the events whose model
+/// couldn't be built, which are dead-lettered.
+pub async fn record_build_failures<M>(
+    handler: &str,
+    built: Vec<(EventPayload, Result<M>)>,
+) -> Vec<(EventPayload, M)> {
+    let mut models = Vec::with_capacity(built.len());
+    for (payload, model) in built {
+        match model {
+            Ok(model) => models.push((payload, model)),
+            Err(e) => {
+                error!(handler, block_number = payload.block_number, error = %e, "Failed to build model from event");
+                record_failed_event(handler, &payload, &e).await;
+            }
+        }
+    }
+    models
+}
+
+/// Store a batch of votes. If the batch fails, typically because one vote
+/// is for a proposal that isn't indexed yet, the votes are stored one by one
+/// and only those that still fail are dead-lettered.
+#[instrument(name = "failed_events_store_votes", skip(votes), fields(vote_count = votes.len()))]
+pub async fn store_votes_or_record(
+    handler: &str,
+    votes: Vec<(EventPayload, vote::ActiveModel)>,
+    governor_id: Uuid,
+) {
+    if votes.is_empty() {
+        return;
+    }
+
+    let batch = votes.iter().map(|(_, vote)| vote.clone()).collect();
+    let Err(batch_error) = store_votes(batch, governor_id).await else {
+        return;
+    };
+    warn!(error = %batch_error, "Failed to store vote batch, storing votes one by one");
+
+    for (payload, vote) in votes {
+        if let Err(e) = store_votes(vec![vote], governor_id).await {
+            record_failed_event(handler, &payload, &e).await;
+        }
+    }
+}
+
+async fn try_record_failed_event(
+    handler: &str,
+    payload: &EventPayload,
+    error: &anyhow::Error,
+) -> Result<()> {
+    let db = DB.get().context("DB not initialized")?;
+
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"
+            INSERT INTO failed_event
+                (handler, payload, block_number, transaction_hash, log_index, error, next_attempt_at)
+            VALUES ($1, $2, $3, $4, $5, $6, {NOW_UTC_SQL} + INTERVAL '1 minute')
+            ON CONFLICT (handler, transaction_hash, log_index) DO UPDATE SET
+                payload = EXCLUDED.payload,
+                error = EXCLUDED.error,
+                attempts = failed_event.attempts + 1,
+                next_attempt_at = {},
+                updated_at = {NOW_UTC_SQL}
+            "#,
+            NEXT_ATTEMPT_SQL.replace("attempts", "(failed_event.attempts + 1)")
+        ),
+        vec![
+            handler.into(),
+            serde_json::to_value(payload)?.into(),
+            (payload.block_number as i64).into(),
+            payload.transaction_hash.clone().into(),
+            (payload.log_index as i32).into(),
+            format!("{error:#}").into(),
+        ],
+    ))
+    .await
+    .context("Failed to insert failed event")?;
+
+    warn!(
+        handler,
+        transaction_hash = %payload.transaction_hash,
+        log_index = payload.log_index,
+        error = %error,
+        "Recorded failed event for replay"
+    );
+    Ok(())
+}
+
+/// Failed events whose next attempt is due, oldest log first so replays
+/// apply in chain order.
+pub async fn due_failed_events(limit: u64) -> Result<Vec<failed_event::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    failed_event::Entity::find()
+        .filter(Expr::col(failed_event::Column::NextAttemptAt).lte(Expr::cust(NOW_UTC_SQL)))
+        .order_by_asc(failed_event::Column::BlockNumber)
+        .order_by_asc(failed_event::Column::LogIndex)
+        .limit(limit)
+        .all(db)
+        .await
+        .context("Failed to fetch due failed events")
+}
+
+/// All failed events, optionally for one handler, oldest log first.
+pub async fn list_failed_events(handler: Option<&str>) -> Result<Vec<failed_event::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let mut query = failed_event::Entity::find();
+    if let Some(handler) = handler {
+        query = query.filter(failed_event::Column::Handler.eq(handler));
+    }
+    query
+        .order_by_asc(failed_event::Column::BlockNumber)
+        .order_by_asc(failed_event::Column::LogIndex)
+        .all(db)
+        .await
+        .context("Failed to list failed events")
+}
+
+pub async fn find_failed_event(id: Uuid) -> Result<Option<failed_event::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    failed_event::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .context("Failed to fetch failed event")
+}
+
+/// Remove a failed event, either because its replay succeeded or because an
+/// operator discarded it. Returns whether it existed.
+pub async fn delete_failed_event(id: Uuid) -> Result<bool> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let result = failed_event::Entity::delete_by_id(id)
+        .exec(db)
+        .await
+        .context("Failed to delete failed event")?;
+    Ok(result.rows_affected > 0)
+}
+
+/// Count a failed replay and push the next attempt back.
+pub async fn reschedule_failed_event(id: Uuid, error: &anyhow::Error) -> Result<()> {
+    let db = DB.get().context("DB not initialized")?;
+
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"
+            UPDATE failed_event SET
+                error = $2,
+                attempts = attempts + 1,
+                next_attempt_at = {},
+                updated_at = {NOW_UTC_SQL}
+            WHERE id = $1
+            "#,
+            NEXT_ATTEMPT_SQL.replace("attempts", "(attempts + 1)")
+        ),
+        vec![id.into(), format!("{error:#}").into()],
+    ))
+    .await
+    .context("Failed to reschedule failed event")?;
+
+    info!(failed_event_id = %id, error = %error, "Failed event replay failed, rescheduled");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        primitives::{U256, address},
+        sol,
+    };
+
+    sol! {
+        #[derive(Debug, PartialEq)]
+        event VoteCast(address indexed voter, uint256 proposalId, uint8 support, uint256 weight, string reason);
+    }
+
+    #[test]
+    fn test_event_payload_roundtrips_through_json() {
+        let event = VoteCast {
+            voter: address!("0x1a9C8182C09F50C8318d769245beA52c32BE35BC"),
+            proposalId: U256::from(42u64),
+            support: 1,
+            weight: U256::from(10u64).pow(U256::from(18u64)),
+            reason: "because".to_string(),
+        };
+        let log_data = event.encode_log_data();
+        let payload = EventPayload {
+            topics: log_data.topics().to_vec(),
+            data: log_data.data,
+            block_number: 100,
+            transaction_hash: "0xabc".to_string(),
+            log_index: 3,
+        };
+
+        let json = serde_json::to_value(&payload).unwrap();
+        let restored: EventPayload = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, payload);
+        assert_eq!(restored.decode::<VoteCast>().unwrap(), event);
+    }
+}
@@ -0,0 +1,841 @@
+use crate::{
+    extensions::{
+        db_extension::{DAO_SLUG_GOVERNOR_TYPE_ID_MAP, DB},
+        failed_events::{EventPayload, record_failed_event},
+    },
+    rindexer_lib::indexers::{
+        all_handlers::replay_event,
+        rindexer::{
+            contracts::{
+                GovernorVoteTotals, batch_bravo_vote_totals, batch_governor_vote_totals,
+                provider_for_network,
+            },
+            uni_governor,
+        },
+    },
+};
+use alloy::{
+    primitives::{Address, B256, Bytes, U256, keccak256},
+    providers::Provider,
+};
+use anyhow::{Context, Result};
+use futures::{StreamExt, stream};
+use proposalsapp_db::models::{governor_backfill_heal, governor_backfill_state, proposal, vote};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DbBackend, EntityTrait, FromQueryResult, PaginatorTrait, QueryFilter, Statement,
+    prelude::Uuid,
+    sea_query::OnConflict,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use tracing::{info, instrument, warn};
+
+/// Signatures shared by the OpenZeppelin and GovernorBravo governors.
+const PROPOSAL_CREATED_SIGNATURE: &str =
+    "ProposalCreated(uint256,address,address[],uint256[],string[],bytes[],uint256,uint256,string)";
+const VOTE_CAST_SIGNATURE: &str = "VoteCast(address,uint256,uint8,uint256,string)";
+
+/// ProposalCreated windows scanned per governor and run, so catching up from
+/// the start block is spread over several runs.
+const MAX_WINDOWS_PER_RUN: usize = 20;
+
+/// Proposals are only checked once voting ended this long ago, leaving the
+/// indexer time to store the last votes.
+const VOTE_CHECK_GRACE: &str = "1 hour";
+
+const PROPOSAL_VOTES_BATCH_SIZE: usize = 100;
+const CONCURRENCY_LIMIT: usize = 10;
+
+/// Relative difference tolerated between summed stored votes and on-chain
+/// totals. Stored voting power goes through a u256 -> f64 conversion per
+/// vote, so anything above rounding noise is a missing vote.
+const VOTE_TOTALS_TOLERANCE: f64 = 1e-9;
+
+/// How missing proposals of a governor are found.
+enum ProposalDiscovery {
+    /// Scan ProposalCreated logs in windows of `log_block_range` blocks.
+    Logs,
+    /// Ids are sequential below `proposalCount()`, see
+    /// [`uni_governor::backfill_missing_proposals_and_votes`].
+    ProposalCount,
+}
+
+/// Where a governor's vote totals are read from.
+enum VoteTotals {
+    /// `proposalVotes(proposalId)` of GovernorCountingSimple.
+    OpenZeppelin,
+    /// `proposals(proposalId)` of GovernorBravo.
+    Bravo,
+    /// Votes aren't indexed for this governor.
+    NotIndexed,
+}
+
+/// Blocks holding the VoteCast logs of a proposal.
+enum VoteBlocks {
+    /// The proposal's start and end blocks, on the governor's own chain.
+    ProposalBlocks,
+    /// This many blocks after the proposal was created, for governors whose
+    /// start and end blocks are on another chain (Arbitrum uses L1 blocks).
+    AfterCreation(u64),
+}
+
+/// Governor contract as configured in rindexer.yaml.
+struct BackfillGovernor {
+    dao_slug: &'static str,
+    governor_type: &'static str,
+    contract: &'static str,
+    /// Snake case contract name used by rindexer's sync tables.
+    sync_table: &'static str,
+    network: &'static str,
+    address: &'static str,
+    start_block: u64,
+    discovery: ProposalDiscovery,
+    vote_totals: VoteTotals,
+    vote_blocks: VoteBlocks,
+    log_block_range: u64,
+}
+
+const BACKFILL_GOVERNORS: &[BackfillGovernor] = &[
+    BackfillGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_CORE",
+        contract: "ArbitrumCoreGovernor",
+        sync_table: "arbitrum_core_governor",
+        network: "arbitrum",
+        address: "0xf07ded9dc292157749b6fd268e37df6ea38395b9",
+        start_block: 70_398_215,
+        discovery: ProposalDiscovery::Logs,
+        vote_totals: VoteTotals::OpenZeppelin,
+        vote_blocks: VoteBlocks::AfterCreation(8_000_000),
+        log_block_range: 500_000,
+    },
+    BackfillGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_TREASURY",
+        contract: "ArbitrumTreasuryGovernor",
+        sync_table: "arbitrum_treasury_governor",
+        network: "arbitrum",
+        address: "0x789fc99093b09ad01c34dc7251d0c89ce743e5a4",
+        start_block: 70_398_215,
+        discovery: ProposalDiscovery::Logs,
+        vote_totals: VoteTotals::OpenZeppelin,
+        vote_blocks: VoteBlocks::AfterCreation(8_000_000),
+        log_block_range: 500_000,
+    },
+    BackfillGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_SC_NOMINATIONS",
+        contract: "ArbitrumSCNominations",
+        sync_table: "arbitrum_sc_nominations",
+        network: "arbitrum",
+        address: "0x8a1cda8dee421cd06023470608605934c16a05a0",
+        start_block: 121_763_102,
+        discovery: ProposalDiscovery::Logs,
+        vote_totals: VoteTotals::NotIndexed,
+        vote_blocks: VoteBlocks::ProposalBlocks,
+        log_block_range: 500_000,
+    },
+    BackfillGovernor {
+        dao_slug: "uniswap",
+        governor_type: "UNISWAP_GOVERNOR",
+        contract: "UniGovernor",
+        sync_table: "uni_governor",
+        network: "ethereum",
+        address: "0x408ed6354d4973f66138c91495f2f2fcbd8724c3",
+        start_block: 13_059_157,
+        discovery: ProposalDiscovery::ProposalCount,
+        vote_totals: VoteTotals::Bravo,
+        vote_blocks: VoteBlocks::ProposalBlocks,
+        log_block_range: 9_999,
+    },
+];
+
+/// Vote totals summed from the stored votes of a proposal, in tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StoredVoteTotals {
+    pub for_votes: f64,
+    pub against_votes: f64,
+    pub abstain_votes: f64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct StoredProposalVotes {
+    external_id: String,
+    block_created_at: Option<i32>,
+    block_start_at: Option<i32>,
+    block_end_at: Option<i32>,
+    for_votes: f64,
+    against_votes: f64,
+    abstain_votes: f64,
+    /// On-chain totals recorded by the last votes heal of the proposal.
+    healed_onchain: Option<Value>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct LastSyncedBlock {
+    last_synced_block: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcLog {
+    block_number: String,
+    transaction_hash: String,
+    log_index: String,
+    data: String,
+    topics: Vec<String>,
+}
+
+fn parse_hex_u64(value: &str) -> Result<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16)
+        .context("Failed to parse hex value into u64")
+}
+
+fn event_payload_from_log(log: RpcLog) -> Result<EventPayload> {
+    Ok(EventPayload {
+        topics: log
+            .topics
+            .into_iter()
+            .map(|topic| topic.parse::<B256>().context("Invalid log topic"))
+            .collect::<Result<Vec<_>>>()?,
+        data: log.data.parse::<Bytes>().context("Invalid log data")?,
+        block_number: parse_hex_u64(&log.block_number)?,
+        transaction_hash: log.transaction_hash,
+        log_index: parse_hex_u64(&log.log_index)?,
+    })
+}
+
+/// Proposal id of a ProposalCreated log, the first word of its data for both
+/// OpenZeppelin and GovernorBravo.
+fn proposal_created_id(payload: &EventPayload) -> Result<U256> {
+    let word = payload
+        .data
+        .get(..32)
+        .context("ProposalCreated log data is too short")?;
+    Ok(U256::from_be_slice(word))
+}
+
+/// Proposal id of a VoteCast log, the first word of its data as only the
+/// voter is indexed.
+pub fn vote_cast_proposal_id(payload: &EventPayload) -> Result<U256> {
+    let word = payload
+        .data
+        .get(..32)
+        .context("VoteCast log data is too short")?;
+    Ok(U256::from_be_slice(word))
+}
+
+pub fn wei_to_tokens(value: U256) -> f64 {
+    value.to::<u128>() as f64 / (10.0f64.powi(18))
+}
+
+pub fn amounts_match(stored: f64, onchain: f64) -> bool {
+    (stored - onchain).abs() <= VOTE_TOTALS_TOLERANCE * stored.abs().max(onchain.abs()).max(1.0)
+}
+
+fn totals_match(stored: StoredVoteTotals, onchain: GovernorVoteTotals) -> bool {
+    amounts_match(stored.for_votes, wei_to_tokens(onchain.for_votes))
+        && amounts_match(stored.against_votes, wei_to_tokens(onchain.against_votes))
+        && amounts_match(stored.abstain_votes, wei_to_tokens(onchain.abstain_votes))
+}
+
+fn onchain_totals_json(onchain: GovernorVoteTotals) -> Value {
+    json!({
+        "for": onchain.for_votes.to_string(),
+        "against": onchain.against_votes.to_string(),
+        "abstain": onchain.abstain_votes.to_string(),
+    })
+}
+
+fn governor_id(governor: &BackfillGovernor) -> Result<Uuid> {
+    DAO_SLUG_GOVERNOR_TYPE_ID_MAP
+        .get()
+        .context("DAO_SLUG_GOVERNOR_TYPE_ID_MAP not initialized")?
+        .lock()
+        .unwrap()
+        .get(governor.dao_slug)
+        .and_then(|governors| governors.get(governor.governor_type))
+        .copied()
+        .with_context(|| {
+            format!(
+                "Governor {} not found for DAO '{}'",
+                governor.governor_type, governor.dao_slug
+            )
+        })
+}
+
+/// Last block rindexer synced for one event of a contract, from its sync
+/// table, e.g. `rindexer_last_synced_block("uni_governor", "vote_cast",
+/// "ethereum")`. Events above it aren't expected in the database yet.
+pub async fn rindexer_last_synced_block(
+    sync_table: &str,
+    event: &str,
+    network: &str,
+) -> Result<Option<u64>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let synced = LastSyncedBlock::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            "SELECT last_synced_block::BIGINT AS last_synced_block FROM rindexer_internal.rindexer_{sync_table}_{event} WHERE network = $1"
+        ),
+        vec![network.into()],
+    ))
+    .one(db)
+    .await
+    .with_context(|| format!("Failed to read last synced block of {sync_table} {event}"))?;
+
+    Ok(synced
+        .and_then(|synced| synced.last_synced_block)
+        .map(|block| block as u64))
+}
+
+async fn fetch_governor_logs(
+    governor: &BackfillGovernor,
+    signature: &str,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EventPayload>> {
+    let provider = provider_for_network(governor.network).await;
+    let logs = provider
+        .client()
+        .request::<(Value,), Vec<RpcLog>>(
+            "eth_getLogs",
+            (json!({
+                "address": governor.address,
+                "topics": [keccak256(signature).to_string()],
+                "fromBlock": format!("0x{:x}", from_block),
+                "toBlock": format!("0x{:x}", to_block),
+            }),),
+        )
+        .await
+        .with_context(|| format!("Failed to fetch {} logs", governor.contract))?;
+
+    logs.into_iter().map(event_payload_from_log).collect()
+}
+
+async fn record_heal(
+    governor_id: Uuid,
+    proposal_external_id: &str,
+    kind: &str,
+    healed_count: usize,
+    details: Value,
+) -> Result<()> {
+    let db = DB.get().context("DB not initialized")?;
+
+    governor_backfill_heal::Entity::insert(governor_backfill_heal::ActiveModel {
+        id: NotSet,
+        governor_id: Set(governor_id),
+        proposal_external_id: Set(proposal_external_id.to_string()),
+        kind: Set(kind.to_string()),
+        healed_count: Set(healed_count as i32),
+        details: Set(details),
+        created_at: NotSet,
+    })
+    .exec(db)
+    .await
+    .context("Failed to record governor backfill heal")?;
+    Ok(())
+}
+
+async fn last_scanned_block(governor: &BackfillGovernor, governor_id: Uuid) -> Result<u64> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let state = governor_backfill_state::Entity::find_by_id(governor_id)
+        .one(db)
+        .await
+        .context("Failed to fetch governor backfill state")?;
+    Ok(state
+        .map(|state| state.last_scanned_block as u64)
+        .unwrap_or(governor.start_block.saturating_sub(1)))
+}
+
+async fn store_last_scanned_block(governor_id: Uuid, block: u64) -> Result<()> {
+    let db = DB.get().context("DB not initialized")?;
+
+    governor_backfill_state::Entity::insert(governor_backfill_state::ActiveModel {
+        governor_id: Set(governor_id),
+        last_scanned_block: Set(block as i64),
+        updated_at: Set(chrono::Utc::now().naive_utc()),
+    })
+    .on_conflict(
+        OnConflict::column(governor_backfill_state::Column::GovernorId)
+            .update_columns([
+                governor_backfill_state::Column::LastScannedBlock,
+                governor_backfill_state::Column::UpdatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec(db)
+    .await
+    .context("Failed to store governor backfill state")?;
+    Ok(())
+}
+
+/// Scan the ProposalCreated logs after the governor's cursor, up to what
+/// rindexer synced, and replay those whose proposal isn't stored. Returns
+/// the number of proposals healed.
+#[instrument(name = "governor_backfill_scan_proposals", skip_all, fields(governor = governor.governor_type))]
+async fn scan_proposal_logs(governor: &BackfillGovernor, governor_id: Uuid) -> Result<usize> {
+    let db = DB.get().context("DB not initialized")?;
+    let Some(synced_block) =
+        rindexer_last_synced_block(governor.sync_table, "proposal_created", governor.network)
+            .await?
+    else {
+        return Ok(0);
+    };
+
+    let handler = format!("{}::ProposalCreated", governor.contract);
+    let mut cursor = last_scanned_block(governor, governor_id).await?;
+    let mut healed = 0;
+
+    for _ in 0..MAX_WINDOWS_PER_RUN {
+        if cursor >= synced_block {
+            break;
+        }
+        let from_block = cursor + 1;
+        let to_block = from_block
+            .saturating_add(governor.log_block_range - 1)
+            .min(synced_block);
+
+        let logs =
+            fetch_governor_logs(governor, PROPOSAL_CREATED_SIGNATURE, from_block, to_block).await?;
+        let external_ids = logs
+            .iter()
+            .map(|payload| proposal_created_id(payload).map(|id| id.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let stored: HashSet<String> = proposal::Entity::find()
+            .filter(proposal::Column::GovernorId.eq(governor_id))
+            .filter(proposal::Column::ExternalId.is_in(external_ids.clone()))
+            .all(db)
+            .await
+            .context("Failed to fetch stored proposals")?
+            .into_iter()
+            .map(|proposal| proposal.external_id)
+            .collect();
+
+        for (payload, external_id) in logs.iter().zip(&external_ids) {
+            if stored.contains(external_id) {
+                continue;
+            }
+
+            warn!(
+                proposal_id = %external_id,
+                block_number = payload.block_number,
+                "Proposal missing from the database, replaying ProposalCreated"
+            );
+            if let Err(e) = replay_event(governor.contract, "ProposalCreated", payload).await {
+                record_failed_event(&handler, payload, &e).await;
+                continue;
+            }
+            record_heal(
+                governor_id,
+                external_id,
+                "proposal",
+                1,
+                json!({
+                    "block_number": payload.block_number,
+                    "transaction_hash": payload.transaction_hash,
+                }),
+            )
+            .await?;
+            healed += 1;
+        }
+
+        store_last_scanned_block(governor_id, to_block).await?;
+        cursor = to_block;
+    }
+
+    info!(
+        last_scanned_block = cursor,
+        synced_block, healed, "Scanned ProposalCreated logs"
+    );
+    Ok(healed)
+}
+
+/// Heal proposals missing below `proposalCount()` and record them.
+async fn backfill_counted_proposals(governor_id: Uuid) -> Result<usize> {
+    let backfilled = uni_governor::backfill_missing_proposals_and_votes().await?;
+
+    for (proposal_id, vote_count) in &backfilled {
+        record_heal(
+            governor_id,
+            &proposal_id.to_string(),
+            "proposal",
+            1,
+            json!({ "backfilled_votes": vote_count }),
+        )
+        .await?;
+    }
+    Ok(backfilled.len())
+}
+
+/// Blocks to fetch VoteCast logs from for a proposal, capped at what rindexer
+/// synced.
+fn vote_block_range(
+    governor: &BackfillGovernor,
+    proposal: &StoredProposalVotes,
+    synced_block: u64,
+) -> Option<(u64, u64)> {
+    let (from_block, to_block) = match governor.vote_blocks {
+        VoteBlocks::ProposalBlocks => (
+            proposal.block_start_at? as u64,
+            proposal.block_end_at? as u64,
+        ),
+        VoteBlocks::AfterCreation(span) => {
+            let created = proposal.block_created_at? as u64;
+            (created, created.saturating_add(span))
+        }
+    };
+    let to_block = to_block.min(synced_block);
+    (from_block <= to_block).then_some((from_block, to_block))
+}
+
+/// Vote totals at `block`, the last block whose votes rindexer stored.
+async fn fetch_onchain_totals(
+    governor: &BackfillGovernor,
+    proposal_ids: &[U256],
+    block: u64,
+) -> Result<Vec<Result<GovernorVoteTotals>>> {
+    let address: Address = governor
+        .address
+        .parse()
+        .context("Invalid governor address")?;
+    Ok(match governor.vote_totals {
+        VoteTotals::OpenZeppelin => {
+            batch_governor_vote_totals(governor.network, address, proposal_ids, Some(block)).await
+        }
+        VoteTotals::Bravo => {
+            batch_bravo_vote_totals(governor.network, address, proposal_ids, Some(block)).await
+        }
+        VoteTotals::NotIndexed => Vec::new(),
+    })
+}
+
+async fn stored_vote_count(governor_id: Uuid, proposal_external_id: &str) -> Result<u64> {
+    let db = DB.get().context("DB not initialized")?;
+
+    vote::Entity::find()
+        .filter(vote::Column::GovernorId.eq(governor_id))
+        .filter(vote::Column::ProposalExternalId.eq(proposal_external_id))
+        .count(db)
+        .await
+        .context("Failed to count stored votes")
+}
+
+/// Re-fetch and replay every VoteCast log of a proposal. Returns the number
+/// of logs replayed and of votes that were missing.
+async fn refetch_proposal_votes(
+    governor: &BackfillGovernor,
+    governor_id: Uuid,
+    proposal_external_id: &str,
+    (from_block, to_block): (u64, u64),
+) -> Result<(usize, u64)> {
+    let proposal_id = proposal_external_id
+        .parse::<U256>()
+        .context("Invalid proposal id")?;
+    let before = stored_vote_count(governor_id, proposal_external_id).await?;
+
+    let mut payloads = Vec::new();
+    let mut chunk_start = from_block;
+    while chunk_start <= to_block {
+        let chunk_end = chunk_start
+            .saturating_add(governor.log_block_range)
+            .min(to_block);
+        for payload in
+            fetch_governor_logs(governor, VOTE_CAST_SIGNATURE, chunk_start, chunk_end).await?
+        {
+            if vote_cast_proposal_id(&payload)? == proposal_id {
+                payloads.push(payload);
+            }
+        }
+        if chunk_end == to_block {
+            break;
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    let contract = governor.contract;
+    let handler = format!("{contract}::VoteCast");
+    stream::iter(payloads.iter().cloned())
+        .map(|payload| {
+            let handler = handler.clone();
+            async move {
+                if let Err(e) = replay_event(contract, "VoteCast", &payload).await {
+                    record_failed_event(&handler, &payload, &e).await;
+                }
+            }
+        })
+        .buffer_unordered(CONCURRENCY_LIMIT)
+        .collect::<Vec<_>>()
+        .await;
+
+    let after = stored_vote_count(governor_id, proposal_external_id).await?;
+    Ok((payloads.len(), after.saturating_sub(before)))
+}
+
+/// Compare the summed stored votes of every ended proposal with the
+/// contract's totals, and re-fetch the VoteCast logs of proposals that
+/// differ. A proposal is only re-fetched again once its on-chain totals
+/// change, so votes the indexer doesn't track (e.g. VoteCastWithParams)
+/// don't cause a re-fetch every run. Returns the number of votes healed.
+#[instrument(name = "governor_backfill_check_votes", skip_all, fields(governor = governor.governor_type))]
+async fn check_vote_totals(governor: &BackfillGovernor, governor_id: Uuid) -> Result<u64> {
+    let db = DB.get().context("DB not initialized")?;
+    if matches!(governor.vote_totals, VoteTotals::NotIndexed) {
+        return Ok(0);
+    }
+    let Some(synced_block) =
+        rindexer_last_synced_block(governor.sync_table, "vote_cast", governor.network).await?
+    else {
+        return Ok(0);
+    };
+
+    let proposals = StoredProposalVotes::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"
+            SELECT
+                p.external_id,
+                p.block_created_at,
+                p.block_start_at,
+                p.block_end_at,
+                COALESCE(SUM(v.voting_power) FILTER (WHERE v.choice = '0'::jsonb), 0.0) AS for_votes,
+                COALESCE(SUM(v.voting_power) FILTER (WHERE v.choice = '1'::jsonb), 0.0) AS against_votes,
+                COALESCE(SUM(v.voting_power) FILTER (WHERE v.choice = '2'::jsonb), 0.0) AS abstain_votes,
+                h.details -> 'onchain' AS healed_onchain
+            FROM proposal p
+            LEFT JOIN vote v ON v.proposal_id = p.id
+            LEFT JOIN LATERAL (
+                SELECT details
+                FROM governor_backfill_heal
+                WHERE governor_id = p.governor_id
+                  AND proposal_external_id = p.external_id
+                  AND kind = 'votes'
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) h ON TRUE
+            WHERE p.governor_id = $1
+              AND p.end_at < timezone('utc', now()) - INTERVAL '{VOTE_CHECK_GRACE}'
+            GROUP BY p.id, h.details
+            "#
+        ),
+        vec![governor_id.into()],
+    ))
+    .all(db)
+    .await
+    .context("Failed to sum stored votes per proposal")?;
+
+    let mut healed = 0;
+    for chunk in proposals.chunks(PROPOSAL_VOTES_BATCH_SIZE) {
+        let proposal_ids = chunk
+            .iter()
+            .map(|proposal| {
+                proposal
+                    .external_id
+                    .parse::<U256>()
+                    .context("Invalid proposal id")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let onchain_totals = fetch_onchain_totals(governor, &proposal_ids, synced_block).await?;
+
+        for (proposal, onchain) in chunk.iter().zip(onchain_totals) {
+            let onchain = match onchain {
+                Ok(onchain) => onchain,
+                Err(e) => {
+                    warn!(proposal_id = %proposal.external_id, error = %e, "Failed to read on-chain vote totals");
+                    continue;
+                }
+            };
+            let stored = StoredVoteTotals {
+                for_votes: proposal.for_votes,
+                against_votes: proposal.against_votes,
+                abstain_votes: proposal.abstain_votes,
+            };
+            let onchain_json = onchain_totals_json(onchain);
+            if totals_match(stored, onchain)
+                || proposal.healed_onchain.as_ref() == Some(&onchain_json)
+            {
+                continue;
+            }
+            let Some(block_range) = vote_block_range(governor, proposal, synced_block) else {
+                continue;
+            };
+
+            warn!(
+                proposal_id = %proposal.external_id,
+                stored = ?stored,
+                onchain = %onchain_json,
+                "Stored votes differ from on-chain totals, re-fetching VoteCast logs"
+            );
+            let (refetched, missing) =
+                refetch_proposal_votes(governor, governor_id, &proposal.external_id, block_range)
+                    .await?;
+            record_heal(
+                governor_id,
+                &proposal.external_id,
+                "votes",
+                missing as usize,
+                json!({
+                    "stored": {
+                        "for": stored.for_votes,
+                        "against": stored.against_votes,
+                        "abstain": stored.abstain_votes,
+                    },
+                    "onchain": onchain_json,
+                    "from_block": block_range.0,
+                    "to_block": block_range.1,
+                    "refetched_votes": refetched,
+                    "missing_votes": missing,
+                }),
+            )
+            .await?;
+            healed += missing;
+        }
+    }
+
+    info!(
+        checked_proposals = proposals.len(),
+        healed_votes = healed,
+        "Checked proposal vote totals"
+    );
+    Ok(healed)
+}
+
+/// Heal missing proposals and votes of every governor, or only those of one
+/// DAO. Every heal is recorded in `governor_backfill_heal`.
+#[instrument(name = "governor_backfill", skip_all, fields(dao_slug = ?dao_slug))]
+pub async fn backfill_governors(dao_slug: Option<&str>) -> Result<()> {
+    for governor in BACKFILL_GOVERNORS
+        .iter()
+        .filter(|governor| dao_slug.is_none_or(|slug| governor.dao_slug == slug))
+    {
+        let governor_id = governor_id(governor)?;
+
+        let healed_proposals = match governor.discovery {
+            ProposalDiscovery::Logs => scan_proposal_logs(governor, governor_id).await,
+            ProposalDiscovery::ProposalCount => backfill_counted_proposals(governor_id).await,
+        }
+        .with_context(|| format!("Failed to backfill proposals of {}", governor.contract))?;
+
+        let healed_votes = check_vote_totals(governor, governor_id)
+            .await
+            .with_context(|| format!("Failed to check vote totals of {}", governor.contract))?;
+
+        if healed_proposals > 0 || healed_votes > 0 {
+            info!(
+                governor = governor.governor_type,
+                healed_proposals, healed_votes, "Backfilled governor"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        primitives::{address, hex},
+        sol,
+        sol_types::SolEvent,
+    };
+
+    sol! {
+        event ProposalCreated(uint256 proposalId, address proposer, address[] targets, uint256[] values, string[] signatures, bytes[] calldatas, uint256 startBlock, uint256 endBlock, string description);
+        event VoteCast(address indexed voter, uint256 proposalId, uint8 support, uint256 weight, string reason);
+    }
+
+    fn tokens(amount: u64) -> U256 {
+        U256::from(amount) * U256::from(10u64).pow(U256::from(18u64))
+    }
+
+    #[test]
+    fn test_signatures_match_governor_events() {
+        assert_eq!(PROPOSAL_CREATED_SIGNATURE, ProposalCreated::SIGNATURE);
+        assert_eq!(VOTE_CAST_SIGNATURE, VoteCast::SIGNATURE);
+    }
+
+    #[test]
+    fn test_proposal_ids_from_logs() {
+        let proposal_id = U256::from_str_radix(
+            "77049969659962393408182308518930939247285848107346513112985531885924337078488",
+            10,
+        )
+        .unwrap();
+        let created = ProposalCreated {
+            proposalId: proposal_id,
+            proposer: address!("0x1a9C8182C09F50C8318d769245beA52c32BE35BC"),
+            targets: vec![],
+            values: vec![],
+            signatures: vec![],
+            calldatas: vec![],
+            startBlock: U256::from(1u64),
+            endBlock: U256::from(2u64),
+            description: "# Title".to_string(),
+        };
+        let log_data = created.encode_log_data();
+        let payload = event_payload_from_log(RpcLog {
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: "0x3".to_string(),
+            data: format!("0x{}", hex::encode(&log_data.data)),
+            topics: log_data.topics().iter().map(|t| t.to_string()).collect(),
+        })
+        .unwrap();
+        assert_eq!(payload.block_number, 16);
+        assert_eq!(payload.log_index, 3);
+        assert_eq!(proposal_created_id(&payload).unwrap(), proposal_id);
+
+        let vote = VoteCast {
+            voter: address!("0x1a9C8182C09F50C8318d769245beA52c32BE35BC"),
+            proposalId: proposal_id,
+            support: 1,
+            weight: tokens(5),
+            reason: String::new(),
+        };
+        let log_data = vote.encode_log_data();
+        let payload = EventPayload {
+            topics: log_data.topics().to_vec(),
+            data: log_data.data,
+            block_number: 17,
+            transaction_hash: "0xdef".to_string(),
+            log_index: 0,
+        };
+        assert_eq!(vote_cast_proposal_id(&payload).unwrap(), proposal_id);
+    }
+
+    #[test]
+    fn test_totals_match_tolerates_rounding_only() {
+        let onchain = GovernorVoteTotals {
+            for_votes: tokens(1_000_000) + U256::from(1u64),
+            against_votes: tokens(250),
+            abstain_votes: U256::ZERO,
+        };
+        let stored = StoredVoteTotals {
+            for_votes: 1_000_000.0,
+            against_votes: 250.0,
+            abstain_votes: 0.0,
+        };
+        assert!(totals_match(stored, onchain));
+
+        let missing_vote = StoredVoteTotals {
+            against_votes: 200.0,
+            ..stored
+        };
+        assert!(!totals_match(missing_vote, onchain));
+
+        let missing_small_vote = StoredVoteTotals {
+            abstain_votes: 0.0,
+            ..stored
+        };
+        let onchain_with_abstain = GovernorVoteTotals {
+            abstain_votes: tokens(1),
+            ..onchain
+        };
+        assert!(!totals_match(missing_small_vote, onchain_with_abstain));
+    }
+}
@@ -1,3 +1,17 @@
 pub mod block_time;
 pub mod db_extension;
+pub mod delegate_verification;
+pub mod delegation_flows;
+pub mod ens_identity;
+pub mod failed_events;
+pub mod governor_backfill;
+pub mod multicall;
+pub mod proposal_description;
+pub mod proposal_revisions;
+pub mod proposal_transitions;
 pub mod snapshot_api;
+pub mod tally_audit;
+#[cfg(test)]
+pub mod test_fixtures;
+pub mod voting_power;
+pub mod voting_power_gaps;
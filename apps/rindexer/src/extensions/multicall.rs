@@ -0,0 +1,216 @@
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, Bytes, address},
+    sol,
+    sol_types::SolCall,
+};
+use anyhow::{Result, anyhow};
+use rindexer::provider::RindexerProvider;
+use std::{marker::PhantomData, sync::Arc};
+use tracing::{debug, instrument, warn};
+
+/// Multicall3, deployed at the same address on Ethereum and Arbitrum.
+pub const MULTICALL3_ADDRESS: Address = address!("0xcA11bde05977b3631167028862bE2a173976CA11");
+
+/// Calls per `aggregate3` request. Keeps the request below common RPC
+/// gas/size limits for `eth_call`.
+const MULTICALL_CHUNK_SIZE: usize = 200;
+
+sol! {
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct CallResult {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (CallResult[] memory returnData);
+    }
+}
+
+/// Handle to a call queued on a [`Multicall`], used to fetch its decoded
+/// return value from [`MulticallResults`].
+pub struct CallId<C> {
+    index: usize,
+    _call: PhantomData<fn() -> C>,
+}
+
+impl<C> Clone for CallId<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for CallId<C> {}
+
+/// Collects contract reads and executes them through Multicall3 in as few
+/// `eth_call`s as possible. Every call is sent with `allowFailure`, so one
+/// reverting call never fails the rest of the batch.
+pub struct Multicall {
+    provider: Arc<RindexerProvider>,
+    calls: Vec<IMulticall3::Call3>,
+    block: Option<u64>,
+}
+
+impl Multicall {
+    pub fn new(provider: Arc<RindexerProvider>) -> Self {
+        Self {
+            provider,
+            calls: Vec::new(),
+            block: None,
+        }
+    }
+
+    /// Read the state at `block` instead of the latest block.
+    pub fn at_block(mut self, block: u64) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn add<C: SolCall>(&mut self, target: Address, call: &C) -> CallId<C> {
+        self.calls.push(IMulticall3::Call3 {
+            target,
+            allowFailure: true,
+            callData: call.abi_encode().into(),
+        });
+        CallId {
+            index: self.calls.len() - 1,
+            _call: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Execute all queued calls. Chunks are sent concurrently; if a whole
+    /// chunk fails at the RPC level every call in it reports that error.
+    #[instrument(name = "multicall_execute", skip(self), fields(call_count = self.calls.len()))]
+    pub async fn execute(self) -> MulticallResults {
+        if self.calls.is_empty() {
+            return MulticallResults::default();
+        }
+
+        let multicall = IMulticall3::new(MULTICALL3_ADDRESS, self.provider.clone());
+        let block = self.block;
+        let chunks = self.calls.chunks(MULTICALL_CHUNK_SIZE).map(|chunk| {
+            let multicall = &multicall;
+            async move {
+                let mut aggregate = multicall.aggregate3(chunk.to_vec());
+                if let Some(block) = block {
+                    aggregate = aggregate.block(BlockId::number(block));
+                }
+                match aggregate.call().await {
+                    Ok(results) if results.len() == chunk.len() => results
+                        .into_iter()
+                        .map(|r| {
+                            if r.success {
+                                Ok(r.returnData)
+                            } else {
+                                Err("call reverted".to_string())
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    Ok(results) => {
+                        warn!(
+                            expected = chunk.len(),
+                            received = results.len(),
+                            "Multicall returned an unexpected number of results"
+                        );
+                        vec![Err("malformed multicall response".to_string()); chunk.len()]
+                    }
+                    Err(e) => {
+                        warn!(error = %e, chunk_size = chunk.len(), "Multicall request failed");
+                        vec![Err(format!("multicall request failed: {e}")); chunk.len()]
+                    }
+                }
+            }
+        });
+
+        let results: Vec<_> = futures::future::join_all(chunks)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+
+        debug!(
+            failed_calls = results.iter().filter(|r| r.is_err()).count(),
+            "Multicall completed"
+        );
+
+        MulticallResults { results }
+    }
+}
+
+/// Raw per-call outcomes of an executed [`Multicall`].
+#[derive(Debug, Default)]
+pub struct MulticallResults {
+    results: Vec<std::result::Result<Bytes, String>>,
+}
+
+impl MulticallResults {
+    /// Decoded return value of the call behind `id`.
+    pub fn get<C: SolCall>(&self, id: &CallId<C>) -> Result<C::Return> {
+        match self.results.get(id.index) {
+            Some(Ok(data)) => C::abi_decode_returns(data)
+                .map_err(|e| anyhow!("Failed to decode multicall result: {e}")),
+            Some(Err(e)) => Err(anyhow!("{e}")),
+            None => Err(anyhow!("No multicall result for call {}", id.index)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{primitives::U256, sol_types::SolValue};
+
+    sol! {
+        function quorum(uint256 blockNumber) external view returns (uint256);
+        function state(uint256 proposalId) external view returns (uint8);
+    }
+
+    fn call_id<C>(index: usize) -> CallId<C> {
+        CallId {
+            index,
+            _call: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_results_decode_per_call() {
+        let results = MulticallResults {
+            results: vec![
+                Ok(U256::from(42u64).abi_encode().into()),
+                Err("call reverted".to_string()),
+                Ok(U256::from(7u64).abi_encode().into()),
+            ],
+        };
+
+        assert_eq!(
+            results.get(&call_id::<quorumCall>(0)).unwrap(),
+            U256::from(42u64)
+        );
+        assert!(results.get(&call_id::<stateCall>(1)).is_err());
+        assert_eq!(results.get(&call_id::<stateCall>(2)).unwrap(), 7u8);
+        assert!(results.get(&call_id::<stateCall>(3)).is_err());
+    }
+
+    #[test]
+    fn test_results_reject_malformed_return_data() {
+        let results = MulticallResults {
+            results: vec![Ok(Bytes::from_static(&[1, 2, 3]))],
+        };
+        assert!(results.get(&call_id::<quorumCall>(0)).is_err());
+    }
+}
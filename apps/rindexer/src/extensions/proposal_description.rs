@@ -0,0 +1,386 @@
+use crate::extensions::db_extension::DB;
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use proposalsapp_db::models::dao_discourse;
+use regex::Regex;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, prelude::Uuid};
+use serde::Serialize;
+use tracing::warn;
+
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Hosts of Snapshot frontends, subdomains included.
+const SNAPSHOT_HOSTS: &[&str] = &["snapshot.org", "snapshot.box"];
+
+/// Hosts of on-chain governance frontends, subdomains included.
+const GOVERNANCE_HOSTS: &[&str] = &[
+    "tally.xyz",
+    "agora.xyz",
+    "boardroom.io",
+    "vote.uniswapfoundation.org",
+    "app.uniswap.org",
+];
+
+static URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).expect("valid url regex"));
+static ADDRESS_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b0x[0-9a-fA-F]{40}\b").expect("valid address regex"));
+static TX_HASH_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b0x[0-9a-fA-F]{64}\b").expect("valid tx hash regex"));
+
+/// What an on-chain proposal description says, beyond its text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedDescription {
+    pub title: String,
+    pub sections: Vec<DescriptionSection>,
+    pub references: DescriptionReferences,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DescriptionSection {
+    pub level: u8,
+    pub heading: String,
+}
+
+/// Links and on-chain references of a description, in order of appearance
+/// and without duplicates. Addresses and hashes are lowercase.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DescriptionReferences {
+    pub forum_links: Vec<String>,
+    pub snapshot_links: Vec<String>,
+    pub governance_links: Vec<String>,
+    pub other_links: Vec<String>,
+    pub addresses: Vec<String>,
+    pub tx_hashes: Vec<String>,
+}
+
+impl ParsedDescription {
+    /// The forum topic the proposal links to, falling back to any forum
+    /// link when none points to a topic.
+    pub fn discussion_url(&self) -> Option<String> {
+        let forum_links = &self.references.forum_links;
+        forum_links
+            .iter()
+            .find(|link| link.contains("/t/"))
+            .or_else(|| forum_links.first())
+            .cloned()
+    }
+}
+
+/// Parse a Markdown proposal description. Links to `forum_hosts`, the hosts
+/// of the DAO's Discourse forums, are forum links.
+pub fn parse_description(description: &str, forum_hosts: &[String]) -> ParsedDescription {
+    let mut lines = description
+        .lines()
+        .skip_while(|line| clean_heading(line).is_empty());
+    let title = lines
+        .next()
+        .map(clean_heading)
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let mut sections = Vec::new();
+    let mut in_code_block = false;
+    for line in lines {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(char::is_whitespace) {
+            sections.push(DescriptionSection {
+                level: level as u8,
+                heading: clean_heading(line),
+            });
+        }
+    }
+
+    ParsedDescription {
+        title: truncate_title(title),
+        sections,
+        references: extract_references(description, forum_hosts),
+    }
+}
+
+/// Parse the description of a proposal of `dao_id`, matching forum links
+/// against the DAO's Discourse forums.
+pub async fn parse_proposal_description(description: &str, dao_id: Uuid) -> ParsedDescription {
+    let forum_hosts = match forum_hosts(dao_id).await {
+        Ok(hosts) => hosts,
+        Err(e) => {
+            warn!(dao_id = %dao_id, error = %e, "Failed to load forum hosts, no link will be a forum link");
+            Vec::new()
+        }
+    };
+    parse_description(description, &forum_hosts)
+}
+
+/// Hosts of the Discourse forums of a DAO.
+pub async fn forum_hosts(dao_id: Uuid) -> Result<Vec<String>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let forums = dao_discourse::Entity::find()
+        .filter(dao_discourse::Column::DaoId.eq(dao_id))
+        .all(db)
+        .await
+        .context("Failed to fetch DAO forums")?;
+    Ok(forums
+        .iter()
+        .filter_map(|forum| url_host(&forum.discourse_base_url))
+        .collect())
+}
+
+fn extract_references(description: &str, forum_hosts: &[String]) -> DescriptionReferences {
+    let mut references = DescriptionReferences::default();
+
+    for url in URL_REGEX.find_iter(description) {
+        let url = url
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_', '\'']);
+        let Some(host) = url_host(url) else {
+            continue;
+        };
+
+        // Explorer links point to the address or transaction they reference
+        let path: Vec<&str> = url.split(['/', '?', '#']).collect();
+        for pair in path.windows(2) {
+            match pair {
+                ["tx", hash] if is_hex(hash, 64) => push_unique(&mut references.tx_hashes, hash),
+                ["address" | "token", address] if is_hex(address, 40) => {
+                    push_unique(&mut references.addresses, address)
+                }
+                _ => {}
+            }
+        }
+
+        let links = if forum_hosts.contains(&host) {
+            &mut references.forum_links
+        } else if matches_host(&host, SNAPSHOT_HOSTS) {
+            &mut references.snapshot_links
+        } else if matches_host(&host, GOVERNANCE_HOSTS) {
+            &mut references.governance_links
+        } else {
+            &mut references.other_links
+        };
+        if !links.iter().any(|link| link == url) {
+            links.push(url.to_string());
+        }
+    }
+
+    // Hex inside links is matched above, as Snapshot proposal ids look like
+    // transaction hashes
+    let text = URL_REGEX.replace_all(description, " ");
+    for address in ADDRESS_REGEX.find_iter(&text) {
+        push_unique(&mut references.addresses, address.as_str());
+    }
+    for hash in TX_HASH_REGEX.find_iter(&text) {
+        push_unique(&mut references.tx_hashes, hash.as_str());
+    }
+
+    references
+}
+
+/// Lowercase host of a URL, without `www.` and port.
+fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+fn matches_host(host: &str, hosts: &[&str]) -> bool {
+    hosts.iter().any(|known| {
+        host == *known
+            || host
+                .strip_suffix(known)
+                .is_some_and(|subdomain| subdomain.ends_with('.'))
+    })
+}
+
+fn is_hex(value: &str, digits: usize) -> bool {
+    value.len() == digits + 2
+        && value.starts_with("0x")
+        && value[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    let value = value.to_lowercase();
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+/// A line without heading markers and the emphasis wrapping it.
+fn clean_heading(line: &str) -> String {
+    let heading = line.trim().trim_matches('#').trim();
+    ["**", "__"]
+        .iter()
+        .find_map(|marker| {
+            heading
+                .strip_prefix(marker)
+                .and_then(|heading| heading.strip_suffix(marker))
+        })
+        .unwrap_or(heading)
+        .trim()
+        .to_string()
+}
+
+fn truncate_title(title: String) -> String {
+    if title.chars().count() > MAX_TITLE_CHARS {
+        title.chars().take(MAX_TITLE_CHARS).collect()
+    } else {
+        title
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARBITRUM_FORUM: &str = "forum.arbitrum.foundation";
+    const UNISWAP_FORUM: &str = "gov.uniswap.org";
+
+    fn parse(description: &str, forum: &str) -> ParsedDescription {
+        parse_description(description, &[forum.to_string()])
+    }
+
+    fn headings(parsed: &ParsedDescription) -> Vec<(u8, &str)> {
+        parsed
+            .sections
+            .iter()
+            .map(|section| (section.level, section.heading.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_arbitrum_descriptions() {
+        let arbos = parse(
+            include_str!("../../tests/fixtures/proposal_descriptions/arbitrum_arbos_11.md"),
+            ARBITRUM_FORUM,
+        );
+        assert_eq!(arbos.title, "Constitutional AIP: ArbOS Version 11");
+        assert_eq!(
+            headings(&arbos),
+            vec![
+                (2, "Abstract"),
+                (2, "Motivation"),
+                (2, "Rationale"),
+                (2, "Specifications and Implementation"),
+                (3, "Upgrade action"),
+            ]
+        );
+        assert_eq!(
+            arbos.discussion_url().as_deref(),
+            Some("https://forum.arbitrum.foundation/t/aip-arbos-version-11/19695")
+        );
+        assert_eq!(arbos.references.snapshot_links.len(), 1);
+        assert_eq!(
+            arbos.references.governance_links,
+            vec![
+                "https://www.tally.xyz/gov/arbitrum/proposal/77069694702187027448745871790562515795432836429094222862498991082283032976814"
+            ]
+        );
+        assert_eq!(
+            arbos.references.addresses,
+            vec![
+                "0x5d5ad1db9cd0c1d4db0d7a4f4bb0f34a4a5d7f8c",
+                "0xcf57572261c7c2bcf21ffd220ea7d1a27d40a827",
+            ]
+        );
+        // The Snapshot proposal id isn't a transaction
+        assert!(arbos.references.tx_hashes.is_empty());
+
+        let stip = parse(
+            include_str!("../../tests/fixtures/proposal_descriptions/arbitrum_stip.md"),
+            ARBITRUM_FORUM,
+        );
+        assert_eq!(
+            stip.title,
+            "Non-Constitutional: Arbitrum Short-Term Incentive Program (Arbitrum Improvement Proposal)"
+        );
+        assert_eq!(
+            stip.discussion_url().as_deref(),
+            Some(
+                "https://forum.arbitrum.foundation/t/proposal-activate-arbitrum-short-term-incentive-program-arbitrum-improvement-proposal/16131"
+            )
+        );
+        assert_eq!(stip.references.forum_links.len(), 2);
+        assert_eq!(
+            stip.references.addresses,
+            vec![
+                "0x912ce59144191c1204e64559fe8253a0e49e6548",
+                "0x2e3bef6830ae84bb4225d318f9f61b6b88c147bf",
+            ]
+        );
+        assert_eq!(
+            stip.references.tx_hashes,
+            vec!["0x4d1e2f3a4b5c6d7e8f9012a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7"]
+        );
+    }
+
+    #[test]
+    fn test_parse_uniswap_descriptions() {
+        let base = parse(
+            include_str!("../../tests/fixtures/proposal_descriptions/uniswap_deploy_base.md"),
+            UNISWAP_FORUM,
+        );
+        assert_eq!(base.title, "Deploy Uniswap v3 on Base");
+        assert_eq!(
+            base.discussion_url().as_deref(),
+            Some("https://gov.uniswap.org/t/deploy-uniswap-v3-on-base/21062")
+        );
+        assert_eq!(base.references.snapshot_links.len(), 2);
+        assert_eq!(base.references.addresses.len(), 4);
+        assert!(base.references.other_links.is_empty());
+
+        let fee_tier = parse(
+            include_str!("../../tests/fixtures/proposal_descriptions/uniswap_fee_tier.md"),
+            UNISWAP_FORUM,
+        );
+        assert_eq!(
+            fee_tier.title,
+            "Reactivate the 1bp fee tier on the Polygon deployment"
+        );
+        assert!(fee_tier.sections.is_empty());
+        assert_eq!(
+            fee_tier.discussion_url().as_deref(),
+            Some("https://gov.uniswap.org/t/add-1-bp-fee-tier/14913")
+        );
+        assert_eq!(
+            fee_tier.references.governance_links,
+            vec!["https://vote.uniswapfoundation.org/proposals/14"]
+        );
+        assert_eq!(
+            fee_tier.references.addresses,
+            vec!["0x1f9840a85d5af5bf1d1762f925bdaddc4201f984"]
+        );
+        assert_eq!(
+            fee_tier.references.other_links,
+            vec!["https://etherscan.io/token/0x1f9840a85d5af5bf1d1762f925bdaddc4201f984"]
+        );
+    }
+
+    #[test]
+    fn test_parse_description_edge_cases() {
+        // No forum configured: forum links are other links
+        let unconfigured = parse_description("# Title\n\nhttps://gov.uniswap.org/t/topic/1", &[]);
+        assert_eq!(unconfigured.discussion_url(), None);
+        assert_eq!(unconfigured.references.other_links.len(), 1);
+
+        assert_eq!(parse_description("", &[]).title, "Unknown");
+        assert_eq!(parse_description("\n#\n## Title ##\n", &[]).title, "Title");
+        assert_eq!(
+            parse_description(&"a".repeat(200), &[])
+                .title
+                .chars()
+                .count(),
+            MAX_TITLE_CHARS
+        );
+        assert!(matches_host("v1.snapshot.box", SNAPSHOT_HOSTS));
+        assert!(!matches_host("notsnapshot.org", SNAPSHOT_HOSTS));
+    }
+}
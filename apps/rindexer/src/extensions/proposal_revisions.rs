@@ -0,0 +1,268 @@
+use crate::extensions::db_extension::DB;
+use alloy::primitives::keccak256;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use proposalsapp_db::models::{proposal, proposal_revision};
+use sea_orm::{
+    ActiveValue::NotSet, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+    prelude::Uuid,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, instrument};
+use utils::diff::{DiffNode, diff_lines};
+
+/// The material content of a proposal, in hashing order.
+#[derive(Serialize)]
+struct RevisionContent<'a> {
+    name: &'a str,
+    body: &'a str,
+    choices: &'a Value,
+    discussion_url: Option<&'a str>,
+    start: RevisionTime,
+    end: RevisionTime,
+}
+
+/// On-chain proposals start and end at a block, and their times are only
+/// estimates that move as blocks are produced. Hashing the block keeps
+/// re-estimates from adding revisions.
+#[derive(Serialize)]
+enum RevisionTime {
+    Block(i32),
+    Time(NaiveDateTime),
+}
+
+impl RevisionTime {
+    fn new(block: Option<i32>, time: NaiveDateTime) -> Self {
+        block.map_or(Self::Time(time), Self::Block)
+    }
+}
+
+/// What changed between two revisions, possibly of different proposals such
+/// as a temp check and its on-chain proposal. Text fields are line diffs;
+/// the other fields are only set when they changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RevisionDiff {
+    pub title: DiffNode,
+    pub body: DiffNode,
+    pub choices: DiffNode,
+    pub discussion_url: Option<(Option<String>, Option<String>)>,
+    pub start_at: Option<(NaiveDateTime, NaiveDateTime)>,
+    pub end_at: Option<(NaiveDateTime, NaiveDateTime)>,
+}
+
+impl RevisionDiff {
+    pub fn has_changes(&self) -> bool {
+        self.title.has_changes()
+            || self.body.has_changes()
+            || self.choices.has_changes()
+            || self.discussion_url.is_some()
+            || self.start_at.is_some()
+            || self.end_at.is_some()
+    }
+}
+
+/// Hash of the title, body, choices, dates and discussion link of a
+/// proposal.
+pub fn content_hash(proposal: &proposal::Model) -> String {
+    let content = RevisionContent {
+        name: &proposal.name,
+        body: &proposal.body,
+        choices: &proposal.choices,
+        discussion_url: proposal.discussion_url.as_deref(),
+        start: RevisionTime::new(proposal.block_start_at, proposal.start_at),
+        end: RevisionTime::new(proposal.block_end_at, proposal.end_at),
+    };
+    let json = serde_json::to_vec(&content).expect("proposal content is serializable");
+    keccak256(json).to_string()
+}
+
+/// Store the content of `proposal` as its next revision, unless it's the
+/// same as the latest one. Returns the new revision, if any.
+pub async fn record_revision<C: ConnectionTrait>(
+    conn: &C,
+    proposal: &proposal::Model,
+) -> Result<Option<proposal_revision::Model>> {
+    let hash = content_hash(proposal);
+
+    let latest = proposal_revision::Entity::find()
+        .filter(proposal_revision::Column::ProposalId.eq(proposal.id))
+        .order_by_desc(proposal_revision::Column::Revision)
+        .one(conn)
+        .await
+        .context("Failed to fetch the latest proposal revision")?;
+    if latest
+        .as_ref()
+        .is_some_and(|latest| latest.content_hash == hash)
+    {
+        return Ok(None);
+    }
+
+    let revision = latest.map_or(1, |latest| latest.revision + 1);
+    let inserted = proposal_revision::Entity::insert(proposal_revision::ActiveModel {
+        id: NotSet,
+        proposal_id: Set(proposal.id),
+        revision: Set(revision),
+        name: Set(proposal.name.clone()),
+        body: Set(proposal.body.clone()),
+        choices: Set(proposal.choices.clone()),
+        discussion_url: Set(proposal.discussion_url.clone()),
+        start_at: Set(proposal.start_at),
+        end_at: Set(proposal.end_at),
+        content_hash: Set(hash),
+        created_at: NotSet,
+    })
+    .exec_with_returning(conn)
+    .await
+    .context("Failed to record proposal revision")?;
+
+    debug!(proposal_id = %proposal.id, revision, "Proposal revision recorded");
+    Ok(Some(inserted))
+}
+
+#[instrument(name = "proposal_revisions", skip_all, fields(proposal_id = %proposal_id))]
+pub async fn proposal_revisions(proposal_id: Uuid) -> Result<Vec<proposal_revision::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    proposal_revision::Entity::find()
+        .filter(proposal_revision::Column::ProposalId.eq(proposal_id))
+        .order_by_asc(proposal_revision::Column::Revision)
+        .all(db)
+        .await
+        .context("Failed to fetch proposal revisions")
+}
+
+pub async fn find_proposal_revision(id: Uuid) -> Result<Option<proposal_revision::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    proposal_revision::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .context("Failed to fetch proposal revision")
+}
+
+pub fn diff_revisions(
+    before: &proposal_revision::Model,
+    after: &proposal_revision::Model,
+) -> RevisionDiff {
+    fn changed<T: PartialEq + Clone>(before: &T, after: &T) -> Option<(T, T)> {
+        (before != after).then(|| (before.clone(), after.clone()))
+    }
+
+    RevisionDiff {
+        title: diff_lines(&before.name, &after.name),
+        body: diff_lines(&before.body, &after.body),
+        choices: diff_lines(
+            &choice_lines(&before.choices),
+            &choice_lines(&after.choices),
+        ),
+        discussion_url: changed(&before.discussion_url, &after.discussion_url),
+        start_at: changed(&before.start_at, &after.start_at),
+        end_at: changed(&before.end_at, &after.end_at),
+    }
+}
+
+/// One choice per line, so choices diff like text.
+fn choice_lines(choices: &Value) -> String {
+    match choices {
+        Value::Array(choices) => choices
+            .iter()
+            .map(|choice| match choice {
+                Value::String(choice) => format!("{choice}\n"),
+                choice => format!("{choice}\n"),
+            })
+            .collect(),
+        choices => choices.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extensions::test_fixtures;
+    use chrono::DateTime;
+    use proposalsapp_db::models::sea_orm_active_enums::ProposalState;
+    use serde_json::json;
+
+    fn time(timestamp: i64) -> NaiveDateTime {
+        DateTime::from_timestamp(timestamp, 0).unwrap().naive_utc()
+    }
+
+    fn proposal() -> proposal::Model {
+        proposal::Model {
+            name: "Fund the grants program".to_string(),
+            body: "Budget: 1M ARB".to_string(),
+            created_at: time(0),
+            start_at: time(1_000),
+            end_at: time(2_000),
+            ..test_fixtures::proposal()
+        }
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let original = proposal();
+        assert_eq!(content_hash(&original), content_hash(&proposal()));
+
+        let mut state_changed = proposal();
+        state_changed.proposal_state = ProposalState::Active;
+        state_changed.quorum = 100.0;
+        assert_eq!(content_hash(&original), content_hash(&state_changed));
+
+        let mut edited = proposal();
+        edited.body = "Budget: 2M ARB".to_string();
+        assert_ne!(content_hash(&original), content_hash(&edited));
+
+        let mut linked = proposal();
+        linked.discussion_url = Some("https://forum.arbitrum.foundation/t/1".to_string());
+        assert_ne!(content_hash(&original), content_hash(&linked));
+
+        let mut rescheduled = proposal();
+        rescheduled.end_at = time(3_000);
+        assert_ne!(content_hash(&original), content_hash(&rescheduled));
+
+        // Re-estimated times of an on-chain proposal aren't edits
+        let mut onchain = proposal();
+        onchain.block_start_at = Some(100);
+        onchain.block_end_at = Some(200);
+        let mut reestimated = onchain.clone();
+        reestimated.start_at = time(1_012);
+        reestimated.end_at = time(2_024);
+        assert_eq!(content_hash(&onchain), content_hash(&reestimated));
+    }
+
+    #[test]
+    fn test_diff_revisions() {
+        let revision =
+            |name: &str, choices: Value, end_at: NaiveDateTime| proposal_revision::Model {
+                id: Uuid::nil(),
+                proposal_id: Uuid::nil(),
+                revision: 1,
+                name: name.to_string(),
+                body: "Budget: 1M ARB".to_string(),
+                choices,
+                discussion_url: None,
+                start_at: time(1_000),
+                end_at,
+                content_hash: String::new(),
+                created_at: time(0),
+            };
+        let temp_check = revision(
+            "[Temp Check] Grants",
+            json!(["For", "Against"]),
+            time(2_000),
+        );
+        let onchain = revision("Grants", json!(["For", "Against", "Abstain"]), time(3_000));
+
+        let diff = diff_revisions(&temp_check, &onchain);
+        assert!(diff.has_changes());
+        assert!(diff.title.has_changes());
+        assert!(!diff.body.has_changes());
+        assert_eq!(diff.choices.get_after_content(), "For\nAgainst\nAbstain\n");
+        assert_eq!(diff.end_at, Some((time(2_000), time(3_000))));
+        assert_eq!(diff.start_at, None);
+        assert_eq!(diff.discussion_url, None);
+
+        assert!(!diff_revisions(&temp_check, &temp_check).has_changes());
+    }
+}
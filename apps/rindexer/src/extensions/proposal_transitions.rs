@@ -0,0 +1,1050 @@
+use crate::{
+    extensions::{
+        block_time::{current_block, estimate_timestamp},
+        db_extension::{DAO_SLUG_GOVERNOR_TYPE_ID_MAP, DB},
+        failed_events::{EventPayload, list_failed_events},
+        governor_backfill::{
+            amounts_match, rindexer_last_synced_block, vote_cast_proposal_id, wei_to_tokens,
+        },
+    },
+    rindexer_lib::indexers::rindexer::contracts::{
+        ARBITRUM_CORE_GOVERNOR_ADDRESS, ARBITRUM_SC_NOMINATIONS_ADDRESS,
+        ARBITRUM_TREASURY_GOVERNOR_ADDRESS, UNI_GOVERNOR_ADDRESS, batch_bravo_etas,
+        batch_governor_etas, batch_governor_quorums, batch_governor_states,
+    },
+};
+use alloy::primitives::{Address, U256};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use proposalsapp_db::models::{
+    proposal, proposal_state_history, sea_orm_active_enums::ProposalState, vote,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait, prelude::Uuid,
+};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Notify;
+use tracing::{debug, error, info, instrument, warn};
+use utils::outbox::{GovernanceEvent, publish};
+
+/// Start and end blocks of every scheduled governor are Ethereum blocks, the
+/// Arbitrum governors included since they use L1 block numbers.
+const TRANSITION_BLOCKS_NETWORK: &str = "ethereum";
+
+/// How long to wait before checking again a block that should have been
+/// reached by its estimated time, or a transition whose reads failed.
+const RECHECK_SECONDS: i64 = 60;
+
+/// Wakes the scheduler when a proposal is stored, so new and extended
+/// proposals are scheduled without waiting for the next sweep.
+static SCHEDULE_CHANGED: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// How a governor exposes its quorum and ETA.
+enum GovernorKind {
+    /// `quorum(proposalSnapshot)` and `proposalEta`.
+    OpenZeppelin,
+    /// A fixed `quorumVotes` set at creation, and `proposals().eta`.
+    Bravo,
+}
+
+/// Where the state of an ended proposal comes from.
+enum FinalState {
+    /// Computed from the stored votes and quorum.
+    Tally,
+    /// Read from the governor.
+    Onchain,
+}
+
+struct ScheduledGovernor {
+    dao_slug: &'static str,
+    governor_type: &'static str,
+    /// Contract name as configured in rindexer.yaml.
+    contract: &'static str,
+    /// Snake case contract name used by rindexer's sync tables.
+    sync_table: &'static str,
+    network: &'static str,
+    address: Address,
+    kind: GovernorKind,
+    final_state: FinalState,
+}
+
+const SCHEDULED_GOVERNORS: &[ScheduledGovernor] = &[
+    ScheduledGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_CORE",
+        contract: "ArbitrumCoreGovernor",
+        sync_table: "arbitrum_core_governor",
+        network: "arbitrum",
+        address: ARBITRUM_CORE_GOVERNOR_ADDRESS,
+        kind: GovernorKind::OpenZeppelin,
+        final_state: FinalState::Tally,
+    },
+    ScheduledGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_TREASURY",
+        contract: "ArbitrumTreasuryGovernor",
+        sync_table: "arbitrum_treasury_governor",
+        network: "arbitrum",
+        address: ARBITRUM_TREASURY_GOVERNOR_ADDRESS,
+        kind: GovernorKind::OpenZeppelin,
+        final_state: FinalState::Tally,
+    },
+    ScheduledGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_SC_NOMINATIONS",
+        contract: "ArbitrumSCNominations",
+        sync_table: "arbitrum_sc_nominations",
+        network: "arbitrum",
+        address: ARBITRUM_SC_NOMINATIONS_ADDRESS,
+        kind: GovernorKind::OpenZeppelin,
+        final_state: FinalState::Onchain,
+    },
+    ScheduledGovernor {
+        dao_slug: "uniswap",
+        governor_type: "UNISWAP_GOVERNOR",
+        contract: "UniGovernor",
+        sync_table: "uni_governor",
+        network: "ethereum",
+        address: UNI_GOVERNOR_ADDRESS,
+        kind: GovernorKind::Bravo,
+        final_state: FinalState::Tally,
+    },
+];
+
+/// The next event in a proposal's lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// The start block is reached and voting opens.
+    Start,
+    /// The end block is reached and voting closes.
+    End,
+    /// The timelock ETA passed and the proposal can be executed.
+    EtaPassed,
+}
+
+/// Same encoding for OpenZeppelin and GovernorBravo.
+pub fn proposal_state_from_contract(state_code: u8) -> ProposalState {
+    match state_code {
+        0 => ProposalState::Pending,
+        1 => ProposalState::Active,
+        2 => ProposalState::Canceled,
+        3 => ProposalState::Defeated,
+        4 => ProposalState::Succeeded,
+        5 => ProposalState::Queued,
+        6 => ProposalState::Expired,
+        7 => ProposalState::Executed,
+        _ => ProposalState::Unknown,
+    }
+}
+
+/// Where a proposal state change comes from, recorded in its history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionSource {
+    /// An indexed governor event, or a contract read made while indexing it.
+    Event,
+    /// The state scheduler or another task reading the governors.
+    Poller,
+    /// The Snapshot indexer.
+    Snapshot,
+    /// An operator.
+    Manual,
+}
+
+impl TransitionSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Event => "event",
+            Self::Poller => "poller",
+            Self::Snapshot => "snapshot",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// The source of a state change and, for events, where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    pub source: TransitionSource,
+    pub block_number: Option<u64>,
+    pub txid: Option<String>,
+}
+
+impl StateChange {
+    pub fn new(source: TransitionSource) -> Self {
+        Self {
+            source,
+            block_number: None,
+            txid: None,
+        }
+    }
+
+    pub fn event(payload: &EventPayload) -> Self {
+        Self {
+            source: TransitionSource::Event,
+            block_number: Some(payload.block_number),
+            txid: Some(payload.transaction_hash.clone()),
+        }
+    }
+}
+
+/// Whether an on-chain proposal may move from `from` to `to`. States only
+/// move forward, and a state that failed to be read (`Unknown`) may become
+/// anything.
+pub fn is_valid_transition(from: &ProposalState, to: &ProposalState) -> bool {
+    use ProposalState::*;
+
+    match (from, to) {
+        (Unknown, Hidden | Unknown) => false,
+        (Unknown, _) => true,
+        (Pending, Active | Canceled) => true,
+        (Active, Succeeded | Defeated | Canceled | Queued | Expired | Executed) => true,
+        (Succeeded, Queued | Canceled | Expired | Executed) => true,
+        (Queued, Canceled | Expired | Executed) => true,
+        _ => false,
+    }
+}
+
+/// Whether a Snapshot proposal may move from `from` to `to`. Closed
+/// proposals are stored as defeated until their scores are final, and
+/// shutter proposals are hidden until voting starts.
+pub fn is_valid_snapshot_transition(from: &ProposalState, to: &ProposalState) -> bool {
+    use ProposalState::*;
+
+    match (from, to) {
+        (Unknown, Unknown) => false,
+        (Unknown, _) => true,
+        (Hidden, Pending | Active | Canceled | Defeated | Executed) => true,
+        (Pending, Active | Canceled | Defeated | Executed) => true,
+        (Active, Canceled | Defeated | Executed) => true,
+        (Defeated, Executed) => true,
+        _ => false,
+    }
+}
+
+/// Whether `source` may move a proposal from `from` to `to`. Manual changes
+/// are explicit corrections and always allowed.
+pub fn is_allowed_transition(
+    source: TransitionSource,
+    from: &ProposalState,
+    to: &ProposalState,
+) -> bool {
+    match source {
+        TransitionSource::Manual => true,
+        TransitionSource::Snapshot => is_valid_snapshot_transition(from, to),
+        TransitionSource::Event | TransitionSource::Poller => is_valid_transition(from, to),
+    }
+}
+
+/// The transition a proposal waits for and the time it's expected at.
+/// `eta` is the timelock ETA of a queued proposal, when known.
+pub fn next_transition(
+    proposal: &proposal::Model,
+    eta: Option<NaiveDateTime>,
+) -> Option<(Transition, NaiveDateTime)> {
+    match proposal.proposal_state {
+        ProposalState::Pending => Some((Transition::Start, proposal.start_at)),
+        ProposalState::Active => Some((Transition::End, proposal.end_at)),
+        ProposalState::Queued => eta.map(|eta| (Transition::EtaPassed, eta)),
+        _ => None,
+    }
+}
+
+/// Final state of an ended proposal from its votes: it succeeds when the
+/// quorum choices (For and Against unless the proposal metadata says
+/// otherwise) reach the quorum and For beats Against.
+pub fn final_state_from_tally(proposal: &proposal::Model, votes: &[vote::Model]) -> ProposalState {
+    let choices: Vec<String> = serde_json::from_value(proposal.choices.clone()).unwrap_or_default();
+    let choice_kind = |index: u64| {
+        choices.get(index as usize).map(|choice| {
+            let choice = choice.to_lowercase();
+            if choice.contains("for") {
+                "for"
+            } else if choice.contains("against") {
+                "against"
+            } else {
+                "other"
+            }
+        })
+    };
+
+    let quorum_choices: Vec<u64> = match proposal
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("quorum_choices"))
+    {
+        Some(serde_json::Value::Array(choices)) => choices
+            .iter()
+            .filter_map(|choice| choice.as_u64())
+            .collect(),
+        _ => vec![0, 1],
+    };
+
+    let mut for_votes = 0.0;
+    let mut against_votes = 0.0;
+    let mut quorum_votes = 0.0;
+    for vote in votes {
+        let Some(choice) = vote.choice.as_u64() else {
+            error!(
+                proposal_id = proposal.external_id,
+                "Vote choice is not a valid u64 for proposal"
+            );
+            continue;
+        };
+        match choice_kind(choice) {
+            Some("for") => for_votes += vote.voting_power,
+            Some("against") => against_votes += vote.voting_power,
+            _ => {}
+        }
+        if quorum_choices.contains(&choice) {
+            quorum_votes += vote.voting_power;
+        }
+    }
+
+    debug!(
+        proposal_id = proposal.external_id,
+        for_votes,
+        against_votes,
+        quorum_votes,
+        required_quorum = proposal.quorum,
+        "Proposal tally computed"
+    );
+
+    if quorum_votes >= proposal.quorum && for_votes > against_votes {
+        ProposalState::Succeeded
+    } else {
+        ProposalState::Defeated
+    }
+}
+
+/// Append a state change to a proposal's history and publish it. `from` is
+/// `None` for the state a proposal is first stored with, which is published
+/// as the proposal's creation instead.
+pub async fn record_state_history<C: ConnectionTrait>(
+    conn: &C,
+    proposal: &proposal::Model,
+    from: Option<ProposalState>,
+    to: ProposalState,
+    change: &StateChange,
+    correction: bool,
+) -> Result<()> {
+    proposal_state_history::Entity::insert(proposal_state_history::ActiveModel {
+        id: NotSet,
+        proposal_id: Set(proposal.id),
+        from_state: Set(from.clone()),
+        to_state: Set(to.clone()),
+        source: Set(change.source.as_str().to_string()),
+        correction: Set(correction),
+        block_number: Set(change.block_number.map(|block| block as i64)),
+        txid: Set(change.txid.clone()),
+        created_at: NotSet,
+    })
+    .exec(conn)
+    .await
+    .context("Failed to record proposal state history")?;
+
+    if let Some(from_state) = from {
+        publish(
+            conn,
+            proposal.dao_id,
+            [GovernanceEvent::ProposalStateChanged {
+                proposal_id: proposal.id,
+                from_state,
+                to_state: to,
+            }],
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn apply_state(
+    proposal: &proposal::Model,
+    to: ProposalState,
+    change: &StateChange,
+    correction: bool,
+    reason: &str,
+) -> Result<proposal::Model> {
+    let db = DB.get().context("DB not initialized")?;
+    let txn = db.begin().await?;
+
+    let mut active_model: proposal::ActiveModel = proposal.clone().into();
+    active_model.proposal_state = Set(to.clone());
+    let updated = active_model
+        .update(&txn)
+        .await
+        .with_context(|| format!("Failed to move proposal {} to {to:?}", proposal.external_id))?;
+    record_state_history(
+        &txn,
+        proposal,
+        Some(proposal.proposal_state.clone()),
+        to.clone(),
+        change,
+        correction,
+    )
+    .await?;
+    txn.commit().await?;
+
+    info!(
+        proposal_id = proposal.external_id,
+        proposal_name = proposal.name,
+        from = ?proposal.proposal_state,
+        to = ?to,
+        source = change.source.as_str(),
+        correction,
+        reason,
+        "Proposal state transitioned"
+    );
+    Ok(updated)
+}
+
+/// Move a proposal to `to` if its source allows it, and record it in the
+/// proposal's history. Returns the updated proposal, or `None` when it's
+/// already in that state or the transition isn't allowed.
+#[instrument(name = "proposal_transitions_transition_proposal_state", skip(proposal, change), fields(proposal_id = proposal.external_id, from = ?proposal.proposal_state, source = change.source.as_str()))]
+pub async fn transition_proposal_state(
+    proposal: &proposal::Model,
+    to: ProposalState,
+    change: &StateChange,
+    reason: &str,
+) -> Result<Option<proposal::Model>> {
+    if proposal.proposal_state == to {
+        return Ok(None);
+    }
+    if !is_allowed_transition(change.source, &proposal.proposal_state, &to) {
+        warn!(to = ?to, reason, "Rejected invalid proposal state transition");
+        return Ok(None);
+    }
+
+    apply_state(proposal, to, change, false, reason)
+        .await
+        .map(Some)
+}
+
+/// Set a proposal's state regardless of the state machine, for explicit
+/// corrections. Recorded in the history as a correction.
+#[instrument(name = "proposal_transitions_correct_proposal_state", skip(proposal, change), fields(proposal_id = proposal.external_id, from = ?proposal.proposal_state, source = change.source.as_str()))]
+pub async fn correct_proposal_state(
+    proposal: &proposal::Model,
+    to: ProposalState,
+    change: &StateChange,
+    reason: &str,
+) -> Result<Option<proposal::Model>> {
+    if proposal.proposal_state == to {
+        return Ok(None);
+    }
+
+    apply_state(proposal, to, change, true, reason)
+        .await
+        .map(Some)
+}
+
+/// A proposal's state changes, oldest first.
+pub async fn proposal_state_history(
+    proposal_id: Uuid,
+) -> Result<Vec<proposal_state_history::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    proposal_state_history::Entity::find()
+        .filter(proposal_state_history::Column::ProposalId.eq(proposal_id))
+        .order_by_asc(proposal_state_history::Column::CreatedAt)
+        .all(db)
+        .await
+        .context("Failed to fetch proposal state history")
+}
+
+/// Wake the scheduler to reschedule proposals.
+pub fn notify_schedule_changed() {
+    SCHEDULE_CHANGED.notify_one();
+}
+
+/// Resolves when [`notify_schedule_changed`] was called since the last wait.
+pub async fn schedule_changed() {
+    SCHEDULE_CHANGED.notified().await;
+}
+
+fn governor_id(governor: &ScheduledGovernor) -> Result<Uuid> {
+    DAO_SLUG_GOVERNOR_TYPE_ID_MAP
+        .get()
+        .context("DAO_SLUG_GOVERNOR_TYPE_ID_MAP not initialized")?
+        .lock()
+        .unwrap()
+        .get(governor.dao_slug)
+        .and_then(|governors| governors.get(governor.governor_type))
+        .copied()
+        .with_context(|| {
+            format!(
+                "Governor {} not found for DAO '{}'",
+                governor.governor_type, governor.dao_slug
+            )
+        })
+}
+
+fn parse_proposal_id(proposal: &proposal::Model) -> Result<U256> {
+    proposal
+        .external_id
+        .parse::<U256>()
+        .with_context(|| format!("Invalid proposal id {}", proposal.external_id))
+}
+
+/// Whether every vote of an ended proposal is stored, so its tally is
+/// final: rindexer synced the governor's VoteCast logs through the end of
+/// voting, and none of the proposal's votes waits in `failed_event`.
+async fn votes_settled(governor: &ScheduledGovernor, proposal: &proposal::Model) -> Result<bool> {
+    let Some(synced_block) =
+        rindexer_last_synced_block(governor.sync_table, "vote_cast", governor.network).await?
+    else {
+        return Ok(false);
+    };
+    let synced_through_end = if governor.network == TRANSITION_BLOCKS_NETWORK {
+        proposal
+            .block_end_at
+            .is_some_and(|block_end_at| synced_block >= block_end_at as u64)
+    } else {
+        // The end block is on another chain, compare times instead
+        estimate_timestamp(governor.network, synced_block).await? >= proposal.end_at
+    };
+    if !synced_through_end {
+        return Ok(false);
+    }
+
+    let proposal_id = parse_proposal_id(proposal)?;
+    let handler = format!("{}::VoteCast", governor.contract);
+    let failed_vote = list_failed_events(Some(&handler))
+        .await?
+        .into_iter()
+        .filter_map(|failed| serde_json::from_value::<EventPayload>(failed.payload).ok())
+        .any(|payload| vote_cast_proposal_id(&payload).is_ok_and(|id| id == proposal_id));
+    Ok(!failed_vote)
+}
+
+fn eta_from_timestamp(eta: U256) -> Option<NaiveDateTime> {
+    if eta.is_zero() {
+        return None;
+    }
+    DateTime::from_timestamp(eta.try_into().ok()?, 0).map(|eta| eta.naive_utc())
+}
+
+async fn proposals_in_states(
+    governor_id: Uuid,
+    states: Vec<ProposalState>,
+) -> Result<Vec<proposal::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    proposal::Entity::find()
+        .filter(proposal::Column::GovernorId.eq(governor_id))
+        .filter(proposal::Column::ProposalState.is_in(states))
+        .all(db)
+        .await
+        .context("Failed to fetch scheduled proposals")
+}
+
+async fn read_state(governor: &ScheduledGovernor, proposal_id: U256) -> Result<ProposalState> {
+    let state = batch_governor_states(governor.network, governor.address, &[proposal_id])
+        .await
+        .pop()
+        .context("Missing state result")??;
+    Ok(proposal_state_from_contract(state))
+}
+
+/// Set the quorum of an OpenZeppelin proposal from its snapshot. Bravo
+/// quorums are fixed at creation.
+async fn refresh_quorum(
+    governor: &ScheduledGovernor,
+    proposal: proposal::Model,
+) -> Result<proposal::Model> {
+    if !matches!(governor.kind, GovernorKind::OpenZeppelin) {
+        return Ok(proposal);
+    }
+
+    let quorum = batch_governor_quorums(
+        governor.network,
+        governor.address,
+        &[parse_proposal_id(&proposal)?],
+    )
+    .await
+    .pop()
+    .context("Missing quorum result")??;
+    let quorum = wei_to_tokens(quorum);
+    if amounts_match(proposal.quorum, quorum) {
+        return Ok(proposal);
+    }
+
+    debug!(
+        proposal_id = proposal.external_id,
+        old_quorum = proposal.quorum,
+        new_quorum = quorum,
+        "Updating proposal quorum"
+    );
+    let db = DB.get().context("DB not initialized")?;
+    let mut active_model: proposal::ActiveModel = proposal.into();
+    active_model.quorum = Set(quorum);
+    active_model
+        .update(db)
+        .await
+        .context("Failed to update proposal quorum")
+}
+
+/// Re-estimate the start and end times of a proposal from its blocks.
+async fn refresh_times(proposal: proposal::Model) -> Result<proposal::Model> {
+    let (Some(block_start_at), Some(block_end_at)) =
+        (proposal.block_start_at, proposal.block_end_at)
+    else {
+        return Ok(proposal);
+    };
+
+    let start_at = estimate_timestamp(TRANSITION_BLOCKS_NETWORK, block_start_at as u64)
+        .await
+        .context("Failed to estimate start_at timestamp")?;
+    let end_at = estimate_timestamp(TRANSITION_BLOCKS_NETWORK, block_end_at as u64)
+        .await
+        .context("Failed to estimate end_at timestamp")?;
+    if start_at == proposal.start_at && end_at == proposal.end_at {
+        return Ok(proposal);
+    }
+
+    debug!(
+        proposal_id = proposal.external_id,
+        old_start_at = ?proposal.start_at,
+        new_start_at = ?start_at,
+        old_end_at = ?proposal.end_at,
+        new_end_at = ?end_at,
+        "Updating proposal times"
+    );
+    let db = DB.get().context("DB not initialized")?;
+    let mut active_model: proposal::ActiveModel = proposal.into();
+    active_model.start_at = Set(start_at);
+    active_model.end_at = Set(end_at);
+    active_model
+        .update(db)
+        .await
+        .context("Failed to update proposal times")
+}
+
+/// What applying a due transition led to.
+enum Outcome {
+    /// The proposal changed state and may have another transition due.
+    Transitioned(Box<proposal::Model>),
+    /// The transition isn't due yet after all; check again at this time.
+    Rescheduled(NaiveDateTime),
+    /// Nothing to wait for until the next sweep.
+    Idle,
+}
+
+/// Wakes proposals exactly when their next transition is expected and
+/// applies it. ETAs are only known for proposals the last sweep saw queued.
+#[derive(Default)]
+pub struct TransitionScheduler {
+    etas: HashMap<Uuid, NaiveDateTime>,
+}
+
+impl TransitionScheduler {
+    /// Apply every transition that is due and return when the next one is.
+    #[instrument(name = "proposal_transitions_apply_due_transitions", skip_all)]
+    pub async fn apply_due_transitions(&mut self) -> Result<Option<NaiveDateTime>> {
+        let now = Utc::now().naive_utc();
+        let mut head_block = None;
+        let mut next_due: Option<NaiveDateTime> = None;
+
+        for governor in SCHEDULED_GOVERNORS {
+            let governor_id = match governor_id(governor) {
+                Ok(id) => id,
+                Err(e) => {
+                    warn!(governor = governor.governor_type, error = %e, "Skipping governor");
+                    continue;
+                }
+            };
+
+            let proposals = proposals_in_states(
+                governor_id,
+                vec![
+                    ProposalState::Pending,
+                    ProposalState::Active,
+                    ProposalState::Queued,
+                ],
+            )
+            .await?;
+
+            for proposal in proposals {
+                let proposal_id = proposal.external_id.clone();
+                let due = match self.advance(governor, proposal, now, &mut head_block).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        error!(governor = governor.governor_type, proposal_id, error = %e, "Failed to apply proposal transition");
+                        Some(now + Duration::seconds(RECHECK_SECONDS))
+                    }
+                };
+                if let Some(due) = due {
+                    next_due = Some(next_due.map_or(due, |next| next.min(due)));
+                }
+            }
+        }
+
+        Ok(next_due)
+    }
+
+    /// Apply the due transitions of a proposal, catching up on several if it
+    /// fell behind, and return when its next one is.
+    async fn advance(
+        &mut self,
+        governor: &ScheduledGovernor,
+        mut proposal: proposal::Model,
+        now: NaiveDateTime,
+        head_block: &mut Option<u64>,
+    ) -> Result<Option<NaiveDateTime>> {
+        while let Some((transition, due)) =
+            next_transition(&proposal, self.etas.get(&proposal.id).copied())
+        {
+            if due > now {
+                return Ok(Some(due));
+            }
+            match self
+                .apply(governor, proposal, transition, now, head_block)
+                .await?
+            {
+                Outcome::Transitioned(updated) => proposal = *updated,
+                Outcome::Rescheduled(due) => return Ok(Some(due)),
+                Outcome::Idle => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
+
+    #[instrument(name = "proposal_transitions_apply", skip_all, fields(governor = governor.governor_type, proposal_id = proposal.external_id, transition = ?transition))]
+    async fn apply(
+        &mut self,
+        governor: &ScheduledGovernor,
+        proposal: proposal::Model,
+        transition: Transition,
+        now: NaiveDateTime,
+        head_block: &mut Option<u64>,
+    ) -> Result<Outcome> {
+        let recheck_at = now + Duration::seconds(RECHECK_SECONDS);
+
+        let block = match transition {
+            Transition::Start => proposal.block_start_at,
+            Transition::End => proposal.block_end_at,
+            Transition::EtaPassed => None,
+        };
+        if let Some(block) = block {
+            let head = match *head_block {
+                Some(head) => head,
+                None => *head_block.insert(current_block(TRANSITION_BLOCKS_NETWORK).await?),
+            };
+            // Voting is open from the block after the start block through
+            // the end block
+            if head <= block as u64 {
+                let proposal = refresh_times(proposal).await?;
+                let due = match transition {
+                    Transition::Start => proposal.start_at,
+                    _ => proposal.end_at,
+                };
+                debug!(head_block = head, block, due = ?due, "Transition block not reached yet");
+                return Ok(Outcome::Rescheduled(due.max(recheck_at)));
+            }
+        }
+
+        let (proposal, to) = match (transition, &governor.final_state) {
+            (Transition::Start, _) => (proposal, ProposalState::Active),
+            (Transition::End, FinalState::Tally) => {
+                let proposal = refresh_times(proposal).await?;
+                if !votes_settled(governor, &proposal).await? {
+                    debug!("Votes not fully indexed yet, postponing the tally");
+                    return Ok(Outcome::Rescheduled(recheck_at));
+                }
+                let proposal = refresh_quorum(governor, proposal).await?;
+                let db = DB.get().context("DB not initialized")?;
+                let votes = vote::Entity::find()
+                    .filter(vote::Column::ProposalId.eq(proposal.id))
+                    .all(db)
+                    .await
+                    .context("Failed to fetch votes for proposal")?;
+                let to = final_state_from_tally(&proposal, &votes);
+                (proposal, to)
+            }
+            (Transition::End, FinalState::Onchain) | (Transition::EtaPassed, _) => {
+                let state = read_state(governor, parse_proposal_id(&proposal)?).await?;
+                if state == proposal.proposal_state {
+                    if transition == Transition::EtaPassed {
+                        // Still waiting for execution, which the indexer sees
+                        self.etas.remove(&proposal.id);
+                        return Ok(Outcome::Idle);
+                    }
+                    return Ok(Outcome::Rescheduled(recheck_at));
+                }
+                (proposal, state)
+            }
+        };
+
+        let reason = match transition {
+            Transition::Start => "voting started",
+            Transition::End => "voting ended",
+            Transition::EtaPassed => "timelock eta passed",
+        };
+        let Some(updated) = transition_proposal_state(
+            &proposal,
+            to,
+            &StateChange::new(TransitionSource::Poller),
+            reason,
+        )
+        .await?
+        else {
+            return Ok(Outcome::Idle);
+        };
+        if transition == Transition::Start {
+            // The snapshot block has passed, so the quorum is final
+            return Ok(Outcome::Transitioned(Box::new(
+                refresh_quorum(governor, updated).await?,
+            )));
+        }
+        Ok(Outcome::Transitioned(Box::new(updated)))
+    }
+
+    /// Infrequent safety net: re-estimate the times of open proposals,
+    /// reconcile proposals past voting with the chain, since queueing,
+    /// cancellation and expiry aren't indexed, correct defeated ones the
+    /// governor disagrees with, and refresh the ETAs of queued ones.
+    #[instrument(name = "proposal_transitions_sweep", skip_all)]
+    pub async fn sweep(&mut self) -> Result<()> {
+        let mut etas = HashMap::new();
+
+        for governor in SCHEDULED_GOVERNORS {
+            let governor_id = match governor_id(governor) {
+                Ok(id) => id,
+                Err(e) => {
+                    warn!(governor = governor.governor_type, error = %e, "Skipping governor");
+                    continue;
+                }
+            };
+
+            for proposal in proposals_in_states(
+                governor_id,
+                vec![ProposalState::Pending, ProposalState::Active],
+            )
+            .await?
+            {
+                let proposal_id = proposal.external_id.clone();
+                if let Err(e) = refresh_times(proposal).await {
+                    error!(governor = governor.governor_type, proposal_id, error = %e, "Failed to refresh proposal times");
+                }
+            }
+
+            let mut proposals = Vec::new();
+            let mut proposal_ids = Vec::new();
+            for proposal in proposals_in_states(
+                governor_id,
+                vec![
+                    ProposalState::Succeeded,
+                    ProposalState::Defeated,
+                    ProposalState::Queued,
+                    ProposalState::Unknown,
+                ],
+            )
+            .await?
+            {
+                match parse_proposal_id(&proposal) {
+                    Ok(id) => {
+                        proposal_ids.push(id);
+                        proposals.push(proposal);
+                    }
+                    Err(e) => error!(error = %e, "Skipping proposal"),
+                }
+            }
+            if proposals.is_empty() {
+                continue;
+            }
+
+            let states =
+                batch_governor_states(governor.network, governor.address, &proposal_ids).await;
+            let mut queued = HashSet::new();
+            for ((proposal, id), state) in proposals.iter().zip(&proposal_ids).zip(states) {
+                let state = match state {
+                    Ok(state) => proposal_state_from_contract(state),
+                    Err(e) => {
+                        warn!(proposal_id = proposal.external_id, error = %e, "Failed to fetch on-chain state");
+                        continue;
+                    }
+                };
+                if state == ProposalState::Queued {
+                    queued.insert(*id);
+                }
+                let change = StateChange::new(TransitionSource::Poller);
+                let reconciled = if proposal.proposal_state == ProposalState::Defeated {
+                    // A tally computed before all votes were stored can be
+                    // wrong, and the governor has the final say
+                    if matches!(
+                        state,
+                        ProposalState::Pending | ProposalState::Active | ProposalState::Unknown
+                    ) {
+                        continue;
+                    }
+                    correct_proposal_state(proposal, state, &change, "safety sweep").await
+                } else {
+                    transition_proposal_state(proposal, state, &change, "safety sweep").await
+                };
+                if let Err(e) = reconciled {
+                    error!(proposal_id = proposal.external_id, error = %e, "Failed to reconcile proposal state");
+                }
+            }
+
+            let queued: Vec<_> = proposals
+                .iter()
+                .zip(&proposal_ids)
+                .filter(|(_, id)| queued.contains(*id))
+                .collect();
+            let queued_ids: Vec<U256> = queued.iter().map(|(_, id)| **id).collect();
+            let results = match governor.kind {
+                GovernorKind::OpenZeppelin => {
+                    batch_governor_etas(governor.network, governor.address, &queued_ids).await
+                }
+                GovernorKind::Bravo => {
+                    batch_bravo_etas(governor.network, governor.address, &queued_ids).await
+                }
+            };
+            for ((proposal, _), eta) in queued.iter().zip(results) {
+                match eta {
+                    Ok(eta) => {
+                        if let Some(eta) = eta_from_timestamp(eta) {
+                            etas.insert(proposal.id, eta);
+                        }
+                    }
+                    Err(e) => {
+                        warn!(proposal_id = proposal.external_id, error = %e, "Failed to fetch proposal ETA");
+                    }
+                }
+            }
+        }
+
+        info!(
+            queued_with_eta = etas.len(),
+            "Proposal state safety sweep completed"
+        );
+        self.etas = etas;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extensions::test_fixtures::{self, at};
+    use sea_orm::prelude::Json;
+    use serde_json::json;
+
+    fn proposal(state: ProposalState, quorum: f64, metadata: Option<Json>) -> proposal::Model {
+        proposal::Model {
+            quorum,
+            proposal_state: state,
+            metadata,
+            block_start_at: Some(100),
+            block_end_at: Some(200),
+            ..test_fixtures::proposal()
+        }
+    }
+
+    fn vote(choice: u64, voting_power: f64) -> vote::Model {
+        vote::Model {
+            id: Uuid::nil(),
+            voter_address: String::new(),
+            choice: json!(choice),
+            voting_power,
+            reason: None,
+            created_at: at(1),
+            block_created_at: None,
+            txid: None,
+            proposal_external_id: "1".to_string(),
+            proposal_id: Uuid::nil(),
+            dao_id: Uuid::nil(),
+            governor_id: Uuid::nil(),
+        }
+    }
+
+    #[test]
+    fn test_next_transition() {
+        assert_eq!(
+            next_transition(&proposal(ProposalState::Pending, 0.0, None), None),
+            Some((Transition::Start, at(1)))
+        );
+        assert_eq!(
+            next_transition(&proposal(ProposalState::Active, 0.0, None), Some(at(5))),
+            Some((Transition::End, at(2)))
+        );
+        assert_eq!(
+            next_transition(&proposal(ProposalState::Queued, 0.0, None), Some(at(5))),
+            Some((Transition::EtaPassed, at(5)))
+        );
+        assert_eq!(
+            next_transition(&proposal(ProposalState::Queued, 0.0, None), None),
+            None
+        );
+        assert_eq!(
+            next_transition(&proposal(ProposalState::Executed, 0.0, None), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_valid_transition() {
+        use ProposalState::*;
+
+        assert!(is_valid_transition(&Pending, &Active));
+        assert!(is_valid_transition(&Active, &Defeated));
+        assert!(is_valid_transition(&Succeeded, &Queued));
+        assert!(is_valid_transition(&Queued, &Executed));
+        assert!(is_valid_transition(&Unknown, &Succeeded));
+        assert!(!is_valid_transition(&Active, &Pending));
+        assert!(!is_valid_transition(&Executed, &Queued));
+        assert!(!is_valid_transition(&Defeated, &Succeeded));
+        assert!(!is_valid_transition(&Pending, &Succeeded));
+    }
+
+    #[test]
+    fn test_is_allowed_transition() {
+        use ProposalState::*;
+        use TransitionSource::*;
+
+        // A late ProposalCreated replay can't reopen an executed proposal
+        assert!(!is_allowed_transition(Event, &Executed, &Active));
+        assert!(is_allowed_transition(Event, &Queued, &Executed));
+        assert!(is_allowed_transition(Poller, &Pending, &Active));
+
+        // Snapshot scores become final after closing, but never the reverse
+        assert!(is_allowed_transition(Snapshot, &Active, &Defeated));
+        assert!(is_allowed_transition(Snapshot, &Defeated, &Executed));
+        assert!(is_allowed_transition(Snapshot, &Hidden, &Active));
+        assert!(!is_allowed_transition(Snapshot, &Executed, &Defeated));
+        assert!(!is_allowed_transition(Snapshot, &Executed, &Active));
+
+        assert!(is_allowed_transition(Manual, &Executed, &Active));
+    }
+
+    #[test]
+    fn test_final_state_from_tally() {
+        let votes = [vote(0, 60.0), vote(1, 40.0), vote(2, 30.0)];
+
+        // For and Against count towards the quorum by default
+        let proposal_default = proposal(ProposalState::Active, 100.0, None);
+        assert_eq!(
+            final_state_from_tally(&proposal_default, &votes),
+            ProposalState::Succeeded
+        );
+
+        // For and Abstain only, like the Arbitrum governors
+        let for_and_abstain = proposal(
+            ProposalState::Active,
+            100.0,
+            Some(json!({ "quorum_choices": [0, 2] })),
+        );
+        assert_eq!(
+            final_state_from_tally(&for_and_abstain, &votes),
+            ProposalState::Defeated
+        );
+
+        let against_wins = [vote(0, 60.0), vote(1, 60.0)];
+        assert_eq!(
+            final_state_from_tally(&proposal(ProposalState::Active, 1.0, None), &against_wins),
+            ProposalState::Defeated
+        );
+    }
+}
@@ -0,0 +1,672 @@
+use crate::{
+    extensions::{
+        db_extension::{DAO_SLUG_GOVERNOR_TYPE_ID_MAP, DB},
+        governor_backfill::{
+            StoredVoteTotals, amounts_match, rindexer_last_synced_block, wei_to_tokens,
+        },
+        proposal_transitions::{
+            StateChange, TransitionSource, correct_proposal_state, proposal_state_from_contract,
+        },
+    },
+    rindexer_lib::indexers::rindexer::contracts::{
+        ARBITRUM_CORE_GOVERNOR_ADDRESS, ARBITRUM_TREASURY_GOVERNOR_ADDRESS, GovernorVoteTotals,
+        UNI_GOVERNOR_ADDRESS, batch_bravo_vote_totals, batch_governor_quorums,
+        batch_governor_states, batch_governor_vote_totals, bravo_quorum_votes,
+    },
+};
+use alloy::primitives::{Address, U256};
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use proposalsapp_db::models::{
+    proposal, proposal_tally_audit, sea_orm_active_enums::ProposalState,
+};
+use sea_orm::{
+    ActiveEnum, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, Set, Statement, prelude::Uuid,
+};
+use std::collections::HashMap;
+use tracing::{error, info, instrument, warn};
+
+/// Ended proposals keep being audited for this long, to catch late state
+/// changes (queue, execution) and votes indexed after the end.
+const RECENTLY_ENDED_DAYS: i64 = 14;
+
+/// A state mismatch this close to a proposal's start or end is a transition
+/// the indexer hasn't applied yet rather than an error.
+const TRANSITION_GRACE_MINUTES: i64 = 60;
+
+/// Relative vote total difference from which a discrepancy is critical.
+const CRITICAL_VOTES_DIFFERENCE: f64 = 0.01;
+
+/// How a governor exposes its tallies.
+enum GovernorKind {
+    /// `proposalVotes` and `quorum(proposalSnapshot)`.
+    OpenZeppelin,
+    /// `proposals` and a fixed `quorumVotes`.
+    Bravo,
+}
+
+struct AuditedGovernor {
+    dao_slug: &'static str,
+    governor_type: &'static str,
+    /// Snake case contract name used by rindexer's sync tables.
+    sync_table: &'static str,
+    network: &'static str,
+    address: Address,
+    kind: GovernorKind,
+}
+
+// SC nominations don't count votes as for/against/abstain, so only the
+// proposal governors are audited.
+const AUDITED_GOVERNORS: &[AuditedGovernor] = &[
+    AuditedGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_CORE",
+        sync_table: "arbitrum_core_governor",
+        network: "arbitrum",
+        address: ARBITRUM_CORE_GOVERNOR_ADDRESS,
+        kind: GovernorKind::OpenZeppelin,
+    },
+    AuditedGovernor {
+        dao_slug: "arbitrum",
+        governor_type: "ARBITRUM_TREASURY",
+        sync_table: "arbitrum_treasury_governor",
+        network: "arbitrum",
+        address: ARBITRUM_TREASURY_GOVERNOR_ADDRESS,
+        kind: GovernorKind::OpenZeppelin,
+    },
+    AuditedGovernor {
+        dao_slug: "uniswap",
+        governor_type: "UNISWAP_GOVERNOR",
+        sync_table: "uni_governor",
+        network: "ethereum",
+        address: UNI_GOVERNOR_ADDRESS,
+        kind: GovernorKind::Bravo,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// Outcome of comparing one field of a proposal with the chain. `severity`
+/// is `None` when they match.
+#[derive(Debug, Clone, PartialEq)]
+struct FieldCheck {
+    field: &'static str,
+    stored_value: String,
+    onchain_value: String,
+    severity: Option<Severity>,
+}
+
+/// What the chain reports for a proposal. Reads that failed are `None` and
+/// their fields aren't checked.
+#[derive(Debug, Clone, Default)]
+struct OnchainTally {
+    votes: Option<GovernorVoteTotals>,
+    quorum: Option<U256>,
+    state: Option<ProposalState>,
+}
+
+/// Discrepancy counts of an audit. The app exports no metrics, so these are
+/// only reported as fields of the audit's completion log.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TallyAuditReport {
+    pub audited_proposals: usize,
+    pub info: usize,
+    pub warning: usize,
+    pub critical: usize,
+    pub corrected: usize,
+}
+
+impl TallyAuditReport {
+    fn add(&mut self, other: TallyAuditReport) {
+        self.audited_proposals += other.audited_proposals;
+        self.info += other.info;
+        self.warning += other.warning;
+        self.critical += other.critical;
+        self.corrected += other.corrected;
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ProposalVoteSums {
+    id: Uuid,
+    for_votes: f64,
+    against_votes: f64,
+    abstain_votes: f64,
+}
+
+/// Whether state and quorum discrepancies are fixed from the chain, set with
+/// `TALLY_AUDIT_AUTO_CORRECT=true`.
+fn auto_correct_enabled() -> bool {
+    std::env::var("TALLY_AUDIT_AUTO_CORRECT").is_ok_and(|value| value == "true" || value == "1")
+}
+
+fn vote_severity(stored: f64, onchain: f64) -> Option<Severity> {
+    if amounts_match(stored, onchain) {
+        None
+    } else if (stored - onchain).abs() >= CRITICAL_VOTES_DIFFERENCE * onchain.abs().max(1.0) {
+        Some(Severity::Critical)
+    } else {
+        Some(Severity::Warning)
+    }
+}
+
+/// Compare a stored proposal and its stored vote totals with the chain.
+fn check_tally(
+    proposal: &proposal::Model,
+    stored_votes: StoredVoteTotals,
+    onchain: &OnchainTally,
+    now: NaiveDateTime,
+) -> Vec<FieldCheck> {
+    let mut checks = Vec::new();
+
+    if let Some(votes) = onchain.votes {
+        for (field, stored, onchain) in [
+            ("for_votes", stored_votes.for_votes, votes.for_votes),
+            (
+                "against_votes",
+                stored_votes.against_votes,
+                votes.against_votes,
+            ),
+            (
+                "abstain_votes",
+                stored_votes.abstain_votes,
+                votes.abstain_votes,
+            ),
+        ] {
+            let onchain_tokens = wei_to_tokens(onchain);
+            checks.push(FieldCheck {
+                field,
+                stored_value: stored.to_string(),
+                onchain_value: onchain_tokens.to_string(),
+                severity: vote_severity(stored, onchain_tokens),
+            });
+        }
+    }
+
+    if let Some(quorum) = onchain.quorum {
+        let onchain_quorum = wei_to_tokens(quorum);
+        checks.push(FieldCheck {
+            field: "quorum",
+            stored_value: proposal.quorum.to_string(),
+            onchain_value: onchain_quorum.to_string(),
+            severity: (!amounts_match(proposal.quorum, onchain_quorum))
+                .then_some(Severity::Warning),
+        });
+    }
+
+    if let Some(state) = &onchain.state {
+        let grace = Duration::minutes(TRANSITION_GRACE_MINUTES);
+        let transitioning =
+            (now - proposal.start_at).abs() < grace || (now - proposal.end_at).abs() < grace;
+        checks.push(FieldCheck {
+            field: "state",
+            stored_value: proposal.proposal_state.to_value(),
+            onchain_value: state.to_value(),
+            severity: (proposal.proposal_state != *state).then_some(if transitioning {
+                Severity::Info
+            } else {
+                Severity::Critical
+            }),
+        });
+    }
+
+    checks
+}
+
+fn governor_id(governor: &AuditedGovernor) -> Result<Uuid> {
+    DAO_SLUG_GOVERNOR_TYPE_ID_MAP
+        .get()
+        .context("DAO_SLUG_GOVERNOR_TYPE_ID_MAP not initialized")?
+        .lock()
+        .unwrap()
+        .get(governor.dao_slug)
+        .and_then(|governors| governors.get(governor.governor_type))
+        .copied()
+        .with_context(|| {
+            format!(
+                "Governor {} not found for DAO '{}'",
+                governor.governor_type, governor.dao_slug
+            )
+        })
+}
+
+/// Active, pending and recently ended proposals of a governor.
+async fn proposals_to_audit(governor_id: Uuid) -> Result<Vec<proposal::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+    let recently_ended = Utc::now().naive_utc() - Duration::days(RECENTLY_ENDED_DAYS);
+
+    proposal::Entity::find()
+        .filter(proposal::Column::GovernorId.eq(governor_id))
+        .filter(
+            proposal::Column::ProposalState
+                .is_in([ProposalState::Pending, ProposalState::Active])
+                .or(proposal::Column::EndAt.gt(recently_ended)),
+        )
+        .filter(proposal::Column::ProposalState.ne(ProposalState::Hidden))
+        .all(db)
+        .await
+        .context("Failed to fetch proposals to audit")
+}
+
+async fn stored_vote_totals(governor_id: Uuid) -> Result<HashMap<Uuid, StoredVoteTotals>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let sums = ProposalVoteSums::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        SELECT
+            proposal_id AS id,
+            COALESCE(SUM(voting_power) FILTER (WHERE choice = '0'::jsonb), 0.0) AS for_votes,
+            COALESCE(SUM(voting_power) FILTER (WHERE choice = '1'::jsonb), 0.0) AS against_votes,
+            COALESCE(SUM(voting_power) FILTER (WHERE choice = '2'::jsonb), 0.0) AS abstain_votes
+        FROM vote
+        WHERE governor_id = $1
+        GROUP BY proposal_id
+        "#,
+        vec![governor_id.into()],
+    ))
+    .all(db)
+    .await
+    .context("Failed to sum stored votes per proposal")?;
+
+    Ok(sums
+        .into_iter()
+        .map(|sums| {
+            (
+                sums.id,
+                StoredVoteTotals {
+                    for_votes: sums.for_votes,
+                    against_votes: sums.against_votes,
+                    abstain_votes: sums.abstain_votes,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Read the tallies of every proposal in a few multicall round trips. Votes
+/// are read at the last block whose votes rindexer stored, state and quorum
+/// at the latest block.
+async fn fetch_onchain_tallies(
+    governor: &AuditedGovernor,
+    proposal_ids: &[U256],
+) -> Result<Vec<OnchainTally>> {
+    let vote_block =
+        rindexer_last_synced_block(governor.sync_table, "vote_cast", governor.network).await?;
+
+    let votes = match (vote_block, &governor.kind) {
+        (None, _) => proposal_ids.iter().map(|_| None).collect(),
+        (Some(block), GovernorKind::OpenZeppelin) => batch_governor_vote_totals(
+            governor.network,
+            governor.address,
+            proposal_ids,
+            Some(block),
+        )
+        .await
+        .into_iter()
+        .map(Result::ok)
+        .collect(),
+        (Some(block), GovernorKind::Bravo) => batch_bravo_vote_totals(
+            governor.network,
+            governor.address,
+            proposal_ids,
+            Some(block),
+        )
+        .await
+        .into_iter()
+        .map(Result::ok)
+        .collect::<Vec<_>>(),
+    };
+
+    let quorums: Vec<Option<U256>> = match governor.kind {
+        GovernorKind::OpenZeppelin => {
+            batch_governor_quorums(governor.network, governor.address, proposal_ids)
+                .await
+                .into_iter()
+                .map(Result::ok)
+                .collect()
+        }
+        GovernorKind::Bravo => {
+            let quorum = bravo_quorum_votes(governor.network, governor.address)
+                .await
+                .inspect_err(|e| warn!(error = %e, "Failed to read quorumVotes"))
+                .ok();
+            proposal_ids.iter().map(|_| quorum).collect()
+        }
+    };
+
+    let states = batch_governor_states(governor.network, governor.address, proposal_ids).await;
+
+    Ok(votes
+        .into_iter()
+        .zip(quorums)
+        .zip(states)
+        .map(|((votes, quorum), state)| OnchainTally {
+            votes,
+            quorum,
+            state: state.ok().map(proposal_state_from_contract),
+        })
+        .collect())
+}
+
+/// Apply the on-chain state or quorum to a proposal. Returns whether the
+/// field can be corrected.
+async fn correct_field(
+    proposal: &proposal::Model,
+    onchain: &OnchainTally,
+    field: &str,
+) -> Result<bool> {
+    match (field, onchain.state.clone(), onchain.quorum) {
+        ("state", Some(state), _) => {
+            // The chain is authoritative, so this may move the state backwards
+            correct_proposal_state(
+                proposal,
+                state,
+                &StateChange::new(TransitionSource::Poller),
+                "tally audit",
+            )
+            .await?;
+        }
+        ("quorum", _, Some(quorum)) => {
+            let db = DB.get().context("DB not initialized")?;
+            let mut active_model: proposal::ActiveModel = proposal.clone().into();
+            active_model.quorum = Set(wei_to_tokens(quorum));
+            proposal::Entity::update(active_model)
+                .exec(db)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to correct quorum of proposal {}",
+                        proposal.external_id
+                    )
+                })?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Open or refresh the discrepancy of a field. A corrected discrepancy is
+/// closed right away.
+async fn record_discrepancy(
+    proposal: &proposal::Model,
+    check: &FieldCheck,
+    severity: Severity,
+    corrected: bool,
+) -> Result<()> {
+    let db = DB.get().context("DB not initialized")?;
+
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        INSERT INTO proposal_tally_audit
+            (proposal_id, governor_id, field, severity, stored_value, onchain_value, corrected)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (proposal_id, field) WHERE resolved_at IS NULL DO UPDATE SET
+            severity = EXCLUDED.severity,
+            stored_value = EXCLUDED.stored_value,
+            onchain_value = EXCLUDED.onchain_value,
+            corrected = EXCLUDED.corrected,
+            last_seen_at = NOW()
+        "#,
+        vec![
+            proposal.id.into(),
+            proposal.governor_id.into(),
+            check.field.into(),
+            severity.as_str().into(),
+            check.stored_value.clone().into(),
+            check.onchain_value.clone().into(),
+            corrected.into(),
+        ],
+    ))
+    .await
+    .context("Failed to record tally discrepancy")?;
+
+    if corrected {
+        resolve_discrepancies(proposal.id, vec![check.field]).await?;
+    }
+    Ok(())
+}
+
+/// Close the open discrepancies of fields that match the chain again or
+/// were corrected.
+async fn resolve_discrepancies(proposal_id: Uuid, fields: Vec<&'static str>) -> Result<()> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let db = DB.get().context("DB not initialized")?;
+
+    proposal_tally_audit::Entity::update_many()
+        .col_expr(
+            proposal_tally_audit::Column::ResolvedAt,
+            Utc::now().naive_utc().into(),
+        )
+        .filter(proposal_tally_audit::Column::ProposalId.eq(proposal_id))
+        .filter(proposal_tally_audit::Column::Field.is_in(fields))
+        .filter(proposal_tally_audit::Column::ResolvedAt.is_null())
+        .exec(db)
+        .await
+        .context("Failed to resolve tally discrepancies")?;
+    Ok(())
+}
+
+#[instrument(name = "tally_audit_governor", skip_all, fields(governor = governor.governor_type))]
+async fn audit_governor(
+    governor: &AuditedGovernor,
+    auto_correct: bool,
+) -> Result<TallyAuditReport> {
+    let governor_id = governor_id(governor)?;
+    let mut report = TallyAuditReport::default();
+
+    let mut proposals = Vec::new();
+    let mut proposal_ids = Vec::new();
+    for proposal in proposals_to_audit(governor_id).await? {
+        match proposal.external_id.parse::<U256>() {
+            Ok(id) => {
+                proposal_ids.push(id);
+                proposals.push(proposal);
+            }
+            Err(e) => {
+                error!(proposal_id = %proposal.external_id, error = %e, "Failed to parse proposal ID");
+            }
+        }
+    }
+    if proposals.is_empty() {
+        return Ok(report);
+    }
+
+    let stored_votes = stored_vote_totals(governor_id).await?;
+    let onchain_tallies = fetch_onchain_tallies(governor, &proposal_ids).await?;
+    let now = Utc::now().naive_utc();
+
+    for (proposal, onchain) in proposals.iter().zip(&onchain_tallies) {
+        let stored = stored_votes
+            .get(&proposal.id)
+            .copied()
+            .unwrap_or(StoredVoteTotals {
+                for_votes: 0.0,
+                against_votes: 0.0,
+                abstain_votes: 0.0,
+            });
+
+        let mut matching = Vec::new();
+        for check in check_tally(proposal, stored, onchain, now) {
+            let Some(severity) = check.severity else {
+                matching.push(check.field);
+                continue;
+            };
+
+            let corrected = auto_correct && correct_field(proposal, onchain, check.field).await?;
+            record_discrepancy(proposal, &check, severity, corrected).await?;
+
+            if corrected {
+                report.corrected += 1;
+            }
+            match severity {
+                Severity::Info => report.info += 1,
+                Severity::Warning => report.warning += 1,
+                Severity::Critical => report.critical += 1,
+            }
+            warn!(
+                proposal_id = %proposal.external_id,
+                field = check.field,
+                severity = severity.as_str(),
+                stored_value = %check.stored_value,
+                onchain_value = %check.onchain_value,
+                corrected,
+                "Tally discrepancy"
+            );
+        }
+        resolve_discrepancies(proposal.id, matching).await?;
+        report.audited_proposals += 1;
+    }
+
+    info!(
+        governor = governor.governor_type,
+        audited_proposals = report.audited_proposals,
+        tally_audit_info = report.info,
+        tally_audit_warning = report.warning,
+        tally_audit_critical = report.critical,
+        tally_audit_corrected = report.corrected,
+        "Tally audit completed"
+    );
+    Ok(report)
+}
+
+/// Audit the stored tallies, quorums and states of every on-chain governor,
+/// or only those of one DAO, against the chain.
+#[instrument(name = "tally_audit", skip_all, fields(dao_slug = ?dao_slug))]
+pub async fn audit_tallies(dao_slug: Option<&str>) -> Result<TallyAuditReport> {
+    let auto_correct = auto_correct_enabled();
+    let mut report = TallyAuditReport::default();
+
+    for governor in AUDITED_GOVERNORS
+        .iter()
+        .filter(|governor| dao_slug.is_none_or(|slug| governor.dao_slug == slug))
+    {
+        report.add(audit_governor(governor, auto_correct).await?);
+    }
+    Ok(report)
+}
+
+/// Open discrepancies, most severe first.
+pub async fn open_discrepancies() -> Result<Vec<proposal_tally_audit::Model>> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let mut discrepancies = proposal_tally_audit::Entity::find()
+        .filter(proposal_tally_audit::Column::ResolvedAt.is_null())
+        .order_by_asc(proposal_tally_audit::Column::FirstSeenAt)
+        .all(db)
+        .await
+        .context("Failed to fetch open tally discrepancies")?;
+    discrepancies.sort_by_key(|discrepancy| match discrepancy.severity.as_str() {
+        "critical" => 0,
+        "warning" => 1,
+        _ => 2,
+    });
+    Ok(discrepancies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extensions::test_fixtures;
+    use chrono::NaiveDate;
+
+    fn tokens(amount: u64) -> U256 {
+        U256::from(amount) * U256::from(10u64).pow(U256::from(18u64))
+    }
+
+    fn proposal(state: ProposalState, quorum: f64, now: NaiveDateTime) -> proposal::Model {
+        proposal::Model {
+            quorum,
+            proposal_state: state,
+            created_at: now - Duration::days(10),
+            start_at: now - Duration::days(9),
+            end_at: now - Duration::days(2),
+            ..test_fixtures::proposal()
+        }
+    }
+
+    fn severities(checks: &[FieldCheck]) -> Vec<(&'static str, Option<Severity>)> {
+        checks
+            .iter()
+            .map(|check| (check.field, check.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_tally() {
+        let now = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let stored = StoredVoteTotals {
+            for_votes: 1_000.0,
+            against_votes: 995.0,
+            abstain_votes: 0.0,
+        };
+        let onchain = OnchainTally {
+            votes: Some(GovernorVoteTotals {
+                for_votes: tokens(1_000),
+                against_votes: tokens(1_000),
+                abstain_votes: tokens(50),
+            }),
+            quorum: Some(tokens(4_000_000)),
+            state: Some(ProposalState::Succeeded),
+        };
+
+        let checks = check_tally(
+            &proposal(ProposalState::Succeeded, 4_000_000.0, now),
+            stored,
+            &onchain,
+            now,
+        );
+        assert_eq!(
+            severities(&checks),
+            vec![
+                ("for_votes", None),
+                ("against_votes", Some(Severity::Warning)),
+                ("abstain_votes", Some(Severity::Critical)),
+                ("quorum", None),
+                ("state", None),
+            ]
+        );
+
+        // Stored state lags behind the chain long after the proposal ended
+        let stale = proposal(ProposalState::Active, 40_000_000.0, now);
+        let checks = check_tally(&stale, stored, &onchain, now);
+        assert_eq!(checks[3].severity, Some(Severity::Warning));
+        assert_eq!(checks[4].severity, Some(Severity::Critical));
+        assert_eq!(checks[4].stored_value, "ACTIVE");
+        assert_eq!(checks[4].onchain_value, "SUCCEEDED");
+
+        // Right after the end the transition may not be applied yet
+        let just_ended = proposal::Model {
+            end_at: now - Duration::minutes(5),
+            ..stale
+        };
+        let checks = check_tally(&just_ended, stored, &onchain, now);
+        assert_eq!(checks[4].severity, Some(Severity::Info));
+
+        // Failed reads aren't checked
+        let checks = check_tally(&just_ended, stored, &OnchainTally::default(), now);
+        assert!(checks.is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+//! Models shared by the tests of the extensions.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use proposalsapp_db::models::{proposal, sea_orm_active_enums::ProposalState};
+use sea_orm::prelude::Uuid;
+use serde_json::json;
+
+/// 2026-01-01 at `hour`.
+pub fn at(hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 1, 1)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+/// A pending proposal, created at [`at`]`(0)` and voted on from `at(1)` to
+/// `at(2)`. Tests override what they check with struct update syntax.
+pub fn proposal() -> proposal::Model {
+    proposal::Model {
+        id: Uuid::nil(),
+        external_id: "1".to_string(),
+        name: String::new(),
+        body: String::new(),
+        url: String::new(),
+        discussion_url: None,
+        choices: json!(["For", "Against", "Abstain"]),
+        quorum: 0.0,
+        proposal_state: ProposalState::Pending,
+        marked_spam: false,
+        created_at: at(0),
+        start_at: at(1),
+        end_at: at(2),
+        block_created_at: None,
+        txid: None,
+        metadata: None,
+        dao_id: Uuid::nil(),
+        author: None,
+        governor_id: Uuid::nil(),
+        block_start_at: None,
+        block_end_at: None,
+    }
+}
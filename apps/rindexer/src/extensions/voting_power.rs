@@ -0,0 +1,695 @@
+use crate::extensions::db_extension::{DAO_SLUG_ID_MAP, DB};
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use proposalsapp_db::models::{dao, voting_power_latest, voting_power_timeseries};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ConnectionTrait, DbBackend, EntityTrait, QuerySelect, Statement, TransactionTrait, Value,
+    prelude::Uuid,
+    sea_query::{Expr, OnConflict},
+};
+use std::collections::HashMap;
+use tracing::{debug, info, instrument, warn};
+
+/// Reduce a timeseries batch to the newest row per (voter, dao_id), ordered
+/// by (block, log_index). Rows missing any of those fields are ignored.
+pub fn newest_per_voter(
+    rows: &[voting_power_timeseries::ActiveModel],
+) -> Vec<voting_power_latest::ActiveModel> {
+    let mut newest: HashMap<(String, Uuid), &voting_power_timeseries::ActiveModel> = HashMap::new();
+
+    for row in rows {
+        let (Some(voter), Some(dao_id), Some(block), Some(log_index)) = (
+            row.voter.try_as_ref(),
+            row.dao_id.try_as_ref(),
+            row.block.try_as_ref(),
+            row.log_index.try_as_ref(),
+        ) else {
+            warn!("Skipping voting power row without voter, dao, block or log index");
+            continue;
+        };
+
+        let key = (voter.clone(), *dao_id);
+        let is_newer = newest.get(&key).is_none_or(|current| {
+            (block, log_index)
+                > (
+                    current.block.try_as_ref().unwrap(),
+                    current.log_index.try_as_ref().unwrap(),
+                )
+        });
+        if is_newer {
+            newest.insert(key, row);
+        }
+    }
+
+    newest
+        .into_values()
+        .map(|row| voting_power_latest::ActiveModel {
+            id: NotSet,
+            voter: row.voter.clone(),
+            voting_power: row.voting_power.clone(),
+            dao_id: row.dao_id.clone(),
+            timestamp: row.timestamp.clone(),
+            block: row.block.clone(),
+            txid: row.txid.clone(),
+            log_index: row.log_index.clone(),
+        })
+        .collect()
+}
+
+/// Collapse DelegateVotesChanged rows to one per (voter, dao_id, block): the
+/// newest event's voting power with the oldest event's previous voting power,
+/// so the stored row still chains onto the voter's previous row.
+pub fn merge_same_block_voting_powers(
+    rows: Vec<voting_power_timeseries::ActiveModel>,
+) -> Vec<voting_power_timeseries::ActiveModel> {
+    // (newest row, oldest log index, oldest previous voting power)
+    type Merged = (voting_power_timeseries::ActiveModel, i32, Option<f64>);
+    let mut merged: HashMap<(String, Uuid, i32), Merged> = HashMap::new();
+
+    for row in rows {
+        let key = (
+            row.voter.clone().unwrap(),
+            row.dao_id.clone().unwrap(),
+            row.block.clone().unwrap(),
+        );
+        let log_index = row.log_index.clone().unwrap();
+        let previous_voting_power = row.previous_voting_power.clone().unwrap();
+
+        match merged.get_mut(&key) {
+            None => {
+                merged.insert(key, (row, log_index, previous_voting_power));
+            }
+            Some((newest, oldest_log_index, oldest_previous)) => {
+                if log_index < *oldest_log_index {
+                    *oldest_log_index = log_index;
+                    *oldest_previous = previous_voting_power;
+                }
+                if log_index > newest.log_index.clone().unwrap() {
+                    *newest = row;
+                }
+            }
+        }
+    }
+
+    merged
+        .into_values()
+        .map(|(mut row, _, previous_voting_power)| {
+            row.previous_voting_power = Set(previous_voting_power);
+            row
+        })
+        .collect()
+}
+
+/// Upsert `voting_power_latest` from a timeseries batch. Existing rows are
+/// only replaced by events at the same or a later (block, log_index), so
+/// batches may arrive in any order. Meant to run in the same transaction as
+/// the timeseries insert.
+#[instrument(name = "voting_power_upsert_latest", skip_all, fields(row_count = rows.len()))]
+pub async fn upsert_voting_power_latest<C: ConnectionTrait>(
+    conn: &C,
+    rows: &[voting_power_timeseries::ActiveModel],
+) -> Result<()> {
+    let latest = newest_per_voter(rows);
+    if latest.is_empty() {
+        return Ok(());
+    }
+
+    let updated = voting_power_latest::Entity::insert_many(latest)
+        .on_conflict(
+            OnConflict::columns([
+                voting_power_latest::Column::Voter,
+                voting_power_latest::Column::DaoId,
+            ])
+            .update_columns([
+                voting_power_latest::Column::VotingPower,
+                voting_power_latest::Column::Timestamp,
+                voting_power_latest::Column::Block,
+                voting_power_latest::Column::LogIndex,
+                voting_power_latest::Column::Txid,
+            ])
+            .action_and_where(Expr::cust(
+                "(EXCLUDED.block, EXCLUDED.log_index) >= (voting_power_latest.block, voting_power_latest.log_index)",
+            ))
+            .to_owned(),
+        )
+        .exec_without_returning(conn)
+        .await
+        .context("Failed to upsert voting_power_latest")?;
+
+    debug!(updated_rows = updated, "Upserted voting_power_latest");
+    Ok(())
+}
+
+/// Newest timeseries row per (dao_id, voter), optionally limited to one DAO
+/// through `$1`.
+const EXPECTED_LATEST_SQL: &str = r#"
+    SELECT DISTINCT ON (dao_id, voter)
+        voter, voting_power, dao_id, timestamp, block, log_index, txid
+    FROM voting_power_timeseries
+    WHERE ($1::uuid IS NULL OR dao_id = $1)
+    ORDER BY dao_id, voter, block DESC, log_index DESC
+"#;
+
+/// Recompute `voting_power_latest` from the timeseries, for one DAO or all
+/// of them. Runs in a single transaction so readers never see a partially
+/// rebuilt table. Returns the number of rows written.
+#[instrument(name = "voting_power_rebuild_latest", skip_all, fields(dao_id = ?dao_id))]
+pub async fn rebuild_voting_power_latest(dao_id: Option<Uuid>) -> Result<u64> {
+    let db = DB.get().context("DB not initialized")?;
+    let txn = db.begin().await.context("Failed to start transaction")?;
+
+    let removed = txn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            DELETE FROM voting_power_latest l
+            WHERE ($1::uuid IS NULL OR l.dao_id = $1)
+              AND NOT EXISTS (
+                SELECT 1 FROM voting_power_timeseries t
+                WHERE t.voter = l.voter AND t.dao_id = l.dao_id
+              )
+            "#,
+            vec![dao_id.into()],
+        ))
+        .await
+        .context("Failed to remove orphaned voting_power_latest rows")?
+        .rows_affected();
+
+    let written = txn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                INSERT INTO voting_power_latest
+                    (voter, voting_power, dao_id, timestamp, block, log_index, txid)
+                SELECT voter, voting_power, dao_id, timestamp, block, log_index, txid
+                FROM ({EXPECTED_LATEST_SQL}) expected
+                ON CONFLICT (voter, dao_id) DO UPDATE SET
+                    voting_power = EXCLUDED.voting_power,
+                    timestamp = EXCLUDED.timestamp,
+                    block = EXCLUDED.block,
+                    log_index = EXCLUDED.log_index,
+                    txid = EXCLUDED.txid
+                "#
+            ),
+            vec![dao_id.into()],
+        ))
+        .await
+        .context("Failed to rebuild voting_power_latest")?
+        .rows_affected();
+
+    txn.commit().await.context("Failed to commit rebuild")?;
+
+    info!(
+        removed_rows = removed,
+        written_rows = written,
+        "Rebuilt voting_power_latest"
+    );
+    Ok(written)
+}
+
+/// Differences between `voting_power_latest` and what the timeseries says
+/// it should contain.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VotingPowerLatestReport {
+    /// Voters with timeseries rows but no latest row
+    pub missing: i64,
+    /// Latest rows without any timeseries rows
+    pub orphaned: i64,
+    /// Latest rows that don't match the newest timeseries row
+    pub stale: i64,
+}
+
+impl VotingPowerLatestReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing == 0 && self.orphaned == 0 && self.stale == 0
+    }
+}
+
+#[instrument(name = "voting_power_verify_latest", skip_all, fields(dao_id = ?dao_id))]
+pub async fn verify_voting_power_latest(dao_id: Option<Uuid>) -> Result<VotingPowerLatestReport> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                WITH expected AS ({EXPECTED_LATEST_SQL}),
+                latest AS (
+                    SELECT voter, voting_power, dao_id, block, log_index
+                    FROM voting_power_latest
+                    WHERE ($1::uuid IS NULL OR dao_id = $1)
+                )
+                SELECT
+                    COUNT(*) FILTER (WHERE l.voter IS NULL) AS missing,
+                    COUNT(*) FILTER (WHERE e.voter IS NULL) AS orphaned,
+                    COUNT(*) FILTER (
+                        WHERE e.voter IS NOT NULL AND l.voter IS NOT NULL
+                          AND (e.block, e.log_index, e.voting_power)
+                              IS DISTINCT FROM (l.block, l.log_index, l.voting_power)
+                    ) AS stale
+                FROM expected e
+                FULL OUTER JOIN latest l ON e.voter = l.voter AND e.dao_id = l.dao_id
+                "#
+            ),
+            vec![dao_id.into()],
+        ))
+        .await
+        .context("Failed to verify voting_power_latest")?
+        .context("Verification query returned no rows")?;
+
+    let report = VotingPowerLatestReport {
+        missing: row.try_get("", "missing")?,
+        orphaned: row.try_get("", "orphaned")?,
+        stale: row.try_get("", "stale")?,
+    };
+
+    if report.is_consistent() {
+        info!("voting_power_latest is consistent with the timeseries");
+    } else {
+        warn!(
+            missing = report.missing,
+            orphaned = report.orphaned,
+            stale = report.stale,
+            "voting_power_latest is inconsistent with the timeseries"
+        );
+    }
+    Ok(report)
+}
+
+/// Voters left out of delegated voting power totals, per DAO slug.
+const EXCLUDED_VOTERS: &[(&str, &[&str])] = &[
+    // ARB token held by excluded entity
+    ("arbitrum", &["0x00000000000000000000000000000000000A4B86"]),
+    // Uniswap timelock contract
+    ("uniswap", &["0x1a9C8182C09F50C8318d769245beA52c32BE35BC"]),
+];
+
+/// Lowercased excluded voters for a DAO slug.
+fn excluded_voters_for_slug(slug: &str) -> Vec<String> {
+    EXCLUDED_VOTERS
+        .iter()
+        .filter(|(dao_slug, _)| *dao_slug == slug)
+        .flat_map(|(_, voters)| voters.iter().map(|voter| voter.to_lowercase()))
+        .collect()
+}
+
+fn excluded_voters(dao_id: Uuid) -> Result<Vec<String>> {
+    let slug = DAO_SLUG_ID_MAP
+        .get()
+        .context("DAO map not initialized")?
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(_, id)| **id == dao_id)
+        .map(|(slug, _)| slug.clone());
+
+    Ok(slug
+        .map(|slug| excluded_voters_for_slug(&slug))
+        .unwrap_or_default())
+}
+
+/// `AND LOWER(<column>) NOT IN (...)` with one placeholder per excluded voter,
+/// numbered from `first_placeholder`. Empty when nothing is excluded.
+fn excluded_voter_filter(column: &str, first_placeholder: usize, count: usize) -> String {
+    if count == 0 {
+        return String::new();
+    }
+    let placeholders = (first_placeholder..first_placeholder + count)
+        .map(|i| format!("${i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("AND LOWER({column}) NOT IN ({placeholders})")
+}
+
+/// Current total delegated voting power of a DAO, read from
+/// `voting_power_latest`.
+#[instrument(name = "voting_power_current_total_delegated", skip_all, fields(dao_id = %dao_id))]
+pub async fn current_total_delegated_voting_power(dao_id: Uuid) -> Result<f64> {
+    let db = DB.get().context("DB not initialized")?;
+    let excluded = excluded_voters(dao_id)?;
+
+    let mut values: Vec<Value> = vec![dao_id.into()];
+    values.extend(excluded.iter().map(|voter| voter.as_str().into()));
+
+    let result = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                SELECT COALESCE(SUM(voting_power), 0.0) AS total_voting_power
+                FROM voting_power_latest
+                WHERE dao_id = $1 {}
+                "#,
+                excluded_voter_filter("voter", 2, excluded.len())
+            ),
+            values,
+        ))
+        .await
+        .context("Failed to query current total delegated voting power")?;
+
+    Ok(result
+        .map(|qr| qr.try_get::<f64>("", "total_voting_power"))
+        .transpose()
+        .context("Failed to get total_voting_power from query result")?
+        .unwrap_or(0.0))
+}
+
+/// Recompute a DAO's delegated voting power snapshots from `from_block`
+/// onwards. Each snapshot is the previous total plus the per-voter changes
+/// in its block, so rows that land behind already-snapshotted blocks are
+/// handled by recomputing the suffix. A DAO without any snapshots is
+/// backfilled from its first block. Returns the number of snapshots written.
+async fn refresh_delegated_voting_power_snapshots<C: ConnectionTrait>(
+    conn: &C,
+    dao_id: Uuid,
+    from_block: i32,
+) -> Result<u64> {
+    // Serialize refreshes per DAO so concurrent batches can't interleave
+    // their delete and insert
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock(hashtextextended('delegated_voting_power_snapshot:' || $1::text, 0))",
+        vec![dao_id.into()],
+    ))
+    .await
+    .context("Failed to lock delegated voting power snapshots")?;
+
+    let has_snapshots = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT EXISTS (SELECT 1 FROM delegated_voting_power_snapshot WHERE dao_id = $1) AS has_snapshots",
+            vec![dao_id.into()],
+        ))
+        .await
+        .context("Failed to check for delegated voting power snapshots")?
+        .map(|qr| qr.try_get::<bool>("", "has_snapshots"))
+        .transpose()?
+        .unwrap_or(false);
+    let from_block = if has_snapshots { from_block } else { 0 };
+
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "DELETE FROM delegated_voting_power_snapshot WHERE dao_id = $1 AND block >= $2",
+        vec![dao_id.into(), from_block.into()],
+    ))
+    .await
+    .context("Failed to delete stale delegated voting power snapshots")?;
+
+    let excluded = excluded_voters(dao_id)?;
+    let mut values: Vec<Value> = vec![dao_id.into(), from_block.into()];
+    values.extend(excluded.iter().map(|voter| voter.as_str().into()));
+
+    let written = conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                WITH changes AS (
+                    SELECT DISTINCT ON (voter, block) voter, block, timestamp, voting_power
+                    FROM voting_power_timeseries
+                    WHERE dao_id = $1 AND block >= $2 {}
+                    ORDER BY voter, block, log_index DESC
+                ),
+                with_delta AS (
+                    SELECT
+                        c.block,
+                        c.timestamp,
+                        c.voting_power - COALESCE(
+                            LAG(c.voting_power) OVER (PARTITION BY c.voter ORDER BY c.block),
+                            (
+                                SELECT t.voting_power FROM voting_power_timeseries t
+                                WHERE t.dao_id = $1 AND t.voter = c.voter AND t.block < $2
+                                ORDER BY t.block DESC, t.log_index DESC
+                                LIMIT 1
+                            ),
+                            0
+                        ) AS delta
+                    FROM changes c
+                ),
+                per_block AS (
+                    SELECT block, MAX(timestamp) AS timestamp, SUM(delta) AS delta
+                    FROM with_delta
+                    GROUP BY block
+                ),
+                base AS (
+                    SELECT COALESCE((
+                        SELECT total_voting_power FROM delegated_voting_power_snapshot
+                        WHERE dao_id = $1 AND block < $2
+                        ORDER BY block DESC
+                        LIMIT 1
+                    ), 0) AS total
+                )
+                INSERT INTO delegated_voting_power_snapshot (dao_id, block, timestamp, total_voting_power)
+                SELECT $1, p.block, p.timestamp, base.total + SUM(p.delta) OVER (ORDER BY p.block)
+                FROM per_block p CROSS JOIN base
+                "#,
+                excluded_voter_filter("voter", 3, excluded.len())
+            ),
+            values,
+        ))
+        .await
+        .context("Failed to write delegated voting power snapshots")?
+        .rows_affected();
+
+    debug!(
+        dao_id = %dao_id,
+        from_block,
+        written_snapshots = written,
+        "Refreshed delegated voting power snapshots"
+    );
+    Ok(written)
+}
+
+/// Bring `delegated_voting_power_snapshot` up to date with a timeseries
+/// batch, recomputing each DAO from the lowest block in the batch. Meant to
+/// run in the same transaction as the timeseries insert.
+#[instrument(name = "voting_power_update_snapshots", skip_all, fields(row_count = rows.len()))]
+pub async fn update_delegated_voting_power_snapshots<C: ConnectionTrait>(
+    conn: &C,
+    rows: &[voting_power_timeseries::ActiveModel],
+) -> Result<()> {
+    let mut from_blocks: HashMap<Uuid, i32> = HashMap::new();
+    for row in rows {
+        if let (Some(dao_id), Some(block)) = (row.dao_id.try_as_ref(), row.block.try_as_ref()) {
+            from_blocks
+                .entry(*dao_id)
+                .and_modify(|from| *from = (*from).min(*block))
+                .or_insert(*block);
+        }
+    }
+
+    for (dao_id, from_block) in from_blocks {
+        refresh_delegated_voting_power_snapshots(conn, dao_id, from_block).await?;
+    }
+    Ok(())
+}
+
+/// Recompute all delegated voting power snapshots, for one DAO or all of
+/// them, e.g. after changing [`EXCLUDED_VOTERS`]. Returns the number of
+/// snapshots written.
+#[instrument(name = "voting_power_rebuild_snapshots", skip_all, fields(dao_id = ?dao_id))]
+pub async fn rebuild_delegated_voting_power_snapshots(dao_id: Option<Uuid>) -> Result<u64> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let dao_ids = match dao_id {
+        Some(dao_id) => vec![dao_id],
+        None => dao::Entity::find()
+            .select_only()
+            .column(dao::Column::Id)
+            .into_tuple::<Uuid>()
+            .all(db)
+            .await
+            .context("Failed to load DAOs")?,
+    };
+
+    let mut written = 0;
+    for dao_id in dao_ids {
+        let txn = db.begin().await.context("Failed to start transaction")?;
+        written += refresh_delegated_voting_power_snapshots(&txn, dao_id, 0).await?;
+        txn.commit()
+            .await
+            .context("Failed to commit snapshot rebuild")?;
+    }
+
+    info!(
+        written_snapshots = written,
+        "Rebuilt delegated voting power snapshots"
+    );
+    Ok(written)
+}
+
+/// Relative difference tolerated between the running snapshot total and a
+/// fresh sum, to absorb floating point drift from summing deltas.
+const SNAPSHOT_TOLERANCE: f64 = 1e-9;
+
+/// Compare the newest snapshot of a DAO against the current total from
+/// `voting_power_latest`. Returns whether they agree.
+#[instrument(name = "voting_power_verify_snapshots", skip_all, fields(dao_id = %dao_id))]
+pub async fn verify_delegated_voting_power_snapshots(dao_id: Uuid) -> Result<bool> {
+    let snapshot_total = total_delegated_voting_power_at(dao_id, Utc::now().naive_utc()).await?;
+    let current_total = current_total_delegated_voting_power(dao_id).await?;
+
+    let consistent =
+        (snapshot_total - current_total).abs() <= SNAPSHOT_TOLERANCE * current_total.abs().max(1.0);
+    if consistent {
+        info!(
+            snapshot_total,
+            current_total, "Delegated voting power snapshots are consistent"
+        );
+    } else {
+        warn!(
+            snapshot_total,
+            current_total, "Delegated voting power snapshots drifted from voting_power_latest"
+        );
+    }
+    Ok(consistent)
+}
+
+/// Total delegated voting power of a DAO as of `timestamp`, read from the
+/// newest snapshot at or before it.
+#[instrument(name = "voting_power_total_delegated_at", skip_all, fields(dao_id = %dao_id, timestamp = ?timestamp))]
+pub async fn total_delegated_voting_power_at(
+    dao_id: Uuid,
+    timestamp: NaiveDateTime,
+) -> Result<f64> {
+    let db = DB.get().context("DB not initialized")?;
+
+    let result = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT total_voting_power
+            FROM delegated_voting_power_snapshot
+            WHERE dao_id = $1 AND timestamp <= $2
+            ORDER BY timestamp DESC, block DESC
+            LIMIT 1
+            "#,
+            vec![dao_id.into(), timestamp.into()],
+        ))
+        .await
+        .context("Failed to query delegated voting power snapshot")?;
+
+    Ok(result
+        .map(|qr| qr.try_get::<f64>("", "total_voting_power"))
+        .transpose()
+        .context("Failed to get total_voting_power from query result")?
+        .unwrap_or(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(
+        voter: &str,
+        dao_id: Uuid,
+        block: i32,
+        log_index: i32,
+        vp: f64,
+    ) -> voting_power_timeseries::ActiveModel {
+        voting_power_timeseries::ActiveModel {
+            id: NotSet,
+            voter: Set(voter.to_string()),
+            voting_power: Set(vp),
+            dao_id: Set(dao_id),
+            timestamp: Set(NaiveDateTime::default()),
+            block: Set(block),
+            txid: Set(Some(format!("0x{block:x}{log_index:x}"))),
+            log_index: Set(log_index),
+            previous_voting_power: NotSet,
+        }
+    }
+
+    #[test]
+    fn test_newest_per_voter_orders_by_block_and_log_index() {
+        let dao = Uuid::from_u128(1);
+        let other_dao = Uuid::from_u128(2);
+        let rows = vec![
+            row("0xa", dao, 10, 5, 3.0),
+            row("0xa", dao, 12, 1, 4.0),
+            row("0xa", dao, 12, 0, 5.0),
+            row("0xa", other_dao, 8, 0, 6.0),
+            row("0xb", dao, 11, 2, 7.0),
+        ];
+
+        let mut latest: Vec<(String, Uuid, f64)> = newest_per_voter(&rows)
+            .into_iter()
+            .map(|m| (m.voter.unwrap(), m.dao_id.unwrap(), m.voting_power.unwrap()))
+            .collect();
+        latest.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+        assert_eq!(
+            latest,
+            vec![
+                ("0xa".to_string(), dao, 4.0),
+                ("0xa".to_string(), other_dao, 6.0),
+                ("0xb".to_string(), dao, 7.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_newest_per_voter_skips_incomplete_rows() {
+        let dao = Uuid::from_u128(1);
+        let mut incomplete = row("0xa", dao, 20, 0, 1.0);
+        incomplete.log_index = NotSet;
+
+        let latest = newest_per_voter(&[row("0xa", dao, 10, 0, 2.0), incomplete]);
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].voting_power.clone().unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_merge_same_block_keeps_newest_balance_and_oldest_previous() {
+        let dao = Uuid::from_u128(1);
+        let event = |block, log_index, previous, vp| {
+            let mut row = row("0xa", dao, block, log_index, vp);
+            row.previous_voting_power = Set(Some(previous));
+            row
+        };
+
+        let mut merged: Vec<(i32, i32, Option<f64>, f64)> = merge_same_block_voting_powers(vec![
+            event(10, 4, 2.0, 3.0),
+            event(10, 1, 1.0, 2.0),
+            event(10, 7, 3.0, 5.0),
+            event(11, 0, 5.0, 6.0),
+        ])
+        .into_iter()
+        .map(|m| {
+            (
+                m.block.unwrap(),
+                m.log_index.unwrap(),
+                m.previous_voting_power.unwrap(),
+                m.voting_power.unwrap(),
+            )
+        })
+        .collect();
+        merged.sort_by_key(|m| m.0);
+
+        assert_eq!(
+            merged,
+            vec![(10, 7, Some(1.0), 5.0), (11, 0, Some(5.0), 6.0)]
+        );
+    }
+
+    #[test]
+    fn test_excluded_voters_are_lowercased_per_dao() {
+        assert_eq!(
+            excluded_voters_for_slug("arbitrum"),
+            vec!["0x00000000000000000000000000000000000a4b86".to_string()]
+        );
+        assert!(excluded_voters_for_slug("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_excluded_voter_filter_numbers_placeholders() {
+        assert_eq!(excluded_voter_filter("voter", 3, 0), "");
+        assert_eq!(
+            excluded_voter_filter("voter", 3, 2),
+            "AND LOWER(voter) NOT IN ($3, $4)"
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    extensions::{
+        block_time::estimate_timestamp,
+        db_extension::{DAO_SLUG_ID_MAP, DB, store_voting_powers},
+        voting_power::merge_same_block_voting_powers,
+    },
+    rindexer_lib::indexers::rindexer::contracts::provider_for_network,
+};
+use alloy::{
+    primitives::{Address, B256, Bytes},
+    providers::Provider,
+    sol,
+    sol_types::SolEvent,
+};
+use anyhow::{Context, Result};
+use proposalsapp_db::models::voting_power_timeseries;
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    DbBackend, FromQueryResult, Statement,
+    prelude::Uuid,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, instrument, warn};
+
+// ARB and UNI emit the same DelegateVotesChanged event
+sol! {
+    #[derive(Debug)]
+    event DelegateVotesChanged(address indexed delegate, uint256 previousBalance, uint256 newBalance);
+}
+
+/// Token whose DelegateVotesChanged events feed a DAO's voting power
+/// timeseries, as configured in rindexer.yaml.
+struct VotingToken {
+    dao_slug: &'static str,
+    network: &'static str,
+    address: &'static str,
+    start_block: i32,
+}
+
+const VOTING_TOKENS: &[VotingToken] = &[
+    VotingToken {
+        dao_slug: "arbitrum",
+        network: "arbitrum",
+        address: "0x912ce59144191c1204e64559fe8253a0e49e6548",
+        start_block: 70_398_215,
+    },
+    VotingToken {
+        dao_slug: "uniswap",
+        network: "ethereum",
+        address: "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984",
+        start_block: 10_861_674,
+    },
+];
+
+const LOG_BLOCK_RANGE: u64 = 9_999;
+
+/// Absolute difference tolerated between an event's previous balance and
+/// the stored balance before it. Both come from the same u256 -> f64
+/// conversion, so anything above rounding noise is a missing event.
+const GAP_TOLERANCE: f64 = 1e-9;
+
+/// A voter whose stored timeseries doesn't chain: the event at `to_block`
+/// reports `reported_previous`, but the newest stored row before it (at
+/// `from_block`, or the token's start block if there is none) has
+/// `stored_previous`. The missing events lie in `[from_block, to_block]`.
+#[derive(Debug, Clone, PartialEq, FromQueryResult)]
+pub struct VotingPowerGap {
+    pub voter: String,
+    pub from_block: i32,
+    pub to_block: i32,
+    pub stored_previous: f64,
+    pub reported_previous: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcLog {
+    block_number: String,
+    transaction_hash: String,
+    log_index: String,
+    data: String,
+    topics: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct DecodedDelegateVotesChangedLog {
+    event_data: DelegateVotesChanged,
+    block_number: u64,
+    log_index: u64,
+    transaction_hash: String,
+}
+
+fn voting_token(dao_slug: &str) -> Result<&'static VotingToken> {
+    VOTING_TOKENS
+        .iter()
+        .find(|token| token.dao_slug == dao_slug)
+        .with_context(|| format!("No voting token configured for DAO '{dao_slug}'"))
+}
+
+fn dao_id_for_slug(dao_slug: &str) -> Result<Uuid> {
+    DAO_SLUG_ID_MAP
+        .get()
+        .context("DAO_SLUG_ID_MAP not initialized")?
+        .lock()
+        .unwrap()
+        .get(dao_slug)
+        .copied()
+        .with_context(|| format!("DAO not found for slug '{dao_slug}'"))
+}
+
+fn parse_hex_u64(value: &str) -> Result<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16)
+        .context("Failed to parse hex value into u64")
+}
+
+fn decode_delegate_votes_changed_log(log: RpcLog) -> Result<DecodedDelegateVotesChangedLog> {
+    let topics = log
+        .topics
+        .into_iter()
+        .map(|topic| topic.parse::<B256>().context("Invalid log topic"))
+        .collect::<Result<Vec<_>>>()?;
+    let data = log
+        .data
+        .parse::<Bytes>()
+        .context("Invalid delegate votes changed log data")?;
+    let event_data = DelegateVotesChanged::decode_raw_log(topics, &data[..])
+        .context("Failed to decode DelegateVotesChanged log")?;
+
+    Ok(DecodedDelegateVotesChangedLog {
+        event_data,
+        block_number: parse_hex_u64(&log.block_number)?,
+        log_index: parse_hex_u64(&log.log_index)?,
+        transaction_hash: log.transaction_hash,
+    })
+}
+
+/// Find every voter of a DAO whose stored events don't chain through their
+/// previous balances. Rows indexed before previous balances were kept are
+/// only used as the stored side of the comparison.
+#[instrument(name = "voting_power_find_gaps", skip_all, fields(dao_slug = dao_slug))]
+pub async fn find_voting_power_gaps(dao_slug: &str) -> Result<Vec<VotingPowerGap>> {
+    let db = DB.get().context("DB not initialized")?;
+    let token = voting_token(dao_slug)?;
+    let dao_id = dao_id_for_slug(dao_slug)?;
+
+    let gaps = VotingPowerGap::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        SELECT voter, from_block, to_block, stored_previous, reported_previous
+        FROM (
+            SELECT
+                voter,
+                COALESCE(LAG(block) OVER w, $2) AS from_block,
+                block AS to_block,
+                COALESCE(LAG(voting_power) OVER w, 0) AS stored_previous,
+                previous_voting_power AS reported_previous
+            FROM voting_power_timeseries
+            WHERE dao_id = $1
+            WINDOW w AS (PARTITION BY voter ORDER BY block, log_index)
+        ) chained
+        WHERE reported_previous IS NOT NULL
+          AND ABS(reported_previous - stored_previous) > $3
+        ORDER BY voter, to_block
+        "#,
+        vec![
+            dao_id.into(),
+            token.start_block.into(),
+            GAP_TOLERANCE.into(),
+        ],
+    ))
+    .all(db)
+    .await
+    .context("Failed to find voting power gaps")?;
+
+    for gap in &gaps {
+        warn!(
+            voter = %gap.voter,
+            from_block = gap.from_block,
+            to_block = gap.to_block,
+            stored_previous = gap.stored_previous,
+            reported_previous = gap.reported_previous,
+            "Voting power timeseries gap"
+        );
+    }
+    info!(gap_count = gaps.len(), "Checked voting power timeseries");
+
+    Ok(gaps)
+}
+
+/// DelegateVotesChanged logs of one delegate in `[from_block, to_block]`.
+async fn fetch_delegate_votes_changed_logs(
+    token: &VotingToken,
+    delegate: Address,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<DecodedDelegateVotesChangedLog>> {
+    let provider = provider_for_network(token.network).await;
+    let mut decoded_logs = Vec::new();
+    let mut chunk_start = from_block;
+
+    while chunk_start <= to_block {
+        let chunk_end = chunk_start.saturating_add(LOG_BLOCK_RANGE).min(to_block);
+        let logs = provider
+            .client()
+            .request::<(serde_json::Value,), Vec<RpcLog>>(
+                "eth_getLogs",
+                (json!({
+                    "address": token.address,
+                    "topics": [
+                        DelegateVotesChanged::SIGNATURE_HASH.to_string(),
+                        delegate.into_word().to_string(),
+                    ],
+                    "fromBlock": format!("0x{:x}", chunk_start),
+                    "toBlock": format!("0x{:x}", chunk_end),
+                }),),
+            )
+            .await
+            .context("Failed to fetch DelegateVotesChanged logs")?;
+
+        for log in logs {
+            decoded_logs.push(decode_delegate_votes_changed_log(log)?);
+        }
+
+        if chunk_end == to_block {
+            break;
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    Ok(decoded_logs)
+}
+
+/// Re-fetch the DelegateVotesChanged logs of every gap and store them,
+/// then check again. Returns the gaps that are left.
+#[instrument(name = "voting_power_heal_gaps", skip_all, fields(dao_slug = dao_slug))]
+pub async fn heal_voting_power_gaps(dao_slug: &str) -> Result<Vec<VotingPowerGap>> {
+    let token = voting_token(dao_slug)?;
+    let dao_id = dao_id_for_slug(dao_slug)?;
+    let gaps = find_voting_power_gaps(dao_slug).await?;
+
+    for gap in &gaps {
+        let delegate: Address = gap
+            .voter
+            .parse()
+            .with_context(|| format!("Invalid voter address {}", gap.voter))?;
+        let logs = fetch_delegate_votes_changed_logs(
+            token,
+            delegate,
+            gap.from_block as u64,
+            gap.to_block as u64,
+        )
+        .await?;
+
+        let mut rows = Vec::with_capacity(logs.len());
+        for log in logs {
+            let timestamp = estimate_timestamp(token.network, log.block_number)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to estimate timestamp for block {}",
+                        log.block_number
+                    )
+                })?;
+
+            rows.push(voting_power_timeseries::ActiveModel {
+                id: NotSet,
+                voter: Set(log.event_data.delegate.to_string()),
+                voting_power: Set(
+                    log.event_data.newBalance.to::<u128>() as f64 / (10.0f64.powi(18))
+                ),
+                dao_id: Set(dao_id),
+                timestamp: Set(timestamp),
+                block: Set(log.block_number as i32),
+                txid: Set(Some(log.transaction_hash)),
+                log_index: Set(log.log_index as i32),
+                previous_voting_power: Set(Some(
+                    log.event_data.previousBalance.to::<u128>() as f64 / (10.0f64.powi(18)),
+                )),
+            });
+        }
+
+        info!(
+            voter = %gap.voter,
+            from_block = gap.from_block,
+            to_block = gap.to_block,
+            refetched_events = rows.len(),
+            "Re-fetched voting power events for gap"
+        );
+        store_voting_powers(merge_same_block_voting_powers(rows)).await?;
+    }
+
+    let remaining = find_voting_power_gaps(dao_slug).await?;
+    if !remaining.is_empty() {
+        warn!(
+            remaining_gaps = remaining.len(),
+            "Voting power gaps left after healing"
+        );
+    }
+    Ok(remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{U256, address, hex};
+
+    #[test]
+    fn test_decode_delegate_votes_changed_log() {
+        let event = DelegateVotesChanged {
+            delegate: address!("0x1a9C8182C09F50C8318d769245beA52c32BE35BC"),
+            previousBalance: U256::from(5u64),
+            newBalance: U256::from(7u64),
+        };
+        let log_data = event.encode_log_data();
+
+        let decoded = decode_delegate_votes_changed_log(RpcLog {
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: "0x3".to_string(),
+            data: format!("0x{}", hex::encode(&log_data.data)),
+            topics: log_data.topics().iter().map(|t| t.to_string()).collect(),
+        })
+        .unwrap();
+
+        assert_eq!(decoded.event_data.delegate, event.delegate);
+        assert_eq!(decoded.event_data.previousBalance, U256::from(5u64));
+        assert_eq!(decoded.event_data.newBalance, U256::from(7u64));
+        assert_eq!(decoded.block_number, 16);
+        assert_eq!(decoded.log_index, 3);
+        assert_eq!(
+            log_data.topics()[1],
+            event.delegate.into_word(),
+            "delegate topic used to filter eth_getLogs"
+        );
+    }
+
+    #[test]
+    fn test_voting_tokens_cover_dao_slugs() {
+        assert_eq!(voting_token("arbitrum").unwrap().network, "arbitrum");
+        assert_eq!(voting_token("uniswap").unwrap().network, "ethereum");
+        assert!(voting_token("ens").is_err());
+    }
+}
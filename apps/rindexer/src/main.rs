@@ -10,12 +10,19 @@ use rindexer::{
 };
 use std::{env, time::Duration};
 use tasks::{
-    onchain_proposals_updates::run_periodic_proposal_state_update,
-    snapshot_indexer::run_periodic_snapshot_indexing,
+    anomaly_detector::run_anomaly_detector,
+    delegate_verification::run_periodic_delegate_verification,
+    delegation_flows::run_periodic_delegation_flow_sync,
+    failed_event_replay::run_periodic_failed_event_replay,
+    governance_events::run_periodic_governance_event_pruning,
+    governor_backfill::run_periodic_governor_backfill, notifications::run_notification_producer,
+    onchain_proposals_updates::run_proposal_state_scheduler,
+    snapshot_indexer::run_periodic_snapshot_indexing, tally_audit::run_periodic_tally_audit,
 };
 use tracing::{error, info, instrument, warn};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
+mod commands;
 mod extensions;
 mod rindexer_lib;
 mod tasks;
@@ -57,6 +64,12 @@ async fn main() -> Result<()> {
         .await
         .context("Failed to initialize database")?;
 
+    // Operator commands run instead of the indexer
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some((command, command_args)) = args.split_first() {
+        return commands::run_command(command, command_args).await;
+    }
+
     // Spawn periodic tasks and store their handles
     let snapshot_indexing_handle = tokio::spawn(async {
         if let Err(e) = run_periodic_snapshot_indexing().await {
@@ -66,7 +79,65 @@ async fn main() -> Result<()> {
 
     let proposal_state_handle = tokio::spawn(async {
         run_task_forever("proposal-state", Duration::from_secs(5), || async {
-            run_periodic_proposal_state_update().await
+            run_proposal_state_scheduler().await
+        })
+        .await;
+    });
+
+    let delegation_flow_handle = tokio::spawn(async {
+        run_task_forever("delegation-flows", Duration::from_secs(5), || async {
+            run_periodic_delegation_flow_sync().await
+        })
+        .await;
+    });
+
+    let failed_event_replay_handle = tokio::spawn(async {
+        run_task_forever("failed-event-replay", Duration::from_secs(5), || async {
+            run_periodic_failed_event_replay().await
+        })
+        .await;
+    });
+
+    let governor_backfill_handle = tokio::spawn(async {
+        run_task_forever("governor-backfill", Duration::from_secs(5), || async {
+            run_periodic_governor_backfill().await
+        })
+        .await;
+    });
+
+    let tally_audit_handle = tokio::spawn(async {
+        run_task_forever("tally-audit", Duration::from_secs(5), || async {
+            run_periodic_tally_audit().await
+        })
+        .await;
+    });
+
+    let notification_producer_handle = tokio::spawn(async {
+        run_task_forever("notification-producer", Duration::from_secs(5), || async {
+            run_notification_producer().await
+        })
+        .await;
+    });
+
+    let anomaly_detector_handle = tokio::spawn(async {
+        run_task_forever("anomaly-detector", Duration::from_secs(5), || async {
+            run_anomaly_detector().await
+        })
+        .await;
+    });
+
+    let governance_event_pruning_handle = tokio::spawn(async {
+        run_task_forever(
+            "governance-event-pruning",
+            Duration::from_secs(5),
+            || async { run_periodic_governance_event_pruning().await },
+        )
+        .await;
+    });
+
+    let delegate_verification_handle = tokio::spawn(async {
+        run_task_forever("delegate-verification", Duration::from_secs(5), || async {
+            run_periodic_delegate_verification().await
         })
         .await;
     });
@@ -140,6 +211,30 @@ async fn main() -> Result<()> {
         result = proposal_state_handle => {
             error!("Proposal state task completed unexpectedly: {:?}", result);
         }
+        result = delegation_flow_handle => {
+            error!("Delegation flow task completed unexpectedly: {:?}", result);
+        }
+        result = failed_event_replay_handle => {
+            error!("Failed event replay task completed unexpectedly: {:?}", result);
+        }
+        result = governor_backfill_handle => {
+            error!("Governor backfill task completed unexpectedly: {:?}", result);
+        }
+        result = tally_audit_handle => {
+            error!("Tally audit task completed unexpectedly: {:?}", result);
+        }
+        result = notification_producer_handle => {
+            error!("Notification producer task completed unexpectedly: {:?}", result);
+        }
+        result = anomaly_detector_handle => {
+            error!("Anomaly detector task completed unexpectedly: {:?}", result);
+        }
+        result = governance_event_pruning_handle => {
+            error!("Governance event pruning task completed unexpectedly: {:?}", result);
+        }
+        result = delegate_verification_handle => {
+            error!("Delegate verification task completed unexpectedly: {:?}", result);
+        }
         result = rindexer_handle => {
             error!("Rindexer task completed unexpectedly: {:?}", result);
         }
@@ -4,6 +4,12 @@ use super::rindexer::arbitrum_sc_nominations::arbitrum_sc_nominations_handlers;
 use super::rindexer::arbitrum_treasury_governor::arbitrum_treasury_governor_handlers;
 use super::rindexer::uni_governor::uni_governor_handlers;
 use super::rindexer::uni_token::uni_token_handlers;
+use super::rindexer::{
+    arb_token, arbitrum_core_governor, arbitrum_sc_nominations, arbitrum_treasury_governor,
+    uni_governor, uni_token,
+};
+use crate::extensions::failed_events::EventPayload;
+use anyhow::{Result, bail};
 use rindexer::event::callback_registry::EventCallbackRegistry;
 use std::path::PathBuf;
 
@@ -17,3 +23,19 @@ pub async fn register_all_handlers(manifest_path: &PathBuf) -> EventCallbackRegi
     uni_governor_handlers(manifest_path, &mut registry).await;
     registry
 }
+
+/// Run a raw event of a rindexer.yaml contract through the processing of its
+/// handler, e.g. `replay_event("ArbitrumCoreGovernor", "VoteCast", payload)`.
+pub async fn replay_event(contract: &str, event_name: &str, payload: &EventPayload) -> Result<()> {
+    match contract {
+        "ARBToken" => arb_token::replay_event(event_name, payload).await,
+        "UNIToken" => uni_token::replay_event(event_name, payload).await,
+        "ArbitrumCoreGovernor" => arbitrum_core_governor::replay_event(event_name, payload).await,
+        "ArbitrumTreasuryGovernor" => {
+            arbitrum_treasury_governor::replay_event(event_name, payload).await
+        }
+        "ArbitrumSCNominations" => arbitrum_sc_nominations::replay_event(event_name, payload).await,
+        "UniGovernor" => uni_governor::replay_event(event_name, payload).await,
+        _ => bail!("Unknown contract {contract}"),
+    }
+}
@@ -1,14 +1,19 @@
 #![allow(non_snake_case)]
 use super::super::super::typings::rindexer::events::arb_token::{
-    ARBTokenEventType, DelegateChangedEvent, DelegateVotesChangedEvent, no_extensions,
+    ARBTokenEventType, DelegateChangedData, DelegateChangedEvent, DelegateVotesChangedData,
+    DelegateVotesChangedEvent, TransferData, TransferEvent, no_extensions,
 };
 use crate::extensions::{
     block_time::estimate_timestamp,
     db_extension::{DAO_SLUG_ID_MAP, store_delegations, store_voting_powers},
+    delegation_flows::store_token_transfers,
+    failed_events::{EventPayload, record_build_failures, record_failed_events},
+    voting_power::merge_same_block_voting_powers,
 };
 use alloy::hex::ToHexExt;
+use anyhow::{Context, Result};
 use futures::stream::{self, StreamExt};
-use proposalsapp_db::models::{delegation, voting_power_timeseries};
+use proposalsapp_db::models::{delegation, token_transfer, voting_power_timeseries};
 use rindexer::{
     EthereumSqlTypeWrapper, PgType, RindexerColorize,
     event::callback_registry::EventCallbackRegistry, indexer::IndexingEventProgressStatus,
@@ -34,6 +39,76 @@ fn get_dao_id() -> Option<Uuid> {
         .copied()
 }
 
+async fn build_delegation_model_from_event(
+    event_data: &DelegateChangedData,
+    payload: &EventPayload,
+    dao_id: Uuid,
+) -> Result<delegation::ActiveModel> {
+    let timestamp = estimate_timestamp("arbitrum", payload.block_number)
+        .await
+        .context("Failed to estimate timestamp for DelegateChanged event")?;
+
+    Ok(delegation::ActiveModel {
+        id: NotSet,
+        delegator: Set(event_data.delegator.to_string()),
+        delegate: Set(event_data.toDelegate.to_string()),
+        dao_id: Set(dao_id),
+        block: Set(payload.block_number as i32),
+        timestamp: Set(timestamp),
+        txid: Set(Some(payload.transaction_hash.clone())),
+        from_delegate: Set(Some(event_data.fromDelegate.to_string())),
+        log_index: Set(payload.log_index as i32),
+        weight: NotSet,
+    })
+}
+
+async fn build_voting_power_model_from_event(
+    event_data: &DelegateVotesChangedData,
+    payload: &EventPayload,
+    dao_id: Uuid,
+) -> Result<voting_power_timeseries::ActiveModel> {
+    let timestamp = estimate_timestamp("arbitrum", payload.block_number)
+        .await
+        .context("Failed to estimate timestamp for DelegateVotesChanged event")?;
+
+    Ok(voting_power_timeseries::ActiveModel {
+        id: NotSet,
+        voter: Set(event_data.delegate.to_string()),
+        voting_power: Set(event_data.newBalance.to::<u128>() as f64 / (10.0f64.powi(18))),
+        dao_id: Set(dao_id),
+        block: Set(payload.block_number as i32),
+        timestamp: Set(timestamp),
+        txid: Set(Some(payload.transaction_hash.clone())),
+        log_index: Set(payload.log_index as i32),
+        previous_voting_power: Set(Some(
+            event_data.previousBalance.to::<u128>() as f64 / (10.0f64.powi(18)),
+        )),
+    })
+}
+
+async fn build_transfer_model_from_event(
+    event_data: &TransferData,
+    payload: &EventPayload,
+    dao_id: Uuid,
+) -> Result<token_transfer::ActiveModel> {
+    let timestamp = estimate_timestamp("arbitrum", payload.block_number)
+        .await
+        .context("Failed to estimate timestamp for Transfer event")?;
+
+    Ok(token_transfer::ActiveModel {
+        id: NotSet,
+        dao_id: Set(dao_id),
+        from_address: Set(event_data.from.to_string()),
+        to_address: Set(event_data.to.to_string()),
+        amount: Set(event_data.value.to::<u128>() as f64 / (10.0f64.powi(18))),
+        block: Set(payload.block_number as i32),
+        log_index: Set(payload.log_index as i32),
+        timestamp: Set(timestamp),
+        txid: Set(payload.transaction_hash.clone()),
+        flow_synced: NotSet,
+    })
+}
+
 #[instrument(
     name = "arb_token_delegate_changed_handler",
     skip(manifest_path, registry)
@@ -59,70 +134,53 @@ async fn delegate_changed_handler(manifest_path: &PathBuf, registry: &mut EventC
                     .unwrap();
 
                 // Process results in parallel using futures streams
-                let delegations: Vec<delegation::ActiveModel> = stream::iter(results)
-                    .map(|result| async move {
-                        let block_number = result.tx_information.block_number;
-                        let delegator_addr = result.event_data.delegator;
-                        let delegate_addr = result.event_data.toDelegate;
-                        let tx_hash = result.tx_information.transaction_hash;
-
-                        let created_at = match estimate_timestamp("arbitrum", block_number).await {
-                            Ok(ts) => ts,
-                            Err(e) => {
-                                error!(
-                                    block_number = block_number,
-                                    error = %e,
-                                    "Failed to estimate timestamp for DelegateChanged event"
-                                );
-                                // Returning `Err` here will stop processing the batch.
-                                // Depending on your error handling strategy, you might want to handle this differently,
-                                // e.g., skip this event and continue with others, or retry.
-                                return None; // Skip this delegation if timestamp estimation fails
-                            }
-                        };
-
-                        debug!(
-                            event_name = "ARBToken::DelegateChanged",
-                            block_number = block_number,
-                            "Processed single event within batch"
-                        );
-
-                        Some(delegation::ActiveModel {
-                            id: NotSet,
-                            delegator: Set(delegator_addr.to_string()),
-                            delegate: Set(delegate_addr.to_string()),
-                            dao_id: Set(dao_id),
-                            block: Set(block_number as i32),
-                            timestamp: Set(created_at),
-                            txid: Set(Some(result.tx_information.transaction_hash.to_string())),
+                let built: Vec<(EventPayload, Result<delegation::ActiveModel>)> =
+                    stream::iter(results)
+                        .map(|result| async move {
+                            let payload =
+                                EventPayload::new(&result.event_data, &result.tx_information);
+                            let delegation = build_delegation_model_from_event(
+                                &result.event_data,
+                                &payload,
+                                dao_id,
+                            )
+                            .await;
+                            (payload, delegation)
                         })
-                    })
-                    .buffer_unordered(CONCURRENCY_LIMIT)
-                    .filter_map(|delegation_opt| async { delegation_opt }) // Filter out None values
-                    .collect::<Vec<_>>()
-                    .await;
+                        .buffer_unordered(CONCURRENCY_LIMIT)
+                        .collect()
+                        .await;
+                let delegations = record_build_failures("ARBToken::DelegateChanged", built).await;
 
                 if !delegations.is_empty() {
-                    // Deduplicate delegations by keeping only the last one for each (delegator, dao_id, block) combination
+                    // Deduplicate delegations by keeping only the newest event (highest log index)
+                    // for each (delegator, dao_id, block) combination
                     let mut deduped_delegations: HashMap<
                         (String, Uuid, i32),
-                        delegation::ActiveModel,
+                        (EventPayload, delegation::ActiveModel),
                     > = HashMap::new();
 
-                    for delegation in delegations {
+                    for (payload, delegation) in delegations {
                         let delegator = delegation.delegator.clone().unwrap();
                         let dao_id = delegation.dao_id.clone().unwrap();
                         let block = delegation.block.clone().unwrap();
 
                         let key = (delegator, dao_id, block);
-                        deduped_delegations.insert(key, delegation);
+                        let is_newer = deduped_delegations.get(&key).is_none_or(|(_, existing)| {
+                            existing.log_index.clone().unwrap()
+                                < delegation.log_index.clone().unwrap()
+                        });
+                        if is_newer {
+                            deduped_delegations.insert(key, (payload, delegation));
+                        }
                     }
 
-                    let final_delegations: Vec<delegation::ActiveModel> =
-                        deduped_delegations.into_values().collect();
+                    let (payloads, final_delegations): (Vec<_>, Vec<_>) =
+                        deduped_delegations.into_values().unzip();
 
                     if let Err(e) = store_delegations(final_delegations).await {
                         error!(error = %e, "Failed to store delegations");
+                        record_failed_events("ARBToken::DelegateChanged", &payloads, &e).await;
                     }
                 }
 
@@ -170,67 +228,36 @@ async fn delegate_votes_changed_handler(
                     .unwrap();
 
                 // Process results in parallel using futures streams
-                let vps: Vec<voting_power_timeseries::ActiveModel> = stream::iter(results)
-                    .map(|result| async move {
-                        let block_number = result.tx_information.block_number;
-                        let delegate_addr = result.event_data.delegate;
-                        let new_balance = result.event_data.newBalance;
-                        let tx_hash = result.tx_information.transaction_hash;
-
-                        let created_at = match estimate_timestamp("arbitrum", block_number).await {
-                            Ok(ts) => ts,
-                            Err(e) => {
-                                error!(
-                                    block_number = block_number,
-                                    error = %e,
-                                    "Failed to estimate timestamp for DelegateVotesChanged event"
-                                );
-                                return None; // Skip this voting power update if timestamp estimation fails
-                            }
-                        };
-
-                        debug!(
-                            event_name = "ARBToken::DelegateVotesChanged",
-                            block_number = block_number,
-                            "Processed single event within batch"
-                        );
-
-                        Some(voting_power_timeseries::ActiveModel {
-                            id: NotSet,
-                            voter: Set(delegate_addr.to_string()),
-                            voting_power: Set(new_balance.to::<u128>() as f64 / (10.0f64.powi(18))),
-                            dao_id: Set(dao_id),
-                            block: Set(block_number as i32),
-                            timestamp: Set(created_at),
-                            txid: Set(Some(result.tx_information.transaction_hash.to_string())),
+                let built: Vec<(EventPayload, Result<voting_power_timeseries::ActiveModel>)> =
+                    stream::iter(results)
+                        .map(|result| async move {
+                            let payload =
+                                EventPayload::new(&result.event_data, &result.tx_information);
+                            let vp = build_voting_power_model_from_event(
+                                &result.event_data,
+                                &payload,
+                                dao_id,
+                            )
+                            .await;
+                            (payload, vp)
                         })
-                    })
-                    .buffer_unordered(CONCURRENCY_LIMIT)
-                    .filter_map(|vp_opt| async { vp_opt }) // Filter out None values
-                    .collect::<Vec<_>>()
-                    .await;
+                        .buffer_unordered(CONCURRENCY_LIMIT)
+                        .collect()
+                        .await;
+                let (payloads, vps): (Vec<_>, Vec<_>) =
+                    record_build_failures("ARBToken::DelegateVotesChanged", built)
+                        .await
+                        .into_iter()
+                        .unzip();
 
                 if !vps.is_empty() {
-                    // Deduplicate voting powers by keeping only the last one for each (voter, dao_id, block) combination
-                    let mut deduped_vps: HashMap<
-                        (String, Uuid, i32),
-                        voting_power_timeseries::ActiveModel,
-                    > = HashMap::new();
-
-                    for vp in vps {
-                        let voter = vp.voter.clone().unwrap();
-                        let dao_id = vp.dao_id.clone().unwrap();
-                        let block = vp.block.clone().unwrap();
-
-                        let key = (voter, dao_id, block);
-                        deduped_vps.insert(key, vp);
-                    }
-
-                    let final_vps: Vec<voting_power_timeseries::ActiveModel> =
-                        deduped_vps.into_values().collect();
+                    // Keep one row per (voter, dao_id, block) so the stored rows still
+                    // chain through their previous balances
+                    let final_vps = merge_same_block_voting_powers(vps);
 
                     if let Err(e) = store_voting_powers(final_vps).await {
                         error!(error = %e, "Failed to store voting powers");
+                        record_failed_events("ARBToken::DelegateVotesChanged", &payloads, &e).await;
                     }
                 }
 
@@ -251,9 +278,112 @@ async fn delegate_votes_changed_handler(
     .await;
 }
 
+#[instrument(name = "arb_token_transfer_handler", skip(manifest_path, registry))]
+async fn transfer_handler(manifest_path: &PathBuf, registry: &mut EventCallbackRegistry) {
+    ARBTokenEventType::Transfer(
+        TransferEvent::handler(
+            |results, context| async move {
+                if results.is_empty() {
+                    debug!("No Transfer events to process in this batch.");
+                    return Ok(());
+                }
+
+                let results_len = results.len();
+                debug!(
+                    event_count = results_len,
+                    event_name = "ARBToken::Transfer",
+                    "Processing events"
+                );
+
+                let dao_id = get_dao_id()
+                    .ok_or_else(|| rindexer_error!("Failed to get DAO ID for 'arbitrum'"))
+                    .unwrap();
+
+                let built: Vec<(EventPayload, Result<token_transfer::ActiveModel>)> =
+                    stream::iter(results)
+                        .map(|result| async move {
+                            let payload =
+                                EventPayload::new(&result.event_data, &result.tx_information);
+                            let transfer = build_transfer_model_from_event(
+                                &result.event_data,
+                                &payload,
+                                dao_id,
+                            )
+                            .await;
+                            (payload, transfer)
+                        })
+                        .buffer_unordered(CONCURRENCY_LIMIT)
+                        .collect()
+                        .await;
+                let (payloads, transfers): (Vec<_>, Vec<_>) =
+                    record_build_failures("ARBToken::Transfer", built)
+                        .await
+                        .into_iter()
+                        .unzip();
+
+                if let Err(e) = store_token_transfers(transfers).await {
+                    error!(error = %e, "Failed to store token transfers");
+                    record_failed_events("ARBToken::Transfer", &payloads, &e).await;
+                }
+
+                info!(
+                    event_name = "ARBToken::Transfer",
+                    indexed_event_count = results_len,
+                    status = "INDEXED",
+                    "ARBToken::Transfer - INDEXED"
+                );
+
+                Ok(())
+            },
+            no_extensions(),
+        )
+        .await,
+    )
+    .register(manifest_path, registry)
+    .await;
+}
+
 #[instrument(name = "arb_token_handlers", skip(manifest_path, registry))]
 pub async fn arb_token_handlers(manifest_path: &PathBuf, registry: &mut EventCallbackRegistry) {
     delegate_changed_handler(manifest_path, registry).await;
     delegate_votes_changed_handler(manifest_path, registry).await;
+    transfer_handler(manifest_path, registry).await;
     info!("ARB Token handlers registered.");
 }
+
+/// Replay a dead-lettered event through the same processing as its handler.
+#[instrument(name = "arb_token_replay_event", skip(payload), fields(transaction_hash = %payload.transaction_hash, log_index = payload.log_index))]
+pub async fn replay_event(event_name: &str, payload: &EventPayload) -> Result<()> {
+    let dao_id = get_dao_id().context("Failed to get DAO ID for 'arbitrum'")?;
+
+    match event_name {
+        "DelegateChanged" => {
+            let delegation = build_delegation_model_from_event(
+                &payload.decode::<DelegateChangedData>()?,
+                payload,
+                dao_id,
+            )
+            .await?;
+            store_delegations(vec![delegation]).await
+        }
+        "DelegateVotesChanged" => {
+            let vp = build_voting_power_model_from_event(
+                &payload.decode::<DelegateVotesChangedData>()?,
+                payload,
+                dao_id,
+            )
+            .await?;
+            store_voting_powers(vec![vp]).await
+        }
+        "Transfer" => {
+            let transfer = build_transfer_model_from_event(
+                &payload.decode::<TransferData>()?,
+                payload,
+                dao_id,
+            )
+            .await?;
+            store_token_transfers(vec![transfer]).await
+        }
+        _ => anyhow::bail!("No ARBToken handler for event {event_name}"),
+    }
+}
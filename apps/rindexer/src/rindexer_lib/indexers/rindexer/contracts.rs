@@ -1,59 +1,295 @@
-use crate::rindexer_lib::typings::{
-    networks::{get_arbitrum_provider, get_ethereum_provider},
-    rindexer::events::{
-        arbitrum_core_governor_abi_gen::RindexerArbitrumCoreGovernorGen::{
-            self, RindexerArbitrumCoreGovernorGenInstance,
+use crate::{
+    extensions::multicall::Multicall,
+    rindexer_lib::typings::{
+        networks::{get_arbitrum_provider, get_ethereum_provider},
+        rindexer::events::{
+            arbitrum_core_governor_abi_gen::RindexerArbitrumCoreGovernorGen::{
+                self, RindexerArbitrumCoreGovernorGenInstance,
+            },
+            arbitrum_sc_nominations_abi_gen::RindexerArbitrumSCNominationsGen::{
+                self, RindexerArbitrumSCNominationsGenInstance,
+            },
+            arbitrum_treasury_governor_abi_gen::RindexerArbitrumTreasuryGovernorGen::{
+                self, RindexerArbitrumTreasuryGovernorGenInstance,
+            },
+            uni_governor_abi_gen::RindexerUniGovernorGen::{self, RindexerUniGovernorGenInstance},
         },
-        arbitrum_sc_nominations_abi_gen::RindexerArbitrumSCNominationsGen::{
-            self, RindexerArbitrumSCNominationsGenInstance,
-        },
-        arbitrum_treasury_governor_abi_gen::RindexerArbitrumTreasuryGovernorGen::{
-            self, RindexerArbitrumTreasuryGovernorGenInstance,
-        },
-        uni_governor_abi_gen::RindexerUniGovernorGen::{self, RindexerUniGovernorGenInstance},
     },
 };
-use alloy::{network::AnyNetwork, primitives::Address};
+use alloy::{
+    network::AnyNetwork,
+    primitives::{Address, U256, address},
+    sol,
+};
+use anyhow::{Result, anyhow};
 use rindexer::provider::RindexerProvider;
 use std::sync::Arc;
 
+pub const ARBITRUM_CORE_GOVERNOR_ADDRESS: Address =
+    address!("0xf07ded9dc292157749b6fd268e37df6ea38395b9");
+pub const ARBITRUM_SC_NOMINATIONS_ADDRESS: Address =
+    address!("0x8a1cda8dee421cd06023470608605934c16a05a0");
+pub const ARBITRUM_TREASURY_GOVERNOR_ADDRESS: Address =
+    address!("0x789fc99093b09ad01c34dc7251d0c89ce743e5a4");
+pub const UNI_GOVERNOR_ADDRESS: Address = address!("0x408ed6354d4973f66138c91495f2f2fcbd8724c3");
+
+// Read-only subset shared by the OpenZeppelin governors (core, treasury and
+// SC nominations), used to batch reads across proposals.
+sol! {
+    interface IGovernorReads {
+        function state(uint256 proposalId) external view returns (uint8);
+        function proposalSnapshot(uint256 proposalId) external view returns (uint256);
+        function quorum(uint256 blockNumber) external view returns (uint256);
+        function proposalVotes(uint256 proposalId) external view returns (uint256 againstVotes, uint256 forVotes, uint256 abstainVotes);
+        function proposalEta(uint256 proposalId) external view returns (uint256);
+    }
+}
+
+// GovernorBravo (Uniswap) keeps its vote totals in the proposals mapping.
+sol! {
+    interface IGovernorBravoReads {
+        function proposals(uint256 proposalId) external view returns (uint256 id, address proposer, uint256 eta, uint256 startBlock, uint256 endBlock, uint256 forVotes, uint256 againstVotes, uint256 abstainVotes, bool canceled, bool executed);
+        function quorumVotes() external view returns (uint256);
+    }
+}
+
+/// On-chain vote totals of a proposal, in token wei.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GovernorVoteTotals {
+    pub for_votes: U256,
+    pub against_votes: U256,
+    pub abstain_votes: U256,
+}
+
 pub async fn arbitrum_core_governor_contract(
     network: &str,
 ) -> RindexerArbitrumCoreGovernorGenInstance<Arc<RindexerProvider>, AnyNetwork> {
-    let address: Address = "0xf07ded9dc292157749b6fd268e37df6ea38395b9"
-        .parse()
-        .expect("Invalid address");
-    RindexerArbitrumCoreGovernorGen::new(address, provider_for_network(network).await)
+    RindexerArbitrumCoreGovernorGen::new(
+        ARBITRUM_CORE_GOVERNOR_ADDRESS,
+        provider_for_network(network).await,
+    )
 }
 
 pub async fn arbitrum_sc_nominations_contract(
     network: &str,
 ) -> RindexerArbitrumSCNominationsGenInstance<Arc<RindexerProvider>, AnyNetwork> {
-    let address: Address = "0x8a1cda8dee421cd06023470608605934c16a05a0"
-        .parse()
-        .expect("Invalid address");
-    RindexerArbitrumSCNominationsGen::new(address, provider_for_network(network).await)
+    RindexerArbitrumSCNominationsGen::new(
+        ARBITRUM_SC_NOMINATIONS_ADDRESS,
+        provider_for_network(network).await,
+    )
 }
 
 pub async fn arbitrum_treasury_governor_contract(
     network: &str,
 ) -> RindexerArbitrumTreasuryGovernorGenInstance<Arc<RindexerProvider>, AnyNetwork> {
-    let address: Address = "0x789fc99093b09ad01c34dc7251d0c89ce743e5a4"
-        .parse()
-        .expect("Invalid address");
-    RindexerArbitrumTreasuryGovernorGen::new(address, provider_for_network(network).await)
+    RindexerArbitrumTreasuryGovernorGen::new(
+        ARBITRUM_TREASURY_GOVERNOR_ADDRESS,
+        provider_for_network(network).await,
+    )
 }
 
 pub async fn uni_governor_contract(
     network: &str,
 ) -> RindexerUniGovernorGenInstance<Arc<RindexerProvider>, AnyNetwork> {
-    let address: Address = "0x408ed6354d4973f66138c91495f2f2fcbd8724c3"
-        .parse()
-        .expect("Invalid address");
-    RindexerUniGovernorGen::new(address, provider_for_network(network).await)
+    RindexerUniGovernorGen::new(UNI_GOVERNOR_ADDRESS, provider_for_network(network).await)
+}
+
+/// `state(proposalId)` for every id, in one multicall round trip. Results
+/// line up with `proposal_ids`.
+pub async fn batch_governor_states(
+    network: &str,
+    governor: Address,
+    proposal_ids: &[U256],
+) -> Vec<Result<u8>> {
+    let mut batch = Multicall::new(provider_for_network(network).await);
+    let calls: Vec<_> = proposal_ids
+        .iter()
+        .map(|id| batch.add(governor, &IGovernorReads::stateCall { proposalId: *id }))
+        .collect();
+
+    let results = batch.execute().await;
+    calls.iter().map(|call| results.get(call)).collect()
+}
+
+/// `quorum(proposalSnapshot(proposalId))` for every id, in two multicall
+/// round trips regardless of the number of proposals. Results line up with
+/// `proposal_ids`.
+pub async fn batch_governor_quorums(
+    network: &str,
+    governor: Address,
+    proposal_ids: &[U256],
+) -> Vec<Result<U256>> {
+    let provider = provider_for_network(network).await;
+
+    let mut snapshot_batch = Multicall::new(provider.clone());
+    let snapshot_calls: Vec<_> = proposal_ids
+        .iter()
+        .map(|id| {
+            snapshot_batch.add(
+                governor,
+                &IGovernorReads::proposalSnapshotCall { proposalId: *id },
+            )
+        })
+        .collect();
+    let snapshots = snapshot_batch.execute().await;
+
+    let mut quorum_batch = Multicall::new(provider);
+    let quorum_calls: Vec<_> = snapshot_calls
+        .iter()
+        .map(|call| {
+            snapshots.get(call).map(|snapshot_block| {
+                quorum_batch.add(
+                    governor,
+                    &IGovernorReads::quorumCall {
+                        blockNumber: snapshot_block,
+                    },
+                )
+            })
+        })
+        .collect();
+    let quorums = quorum_batch.execute().await;
+
+    quorum_calls
+        .into_iter()
+        .map(|call| match call {
+            Ok(call) => quorums.get(&call),
+            Err(e) => Err(anyhow!("Failed to fetch proposal snapshot block: {e}")),
+        })
+        .collect()
+}
+
+/// `proposalVotes(proposalId)` of an OpenZeppelin governor for every id, in
+/// one multicall round trip, at `block` or the latest block. Results line up
+/// with `proposal_ids`.
+pub async fn batch_governor_vote_totals(
+    network: &str,
+    governor: Address,
+    proposal_ids: &[U256],
+    block: Option<u64>,
+) -> Vec<Result<GovernorVoteTotals>> {
+    let mut batch = Multicall::new(provider_for_network(network).await);
+    if let Some(block) = block {
+        batch = batch.at_block(block);
+    }
+    let calls: Vec<_> = proposal_ids
+        .iter()
+        .map(|id| {
+            batch.add(
+                governor,
+                &IGovernorReads::proposalVotesCall { proposalId: *id },
+            )
+        })
+        .collect();
+
+    let results = batch.execute().await;
+    calls
+        .iter()
+        .map(|call| {
+            results.get(call).map(|votes| GovernorVoteTotals {
+                for_votes: votes.forVotes,
+                against_votes: votes.againstVotes,
+                abstain_votes: votes.abstainVotes,
+            })
+        })
+        .collect()
+}
+
+/// `proposals(proposalId)` vote totals of a GovernorBravo governor for every
+/// id, in one multicall round trip, at `block` or the latest block. Results
+/// line up with `proposal_ids`.
+pub async fn batch_bravo_vote_totals(
+    network: &str,
+    governor: Address,
+    proposal_ids: &[U256],
+    block: Option<u64>,
+) -> Vec<Result<GovernorVoteTotals>> {
+    let mut batch = Multicall::new(provider_for_network(network).await);
+    if let Some(block) = block {
+        batch = batch.at_block(block);
+    }
+    let calls: Vec<_> = proposal_ids
+        .iter()
+        .map(|id| {
+            batch.add(
+                governor,
+                &IGovernorBravoReads::proposalsCall { proposalId: *id },
+            )
+        })
+        .collect();
+
+    let results = batch.execute().await;
+    calls
+        .iter()
+        .map(|call| {
+            results.get(call).map(|proposal| GovernorVoteTotals {
+                for_votes: proposal.forVotes,
+                against_votes: proposal.againstVotes,
+                abstain_votes: proposal.abstainVotes,
+            })
+        })
+        .collect()
+}
+
+/// `proposalEta(proposalId)` of an OpenZeppelin governor for every id, in
+/// one multicall round trip. Unqueued proposals have an ETA of zero. Results
+/// line up with `proposal_ids`.
+pub async fn batch_governor_etas(
+    network: &str,
+    governor: Address,
+    proposal_ids: &[U256],
+) -> Vec<Result<U256>> {
+    let mut batch = Multicall::new(provider_for_network(network).await);
+    let calls: Vec<_> = proposal_ids
+        .iter()
+        .map(|id| {
+            batch.add(
+                governor,
+                &IGovernorReads::proposalEtaCall { proposalId: *id },
+            )
+        })
+        .collect();
+
+    let results = batch.execute().await;
+    calls.iter().map(|call| results.get(call)).collect()
+}
+
+/// `proposals(proposalId).eta` of a GovernorBravo governor for every id, in
+/// one multicall round trip. Results line up with `proposal_ids`.
+pub async fn batch_bravo_etas(
+    network: &str,
+    governor: Address,
+    proposal_ids: &[U256],
+) -> Vec<Result<U256>> {
+    let mut batch = Multicall::new(provider_for_network(network).await);
+    let calls: Vec<_> = proposal_ids
+        .iter()
+        .map(|id| {
+            batch.add(
+                governor,
+                &IGovernorBravoReads::proposalsCall { proposalId: *id },
+            )
+        })
+        .collect();
+
+    let results = batch.execute().await;
+    calls
+        .iter()
+        .map(|call| results.get(call).map(|proposal| proposal.eta))
+        .collect()
+}
+
+/// `quorumVotes()` of a GovernorBravo governor, the same for every proposal.
+pub async fn bravo_quorum_votes(network: &str, governor: Address) -> Result<U256> {
+    let mut batch = Multicall::new(provider_for_network(network).await);
+    let call = batch.add(governor, &IGovernorBravoReads::quorumVotesCall {});
+    batch.execute().await.get(&call)
+}
+
+pub async fn multicall_for_network(network: &str) -> Multicall {
+    Multicall::new(provider_for_network(network).await)
 }
 
-async fn provider_for_network(network: &str) -> Arc<RindexerProvider> {
+pub async fn provider_for_network(network: &str) -> Arc<RindexerProvider> {
     match network {
         "ethereum" => get_ethereum_provider().await,
         "arbitrum" => get_arbitrum_provider().await,
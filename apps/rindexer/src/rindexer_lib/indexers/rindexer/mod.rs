@@ -3,6 +3,6 @@ pub mod arb_token;
 pub mod arbitrum_core_governor;
 pub mod arbitrum_sc_nominations;
 pub mod arbitrum_treasury_governor;
-mod contracts;
+pub mod contracts;
 pub mod uni_governor;
 pub mod uni_token;
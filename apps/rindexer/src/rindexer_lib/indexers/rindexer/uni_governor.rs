@@ -1,7 +1,7 @@
 #![allow(non_snake_case)]
 use super::super::super::typings::rindexer::events::uni_governor::{
-    ProposalCreatedData, ProposalCreatedEvent, ProposalExecutedEvent, UniGovernorEventType,
-    VoteCastData, VoteCastEvent, no_extensions,
+    ProposalCreatedData, ProposalCreatedEvent, ProposalExecutedData, ProposalExecutedEvent,
+    UniGovernorEventType, VoteCastData, VoteCastEvent, no_extensions,
 };
 use super::contracts::uni_governor_contract;
 use crate::{
@@ -11,6 +11,11 @@ use crate::{
             DAO_SLUG_GOVERNOR_TYPE_ID_MAP, DAO_SLUG_ID_MAP, DB,
             calculate_total_delegated_voting_power, store_proposal, store_votes,
         },
+        failed_events::{
+            EventPayload, record_build_failures, record_failed_event, store_votes_or_record,
+        },
+        proposal_description::parse_proposal_description,
+        proposal_transitions::{StateChange, TransitionSource, proposal_state_from_contract},
     },
     rindexer_lib::typings::networks::get_ethereum_provider,
 };
@@ -37,11 +42,7 @@ use sea_orm::{
 };
 use serde::Deserialize;
 use serde_json::json;
-use std::{
-    collections::{HashMap, HashSet},
-    path::PathBuf,
-    sync::Arc,
-};
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
 use tracing::{debug, error, info, instrument, warn};
 
 fn get_governor_id() -> Option<Uuid> {
@@ -94,20 +95,6 @@ struct DecodedVoteCastLog {
     transaction_hash: String,
 }
 
-fn proposal_state_from_contract(state_code: u8) -> ProposalState {
-    match state_code {
-        0 => ProposalState::Pending,
-        1 => ProposalState::Active,
-        2 => ProposalState::Canceled,
-        3 => ProposalState::Defeated,
-        4 => ProposalState::Succeeded,
-        5 => ProposalState::Queued,
-        6 => ProposalState::Expired,
-        7 => ProposalState::Executed,
-        _ => ProposalState::Unknown,
-    }
-}
-
 fn vote_choice_from_support(support: u8) -> serde_json::Value {
     match support {
         0 => 1.into(),
@@ -138,16 +125,21 @@ async fn build_proposal_model_from_event(
         .await
         .context("Failed to estimate proposal end_at timestamp")?;
 
+    let dao_id = get_dao_id().context("Failed to get DAO ID")?;
+    let description = parse_proposal_description(&event_data.description, dao_id).await;
+
     Ok(proposal::ActiveModel {
         id: NotSet,
         external_id: Set(event_data.id.to_string()),
-        name: Set(extract_title(&event_data.description)),
+        name: Set(description.title.clone()),
         body: Set(event_data.description.clone()),
         url: Set(format!(
             "https://www.tally.xyz/gov/uniswap/proposal/{}",
             event_data.id
         )),
-        discussion_url: NotSet,
+        discussion_url: description
+            .discussion_url()
+            .map_or(NotSet, |url| Set(Some(url))),
         choices: Set(json!(["For", "Against", "Abstain"])),
         quorum: Set(4_000_000.0),
         proposal_state: Set(proposal_state),
@@ -166,11 +158,13 @@ async fn build_proposal_model_from_event(
             "values": event_data.values,
             "calldatas": event_data.calldatas,
             "signatures": event_data.signatures,
+            "references": description.references,
+            "sections": description.sections,
         })
         .into()),
         txid: Set(Some(transaction_hash.to_string())),
-        governor_id: Set(get_governor_id().unwrap()),
-        dao_id: Set(get_dao_id().unwrap()),
+        governor_id: Set(get_governor_id().context("Failed to get governor ID")?),
+        dao_id: Set(dao_id),
         author: Set(Some(event_data.proposer.to_string())),
     })
 }
@@ -180,21 +174,12 @@ async fn build_vote_model_from_event(
     block_number: u64,
     transaction_hash: &str,
     governor_id: Uuid,
-) -> Option<vote::ActiveModel> {
-    let created_at = match estimate_timestamp("ethereum", block_number).await {
-        Ok(ts) => ts,
-        Err(e) => {
-            error!(
-                proposal_id = %event_data.proposalId,
-                block_number = block_number,
-                error = %e,
-                "Failed to estimate created_at timestamp for VoteCast event"
-            );
-            return None;
-        }
-    };
+) -> Result<vote::ActiveModel> {
+    let created_at = estimate_timestamp("ethereum", block_number)
+        .await
+        .context("Failed to estimate created_at timestamp for VoteCast event")?;
 
-    Some(vote::ActiveModel {
+    Ok(vote::ActiveModel {
         id: NotSet,
         voter_address: Set(event_data.voter.to_string()),
         choice: Set(vote_choice_from_support(event_data.support)),
@@ -210,10 +195,82 @@ async fn build_vote_model_from_event(
         proposal_external_id: Set(event_data.proposalId.to_string()),
         proposal_id: NotSet,
         governor_id: Set(governor_id),
-        dao_id: Set(get_dao_id().unwrap()),
+        dao_id: Set(get_dao_id().context("Failed to get DAO ID")?),
     })
 }
 
+#[instrument(name = "uni_governor_process_proposal_created", skip_all, fields(proposal_id = %event_data.id))]
+async fn process_proposal_created(
+    event_data: &ProposalCreatedData,
+    payload: &EventPayload,
+) -> Result<()> {
+    let proposal_id = event_data.id;
+    let uni_governor = uni_governor_contract("ethereum").await;
+
+    let proposal_state_result = uni_governor.state(proposal_id).call().await;
+    let proposal_state = match proposal_state_result {
+        Ok(state_enum) => proposal_state_from_contract(state_enum),
+        Err(e) => {
+            error!(proposal_id = %proposal_id, error = %e, "Failed to fetch proposal state from contract, defaulting to Unknown");
+            ProposalState::Unknown
+        }
+    };
+
+    let proposal = build_proposal_model_from_event(
+        event_data,
+        payload.block_number,
+        &payload.transaction_hash,
+        proposal_state,
+    )
+    .await
+    .context("Failed to build proposal model from ProposalCreated event")?;
+
+    store_proposal(proposal, &StateChange::event(payload))
+        .await
+        .context("Failed to store proposal")?;
+    debug!(proposal_id = %proposal_id, "Proposal stored");
+
+    Ok(())
+}
+
+#[instrument(name = "uni_governor_process_proposal_executed", skip_all, fields(proposal_id = %event_data.id))]
+async fn process_proposal_executed(
+    event_data: &ProposalExecutedData,
+    payload: &EventPayload,
+) -> Result<()> {
+    let proposal_id = event_data.id;
+    let proposal = proposal::ActiveModel {
+        id: NotSet,
+        external_id: Set(proposal_id.to_string()),
+        name: NotSet,
+        body: NotSet,
+        url: NotSet,
+        discussion_url: NotSet,
+        choices: NotSet,
+        quorum: NotSet,
+        proposal_state: Set(ProposalState::Executed),
+        marked_spam: NotSet,
+        created_at: NotSet,
+        start_at: NotSet,
+        end_at: NotSet,
+        block_created_at: NotSet,
+        block_start_at: NotSet,
+        block_end_at: NotSet,
+        metadata: NotSet,
+        txid: NotSet,
+        governor_id: Set(get_governor_id().context("Failed to get governor ID")?),
+        dao_id: Set(get_dao_id().context("Failed to get DAO ID")?),
+        author: NotSet,
+    };
+
+    store_proposal(proposal, &StateChange::event(payload))
+        .await
+        .context("Failed to update proposal state to Executed")?;
+    debug!(proposal_id = %proposal_id, "Proposal state updated to Executed");
+
+    Ok(())
+}
+
 async fn fetch_uni_governor_logs(
     topic_id: &str,
     from_block: u64,
@@ -364,48 +421,13 @@ async fn proposal_created_handler(manifest_path: &PathBuf, registry: &mut EventC
                 );
 
                 for result in results.clone() {
-                    let proposal_id = result.event_data.id;
-                    let block_number = result.tx_information.block_number;
-
-                    let uni_governor = uni_governor_contract("ethereum").await;
-
-                    let proposal_state_result = uni_governor.state(proposal_id).call().await;
-                    let proposal_state = match proposal_state_result {
-                        Ok(state_enum) => proposal_state_from_contract(state_enum),
-                        Err(e) => {
-                            error!(proposal_id = %proposal_id, error = %e, "Failed to fetch proposal state from contract, defaulting to Unknown");
-                            ProposalState::Unknown
-                        }
-                    };
-
-                    let proposal = match build_proposal_model_from_event(
-                        &result.event_data,
-                        block_number,
-                        &result.tx_information.transaction_hash.to_string(),
-                        proposal_state,
-                    )
-                    .await
-                    {
-                        Ok(proposal) => proposal,
-                        Err(e) => {
-                            error!(
-                                proposal_id = %proposal_id,
-                                block_number = block_number,
-                                error = %e,
-                                "Failed to build proposal model from ProposalCreated event"
-                            );
-                            continue;
-                        }
-                    };
-
-                    if let Err(e) = store_proposal(proposal).await {
-                        error!(proposal_id = %proposal_id, error = %e, "Failed to store proposal");
-                    } else {
-                        debug!(proposal_id = %proposal_id, external_id = %result.event_data.id, "Proposal stored");
+                    let payload = EventPayload::new(&result.event_data, &result.tx_information);
+                    if let Err(e) = process_proposal_created(&result.event_data, &payload).await {
+                        error!(proposal_id = %result.event_data.id, error = %e, "Failed to process ProposalCreated event");
+                        record_failed_event("UniGovernor::ProposalCreated", &payload, &e).await;
                     }
                 }
 
-
                 info!(
                     event_name = "UniGovernor::ProposalCreated",
                     event_count = results.len(),
@@ -443,35 +465,10 @@ async fn proposal_executed_handler(manifest_path: &PathBuf, registry: &mut Event
                 );
 
                 for result in results.clone() {
-                    let proposal_id = result.event_data.id;
-                    let proposal = proposal::ActiveModel {
-                        id: NotSet,
-                        external_id: Set(proposal_id.to_string()),
-                        name: NotSet,
-                        body: NotSet,
-                        url: NotSet,
-                        discussion_url: NotSet,
-                        choices: NotSet,
-                        quorum: NotSet,
-                        proposal_state: Set(ProposalState::Executed),
-                        marked_spam: NotSet,
-                        created_at: NotSet,
-                        start_at: NotSet,
-                        end_at: NotSet,
-                        block_created_at: NotSet,
-                        block_start_at: NotSet,
-                        block_end_at: NotSet,
-                        metadata: NotSet,
-                        txid: NotSet,
-                        governor_id: Set(get_governor_id().unwrap()),
-                        dao_id: Set(get_dao_id().unwrap()),
-                        author: NotSet,
-                    };
-
-                    if let Err(e) = store_proposal(proposal).await {
-                        error!(proposal_id = %proposal_id, error = %e, "Failed to update proposal state to Executed");
-                    } else {
-                        debug!(proposal_id = %proposal_id, external_id = %result.event_data.id, "Proposal state updated to Executed");
+                    let payload = EventPayload::new(&result.event_data, &result.tx_information);
+                    if let Err(e) = process_proposal_executed(&result.event_data, &payload).await {
+                        error!(proposal_id = %result.event_data.id, error = %e, "Failed to process ProposalExecuted event");
+                        record_failed_event("UniGovernor::ProposalExecuted", &payload, &e).await;
                     }
                 }
                 info!(
@@ -510,24 +507,27 @@ async fn vote_cast_handler(manifest_path: &PathBuf, registry: &mut EventCallback
                 );
                 let governor_id_for_votes = get_governor_id().unwrap();
 
-                let votes: Vec<vote::ActiveModel> = stream::iter(results)
-                    .map(|result| async move {
-                        build_vote_model_from_event(
-                            &result.event_data,
-                            result.tx_information.block_number,
-                            &result.tx_information.transaction_hash.to_string(),
-                            governor_id_for_votes,
-                        )
-                        .await
-                    })
-                    .buffer_unordered(CONCURRENCY_LIMIT)
-                    .filter_map(|vote_opt| async { vote_opt }) // Filter out None values
-                    .collect::<Vec<_>>()
-                    .await;
-
-                if let Err(e) = store_votes(votes, governor_id_for_votes).await {
-                    error!(error = %e, "Failed to store votes");
-                }
+                let built_votes: Vec<(EventPayload, Result<vote::ActiveModel>)> =
+                    stream::iter(results)
+                        .map(|result| async move {
+                            let payload =
+                                EventPayload::new(&result.event_data, &result.tx_information);
+                            let vote = build_vote_model_from_event(
+                                &result.event_data,
+                                payload.block_number,
+                                &payload.transaction_hash,
+                                governor_id_for_votes,
+                            )
+                            .await;
+                            (payload, vote)
+                        })
+                        .buffer_unordered(CONCURRENCY_LIMIT)
+                        .collect()
+                        .await;
+
+                let votes = record_build_failures("UniGovernor::VoteCast", built_votes).await;
+
+                store_votes_or_record("UniGovernor::VoteCast", votes, governor_id_for_votes).await;
 
                 info!(
                     event_name = "UniGovernor::VoteCast",
@@ -554,32 +554,36 @@ pub async fn uni_governor_handlers(manifest_path: &PathBuf, registry: &mut Event
     info!("Uniswap Governor handlers registered.");
 }
 
-fn extract_title(description: &str) -> String {
-    let mut lines = description
-        .split('\n')
-        .filter(|line| !line.trim().is_empty());
-
-    // Try to find the first non-empty line that isn't just "#" markers
-    let title = lines
-        .find(|line| {
-            let trimmed = line.trim_start_matches('#').trim();
-            !trimmed.is_empty()
-        })
-        .unwrap_or("Unknown")
-        .trim_start_matches('#')
-        .trim()
-        .to_string();
-
-    // Truncate to 120 chars if needed
-    if title.len() > 120 {
-        title.chars().take(120).collect()
-    } else {
-        title
+/// Replay a dead-lettered event through the same processing as its handler.
+#[instrument(name = "uni_governor_replay_event", skip(payload), fields(transaction_hash = %payload.transaction_hash, log_index = payload.log_index))]
+pub async fn replay_event(event_name: &str, payload: &EventPayload) -> Result<()> {
+    match event_name {
+        "ProposalCreated" => {
+            process_proposal_created(&payload.decode::<ProposalCreatedData>()?, payload).await
+        }
+        "ProposalExecuted" => {
+            process_proposal_executed(&payload.decode::<ProposalExecutedData>()?, payload).await
+        }
+        "VoteCast" => {
+            let governor_id = get_governor_id().context("Failed to get governor ID")?;
+            let vote = build_vote_model_from_event(
+                &payload.decode::<VoteCastData>()?,
+                payload.block_number,
+                &payload.transaction_hash,
+                governor_id,
+            )
+            .await?;
+            store_votes(vec![vote], governor_id).await
+        }
+        _ => anyhow::bail!("No UniGovernor handler for event {event_name}"),
     }
 }
 
+/// Store proposals below `proposalCount()` that are missing from the
+/// database, with their votes. Returns the backfilled proposal ids and vote
+/// counts.
 #[instrument(name = "uni_governor_backfill_missing_proposals", skip_all)]
-pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
+pub async fn backfill_missing_proposals_and_votes() -> Result<Vec<(u64, usize)>> {
     let db = DB.get().context("DB not initialized")?;
     let governor_id = get_governor_id().context("Failed to get Uniswap governor ID")?;
     let uni_governor = uni_governor_contract("ethereum").await;
@@ -608,7 +612,7 @@ pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
         .collect();
 
     if missing_ids.is_empty() {
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     warn!(
@@ -618,6 +622,7 @@ pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
         "Detected missing Uniswap proposals in the database; starting targeted backfill"
     );
 
+    let mut backfilled = Vec::new();
     for proposal_id in missing_ids {
         let proposal_state = match uni_governor.state(U256::from(proposal_id)).call().await {
             Ok(state) => proposal_state_from_contract(state),
@@ -680,7 +685,12 @@ pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
             }
         };
 
-        if let Err(error) = store_proposal(proposal_model).await {
+        let change = StateChange {
+            source: TransitionSource::Event,
+            block_number: Some(proposal_created_log.block_number),
+            txid: Some(proposal_created_log.transaction_hash.clone()),
+        };
+        if let Err(error) = store_proposal(proposal_model, &change).await {
             error!(
                 governor = "UNISWAP_GOVERNOR",
                 proposal_id = proposal_id,
@@ -699,7 +709,7 @@ pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
         .with_context(|| format!("Failed to fetch vote logs for missing proposal {proposal_id}"))?;
         let vote_count = vote_logs.len();
 
-        let votes: Vec<vote::ActiveModel> = stream::iter(vote_logs)
+        let votes = stream::iter(vote_logs)
             .map(|vote_log| async move {
                 build_vote_model_from_event(
                     &vote_log.event_data,
@@ -710,9 +720,11 @@ pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
                 .await
             })
             .buffer_unordered(CONCURRENCY_LIMIT)
-            .filter_map(|vote| async move { vote })
-            .collect()
-            .await;
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Failed to build votes for missing proposal {proposal_id}"))?;
 
         if !votes.is_empty() {
             store_votes(votes, governor_id).await.with_context(|| {
@@ -726,292 +738,14 @@ pub async fn backfill_missing_proposals_and_votes() -> Result<()> {
             vote_count = vote_count,
             "Backfilled missing Uniswap proposal and associated votes"
         );
+        backfilled.push((proposal_id, vote_count));
     }
 
-    Ok(())
+    Ok(backfilled)
 }
 
-#[instrument(name = "uni_governor_update_active_proposals_end_time", skip_all)]
-pub async fn update_active_proposals_end_time() -> Result<()> {
-    let db = DB.get().unwrap();
-
-    // Get all active proposals for this governor
-    let active_proposals = proposal::Entity::find()
-        .filter(
-            proposal::Column::GovernorId
-                .eq(get_governor_id().context("Failed to get governor ID")?),
-        )
-        .filter(proposal::Column::ProposalState.eq(ProposalState::Active))
-        .all(db)
-        .await
-        .context("Failed to fetch active proposals")?;
-
-    if active_proposals.is_empty() {
-        return Ok(());
-    }
-
-    info!(
-        governor = "UNISWAP_GOVERNOR",
-        active_proposals_count = active_proposals.len(),
-        "Updating end times for active proposals"
-    );
-
-    for proposal in active_proposals {
-        let proposal_id = proposal.external_id.clone();
-
-        let block_start_at = match proposal.block_start_at {
-            Some(block) => block as u64,
-            None => {
-                error!(proposal_id = %proposal_id, "Missing block_start_at for proposal");
-                continue;
-            }
-        };
-
-        let block_end_at = match proposal.block_end_at {
-            Some(block) => block as u64,
-            None => {
-                error!(proposal_id = %proposal_id, "Missing block_end_at for proposal");
-                continue;
-            }
-        };
-
-        // Re-fetch the times on the block
-        let new_start_at = match estimate_timestamp("ethereum", block_start_at).await {
-            Ok(ts) => ts,
-            Err(e) => {
-                error!(proposal_id = %proposal_id, block_number = block_start_at, error = %e, "Failed to estimate new start_at timestamp");
-                continue;
-            }
-        };
-        let new_end_at = match estimate_timestamp("ethereum", block_end_at).await {
-            Ok(ts) => ts,
-            Err(e) => {
-                error!(proposal_id = %proposal_id, block_number = block_end_at, error = %e, "Failed to estimate new end_at timestamp");
-                continue;
-            }
-        };
-
-        // Only update times changed
-        if new_end_at != proposal.end_at || new_start_at != proposal.start_at {
-            debug!(
-                proposal_id = %proposal_id,
-                old_end_at = ?proposal.end_at,
-                new_end_at = ?new_end_at,
-                old_start_at = ?proposal.start_at,
-                new_start_at = ?new_start_at,
-                "Updating proposal times"
-            );
-
-            // Update the proposal with the new times
-            let mut proposal_active_model: proposal::ActiveModel = proposal.clone().into();
-            proposal_active_model.end_at = Set(new_end_at);
-            proposal_active_model.start_at = Set(new_start_at);
-
-            if let Err(e) = proposal_active_model.update(db).await {
-                error!(proposal_id = %proposal_id, error = %e, "Failed to update proposal times");
-            }
-        }
-    }
-
-    info!(
-        governor = "UNISWAP_GOVERNOR",
-        "Successfully updated end times for active proposals"
-    );
-
-    Ok(())
-}
-
-#[instrument(name = "uni_governor_update_ended_proposals_state", skip_all)]
-pub async fn update_ended_proposals_state() -> Result<()> {
-    info!(
-        governor = "UNISWAP_GOVERNOR",
-        "Running task to update ended proposal states"
-    );
-    let db = DB.get().context("DB not initialized")?;
-
-    let active_proposals = proposal::Entity::find()
-        .filter(proposal::Column::ProposalState.eq(ProposalState::Active))
-        .filter(proposal::Column::EndAt.lt(chrono::Utc::now().naive_utc()))
-        .filter(
-            proposal::Column::GovernorId
-                .eq(get_governor_id().context("Failed to get governor ID")?),
-        )
-        .all(db)
-        .await
-        .context("Failed to fetch ended active proposals")?;
-
-    if active_proposals.is_empty() {
-        info!(
-            governor = "UNISWAP_GOVERNOR",
-            "No active proposals have ended"
-        );
-        return Ok(());
-    }
-
-    info!(
-        governor = "UNISWAP_GOVERNOR",
-        proposal_count = active_proposals.len(),
-        "Processing ended proposals"
-    );
-
-    for proposal in active_proposals {
-        info!(
-            governor = "UNISWAP_GOVERNOR",
-            proposal_id = proposal.external_id,
-            proposal_name = proposal.name,
-            proposal_end_at = ?proposal.end_at,
-            "Proposal end time reached. Calculating final state."
-        );
-
-        let votes = vote::Entity::find()
-            .filter(vote::Column::ProposalId.eq(proposal.id))
-            .all(db)
-            .await
-            .context("Failed to fetch votes for proposal")?;
-
-        let final_state = calculate_final_proposal_state(&proposal, &votes).await?;
-
-        let mut proposal_active_model: proposal::ActiveModel = proposal.clone().into();
-        proposal_active_model.proposal_state = Set(final_state.clone());
-        proposal::Entity::update(proposal_active_model)
-            .exec(db)
-            .await
-            .context("Failed to update proposal state")?;
-
-        info!(
-            governor = "UNISWAP_GOVERNOR",
-            proposal_id = proposal.external_id,
-            proposal_name = proposal.name,
-            final_state = ?final_state,
-            "Proposal state updated to final state."
-        );
-    }
-
-    info!(
-        governor = "UNISWAP_GOVERNOR",
-        "Task to update ended proposals state completed."
-    );
-    Ok(())
-}
-
-#[instrument(name = "uni_governor_calculate_final_proposal_state", skip(proposal, votes), fields(proposal_id = proposal.external_id))]
-async fn calculate_final_proposal_state(
-    proposal: &proposal::Model,
-    votes: &Vec<vote::Model>,
-) -> Result<ProposalState> {
-    let mut for_votes = 0.0;
-    let mut against_votes = 0.0;
-
-    let choices_value = proposal.choices.clone();
-    let choices_vec: Vec<String> =
-        serde_json::from_value(choices_value.clone()).unwrap_or_else(|_| vec![]);
-
-    let mut choice_map: HashMap<usize, String> = HashMap::new();
-    for (index, choice_text) in choices_vec.iter().enumerate() {
-        let lower_choice = choice_text.to_lowercase();
-        if lower_choice.contains("for") {
-            choice_map.insert(index, "for".to_string());
-        } else if lower_choice.contains("against") {
-            choice_map.insert(index, "against".to_string());
-        } else if lower_choice.contains("abstain") {
-            choice_map.insert(index, "abstain".to_string());
-        } else {
-            choice_map.insert(index, "unknown".to_string()); // Default case
-        }
-    }
-
-    for vote in votes {
-        match vote.choice.as_u64() {
-            Some(choice_index) => {
-                match choice_map.get(&(choice_index as usize)).map(|s| s.as_str()) {
-                    Some("for") => for_votes += vote.voting_power,
-                    Some("against") => against_votes += vote.voting_power,
-                    Some(_) | None => {
-                        debug!(
-                            proposal_id = proposal.external_id,
-                            choice_index = choice_index,
-                            "Unknown choice type at index for proposal"
-                        );
-                    }
-                }
-            }
-            None => {
-                error!(
-                    proposal_id = proposal.external_id,
-                    "Vote choice is not a valid u64 for proposal"
-                );
-            }
-        }
-    }
-    debug!(
-        proposal_id = proposal.external_id,
-        for_votes = for_votes,
-        against_votes = against_votes,
-        "Vote counts aggregated."
-    );
-
-    let mut quorum_votes = 0.0;
-    let metadata_value = proposal.metadata.clone();
-    let metadata = metadata_value.unwrap_or_default();
-
-    let quorum_choices_value = metadata.get("quorum_choices");
-    let quorum_choices_indexes: Vec<usize> = match quorum_choices_value {
-        Some(serde_json::Value::Array(arr)) => arr
-            .iter()
-            .filter_map(|v| v.as_u64().map(|u| u as usize))
-            .collect(),
-        _ => {
-            debug!(
-                proposal_id = proposal.external_id,
-                "quorum_choices not found or not an array in metadata, defaulting to [0, 1] (For and Against)."
-            );
-            vec![0, 1] // Default to For and Against if not configured
-        }
-    };
-
-    for vote in votes.iter().filter(|vote| {
-        vote.choice
-            .as_u64()
-            .is_some_and(|choice_index| quorum_choices_indexes.contains(&(choice_index as usize)))
-    }) {
-        quorum_votes += vote.voting_power;
-    }
-
-    let quorum = proposal.quorum;
-    debug!(
-        proposal_id = proposal.external_id,
-        quorum_votes = quorum_votes,
-        required_quorum = quorum,
-        "Quorum votes calculated."
-    );
-
-    if quorum_votes >= quorum && for_votes > against_votes {
-        info!(
-            proposal_id = proposal.external_id,
-            for_votes = for_votes,
-            against_votes = against_votes,
-            quorum_votes = quorum_votes,
-            required_quorum = quorum,
-            "Proposal Succeeded: For votes exceed against votes and quorum is met."
-        );
-        Ok(ProposalState::Succeeded)
-    } else {
-        info!(
-            proposal_id = proposal.external_id,
-            for_votes = for_votes,
-            against_votes = against_votes,
-            quorum_votes = quorum_votes,
-            required_quorum = quorum,
-            "Proposal Defeated: For votes did not exceed against votes or quorum not met."
-        );
-        Ok(ProposalState::Defeated)
-    }
-}
-
-/// Uniswap excluded voter address (Uniswap timelock contract)
-const UNISWAP_EXCLUDED_VOTER: &str = "0x1a9C8182C09F50C8318d769245beA52c32BE35BC";
-
 #[instrument(name = "uni_governor_calculate_total_delegated_vp", skip(timestamp), fields(timestamp = ?timestamp))]
 async fn calculate_total_delegated_vp(timestamp: NaiveDateTime) -> Result<f64> {
-    calculate_total_delegated_voting_power(timestamp, UNISWAP_EXCLUDED_VOTER).await
+    let dao_id = get_dao_id().context("Failed to get DAO ID")?;
+    calculate_total_delegated_voting_power(dao_id, timestamp).await
 }
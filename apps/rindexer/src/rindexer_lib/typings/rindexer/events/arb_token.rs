@@ -361,12 +361,112 @@ where
     }
 }
 
+pub fn transfer_handler<TExtensions, F, Fut>(
+    custom_logic: F,
+) -> TransferEventCallbackType<TExtensions>
+where
+    TransferResult: Clone + 'static,
+    F: for<'a> Fn(Vec<TransferResult>, Arc<EventContext<TExtensions>>) -> Fut
+        + Send
+        + Sync
+        + 'static
+        + Clone,
+    Fut: Future<Output = EventCallbackResult<()>> + Send + 'static,
+    TExtensions: Send + Sync + 'static,
+{
+    Arc::new(move |results, context| {
+        let custom_logic = custom_logic.clone();
+        let results = results.clone();
+        let context = Arc::clone(&context);
+        async move { (custom_logic)(results, context).await }.boxed()
+    })
+}
+
+type TransferEventCallbackType<TExtensions> = Arc<
+    dyn for<'a> Fn(
+            &'a Vec<TransferResult>,
+            Arc<EventContext<TExtensions>>,
+        ) -> BoxFuture<'a, EventCallbackResult<()>>
+        + Send
+        + Sync,
+>;
+
+pub struct TransferEvent<TExtensions>
+where
+    TExtensions: Send + Sync + 'static,
+{
+    callback: TransferEventCallbackType<TExtensions>,
+    context: Arc<EventContext<TExtensions>>,
+}
+
+impl<TExtensions> TransferEvent<TExtensions>
+where
+    TExtensions: Send + Sync + 'static,
+{
+    pub async fn handler<F, Fut>(closure: F, extensions: TExtensions) -> Self
+    where
+        TransferResult: Clone + 'static,
+        F: for<'a> Fn(Vec<TransferResult>, Arc<EventContext<TExtensions>>) -> Fut
+            + Send
+            + Sync
+            + 'static
+            + Clone,
+        Fut: Future<Output = EventCallbackResult<()>> + Send + 'static,
+    {
+        let (reorg_tx, _) = tokio::sync::broadcast::channel(16);
+        Self {
+            callback: transfer_handler(closure),
+            context: Arc::new(EventContext {
+                database: get_or_init_postgres_client().await,
+
+                extensions: Arc::new(extensions),
+                reorg_tx,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl<TExtensions> EventCallback for TransferEvent<TExtensions>
+where
+    TExtensions: Send + Sync,
+{
+    async fn call(&self, events: Vec<EventResult>) -> EventCallbackResult<()> {
+        let events_len = events.len();
+
+        // note some can not downcast because it cant decode
+        // this happens on events which failed decoding due to
+        // not having the right abi for example
+        // transfer events with 2 indexed topics cant decode
+        // transfer events with 3 indexed topics
+        let result: Vec<TransferResult> = events
+            .into_iter()
+            .filter_map(|item| {
+                item.decoded_data
+                    .downcast::<TransferData>()
+                    .ok()
+                    .map(|arc| TransferResult {
+                        event_data: (*arc).clone(),
+                        tx_information: item.tx_information,
+                    })
+            })
+            .collect();
+
+        if result.len() == events_len {
+            (self.callback)(&result, Arc::clone(&self.context)).await
+        } else {
+            panic!("TransferEvent: Unexpected data type - expected: TransferData")
+        }
+    }
+}
+
 pub enum ARBTokenEventType<TExtensions>
 where
     TExtensions: 'static + Send + Sync,
 {
     DelegateChanged(DelegateChangedEvent<TExtensions>),
     DelegateVotesChanged(DelegateVotesChangedEvent<TExtensions>),
+    Transfer(TransferEvent<TExtensions>),
 }
 
 impl<TExtensions> ARBTokenEventType<TExtensions>
@@ -381,6 +481,9 @@ where
             ARBTokenEventType::DelegateVotesChanged(_) => {
                 "0xdec2bacdd2f05b59de34da9b523dff8be42e5e38e818c82fdb0bae774387a724"
             }
+            ARBTokenEventType::Transfer(_) => {
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            }
         }
     }
 
@@ -388,6 +491,7 @@ where
         match self {
             ARBTokenEventType::DelegateChanged(_) => "DelegateChanged",
             ARBTokenEventType::DelegateVotesChanged(_) => "DelegateVotesChanged",
+            ARBTokenEventType::Transfer(_) => "Transfer",
         }
     }
 
@@ -427,6 +531,16 @@ where
                     }
                 })
             }
+
+            ARBTokenEventType::Transfer(_) => Arc::new(move |topics: Vec<B256>, data: Bytes| {
+                match TransferData::decode_raw_log(topics, &data[0..]) {
+                    Ok(event) => {
+                        let result: TransferData = event;
+                        Arc::new(result) as Arc<dyn Any + Send + Sync>
+                    }
+                    Err(error) => Arc::new(error) as Arc<dyn Any + Send + Sync>,
+                }
+            }),
         }
     }
 
@@ -526,6 +640,16 @@ where
                 });
                 (callback, reorg_sender)
             }
+
+            ARBTokenEventType::Transfer(event) => {
+                let reorg_sender = Some(event.context.reorg_tx.clone());
+                let event = Arc::new(event);
+                let callback = Arc::new(move |result| {
+                    let event = Arc::clone(&event);
+                    async move { event.call(result).await }.boxed()
+                });
+                (callback, reorg_sender)
+            }
         };
 
         registry.register_event(EventCallbackRegistryInformation {
@@ -0,0 +1,11 @@
+use crate::extensions::db_extension::DB;
+use anyhow::{Context, Result};
+use tracing::{info, instrument};
+use utils::anomalies::AnomalyDetector;
+
+#[instrument(name = "run_anomaly_detector", skip_all)]
+pub async fn run_anomaly_detector() -> Result<()> {
+    info!("Starting anomaly detector.");
+    let db = DB.get().context("DB not initialized")?;
+    AnomalyDetector::new(db.clone()).run().await
+}
@@ -0,0 +1,19 @@
+use crate::extensions::delegate_verification::{ProviderSignatureValidator, verify_pending_claims};
+use anyhow::Result;
+use tokio::time;
+use tracing::{info, instrument};
+
+#[instrument(name = "run_periodic_delegate_verification", skip_all)]
+pub async fn run_periodic_delegate_verification() -> Result<()> {
+    info!("Starting periodic task for delegate claim verification.");
+    let validator = ProviderSignatureValidator::new().await;
+    let mut interval = time::interval(time::Duration::from_secs(5 * 60));
+
+    loop {
+        interval.tick().await;
+        let checked = verify_pending_claims(&validator).await?;
+        if checked > 0 {
+            info!(checked, "Verified delegate claims");
+        }
+    }
+}
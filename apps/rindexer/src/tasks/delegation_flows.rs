@@ -0,0 +1,32 @@
+use crate::extensions::{db_extension::DAO_SLUG_ID_MAP, delegation_flows::sync_delegation_flows};
+use anyhow::{Context, Result};
+use tokio::time;
+use tracing::{info, instrument};
+
+/// DAOs whose token Transfer and DelegateChanged events are indexed.
+const DELEGATION_FLOW_DAOS: [&str; 2] = ["arbitrum", "uniswap"];
+
+#[instrument(name = "run_periodic_delegation_flow_sync", skip_all)]
+pub async fn run_periodic_delegation_flow_sync() -> Result<()> {
+    info!("Starting periodic task for delegation flow sync.");
+    let mut interval = time::interval(time::Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        for slug in DELEGATION_FLOW_DAOS {
+            let dao_id = DAO_SLUG_ID_MAP
+                .get()
+                .context("DAO_SLUG_ID_MAP not initialized")?
+                .lock()
+                .unwrap()
+                .get(slug)
+                .copied()
+                .with_context(|| format!("DAO not found for slug '{slug}'"))?;
+
+            sync_delegation_flows(dao_id)
+                .await
+                .with_context(|| format!("Failed to sync delegation flows for {slug}"))?;
+        }
+    }
+}
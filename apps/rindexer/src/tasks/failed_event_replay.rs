@@ -0,0 +1,82 @@
+use crate::{
+    extensions::failed_events::{
+        EventPayload, delete_failed_event, due_failed_events, reschedule_failed_event,
+    },
+    rindexer_lib::indexers::all_handlers::replay_event,
+};
+use anyhow::{Context, Result};
+use proposalsapp_db::models::failed_event;
+use tokio::time;
+use tracing::{info, instrument, warn};
+
+/// Failed events replayed per tick; the rest wait for the next one.
+const REPLAY_BATCH_SIZE: u64 = 100;
+
+/// Replay one failed event through the handler that recorded it, e.g.
+/// `ArbitrumCoreGovernor::VoteCast`.
+#[instrument(name = "replay_failed_event", skip_all, fields(failed_event_id = %failed_event.id, handler = %failed_event.handler))]
+pub async fn replay_failed_event(failed_event: &failed_event::Model) -> Result<()> {
+    let payload: EventPayload = serde_json::from_value(failed_event.payload.clone())
+        .context("Failed to parse failed event payload")?;
+    let (contract, event_name) = failed_event
+        .handler
+        .split_once("::")
+        .with_context(|| format!("Invalid handler name {}", failed_event.handler))?;
+
+    replay_event(contract, event_name, &payload).await
+}
+
+/// Replay a failed event and delete it once it succeeds, or push its next
+/// attempt back. Returns whether the replay succeeded.
+pub async fn replay_and_resolve(failed_event: &failed_event::Model) -> Result<bool> {
+    match replay_failed_event(failed_event).await {
+        Ok(()) => {
+            delete_failed_event(failed_event.id).await?;
+            info!(
+                failed_event_id = %failed_event.id,
+                handler = %failed_event.handler,
+                attempts = failed_event.attempts,
+                "Failed event replayed"
+            );
+            Ok(true)
+        }
+        Err(e) => {
+            warn!(
+                failed_event_id = %failed_event.id,
+                handler = %failed_event.handler,
+                attempts = failed_event.attempts,
+                error = %e,
+                "Failed event replay failed"
+            );
+            reschedule_failed_event(failed_event.id, &e).await?;
+            Ok(false)
+        }
+    }
+}
+
+#[instrument(name = "run_periodic_failed_event_replay", skip_all)]
+pub async fn run_periodic_failed_event_replay() -> Result<()> {
+    info!("Starting periodic task for failed event replay.");
+    let mut interval = time::interval(time::Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        let due = due_failed_events(REPLAY_BATCH_SIZE).await?;
+        if due.is_empty() {
+            continue;
+        }
+
+        let mut replayed = 0;
+        for failed_event in &due {
+            if replay_and_resolve(failed_event).await? {
+                replayed += 1;
+            }
+        }
+        info!(
+            due_count = due.len(),
+            replayed_count = replayed,
+            "Replayed due failed events"
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use crate::extensions::db_extension::DB;
+use anyhow::{Context, Result};
+use tokio::time;
+use tracing::{info, instrument};
+use utils::outbox::{RETENTION, prune};
+
+#[instrument(name = "run_periodic_governance_event_pruning", skip_all)]
+pub async fn run_periodic_governance_event_pruning() -> Result<()> {
+    info!("Starting periodic task for governance event pruning.");
+    let db = DB.get().context("DB not initialized")?;
+    let mut interval = time::interval(time::Duration::from_secs(60 * 60));
+
+    loop {
+        interval.tick().await;
+        prune(db, RETENTION).await?;
+    }
+}
@@ -0,0 +1,15 @@
+use crate::extensions::governor_backfill::backfill_governors;
+use anyhow::Result;
+use tokio::time;
+use tracing::{info, instrument};
+
+#[instrument(name = "run_periodic_governor_backfill", skip_all)]
+pub async fn run_periodic_governor_backfill() -> Result<()> {
+    info!("Starting periodic task for governor backfill.");
+    let mut interval = time::interval(time::Duration::from_secs(5 * 60));
+
+    loop {
+        interval.tick().await;
+        backfill_governors(None).await?;
+    }
+}
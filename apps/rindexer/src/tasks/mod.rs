@@ -1,2 +1,10 @@
+pub mod anomaly_detector;
+pub mod delegate_verification;
+pub mod delegation_flows;
+pub mod failed_event_replay;
+pub mod governance_events;
+pub mod governor_backfill;
+pub mod notifications;
 pub mod onchain_proposals_updates;
 pub mod snapshot_indexer;
+pub mod tally_audit;
@@ -0,0 +1,11 @@
+use crate::extensions::db_extension::DB;
+use anyhow::{Context, Result};
+use tracing::{info, instrument};
+use utils::notifications::NotificationProducer;
+
+#[instrument(name = "run_notification_producer", skip_all)]
+pub async fn run_notification_producer() -> Result<()> {
+    info!("Starting notification producer.");
+    let db = DB.get().context("DB not initialized")?;
+    NotificationProducer::new(db.clone()).run().await
+}
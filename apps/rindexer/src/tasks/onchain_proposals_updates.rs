@@ -1,52 +1,52 @@
-use crate::rindexer_lib::indexers::rindexer::{
-    arbitrum_core_governor, arbitrum_sc_nominations, arbitrum_treasury_governor, uni_governor,
-};
+use crate::extensions::proposal_transitions::{TransitionScheduler, schedule_changed};
 use anyhow::{Context, Result};
-use tokio::time;
-use tracing::{info, instrument};
+use chrono::Utc;
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, info, instrument};
 
-#[instrument(name = "run_periodic_proposal_state_update", skip_all)]
-pub async fn run_periodic_proposal_state_update() -> Result<()> {
-    info!("Starting periodic task for proposal state updates.");
-    let mut interval = time::interval(time::Duration::from_secs(60));
+/// The safety sweep re-estimates proposal times and reconciles states that
+/// aren't indexed, such as queueing and expiry.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30 * 60);
 
-    loop {
-        interval.tick().await;
+/// Upper bound on a sleep, so a wrong clock or estimate can't stall the
+/// scheduler until the next sweep.
+const MAX_SLEEP: Duration = Duration::from_secs(10 * 60);
 
-        uni_governor::backfill_missing_proposals_and_votes()
-            .await
-            .context("Failed to backfill missing proposals for uni_governor")?;
+/// Sleeps until the next expected proposal transition, the next sweep or a
+/// stored proposal, whichever comes first, then applies what is due.
+#[instrument(name = "run_proposal_state_scheduler", skip_all)]
+pub async fn run_proposal_state_scheduler() -> Result<()> {
+    info!("Starting proposal state scheduler.");
+    let mut scheduler = TransitionScheduler::default();
+    let mut next_sweep = Instant::now();
 
-        arbitrum_core_governor::update_active_proposals_end_time()
-            .await
-            .context("Failed to update active proposals end time for arbitrum_core_governor")?;
-        arbitrum_treasury_governor::update_active_proposals_end_time()
-            .await
-            .context("Failed to update active proposals end time for arbitrum_treasury_governor")?;
-        uni_governor::update_active_proposals_end_time()
-            .await
-            .context("Failed to update active proposals end time for uni_governor")?;
+    loop {
+        if Instant::now() >= next_sweep {
+            scheduler
+                .sweep()
+                .await
+                .context("Failed to sweep proposal states")?;
+            next_sweep = Instant::now() + SWEEP_INTERVAL;
+        }
 
-        arbitrum_core_governor::update_ended_proposals_state()
+        let next_due = scheduler
+            .apply_due_transitions()
             .await
-            .context("Failed to update ended proposals state for arbitrum_core_governor")?;
-        arbitrum_treasury_governor::update_ended_proposals_state()
-            .await
-            .context("Failed to update ended proposals state for arbitrum_treasury_governor")?;
-        uni_governor::update_ended_proposals_state()
-            .await
-            .context("Failed to update ended proposals state for uni_governor")?;
-        arbitrum_sc_nominations::update_ended_proposals_state()
-            .await
-            .context("Failed to update ended proposals state for arbitrum_sc_nominations")?;
+            .context("Failed to apply proposal transitions")?;
 
-        arbitrum_core_governor::update_active_proposals_quorum()
-            .await
-            .context("Failed to update active proposals quorum for arbitrum_core_governor")?;
-        arbitrum_treasury_governor::update_active_proposals_quorum()
-            .await
-            .context("Failed to update active proposals quorum for arbitrum_treasury_governor")?;
+        let until_due = next_due
+            .map(|due| {
+                (due - Utc::now().naive_utc())
+                    .to_std()
+                    .unwrap_or(Duration::ZERO)
+            })
+            .unwrap_or(MAX_SLEEP);
+        let wake_at = (Instant::now() + until_due.min(MAX_SLEEP)).min(next_sweep);
+        debug!(next_due = ?next_due, sleep_secs = (wake_at - Instant::now()).as_secs(), "Proposal state scheduler sleeping");
 
-        info!("Successfully updated proposals states");
+        tokio::select! {
+            _ = time::sleep_until(wake_at) => {}
+            _ = schedule_changed() => debug!("Proposal stored, rescheduling"),
+        }
     }
 }
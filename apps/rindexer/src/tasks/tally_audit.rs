@@ -0,0 +1,15 @@
+use crate::extensions::tally_audit::audit_tallies;
+use anyhow::Result;
+use tokio::time;
+use tracing::{info, instrument};
+
+#[instrument(name = "run_periodic_tally_audit", skip_all)]
+pub async fn run_periodic_tally_audit() -> Result<()> {
+    info!("Starting periodic task for tally audits.");
+    let mut interval = time::interval(time::Duration::from_secs(15 * 60));
+
+    loop {
+        interval.tick().await;
+        audit_tallies(None).await?;
+    }
+}
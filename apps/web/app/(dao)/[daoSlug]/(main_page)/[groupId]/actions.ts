@@ -213,6 +213,7 @@ export async function getGroup(groupId: string) {
         ? await db
             .selectFrom('discourseTopic')
             .selectAll()
+            .where('restricted', '=', false)
             .where((eb) =>
               eb.or(
                 topicFilters.map((item) =>
@@ -325,6 +326,7 @@ export async function getBodyVersions(groupId: string, withContent: boolean) {
       ? await db
           .selectFrom('discourseTopic')
           .selectAll()
+          .where('restricted', '=', false)
           .where((eb) =>
             eb.or(
               topicFilters.map((item) =>
@@ -342,6 +344,7 @@ export async function getBodyVersions(groupId: string, withContent: boolean) {
     discourseTopics.length > 0
       ? await db
           .selectFrom('discoursePost')
+          .where('restricted', '=', false)
           .selectAll()
           .where('postNumber', '=', 1)
           .where((eb) =>
@@ -628,6 +631,7 @@ async function getAuthor(groupId: string) {
       ? await db
           .selectFrom('discourseTopic')
           .selectAll()
+          .where('restricted', '=', false)
           .where((eb) =>
             eb.or(
               topicItems.map((item) =>
@@ -698,6 +702,7 @@ async function getAuthor(groupId: string) {
     // Get the first post of the topic
     const firstPost = await db
       .selectFrom('discoursePost')
+      .where('restricted', '=', false)
       .where('topicId', '=', firstItem.externalId)
       .where('daoDiscourseId', '=', firstItem.daoDiscourseId)
       .where('postNumber', '=', 1)
@@ -1196,6 +1201,7 @@ export async function getFeed(
     const fetchedTopics = await db
       .selectFrom('discourseTopic')
       .selectAll()
+      .where('restricted', '=', false)
       .where((eb) =>
         eb.or(
           topicItems.map((item) =>
@@ -1225,6 +1231,7 @@ export async function getFeed(
   if (topics.length > 0) {
     allPosts = await db
       .selectFrom('discoursePost')
+      .where('restricted', '=', false)
       .where(
         'topicId',
         'in',
@@ -1470,6 +1477,7 @@ export async function getGroupHeader(groupId: string): Promise<{
       ? await db
           .selectFrom('discourseTopic')
           .selectAll()
+          .where('restricted', '=', false)
           .where((eb) =>
             eb.or(
               topicItems.map((item) =>
@@ -1491,6 +1499,7 @@ export async function getGroupHeader(groupId: string): Promise<{
     try {
       const discourseFirstPost = await db
         .selectFrom('discoursePost')
+        .where('restricted', '=', false)
         .where('discoursePost.topicId', '=', topic.externalId)
         .where('daoDiscourseId', '=', topic.daoDiscourseId)
         .where('discoursePost.postNumber', '=', 1)
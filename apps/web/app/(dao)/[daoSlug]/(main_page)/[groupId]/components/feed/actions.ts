@@ -118,6 +118,7 @@ export async function getDelegateByDiscourseUser(
         .selectFrom('discourseTopic')
         .selectAll()
         .where('id', 'in', topicIds)
+        .where('restricted', '=', false)
         .execute();
 
       topicStartTimes = topics.map((topic) => topic.createdAt.getTime());
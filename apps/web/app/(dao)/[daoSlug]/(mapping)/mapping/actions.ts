@@ -390,6 +390,7 @@ export async function fuzzySearchItems(
         db
           .selectFrom('discourseTopic')
           .where('discourseTopic.daoDiscourseId', '=', daoDiscourse.id)
+          .where('discourseTopic.restricted', '=', false)
           .leftJoin(
             'daoDiscourse',
             'daoDiscourse.id',
@@ -401,3 +401,156 @@ export async function deleteDelegate(delegateId: string) {
 
   revalidateTag('delegatesWithMappings', 'max');
 }
+
+export type IdentitySuggestionEvidence =
+  | { kind: 'ens_discourse_record'; ens: string | null; record: string }
+  | { kind: 'ens_stem_matches_username'; ens: string; username: string }
+  | { kind: 'ens_stem_matches_display_name'; ens: string; name: string }
+  | { kind: 'ens_in_profile'; ens: string; field: string; value: string }
+  | {
+      kind: 'address_in_post' | 'delegate_statement';
+      postId: number;
+      topicId: number;
+      topicTitle: string;
+    };
+
+export type IdentitySuggestionsReturnType = AsyncReturnType<
+  typeof getIdentitySuggestions
+>;
+
+export async function getIdentitySuggestions(daoSlug: string) {
+  const dao = await db
+    .selectFrom('dao')
+    .where('slug', '=', daoSlug)
+    .selectAll()
+    .executeTakeFirst();
+
+  if (!dao) return [];
+
+  const suggestions = await db
+    .selectFrom('mappingIdentitySuggestion as suggestion')
+    .innerJoin('voter', 'voter.id', 'suggestion.voterId')
+    .innerJoin(
+      'discourseUser',
+      'discourseUser.id',
+      'suggestion.discourseUserId'
+    )
+    .where('suggestion.daoId', '=', dao.id)
+    .where('suggestion.status', '=', 'pending')
+    .select([
+      'suggestion.id',
+      'suggestion.score',
+      'suggestion.evidence',
+      'voter.address',
+      'voter.ens',
+      'discourseUser.username',
+      'discourseUser.name',
+    ])
+    .orderBy('suggestion.score', 'desc')
+    .limit(100)
+    .execute();
+
+  return suggestions.map((suggestion) => ({
+    ...suggestion,
+    evidence: suggestion.evidence as IdentitySuggestionEvidence[],
+  }));
+}
+
+/**
+ * Link both sides of a suggestion to one delegate: the delegate already
+ * holding either side, or a new one when neither is mapped yet.
+ */
+export async function acceptIdentitySuggestion(suggestionId: string) {
+  await db.transaction().execute(async (trx) => {
+    const suggestion = await trx
+      .selectFrom('mappingIdentitySuggestion')
+      .where('id', '=', suggestionId)
+      .where('status', '=', 'pending')
+      .selectAll()
+      .executeTakeFirst();
+
+    if (!suggestion) return;
+
+    const now = new Date();
+    const [voterMapping, discourseUserMapping] = await Promise.all([
+      trx
+        .selectFrom('delegateToVoter')
+        .innerJoin('delegate', 'delegate.id', 'delegateToVoter.delegateId')
+        .where('delegate.daoId', '=', suggestion.daoId)
+        .where('delegateToVoter.voterId', '=', suggestion.voterId)
+        .where('delegateToVoter.periodEnd', '>=', now)
+        .select('delegateToVoter.delegateId')
+        .executeTakeFirst(),
+      trx
+        .selectFrom('delegateToDiscourseUser')
+        .where('discourseUserId', '=', suggestion.discourseUserId)
+        .where('periodEnd', '>=', now)
+        .select('delegateId')
+        .executeTakeFirst(),
+    ]);
+
+    if (
+      voterMapping &&
+      discourseUserMapping &&
+      voterMapping.delegateId !== discourseUserMapping.delegateId
+    ) {
+      throw new Error(
+        'The voter and discourse user are already mapped to different delegates'
+      );
+    }
+
+    const delegateId =
+      voterMapping?.delegateId ??
+      discourseUserMapping?.delegateId ??
+      (
+        await trx
+          .insertInto('delegate')
+          .values({ daoId: suggestion.daoId })
+          .returning('id')
+          .executeTakeFirstOrThrow()
+      ).id;
+
+    if (!voterMapping) {
+      await trx
+        .insertInto('delegateToVoter')
+        .values({
+          delegateId,
+          voterId: suggestion.voterId,
+          periodStart: now,
+          periodEnd: new Date('2100-01-01'),
+        })
+        .execute();
+    }
+
+    if (!discourseUserMapping) {
+      await trx
+        .insertInto('delegateToDiscourseUser')
+        .values({
+          delegateId,
+          discourseUserId: suggestion.discourseUserId,
+          periodStart: now,
+          periodEnd: new Date('2100-01-01'),
+        })
+        .execute();
+    }
+
+    await trx
+      .updateTable('mappingIdentitySuggestion')
+      .set({ status: 'accepted', updatedAt: now })
+      .where('id', '=', suggestion.id)
+      .execute();
+  });
+
+  revalidateTag('delegatesWithMappings', 'max');
+}
+
+export async function rejectIdentitySuggestion(suggestionId: string) {
+  await db
+    .updateTable('mappingIdentitySuggestion')
+    .set({ status: 'rejected', updatedAt: new Date() })
+    .where('id', '=', suggestionId)
+    .where('status', '=', 'pending')
+    .execute();
+
+  revalidateTag('delegatesWithMappings', 'max');
+}
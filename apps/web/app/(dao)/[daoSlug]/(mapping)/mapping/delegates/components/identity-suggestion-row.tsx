@@ -0,0 +1,114 @@
+'use client';
+
+import { useState } from 'react';
+import {
+  acceptIdentitySuggestion,
+  rejectIdentitySuggestion,
+  type IdentitySuggestionEvidence,
+  type IdentitySuggestionsReturnType,
+} from '../actions';
+import {
+  Badge,
+  Button,
+  MappingTableActionCell,
+  MappingTableCell,
+  MappingTableRow,
+} from '../../components/ui';
+
+function describeEvidence(evidence: IdentitySuggestionEvidence): string {
+  switch (evidence.kind) {
+    case 'ens_discourse_record':
+      return `ENS com.discourse record: ${evidence.record}`;
+    case 'ens_stem_matches_username':
+      return `${evidence.ens} matches username ${evidence.username}`;
+    case 'ens_stem_matches_display_name':
+      return `${evidence.ens} matches display name ${evidence.name}`;
+    case 'ens_in_profile':
+      return `${evidence.ens} in profile ${evidence.field}: ${evidence.value}`;
+    case 'delegate_statement':
+      return `Address in delegate statement "${evidence.topicTitle}"`;
+    case 'address_in_post':
+      return `Address posted in "${evidence.topicTitle}"`;
+  }
+}
+
+export const IdentitySuggestionRow = ({
+  suggestion,
+}: {
+  suggestion: IdentitySuggestionsReturnType[number];
+}) => {
+  const [isSaving, setIsSaving] = useState(false);
+  const [isResolved, setIsResolved] = useState(false);
+
+  const handle = async (action: (suggestionId: string) => Promise<void>) => {
+    setIsSaving(true);
+    try {
+      await action(suggestion.id);
+      setIsResolved(true);
+    } catch (error) {
+      console.error('Error resolving identity suggestion:', error);
+      window.alert(
+        error instanceof Error ? error.message : 'Could not save suggestion'
+      );
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  if (isResolved) {
+    return null;
+  }
+
+  return (
+    <MappingTableRow>
+      <MappingTableCell>
+        <Badge variant={suggestion.score >= 0.8 ? 'green' : 'neutral'}>
+          {Math.round(suggestion.score * 100)}%
+        </Badge>
+      </MappingTableCell>
+      <MappingTableCell>
+        <div className='truncate font-mono text-xs' title={suggestion.address}>
+          {suggestion.address}
+        </div>
+        {suggestion.ens && (
+          <div className='truncate text-xs text-neutral-500 dark:text-neutral-400'>
+            {suggestion.ens}
+          </div>
+        )}
+      </MappingTableCell>
+      <MappingTableCell>
+        <div className='text-sm'>{suggestion.username}</div>
+        {suggestion.name && (
+          <div className='text-xs text-neutral-500 dark:text-neutral-400'>
+            {suggestion.name}
+          </div>
+        )}
+      </MappingTableCell>
+      <MappingTableCell>
+        <ul className='list-disc space-y-1 pl-4 text-xs text-neutral-600 dark:text-neutral-300'>
+          {suggestion.evidence.map((evidence, index) => (
+            <li key={index}>{describeEvidence(evidence)}</li>
+          ))}
+        </ul>
+      </MappingTableCell>
+      <MappingTableActionCell>
+        <Button
+          onClick={() => handle(acceptIdentitySuggestion)}
+          variant='primary'
+          disabled={isSaving}
+          className='min-w-[80px]'
+        >
+          Accept
+        </Button>
+        <Button
+          onClick={() => handle(rejectIdentitySuggestion)}
+          variant='danger'
+          disabled={isSaving}
+          className='min-w-[80px]'
+        >
+          Reject
+        </Button>
+      </MappingTableActionCell>
+    </MappingTableRow>
+  );
+};
@@ -1,6 +1,11 @@
-import { createDelegate, getDelegatesWithMappings } from './actions';
+import {
+  createDelegate,
+  getDelegatesWithMappings,
+  getIdentitySuggestions,
+} from './actions';
 import { getDao } from '../actions';
 import { DelegateRow } from './components/edit-delegate-row';
+import { IdentitySuggestionRow } from './components/identity-suggestion-row';
 import { Suspense } from 'react';
 import { Button, MappingTable, PageHeader } from '../components/ui';
 import { Spinner } from '@/app/components/ui/spinner';
@@ -15,6 +20,7 @@ async function DelegatesPage({
   return (
     <div className='container mx-auto p-6'>
       <HeaderContainer daoSlug={daoSlug} />
+      <SuggestionsContainer daoSlug={daoSlug} />
       <DelegatesContainer daoSlug={daoSlug} />
     </div>
   );
@@ -49,6 +55,30 @@ async function HeaderContainer({ daoSlug }: { daoSlug: string }) {
   );
 }
 
+// Pending voter and discourse user pairings suggested by the mapping agent
+async function SuggestionsContainer({ daoSlug }: { daoSlug: string }) {
+  const suggestions = await getIdentitySuggestions(daoSlug);
+
+  if (suggestions.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className='mb-8'>
+      <h2 className='mb-4 text-lg font-semibold text-neutral-900 dark:text-neutral-100'>
+        Suggested Links ({suggestions.length})
+      </h2>
+      <MappingTable
+        headers={['Score', 'Voter', 'Discourse User', 'Evidence', 'Actions']}
+      >
+        {suggestions.map((suggestion) => (
+          <IdentitySuggestionRow key={suggestion.id} suggestion={suggestion} />
+        ))}
+      </MappingTable>
+    </div>
+  );
+}
+
 // Delegates container that loads delegates data independently
 async function DelegatesContainer({ daoSlug }: { daoSlug: string }) {
   const delegatesWithMappings = await getDelegatesWithMappings(daoSlug);
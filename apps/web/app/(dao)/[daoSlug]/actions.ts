@@ -106,6 +106,7 @@ async function getGroupsDataCached(daoSlug: string) {
       (eb) =>
         eb
           .selectFrom('discourseTopic as dt')
+          .where('dt.restricted', '=', false)
           .whereRef(
             sql`(dt."external_id", dt."dao_discourse_id")`,
             'in',
@@ -159,7 +160,8 @@ async function getGroupsDataCached(daoSlug: string) {
                 AND dp.post_number = 1
               JOIN public.discourse_user du ON du.external_id = dp.user_id
                 AND du.dao_discourse_id = dp.dao_discourse_id
-              WHERE (dt."external_id", dt."dao_discourse_id") IN (
+              WHERE NOT dt.restricted
+                AND (dt."external_id", dt."dao_discourse_id") IN (
                 SELECT
                   (item->>'externalId')::int,
                   (item->>'daoDiscourseId')::uuid
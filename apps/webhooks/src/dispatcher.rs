@@ -0,0 +1,446 @@
+use crate::{
+    events::{Trigger, WebhookEventType, WebhookPayload, triggers},
+    signature::{DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, sign},
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures::future::join_all;
+use proposalsapp_db::models::{
+    dao, proposal, sea_orm_active_enums::ProposalState, vote, webhook_delivery,
+    webhook_subscription,
+};
+use reqwest::{Client, header::CONTENT_TYPE};
+use sea_orm::{
+    ActiveValue::NotSet, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait,
+    QueryFilter, Set, Statement, TransactionTrait, sea_query::OnConflict,
+};
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
+use utils::{job_queue::retry_delay, outbox::StoredEvent};
+
+pub const PENDING: &str = "PENDING";
+pub const DELIVERED: &str = "DELIVERED";
+/// Deliveries that failed `MAX_ATTEMPTS` times. They stay in the log and
+/// aren't retried.
+pub const FAILED: &str = "FAILED";
+
+pub const MAX_ATTEMPTS: i32 = 10;
+
+/// A subscription is disabled once this many attempts in a row failed, the
+/// first of them at least `DISABLE_AFTER` ago, so a burst of events during a
+/// short outage doesn't disable it.
+pub const MAX_CONSECUTIVE_FAILURES: i32 = 20;
+pub const DISABLE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Active proposals get `proposal.ending_soon` once they end within this.
+pub const ENDING_SOON_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+const DELIVERY_BATCH_SIZE: i64 = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Claimed deliveries are leased this long, comfortably above the request
+/// timeout, so a crashed dispatcher's deliveries are picked up again.
+const DELIVERY_LEASE: Duration = Duration::from_secs(60);
+
+/// Turns governance events into webhook deliveries for the matching
+/// subscriptions, and sends them.
+#[derive(Clone)]
+pub struct Dispatcher {
+    db: DatabaseConnection,
+    client: Client,
+}
+
+impl Dispatcher {
+    pub fn new(db: DatabaseConnection) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent("proposals.app-webhooks")
+            .build()
+            .context("Failed to build webhook HTTP client")?;
+        Ok(Self { db, client })
+    }
+
+    /// Queue deliveries for the webhook events following from `event`.
+    /// Returns how many were queued; events delivered before are skipped.
+    #[instrument(skip_all, fields(event_id = event.id))]
+    pub async fn handle_event(&self, event: &StoredEvent) -> Result<usize> {
+        let mut queued = 0;
+        for trigger in triggers(&event.event) {
+            let Some(proposal) = proposal::Entity::find_by_id(trigger.proposal_id)
+                .one(&self.db)
+                .await
+                .context("Failed to fetch proposal")?
+            else {
+                warn!(proposal_id = %trigger.proposal_id, "Proposal of governance event not found");
+                continue;
+            };
+
+            let vote = match trigger.vote_id {
+                Some(vote_id) => {
+                    let vote = vote::Entity::find_by_id(vote_id)
+                        .one(&self.db)
+                        .await
+                        .context("Failed to fetch vote")?;
+                    if vote.is_none() {
+                        warn!(vote_id = %vote_id, "Vote of governance event not found");
+                        continue;
+                    }
+                    vote
+                }
+                None => None,
+            };
+
+            queued += self.fan_out(&trigger, &proposal, vote.as_ref()).await?;
+            if trigger.event_type == WebhookEventType::ProposalCreated
+                && proposal.proposal_state == ProposalState::Active
+            {
+                let started = Trigger::proposal(WebhookEventType::ProposalStarted, proposal.id);
+                queued += self.fan_out(&started, &proposal, None).await?;
+            }
+        }
+        Ok(queued)
+    }
+
+    /// Queue `proposal.ending_soon` for active proposals ending within
+    /// [`ENDING_SOON_WINDOW`]. Meant to run periodically.
+    #[instrument(skip_all)]
+    pub async fn queue_ending_soon(&self) -> Result<usize> {
+        let now = Utc::now().naive_utc();
+        let window = chrono::Duration::from_std(ENDING_SOON_WINDOW)?;
+        let proposals = proposal::Entity::find()
+            .filter(proposal::Column::ProposalState.eq(ProposalState::Active))
+            .filter(proposal::Column::EndAt.gt(now))
+            .filter(proposal::Column::EndAt.lte(now + window))
+            .all(&self.db)
+            .await
+            .context("Failed to fetch proposals ending soon")?;
+
+        let mut queued = 0;
+        for proposal in proposals {
+            let trigger = Trigger::proposal(WebhookEventType::ProposalEndingSoon, proposal.id);
+            queued += self.fan_out(&trigger, &proposal, None).await?;
+        }
+        Ok(queued)
+    }
+
+    async fn fan_out(
+        &self,
+        trigger: &Trigger,
+        proposal: &proposal::Model,
+        vote: Option<&vote::Model>,
+    ) -> Result<usize> {
+        if proposal.marked_spam {
+            return Ok(0);
+        }
+
+        let subscriptions = webhook_subscription::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT * FROM webhook_subscription
+                WHERE enabled
+                    AND (dao_id IS NULL OR dao_id = $1)
+                    AND (event_types = '[]'::jsonb OR event_types @> jsonb_build_array($2::text))
+                    AND (
+                        $3::text IS NULL
+                        OR EXISTS (
+                            SELECT 1 FROM jsonb_array_elements_text(tracked_voters) AS voter
+                            WHERE lower(voter) = lower($3)
+                        )
+                    )
+                "#,
+                vec![
+                    proposal.dao_id.into(),
+                    trigger.event_type.as_str().into(),
+                    vote.map(|vote| vote.voter_address.clone()).into(),
+                ],
+            ))
+            .all(&self.db)
+            .await
+            .context("Failed to fetch webhook subscriptions")?;
+        if subscriptions.is_empty() {
+            return Ok(0);
+        }
+
+        let dao = dao::Entity::find_by_id(proposal.dao_id)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch DAO")?
+            .context("DAO of proposal not found")?;
+        let payload = serde_json::to_value(WebhookPayload::new(trigger, &dao, proposal, vote))
+            .context("Failed to serialize webhook payload")?;
+
+        let deliveries = subscriptions
+            .iter()
+            .map(|subscription| webhook_delivery::ActiveModel {
+                id: NotSet,
+                subscription_id: Set(subscription.id),
+                event_type: Set(trigger.event_type.to_string()),
+                event_key: Set(trigger.event_key()),
+                payload: Set(payload.clone()),
+                status: NotSet,
+                attempts: NotSet,
+                next_attempt_at: NotSet,
+                locked_until: NotSet,
+                response_status: NotSet,
+                last_error: NotSet,
+                delivered_at: NotSet,
+                created_at: NotSet,
+                updated_at: NotSet,
+            });
+        let queued = webhook_delivery::Entity::insert_many(deliveries)
+            .on_conflict(
+                OnConflict::columns([
+                    webhook_delivery::Column::SubscriptionId,
+                    webhook_delivery::Column::EventKey,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await
+            .context("Failed to queue webhook deliveries")?;
+
+        debug!(event_key = %trigger.event_key(), queued, "Webhook deliveries queued");
+        Ok(queued as usize)
+    }
+
+    /// Send a batch of due deliveries concurrently and record how each went.
+    /// Returns how many were attempted.
+    #[instrument(skip_all)]
+    pub async fn deliver_due(&self) -> Result<usize> {
+        let deliveries = webhook_delivery::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                WITH due AS (
+                    SELECT d.id FROM webhook_delivery d
+                    JOIN webhook_subscription s ON s.id = d.subscription_id
+                    WHERE d.status = 'PENDING'
+                        AND d.next_attempt_at <= NOW()
+                        AND (d.locked_until IS NULL OR d.locked_until <= NOW())
+                        AND s.enabled
+                    ORDER BY d.next_attempt_at, d.created_at
+                    LIMIT $1
+                    FOR UPDATE OF d SKIP LOCKED
+                )
+                UPDATE webhook_delivery SET
+                    attempts = webhook_delivery.attempts + 1,
+                    locked_until = NOW() + make_interval(secs => $2),
+                    updated_at = NOW()
+                FROM due
+                WHERE webhook_delivery.id = due.id
+                RETURNING webhook_delivery.*
+                "#,
+                vec![
+                    DELIVERY_BATCH_SIZE.into(),
+                    DELIVERY_LEASE.as_secs_f64().into(),
+                ],
+            ))
+            .all(&self.db)
+            .await
+            .context("Failed to claim webhook deliveries")?;
+        if deliveries.is_empty() {
+            return Ok(0);
+        }
+
+        let subscriptions = webhook_subscription::Entity::find()
+            .filter(
+                webhook_subscription::Column::Id
+                    .is_in(deliveries.iter().map(|delivery| delivery.subscription_id)),
+            )
+            .all(&self.db)
+            .await
+            .context("Failed to fetch webhook subscriptions")?;
+
+        let attempted = deliveries.len();
+        let results = join_all(deliveries.into_iter().filter_map(|delivery| {
+            let subscription = subscriptions
+                .iter()
+                .find(|subscription| subscription.id == delivery.subscription_id)?;
+            Some(self.attempt(subscription, delivery))
+        }))
+        .await;
+        for result in results {
+            result?;
+        }
+        Ok(attempted)
+    }
+
+    async fn attempt(
+        &self,
+        subscription: &webhook_subscription::Model,
+        delivery: webhook_delivery::Model,
+    ) -> Result<()> {
+        let body =
+            serde_json::to_vec(&delivery.payload).context("Failed to serialize webhook body")?;
+        let signature = sign(&subscription.secret, Utc::now().timestamp(), &body);
+
+        let response = self
+            .client
+            .post(&subscription.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await;
+
+        let (response_status, error) = match response {
+            Ok(response) if response.status().is_success() => {
+                (Some(response.status().as_u16() as i32), None)
+            }
+            Ok(response) => (
+                Some(response.status().as_u16() as i32),
+                Some(format!("Receiver responded with {}", response.status())),
+            ),
+            Err(e) => (
+                e.status().map(|status| status.as_u16() as i32),
+                Some(format!("Request failed: {e}")),
+            ),
+        };
+
+        match error {
+            None => self.record_success(&delivery, response_status).await,
+            Some(error) => {
+                self.record_failure(&delivery, response_status, &error)
+                    .await
+            }
+        }
+    }
+
+    async fn record_success(
+        &self,
+        delivery: &webhook_delivery::Model,
+        response_status: Option<i32>,
+    ) -> Result<()> {
+        let txn = self.db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE webhook_delivery SET
+                status = 'DELIVERED',
+                response_status = $3,
+                last_error = NULL,
+                delivered_at = NOW(),
+                locked_until = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND attempts = $2
+            "#,
+            vec![
+                delivery.id.into(),
+                delivery.attempts.into(),
+                response_status.into(),
+            ],
+        ))
+        .await
+        .context("Failed to record webhook delivery")?;
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE webhook_subscription SET
+                consecutive_failures = 0,
+                failing_since = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND consecutive_failures > 0
+            "#,
+            vec![delivery.subscription_id.into()],
+        ))
+        .await
+        .context("Failed to reset webhook subscription failures")?;
+        txn.commit().await?;
+
+        debug!(delivery_id = %delivery.id, attempts = delivery.attempts, "Webhook delivered");
+        Ok(())
+    }
+
+    /// Schedule a retry after [`retry_delay`], or give up after
+    /// [`MAX_ATTEMPTS`], and disable the subscription if it keeps failing.
+    async fn record_failure(
+        &self,
+        delivery: &webhook_delivery::Model,
+        response_status: Option<i32>,
+        error: &str,
+    ) -> Result<()> {
+        let (status, retry_in) = if delivery.attempts >= MAX_ATTEMPTS {
+            (FAILED, None)
+        } else {
+            (PENDING, Some(retry_delay(delivery.attempts)))
+        };
+
+        let txn = self.db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE webhook_delivery SET
+                status = $3,
+                next_attempt_at = COALESCE(NOW() + make_interval(secs => $4), next_attempt_at),
+                response_status = $5,
+                last_error = $6,
+                locked_until = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND attempts = $2
+            "#,
+            vec![
+                delivery.id.into(),
+                delivery.attempts.into(),
+                status.into(),
+                retry_in.map(|delay| delay.as_secs_f64()).into(),
+                response_status.into(),
+                error.into(),
+            ],
+        ))
+        .await
+        .context("Failed to record webhook delivery failure")?;
+        let disabled = txn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                WITH failure AS (
+                    SELECT id, COALESCE(
+                        consecutive_failures + 1 >= $2
+                            AND failing_since <= NOW() - make_interval(secs => $3),
+                        FALSE
+                    ) AS disable
+                    FROM webhook_subscription
+                    WHERE id = $1 AND enabled
+                    FOR UPDATE
+                )
+                UPDATE webhook_subscription SET
+                    consecutive_failures = webhook_subscription.consecutive_failures + 1,
+                    failing_since = COALESCE(webhook_subscription.failing_since, NOW()),
+                    enabled = NOT failure.disable,
+                    disabled_at = CASE WHEN failure.disable THEN NOW() END,
+                    updated_at = NOW()
+                FROM failure
+                WHERE webhook_subscription.id = failure.id
+                RETURNING failure.disable AS disabled
+                "#,
+                vec![
+                    delivery.subscription_id.into(),
+                    MAX_CONSECUTIVE_FAILURES.into(),
+                    DISABLE_AFTER.as_secs_f64().into(),
+                ],
+            ))
+            .await
+            .context("Failed to record webhook subscription failure")?
+            .map(|row| row.try_get::<bool>("", "disabled"))
+            .transpose()
+            .context("Failed to read webhook subscription state")?
+            .unwrap_or(false);
+        txn.commit().await?;
+
+        if disabled {
+            warn!(
+                subscription_id = %delivery.subscription_id,
+                "Webhook subscription disabled after failing repeatedly"
+            );
+        }
+        if status == FAILED {
+            warn!(delivery_id = %delivery.id, attempts = delivery.attempts, error, "Webhook delivery failed, giving up");
+        } else {
+            info!(delivery_id = %delivery.id, attempts = delivery.attempts, error, "Webhook delivery failed, will retry");
+        }
+        Ok(())
+    }
+}
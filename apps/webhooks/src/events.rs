@@ -0,0 +1,272 @@
+use chrono::{DateTime, Utc};
+use proposalsapp_db::models::{dao, proposal, sea_orm_active_enums::ProposalState, vote};
+use sea_orm::{ActiveEnum, prelude::Uuid};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use utils::outbox::GovernanceEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "proposal.created")]
+    ProposalCreated,
+    #[serde(rename = "proposal.started")]
+    ProposalStarted,
+    #[serde(rename = "proposal.ending_soon")]
+    ProposalEndingSoon,
+    #[serde(rename = "proposal.ended")]
+    ProposalEnded,
+    /// Only sent for the voters a subscription tracks.
+    #[serde(rename = "vote.cast")]
+    VoteCast,
+}
+
+impl WebhookEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ProposalCreated => "proposal.created",
+            Self::ProposalStarted => "proposal.started",
+            Self::ProposalEndingSoon => "proposal.ending_soon",
+            Self::ProposalEnded => "proposal.ended",
+            Self::VoteCast => "vote.cast",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a webhook event is about. Each one is delivered at most once per
+/// subscription, keyed by [`Trigger::event_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trigger {
+    pub event_type: WebhookEventType,
+    pub proposal_id: Uuid,
+    pub vote_id: Option<Uuid>,
+}
+
+impl Trigger {
+    pub fn proposal(event_type: WebhookEventType, proposal_id: Uuid) -> Self {
+        Self {
+            event_type,
+            proposal_id,
+            vote_id: None,
+        }
+    }
+
+    pub fn event_key(&self) -> String {
+        format!(
+            "{}:{}",
+            self.event_type,
+            self.vote_id.unwrap_or(self.proposal_id)
+        )
+    }
+}
+
+/// Webhook events following from a governance event. A created proposal
+/// that's already active also starts, which is only known once it's loaded.
+pub fn triggers(event: &GovernanceEvent) -> Vec<Trigger> {
+    match event {
+        GovernanceEvent::ProposalCreated { proposal_id, .. } => {
+            vec![Trigger::proposal(
+                WebhookEventType::ProposalCreated,
+                *proposal_id,
+            )]
+        }
+        GovernanceEvent::ProposalStateChanged {
+            proposal_id,
+            from_state,
+            to_state,
+        } => {
+            if *to_state == ProposalState::Active && *from_state != ProposalState::Active {
+                vec![Trigger::proposal(
+                    WebhookEventType::ProposalStarted,
+                    *proposal_id,
+                )]
+            } else if *from_state == ProposalState::Active && has_ended(to_state) {
+                vec![Trigger::proposal(
+                    WebhookEventType::ProposalEnded,
+                    *proposal_id,
+                )]
+            } else {
+                vec![]
+            }
+        }
+        GovernanceEvent::VoteCast {
+            vote_id,
+            proposal_id,
+            ..
+        } => vec![Trigger {
+            event_type: WebhookEventType::VoteCast,
+            proposal_id: *proposal_id,
+            vote_id: Some(*vote_id),
+        }],
+        GovernanceEvent::DelegationChanged { .. }
+        | GovernanceEvent::VotingPowerChanged { .. }
+        | GovernanceEvent::TopicCreated { .. }
+        | GovernanceEvent::PostEdited { .. }
+        | GovernanceEvent::AnomalyDetected { .. } => vec![],
+    }
+}
+
+/// States an active proposal ends in. Only leaving ACTIVE counts, so
+/// proposals canceled before they started never end.
+fn has_ended(state: &ProposalState) -> bool {
+    !matches!(
+        state,
+        ProposalState::Pending
+            | ProposalState::Active
+            | ProposalState::Hidden
+            | ProposalState::Unknown
+    )
+}
+
+/// The JSON body of a webhook request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// The same for every attempt, so receivers can drop duplicates.
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: WebhookEventType,
+    pub created_at: DateTime<Utc>,
+    pub dao: DaoPayload,
+    pub proposal: ProposalPayload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vote: Option<VotePayload>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaoPayload {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalPayload {
+    pub id: Uuid,
+    pub external_id: String,
+    pub name: String,
+    pub url: String,
+    pub discussion_url: Option<String>,
+    pub state: String,
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VotePayload {
+    pub id: Uuid,
+    pub voter_address: String,
+    pub choice: Value,
+    pub voting_power: f64,
+    pub reason: Option<String>,
+    pub txid: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookPayload {
+    pub fn new(
+        trigger: &Trigger,
+        dao: &dao::Model,
+        proposal: &proposal::Model,
+        vote: Option<&vote::Model>,
+    ) -> Self {
+        Self {
+            id: trigger.event_key(),
+            event_type: trigger.event_type,
+            created_at: Utc::now(),
+            dao: DaoPayload {
+                id: dao.id,
+                name: dao.name.clone(),
+                slug: dao.slug.clone(),
+            },
+            proposal: ProposalPayload {
+                id: proposal.id,
+                external_id: proposal.external_id.clone(),
+                name: proposal.name.clone(),
+                url: proposal.url.clone(),
+                discussion_url: proposal.discussion_url.clone(),
+                state: proposal.proposal_state.to_value().to_lowercase(),
+                author: proposal.author.clone(),
+                created_at: proposal.created_at.and_utc(),
+                start_at: proposal.start_at.and_utc(),
+                end_at: proposal.end_at.and_utc(),
+            },
+            vote: vote.map(|vote| VotePayload {
+                id: vote.id,
+                voter_address: vote.voter_address.clone(),
+                choice: vote.choice.clone(),
+                voting_power: vote.voting_power,
+                reason: vote.reason.clone(),
+                txid: vote.txid.clone(),
+                created_at: vote.created_at.and_utc(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_changed(from_state: ProposalState, to_state: ProposalState) -> GovernanceEvent {
+        GovernanceEvent::ProposalStateChanged {
+            proposal_id: Uuid::from_u128(1),
+            from_state,
+            to_state,
+        }
+    }
+
+    fn event_types(event: &GovernanceEvent) -> Vec<WebhookEventType> {
+        triggers(event)
+            .iter()
+            .map(|trigger| trigger.event_type)
+            .collect()
+    }
+
+    #[test]
+    fn test_triggers_of_state_changes() {
+        use ProposalState::*;
+
+        assert_eq!(
+            event_types(&state_changed(Pending, Active)),
+            vec![WebhookEventType::ProposalStarted]
+        );
+        for ended in [Defeated, Succeeded, Canceled, Queued, Expired, Executed] {
+            assert_eq!(
+                event_types(&state_changed(Active, ended)),
+                vec![WebhookEventType::ProposalEnded]
+            );
+        }
+        assert!(event_types(&state_changed(Pending, Canceled)).is_empty());
+        assert!(event_types(&state_changed(Succeeded, Queued)).is_empty());
+        assert!(event_types(&state_changed(Active, Hidden)).is_empty());
+    }
+
+    #[test]
+    fn test_event_keys() {
+        let vote = GovernanceEvent::VoteCast {
+            vote_id: Uuid::from_u128(2),
+            proposal_id: Uuid::from_u128(1),
+            voter_address: "0xabc".to_string(),
+        };
+        assert_eq!(
+            triggers(&vote)[0].event_key(),
+            format!("vote.cast:{}", Uuid::from_u128(2))
+        );
+        assert_eq!(
+            triggers(&state_changed(
+                ProposalState::Active,
+                ProposalState::Defeated
+            ))[0]
+                .event_key(),
+            format!("proposal.ended:{}", Uuid::from_u128(1))
+        );
+    }
+}
@@ -0,0 +1,157 @@
+#![warn(unused_extern_crates)]
+
+use anyhow::{Context, Error, Result};
+use axum::Router;
+use dotenv::dotenv;
+use sea_orm::{ConnectOptions, Database};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::{Instrument, error, info, instrument, warn};
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use utils::outbox::OutboxConsumer;
+use webhooks::dispatcher::Dispatcher;
+
+const CONSUMER_NAME: &str = "webhooks";
+const CONSUMER_RESTART_DELAY: Duration = Duration::from_secs(5);
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const ENDING_SOON_INTERVAL: Duration = Duration::from_secs(60);
+
+#[tokio::main]
+#[instrument]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    // Initialize JSON logging for stdout
+    let env_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info"))
+        .add_directive("hyper_util=off".parse().unwrap())
+        .add_directive("reqwest=off".parse().unwrap());
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(
+            fmt::layer()
+                .json()
+                .with_target(true)
+                .with_file(true)
+                .with_line_number(true)
+                .with_thread_ids(true),
+        )
+        .init();
+
+    info!("Application starting up");
+
+    let database_url =
+        std::env::var("DATABASE_URL").context("DATABASE_URL environment variable not set")?;
+    let mut opt = ConnectOptions::new(database_url);
+    opt.max_connections(10)
+        .min_connections(2)
+        .connect_timeout(Duration::from_secs(15))
+        .acquire_timeout(Duration::from_secs(30))
+        .sqlx_logging(false);
+    let db = Database::connect(opt)
+        .await
+        .context("Failed to connect to the database")?;
+
+    info!("Database initialized.");
+
+    // Start health check server
+    let app = Router::new().route("/health", axum::routing::get(|| async { "OK" }));
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let health_server_handle = tokio::spawn(async move {
+        info!(address = %addr, "Starting health check server");
+        if let Err(e) = axum::serve(listener, app).await {
+            error!(error = %e, "Health check server error");
+        }
+    });
+
+    let dispatcher = Dispatcher::new(db.clone())?;
+    let mut tasks: JoinSet<Result<(), Error>> = JoinSet::new();
+
+    // --- Queue deliveries from governance events ---
+    let consumer = OutboxConsumer::new(db.clone(), CONSUMER_NAME);
+    let event_dispatcher = dispatcher.clone();
+    tasks.spawn(
+        async move {
+            loop {
+                let result = consumer
+                    .run(|event| {
+                        let event_dispatcher = event_dispatcher.clone();
+                        async move { event_dispatcher.handle_event(&event).await.map(|_| ()) }
+                    })
+                    .await;
+                if let Err(e) = result {
+                    error!(error = ?e, "Governance event consumer failed, restarting");
+                }
+                tokio::time::sleep(CONSUMER_RESTART_DELAY).await;
+            }
+        }
+        .instrument(tracing::info_span!("event_consumer")),
+    );
+
+    // --- Queue proposal.ending_soon ---
+    let ending_soon_dispatcher = dispatcher.clone();
+    tasks.spawn(
+        async move {
+            let mut interval = tokio::time::interval(ENDING_SOON_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match ending_soon_dispatcher.queue_ending_soon().await {
+                    Ok(0) => {}
+                    Ok(queued) => info!(queued, "Queued ending soon webhooks"),
+                    Err(e) => error!(error = ?e, "Failed to queue ending soon webhooks"),
+                }
+            }
+        }
+        .instrument(tracing::info_span!("ending_soon")),
+    );
+
+    // --- Send due deliveries ---
+    tasks.spawn(
+        async move {
+            loop {
+                match dispatcher.deliver_due().await {
+                    // Keep going while there's a backlog
+                    Ok(attempted) if attempted > 0 => continue,
+                    Ok(_) => {}
+                    Err(e) => warn!(error = ?e, "Failed to send webhook deliveries"),
+                }
+                tokio::time::sleep(DELIVERY_POLL_INTERVAL).await;
+            }
+        }
+        .instrument(tracing::info_span!("delivery")),
+    );
+
+    info!("All webhook tasks started, application running indefinitely");
+
+    // Wait for any task to complete or for shutdown signal
+    tokio::select! {
+        result = health_server_handle => {
+            error!("Health server task completed unexpectedly: {:?}", result);
+        }
+        result = tasks.join_next() => {
+            match result {
+                Some(Ok(Ok(()))) => {
+                    error!("Webhook task completed unexpectedly (success)");
+                }
+                Some(Ok(Err(e))) => {
+                    error!("Webhook task completed with error: {:?}", e);
+                }
+                Some(Err(e)) => {
+                    error!("Webhook task panicked: {:?}", e);
+                }
+                None => {
+                    error!("All webhook tasks completed unexpectedly");
+                }
+            }
+        }
+        _ = tokio::signal::ctrl_c() => {
+            info!("Received Ctrl+C, shutting down gracefully");
+        }
+    }
+
+    info!("Application shutting down");
+    Ok(())
+}
@@ -0,0 +1,530 @@
+use anyhow::{Context, Result};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use chrono::{Duration as ChronoDuration, Utc};
+use proposalsapp_db::models::{
+    dao, dao_governor, proposal, sea_orm_active_enums::ProposalState, vote, webhook_delivery,
+    webhook_subscription,
+};
+use sea_orm::{
+    ActiveValue::NotSet, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set, prelude::Uuid, sea_query::OnConflict,
+};
+use serde_json::json;
+use serial_test::serial;
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU16, Ordering},
+    },
+    time::Duration,
+};
+use utils::outbox::{GovernanceEvent, StoredEvent};
+use webhooks::{
+    dispatcher::{self, Dispatcher},
+    events::WebhookPayload,
+    signature::{self, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER},
+};
+
+const DAO_ID: Uuid = Uuid::from_u128(1);
+const OTHER_DAO_ID: Uuid = Uuid::from_u128(2);
+const GOVERNOR_ID: Uuid = Uuid::from_u128(10);
+const PROPOSAL_ID: Uuid = Uuid::from_u128(100);
+const SECRET: &str = "whsec_test";
+const TRACKED_VOTER: &str = "0xAbC0000000000000000000000000000000000001";
+
+#[derive(Debug, Clone)]
+struct Received {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// A local endpoint recording the webhooks it receives and answering with a
+/// configurable status.
+struct Receiver {
+    url: String,
+    state: Arc<ReceiverState>,
+}
+
+struct ReceiverState {
+    received: Mutex<Vec<Received>>,
+    status: AtomicU16,
+}
+
+impl Receiver {
+    async fn start() -> Result<Self> {
+        let state = Arc::new(ReceiverState {
+            received: Mutex::new(Vec::new()),
+            status: AtomicU16::new(200),
+        });
+        let app = Router::new()
+            .route("/hook", post(record))
+            .with_state(Arc::clone(&state));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr: SocketAddr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Ok(Self {
+            url: format!("http://{addr}/hook"),
+            state,
+        })
+    }
+
+    fn respond_with(&self, status: StatusCode) {
+        self.state.status.store(status.as_u16(), Ordering::SeqCst);
+    }
+
+    fn take(&self) -> Vec<Received> {
+        std::mem::take(&mut *self.state.received.lock().unwrap())
+    }
+}
+
+async fn record(
+    State(state): State<Arc<ReceiverState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    state
+        .received
+        .lock()
+        .unwrap()
+        .push(Received { headers, body });
+    StatusCode::from_u16(state.status.load(Ordering::SeqCst)).unwrap()
+}
+
+/// The shared database without webhooks, with two DAOs and an active
+/// proposal of the first.
+async fn test_db() -> Result<&'static DatabaseConnection> {
+    let db = test_support::test_db().await;
+    webhook_subscription::Entity::delete_many().exec(db).await?;
+    vote::Entity::delete_many().exec(db).await?;
+    proposal::Entity::delete_many().exec(db).await?;
+
+    for (id, slug) in [(DAO_ID, "test-dao"), (OTHER_DAO_ID, "other-dao")] {
+        dao::Entity::insert(dao::ActiveModel {
+            id: Set(id),
+            name: Set(format!("DAO {slug}")),
+            slug: Set(slug.to_string()),
+            picture: Set("https://example.com/dao.png".to_string()),
+        })
+        .on_conflict(OnConflict::column(dao::Column::Id).do_nothing().to_owned())
+        .do_nothing()
+        .exec(db)
+        .await
+        .context("failed to insert dao")?;
+    }
+    dao_governor::Entity::insert(dao_governor::ActiveModel {
+        id: Set(GOVERNOR_ID),
+        dao_id: Set(DAO_ID),
+        name: Set("Test Governor".to_string()),
+        r#type: Set("TEST_GOVERNOR".to_string()),
+        portal_url: Set(None),
+    })
+    .on_conflict(
+        OnConflict::column(dao_governor::Column::Id)
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await
+    .context("failed to insert dao_governor")?;
+
+    let now = Utc::now().naive_utc();
+    proposal::Entity::insert(proposal::ActiveModel {
+        id: Set(PROPOSAL_ID),
+        external_id: Set("42".to_string()),
+        name: Set("Fund the thing".to_string()),
+        body: Set("Let's fund the thing".to_string()),
+        url: Set("https://example.com/proposal/42".to_string()),
+        discussion_url: Set(None),
+        choices: Set(json!(["For", "Against", "Abstain"])),
+        quorum: Set(100.0),
+        proposal_state: Set(ProposalState::Active),
+        marked_spam: Set(false),
+        created_at: Set(now - ChronoDuration::days(1)),
+        start_at: Set(now - ChronoDuration::days(1)),
+        end_at: Set(now + ChronoDuration::hours(6)),
+        block_created_at: Set(None),
+        txid: Set(None),
+        metadata: Set(None),
+        dao_id: Set(DAO_ID),
+        author: Set(Some("0xauthor".to_string())),
+        governor_id: Set(GOVERNOR_ID),
+        block_start_at: Set(None),
+        block_end_at: Set(None),
+    })
+    .exec(db)
+    .await
+    .context("failed to insert proposal")?;
+
+    Ok(db)
+}
+
+async fn subscribe(
+    db: &DatabaseConnection,
+    url: &str,
+    dao_id: Option<Uuid>,
+    event_types: serde_json::Value,
+    tracked_voters: serde_json::Value,
+) -> Result<Uuid> {
+    let subscription = webhook_subscription::Entity::insert(webhook_subscription::ActiveModel {
+        id: NotSet,
+        url: Set(url.to_string()),
+        secret: Set(SECRET.to_string()),
+        dao_id: Set(dao_id),
+        event_types: Set(event_types),
+        tracked_voters: Set(tracked_voters),
+        enabled: NotSet,
+        consecutive_failures: NotSet,
+        failing_since: NotSet,
+        disabled_at: NotSet,
+        created_at: NotSet,
+        updated_at: NotSet,
+    })
+    .exec_with_returning(db)
+    .await
+    .context("failed to insert webhook_subscription")?;
+    Ok(subscription.id)
+}
+
+fn stored(event: GovernanceEvent) -> StoredEvent {
+    StoredEvent {
+        id: 1,
+        xact_id: 1,
+        dao_id: DAO_ID,
+        event,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+fn proposal_created() -> StoredEvent {
+    stored(GovernanceEvent::ProposalCreated {
+        proposal_id: PROPOSAL_ID,
+        governor_id: GOVERNOR_ID,
+        external_id: "42".to_string(),
+    })
+}
+
+async fn deliveries(
+    db: &DatabaseConnection,
+    subscription_id: Uuid,
+) -> Result<Vec<webhook_delivery::Model>> {
+    Ok(webhook_delivery::Entity::find()
+        .filter(webhook_delivery::Column::SubscriptionId.eq(subscription_id))
+        .order_by_asc(webhook_delivery::Column::EventKey)
+        .all(db)
+        .await?)
+}
+
+async fn subscription(
+    db: &DatabaseConnection,
+    subscription_id: Uuid,
+) -> Result<webhook_subscription::Model> {
+    webhook_subscription::Entity::find_by_id(subscription_id)
+        .one(db)
+        .await?
+        .context("missing webhook_subscription record")
+}
+
+#[test]
+#[serial]
+fn test_deliveries_are_signed_and_sent_once() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let receiver = Receiver::start().await?;
+        let dispatcher = Dispatcher::new(db.clone())?;
+        let subscription_id =
+            subscribe(db, &receiver.url, Some(DAO_ID), json!([]), json!([])).await?;
+
+        // Created already active, so it also starts
+        assert_eq!(dispatcher.handle_event(&proposal_created()).await?, 2);
+        assert_eq!(dispatcher.handle_event(&proposal_created()).await?, 0);
+        assert_eq!(dispatcher.deliver_due().await?, 2);
+        assert_eq!(dispatcher.deliver_due().await?, 0);
+
+        let mut received = receiver.take();
+        received.sort_by_key(|request| request.headers[EVENT_HEADER].to_str().unwrap().to_string());
+        let event_types: Vec<_> = received
+            .iter()
+            .map(|request| request.headers[EVENT_HEADER].to_str().unwrap())
+            .collect();
+        assert_eq!(event_types, vec!["proposal.created", "proposal.started"]);
+
+        let stored = deliveries(db, subscription_id).await?;
+        for (request, delivery) in received.iter().zip(&stored) {
+            let header = request.headers[SIGNATURE_HEADER].to_str()?;
+            signature::verify(
+                SECRET,
+                header,
+                &request.body,
+                Utc::now().timestamp(),
+                Duration::from_secs(300),
+            )?;
+            assert!(
+                signature::verify(
+                    "wrong",
+                    header,
+                    &request.body,
+                    Utc::now().timestamp(),
+                    Duration::from_secs(300)
+                )
+                .is_err()
+            );
+            assert_eq!(
+                request.headers[DELIVERY_HEADER].to_str()?,
+                delivery.id.to_string()
+            );
+
+            let payload: WebhookPayload = serde_json::from_slice(&request.body)?;
+            assert_eq!(payload.id, delivery.event_key);
+            assert_eq!(payload.dao.slug, "test-dao");
+            assert_eq!(payload.proposal.id, PROPOSAL_ID);
+            assert_eq!(payload.proposal.state, "active");
+            assert!(payload.vote.is_none());
+
+            assert_eq!(delivery.status, dispatcher::DELIVERED);
+            assert_eq!(delivery.attempts, 1);
+            assert_eq!(delivery.response_status, Some(200));
+            assert!(delivery.delivered_at.is_some());
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_subscriptions_filter_events() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let dispatcher = Dispatcher::new(db.clone())?;
+        let url = "http://127.0.0.1:9/hook";
+        let all_daos = subscribe(
+            db,
+            url,
+            None,
+            json!([]),
+            json!([TRACKED_VOTER.to_lowercase()]),
+        )
+        .await?;
+        let ended_only =
+            subscribe(db, url, Some(DAO_ID), json!(["proposal.ended"]), json!([])).await?;
+        let other_dao = subscribe(
+            db,
+            url,
+            Some(OTHER_DAO_ID),
+            json!([]),
+            json!([TRACKED_VOTER]),
+        )
+        .await?;
+
+        let mut vote_ids = Vec::new();
+        for (id, voter) in [(200, TRACKED_VOTER), (201, "0xuntracked")] {
+            let vote_id = Uuid::from_u128(id);
+            vote::Entity::insert(vote::ActiveModel {
+                id: Set(vote_id),
+                voter_address: Set(voter.to_string()),
+                choice: Set(json!(0)),
+                voting_power: Set(1000.0),
+                reason: Set(Some("Good idea".to_string())),
+                created_at: Set(Utc::now().naive_utc()),
+                block_created_at: Set(None),
+                txid: Set(None),
+                proposal_external_id: Set("42".to_string()),
+                proposal_id: Set(PROPOSAL_ID),
+                dao_id: Set(DAO_ID),
+                governor_id: Set(GOVERNOR_ID),
+            })
+            .exec(db)
+            .await
+            .context("failed to insert vote")?;
+            vote_ids.push((vote_id, voter));
+        }
+
+        for (vote_id, voter) in &vote_ids {
+            dispatcher
+                .handle_event(&stored(GovernanceEvent::VoteCast {
+                    vote_id: *vote_id,
+                    proposal_id: PROPOSAL_ID,
+                    voter_address: voter.to_string(),
+                }))
+                .await?;
+        }
+        dispatcher
+            .handle_event(&stored(GovernanceEvent::ProposalStateChanged {
+                proposal_id: PROPOSAL_ID,
+                from_state: ProposalState::Active,
+                to_state: ProposalState::Defeated,
+            }))
+            .await?;
+
+        // Votes only of tracked voters, matched case-insensitively
+        let all_daos = deliveries(db, all_daos).await?;
+        assert_eq!(
+            all_daos
+                .iter()
+                .map(|d| d.event_key.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                format!("proposal.ended:{PROPOSAL_ID}"),
+                format!("vote.cast:{}", vote_ids[0].0),
+            ]
+        );
+        let payload: WebhookPayload = serde_json::from_value(all_daos[1].payload.clone())?;
+        assert_eq!(
+            payload
+                .vote
+                .context("vote missing from payload")?
+                .voter_address,
+            TRACKED_VOTER
+        );
+
+        let ended_only = deliveries(db, ended_only).await?;
+        assert_eq!(ended_only.len(), 1);
+        assert_eq!(ended_only[0].event_type, "proposal.ended");
+
+        assert!(deliveries(db, other_dao).await?.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_ending_soon_is_queued_once() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let dispatcher = Dispatcher::new(db.clone())?;
+        let subscription_id = subscribe(
+            db,
+            "http://127.0.0.1:9/hook",
+            None,
+            json!(["proposal.ending_soon"]),
+            json!([]),
+        )
+        .await?;
+
+        assert_eq!(dispatcher.queue_ending_soon().await?, 1);
+        assert_eq!(dispatcher.queue_ending_soon().await?, 0);
+
+        // Nothing for proposals ending later
+        db.execute_unprepared("UPDATE proposal SET end_at = NOW() + INTERVAL '3 days'")
+            .await?;
+        webhook_delivery::Entity::delete_many().exec(db).await?;
+        assert_eq!(dispatcher.queue_ending_soon().await?, 0);
+        assert!(deliveries(db, subscription_id).await?.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_failing_deliveries_retry_then_disable_subscription() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let receiver = Receiver::start().await?;
+        receiver.respond_with(StatusCode::INTERNAL_SERVER_ERROR);
+        let dispatcher = Dispatcher::new(db.clone())?;
+        let subscription_id = subscribe(
+            db,
+            &receiver.url,
+            None,
+            json!(["proposal.created"]),
+            json!([]),
+        )
+        .await?;
+
+        dispatcher.handle_event(&proposal_created()).await?;
+        assert_eq!(dispatcher.deliver_due().await?, 1);
+
+        let delivery = deliveries(db, subscription_id).await?.remove(0);
+        assert_eq!(delivery.status, dispatcher::PENDING);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.response_status, Some(500));
+        assert!(delivery.last_error.is_some());
+        assert!(delivery.next_attempt_at > delivery.updated_at);
+        let failing = subscription(db, subscription_id).await?;
+        assert_eq!(failing.consecutive_failures, 1);
+        assert!(failing.failing_since.is_some());
+        assert!(failing.enabled);
+
+        // Backing off
+        assert_eq!(dispatcher.deliver_due().await?, 0);
+
+        // A success resets the failures
+        receiver.respond_with(StatusCode::OK);
+        db.execute_unprepared("UPDATE webhook_delivery SET next_attempt_at = NOW()")
+            .await?;
+        assert_eq!(dispatcher.deliver_due().await?, 1);
+        let delivery = deliveries(db, subscription_id).await?.remove(0);
+        assert_eq!(delivery.status, dispatcher::DELIVERED);
+        assert_eq!(delivery.attempts, 2);
+        let recovered = subscription(db, subscription_id).await?;
+        assert_eq!(recovered.consecutive_failures, 0);
+        assert!(recovered.failing_since.is_none());
+
+        // Failing for long enough disables it, and its deliveries stop
+        receiver.respond_with(StatusCode::SERVICE_UNAVAILABLE);
+        db.execute_unprepared(&format!(
+            "UPDATE webhook_delivery SET status = 'PENDING', next_attempt_at = NOW();
+             UPDATE webhook_subscription SET consecutive_failures = {},
+                 failing_since = NOW() - INTERVAL '2 days'",
+            dispatcher::MAX_CONSECUTIVE_FAILURES - 1
+        ))
+        .await?;
+        assert_eq!(dispatcher.deliver_due().await?, 1);
+        let disabled = subscription(db, subscription_id).await?;
+        assert!(!disabled.enabled);
+        assert!(disabled.disabled_at.is_some());
+
+        db.execute_unprepared("UPDATE webhook_delivery SET next_attempt_at = NOW()")
+            .await?;
+        assert_eq!(dispatcher.deliver_due().await?, 0);
+        assert_eq!(receiver.take().len(), 3);
+
+        // Out of attempts, a delivery is given up on
+        db.execute_unprepared(&format!(
+            "UPDATE webhook_subscription SET enabled = TRUE, disabled_at = NULL,
+                 consecutive_failures = 0, failing_since = NULL;
+             UPDATE webhook_delivery SET attempts = {}",
+            dispatcher::MAX_ATTEMPTS - 1
+        ))
+        .await?;
+        assert_eq!(dispatcher.deliver_due().await?, 1);
+        let delivery = deliveries(db, subscription_id).await?.remove(0);
+        assert_eq!(delivery.status, dispatcher::FAILED);
+        assert_eq!(delivery.attempts, dispatcher::MAX_ATTEMPTS);
+        assert_eq!(dispatcher.deliver_due().await?, 0);
+
+        Ok(())
+    })
+}
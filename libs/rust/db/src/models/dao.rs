@@ -45,13 +45,22 @@ pub enum Relation {
     DaoDiscourse,
     DaoGovernor,
     Delegate,
+    DelegatedVotingPowerSnapshot,
     Delegation,
+    DelegationFlow,
+    DelegationFlowSyncState,
+    GovernanceAnomaly,
+    GovernanceEvent,
+    MappingProposalDecision,
     Proposal,
     ProposalGroup,
+    TokenBalance,
+    TokenTransfer,
     UserNotification,
     Vote,
     VotingPowerLatest,
     VotingPowerTimeseries,
+    WebhookSubscription,
 }
 
 impl ColumnTrait for Column {
@@ -72,15 +81,32 @@ impl RelationTrait for Relation {
             Self::DaoDiscourse => Entity::has_many(super::dao_discourse::Entity).into(),
             Self::DaoGovernor => Entity::has_many(super::dao_governor::Entity).into(),
             Self::Delegate => Entity::has_many(super::delegate::Entity).into(),
+            Self::DelegatedVotingPowerSnapshot => {
+                Entity::has_many(super::delegated_voting_power_snapshot::Entity).into()
+            }
             Self::Delegation => Entity::has_many(super::delegation::Entity).into(),
+            Self::DelegationFlow => Entity::has_many(super::delegation_flow::Entity).into(),
+            Self::DelegationFlowSyncState => {
+                Entity::has_one(super::delegation_flow_sync_state::Entity).into()
+            }
+            Self::GovernanceAnomaly => Entity::has_many(super::governance_anomaly::Entity).into(),
+            Self::GovernanceEvent => Entity::has_many(super::governance_event::Entity).into(),
+            Self::MappingProposalDecision => {
+                Entity::has_many(super::mapping_proposal_decision::Entity).into()
+            }
             Self::Proposal => Entity::has_many(super::proposal::Entity).into(),
             Self::ProposalGroup => Entity::has_many(super::proposal_group::Entity).into(),
+            Self::TokenBalance => Entity::has_many(super::token_balance::Entity).into(),
+            Self::TokenTransfer => Entity::has_many(super::token_transfer::Entity).into(),
             Self::UserNotification => Entity::has_many(super::user_notification::Entity).into(),
             Self::Vote => Entity::has_many(super::vote::Entity).into(),
             Self::VotingPowerLatest => Entity::has_many(super::voting_power_latest::Entity).into(),
             Self::VotingPowerTimeseries => {
                 Entity::has_many(super::voting_power_timeseries::Entity).into()
             }
+            Self::WebhookSubscription => {
+                Entity::has_many(super::webhook_subscription::Entity).into()
+            }
         }
     }
 }
@@ -103,12 +129,48 @@ impl Related<super::delegate::Entity> for Entity {
     }
 }
 
+impl Related<super::delegated_voting_power_snapshot::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DelegatedVotingPowerSnapshot.def()
+    }
+}
+
 impl Related<super::delegation::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Delegation.def()
     }
 }
 
+impl Related<super::delegation_flow::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DelegationFlow.def()
+    }
+}
+
+impl Related<super::delegation_flow_sync_state::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DelegationFlowSyncState.def()
+    }
+}
+
+impl Related<super::governance_anomaly::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GovernanceAnomaly.def()
+    }
+}
+
+impl Related<super::governance_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GovernanceEvent.def()
+    }
+}
+
+impl Related<super::mapping_proposal_decision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MappingProposalDecision.def()
+    }
+}
+
 impl Related<super::proposal::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Proposal.def()
@@ -121,6 +183,18 @@ impl Related<super::proposal_group::Entity> for Entity {
     }
 }
 
+impl Related<super::token_balance::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TokenBalance.def()
+    }
+}
+
+impl Related<super::token_transfer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TokenTransfer.def()
+    }
+}
+
 impl Related<super::user_notification::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserNotification.def()
@@ -145,4 +219,10 @@ impl Related<super::voting_power_timeseries::Entity> for Entity {
     }
 }
 
+impl Related<super::webhook_subscription::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebhookSubscription.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
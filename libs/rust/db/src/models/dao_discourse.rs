@@ -17,6 +17,8 @@ pub struct Model {
     pub id: Uuid,
     pub dao_id: Uuid,
     pub discourse_base_url: String,
+    pub verification_topic_id: Option<i32>,
+    pub post_high_water_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -24,6 +26,8 @@ pub enum Column {
     Id,
     DaoId,
     DiscourseBaseUrl,
+    VerificationTopicId,
+    PostHighWaterId,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -56,6 +60,8 @@ impl ColumnTrait for Column {
             Self::Id => ColumnType::Uuid.def(),
             Self::DaoId => ColumnType::Uuid.def(),
             Self::DiscourseBaseUrl => ColumnType::Text.def(),
+            Self::VerificationTopicId => ColumnType::Integer.def().null(),
+            Self::PostHighWaterId => ColumnType::Integer.def().null(),
         }
     }
 }
@@ -45,7 +45,10 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Dao,
+    GovernorBackfillHeal,
+    GovernorBackfillState,
     Proposal,
+    ProposalTallyAudit,
     Vote,
 }
 
@@ -69,7 +72,16 @@ impl RelationTrait for Relation {
                 .from(Column::DaoId)
                 .to(super::dao::Column::Id)
                 .into(),
+            Self::GovernorBackfillHeal => {
+                Entity::has_many(super::governor_backfill_heal::Entity).into()
+            }
+            Self::GovernorBackfillState => {
+                Entity::has_one(super::governor_backfill_state::Entity).into()
+            }
             Self::Proposal => Entity::has_many(super::proposal::Entity).into(),
+            Self::ProposalTallyAudit => {
+                Entity::has_many(super::proposal_tally_audit::Entity).into()
+            }
             Self::Vote => Entity::has_many(super::vote::Entity).into(),
         }
     }
@@ -81,12 +93,30 @@ impl Related<super::dao::Entity> for Entity {
     }
 }
 
+impl Related<super::governor_backfill_heal::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GovernorBackfillHeal.def()
+    }
+}
+
+impl Related<super::governor_backfill_state::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GovernorBackfillState.def()
+    }
+}
+
 impl Related<super::proposal::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Proposal.def()
     }
 }
 
+impl Related<super::proposal_tally_audit::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProposalTallyAudit.def()
+    }
+}
+
 impl Related<super::vote::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Vote.def()
@@ -22,6 +22,8 @@ pub struct Model {
     pub proof: Option<Json>,
     pub verified: bool,
     pub created_at: DateTime,
+    pub challenge: Option<String>,
+    pub challenge_issued_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -34,6 +36,8 @@ pub enum Column {
     Proof,
     Verified,
     CreatedAt,
+    Challenge,
+    ChallengeIssuedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -51,6 +55,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Delegate,
+    DelegateToDiscourseUserVerification,
     DiscourseUser,
 }
 
@@ -66,6 +71,8 @@ impl ColumnTrait for Column {
             Self::Proof => ColumnType::JsonBinary.def().null(),
             Self::Verified => ColumnType::Boolean.def(),
             Self::CreatedAt => ColumnType::DateTime.def(),
+            Self::Challenge => ColumnType::Text.def().null().unique(),
+            Self::ChallengeIssuedAt => ColumnType::DateTime.def().null(),
         }
     }
 }
@@ -77,6 +84,9 @@ impl RelationTrait for Relation {
                 .from(Column::DelegateId)
                 .to(super::delegate::Column::Id)
                 .into(),
+            Self::DelegateToDiscourseUserVerification => {
+                Entity::has_many(super::delegate_to_discourse_user_verification::Entity).into()
+            }
             Self::DiscourseUser => Entity::belongs_to(super::discourse_user::Entity)
                 .from(Column::DiscourseUserId)
                 .to(super::discourse_user::Column::Id)
@@ -91,6 +101,12 @@ impl Related<super::delegate::Entity> for Entity {
     }
 }
 
+impl Related<super::delegate_to_discourse_user_verification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DelegateToDiscourseUserVerification.def()
+    }
+}
+
 impl Related<super::discourse_user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::DiscourseUser.def()
@@ -51,6 +51,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Delegate,
+    DelegateToVoterVerification,
     Voter,
 }
 
@@ -77,6 +78,9 @@ impl RelationTrait for Relation {
                 .from(Column::DelegateId)
                 .to(super::delegate::Column::Id)
                 .into(),
+            Self::DelegateToVoterVerification => {
+                Entity::has_many(super::delegate_to_voter_verification::Entity).into()
+            }
             Self::Voter => Entity::belongs_to(super::voter::Entity)
                 .from(Column::VoterId)
                 .to(super::voter::Column::Id)
@@ -91,6 +95,12 @@ impl Related<super::delegate::Entity> for Entity {
     }
 }
 
+impl Related<super::delegate_to_voter_verification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DelegateToVoterVerification.def()
+    }
+}
+
 impl Related<super::voter::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Voter.def()
@@ -12,7 +12,7 @@ impl EntityName for Entity {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Serialize, Deserialize)]
 pub struct Model {
     pub id: Uuid,
     pub delegator: String,
@@ -21,6 +21,9 @@ pub struct Model {
     pub timestamp: DateTime,
     pub block: i32,
     pub txid: Option<String>,
+    pub from_delegate: Option<String>,
+    pub log_index: i32,
+    pub weight: Option<f64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -32,6 +35,9 @@ pub enum Column {
     Timestamp,
     Block,
     Txid,
+    FromDelegate,
+    LogIndex,
+    Weight,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -62,6 +68,9 @@ impl ColumnTrait for Column {
             Self::Timestamp => ColumnType::DateTime.def(),
             Self::Block => ColumnType::Integer.def(),
             Self::Txid => ColumnType::Text.def().null(),
+            Self::FromDelegate => ColumnType::Text.def().null(),
+            Self::LogIndex => ColumnType::Integer.def(),
+            Self::Weight => ColumnType::Double.def().null(),
         }
     }
 }
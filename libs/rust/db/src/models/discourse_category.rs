@@ -30,6 +30,7 @@ pub struct Model {
     pub topics_year: Option<i32>,
     pub topics_all_time: Option<i32>,
     pub dao_discourse_id: Uuid,
+    pub restricted: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -50,6 +51,7 @@ pub enum Column {
     TopicsYear,
     TopicsAllTime,
     DaoDiscourseId,
+    Restricted,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -89,6 +91,7 @@ impl ColumnTrait for Column {
             Self::TopicsYear => ColumnType::Integer.def().null(),
             Self::TopicsAllTime => ColumnType::Integer.def().null(),
             Self::DaoDiscourseId => ColumnType::Uuid.def(),
+            Self::Restricted => ColumnType::Boolean.def(),
         }
     }
 }
@@ -43,6 +43,7 @@ pub struct Model {
     pub dao_discourse_id: Uuid,
     pub can_view_edit_history: bool,
     pub deleted: bool,
+    pub restricted: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -76,6 +77,7 @@ pub enum Column {
     DaoDiscourseId,
     CanViewEditHistory,
     Deleted,
+    Restricted,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -129,6 +131,7 @@ impl ColumnTrait for Column {
             Self::DaoDiscourseId => ColumnType::Uuid.def(),
             Self::CanViewEditHistory => ColumnType::Boolean.def(),
             Self::Deleted => ColumnType::Boolean.def(),
+            Self::Restricted => ColumnType::Boolean.def(),
         }
     }
 }
@@ -33,6 +33,7 @@ pub struct Model {
     pub like_count: i32,
     pub category_id: i32,
     pub dao_discourse_id: Uuid,
+    pub restricted: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -56,6 +57,7 @@ pub enum Column {
     LikeCount,
     CategoryId,
     DaoDiscourseId,
+    Restricted,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -98,6 +100,7 @@ impl ColumnTrait for Column {
             Self::LikeCount => ColumnType::Integer.def(),
             Self::CategoryId => ColumnType::Integer.def(),
             Self::DaoDiscourseId => ColumnType::Uuid.def(),
+            Self::Restricted => ColumnType::Boolean.def(),
         }
     }
 }
@@ -19,6 +19,14 @@ pub struct Model {
     pub data: Json,
     pub status: String,
     pub created_at: DateTime,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime,
+    pub locked_until: Option<DateTime>,
+    pub locked_by: Option<String>,
+    pub last_error: Option<String>,
+    pub dedupe_key: Option<String>,
+    pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -28,6 +36,14 @@ pub enum Column {
     Data,
     Status,
     CreatedAt,
+    Attempts,
+    MaxAttempts,
+    RunAt,
+    LockedUntil,
+    LockedBy,
+    LastError,
+    DedupeKey,
+    UpdatedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -54,6 +70,14 @@ impl ColumnTrait for Column {
             Self::Data => ColumnType::JsonBinary.def(),
             Self::Status => ColumnType::Text.def(),
             Self::CreatedAt => ColumnType::DateTime.def(),
+            Self::Attempts => ColumnType::Integer.def(),
+            Self::MaxAttempts => ColumnType::Integer.def(),
+            Self::RunAt => ColumnType::DateTime.def(),
+            Self::LockedUntil => ColumnType::DateTime.def().null(),
+            Self::LockedBy => ColumnType::Text.def().null(),
+            Self::LastError => ColumnType::Text.def().null(),
+            Self::DedupeKey => ColumnType::Text.def().null(),
+            Self::UpdatedAt => ColumnType::DateTime.def(),
         }
     }
 }
@@ -8,23 +8,40 @@ pub mod dao_discourse;
 pub mod dao_governor;
 pub mod delegate;
 pub mod delegate_to_discourse_user;
+pub mod delegate_to_discourse_user_verification;
 pub mod delegate_to_voter;
+pub mod delegate_to_voter_verification;
+pub mod delegated_voting_power_snapshot;
 pub mod delegation;
+pub mod delegation_flow;
+pub mod delegation_flow_sync_state;
 pub mod discourse_category;
 pub mod discourse_post;
 pub mod discourse_post_like;
 pub mod discourse_post_revision;
 pub mod discourse_topic;
 pub mod discourse_user;
+pub mod failed_event;
+pub mod governance_anomaly;
+pub mod governance_event;
+pub mod governance_event_consumer;
+pub mod governor_backfill_heal;
+pub mod governor_backfill_state;
 pub mod job_queue;
 pub mod kysely_migration;
 pub mod kysely_migration_lock;
+pub mod mapping_proposal_decision;
 pub mod proposal;
 pub mod proposal_group;
+pub mod proposal_revision;
+pub mod proposal_state_history;
+pub mod proposal_tally_audit;
 pub mod sea_orm_active_enums;
 pub mod session;
 pub mod snapshot_message;
 pub mod snapshot_sync_state;
+pub mod token_balance;
+pub mod token_transfer;
 pub mod user;
 pub mod user_notification;
 pub mod user_proposal_group_last_read;
@@ -33,3 +50,5 @@ pub mod vote;
 pub mod voter;
 pub mod voting_power_latest;
 pub mod voting_power_timeseries;
+pub mod webhook_delivery;
+pub mod webhook_subscription;
@@ -6,22 +6,39 @@ pub use super::dao_discourse::Entity as DaoDiscourse;
 pub use super::dao_governor::Entity as DaoGovernor;
 pub use super::delegate::Entity as Delegate;
 pub use super::delegate_to_discourse_user::Entity as DelegateToDiscourseUser;
+pub use super::delegate_to_discourse_user_verification::Entity as DelegateToDiscourseUserVerification;
 pub use super::delegate_to_voter::Entity as DelegateToVoter;
+pub use super::delegate_to_voter_verification::Entity as DelegateToVoterVerification;
+pub use super::delegated_voting_power_snapshot::Entity as DelegatedVotingPowerSnapshot;
 pub use super::delegation::Entity as Delegation;
+pub use super::delegation_flow::Entity as DelegationFlow;
+pub use super::delegation_flow_sync_state::Entity as DelegationFlowSyncState;
 pub use super::discourse_category::Entity as DiscourseCategory;
 pub use super::discourse_post::Entity as DiscoursePost;
 pub use super::discourse_post_like::Entity as DiscoursePostLike;
 pub use super::discourse_post_revision::Entity as DiscoursePostRevision;
 pub use super::discourse_topic::Entity as DiscourseTopic;
 pub use super::discourse_user::Entity as DiscourseUser;
+pub use super::failed_event::Entity as FailedEvent;
+pub use super::governance_anomaly::Entity as GovernanceAnomaly;
+pub use super::governance_event::Entity as GovernanceEvent;
+pub use super::governance_event_consumer::Entity as GovernanceEventConsumer;
+pub use super::governor_backfill_heal::Entity as GovernorBackfillHeal;
+pub use super::governor_backfill_state::Entity as GovernorBackfillState;
 pub use super::job_queue::Entity as JobQueue;
 pub use super::kysely_migration::Entity as KyselyMigration;
 pub use super::kysely_migration_lock::Entity as KyselyMigrationLock;
+pub use super::mapping_proposal_decision::Entity as MappingProposalDecision;
 pub use super::proposal::Entity as Proposal;
 pub use super::proposal_group::Entity as ProposalGroup;
+pub use super::proposal_revision::Entity as ProposalRevision;
+pub use super::proposal_state_history::Entity as ProposalStateHistory;
+pub use super::proposal_tally_audit::Entity as ProposalTallyAudit;
 pub use super::session::Entity as Session;
 pub use super::snapshot_message::Entity as SnapshotMessage;
 pub use super::snapshot_sync_state::Entity as SnapshotSyncState;
+pub use super::token_balance::Entity as TokenBalance;
+pub use super::token_transfer::Entity as TokenTransfer;
 pub use super::user::Entity as User;
 pub use super::user_notification::Entity as UserNotification;
 pub use super::user_proposal_group_last_read::Entity as UserProposalGroupLastRead;
@@ -30,3 +47,5 @@ pub use super::vote::Entity as Vote;
 pub use super::voter::Entity as Voter;
 pub use super::voting_power_latest::Entity as VotingPowerLatest;
 pub use super::voting_power_timeseries::Entity as VotingPowerTimeseries;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
+pub use super::webhook_subscription::Entity as WebhookSubscription;
@@ -79,6 +79,11 @@ impl PrimaryKeyTrait for PrimaryKey {
 pub enum Relation {
     Dao,
     DaoGovernor,
+    GovernanceAnomaly,
+    MappingProposalDecision,
+    ProposalRevision,
+    ProposalStateHistory,
+    ProposalTallyAudit,
     Vote,
 }
 
@@ -122,6 +127,17 @@ impl RelationTrait for Relation {
                 .from(Column::GovernorId)
                 .to(super::dao_governor::Column::Id)
                 .into(),
+            Self::GovernanceAnomaly => Entity::has_many(super::governance_anomaly::Entity).into(),
+            Self::MappingProposalDecision => {
+                Entity::has_many(super::mapping_proposal_decision::Entity).into()
+            }
+            Self::ProposalRevision => Entity::has_many(super::proposal_revision::Entity).into(),
+            Self::ProposalStateHistory => {
+                Entity::has_many(super::proposal_state_history::Entity).into()
+            }
+            Self::ProposalTallyAudit => {
+                Entity::has_many(super::proposal_tally_audit::Entity).into()
+            }
             Self::Vote => Entity::has_many(super::vote::Entity).into(),
         }
     }
@@ -139,6 +155,36 @@ impl Related<super::dao_governor::Entity> for Entity {
     }
 }
 
+impl Related<super::governance_anomaly::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GovernanceAnomaly.def()
+    }
+}
+
+impl Related<super::mapping_proposal_decision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MappingProposalDecision.def()
+    }
+}
+
+impl Related<super::proposal_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProposalRevision.def()
+    }
+}
+
+impl Related<super::proposal_state_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProposalStateHistory.def()
+    }
+}
+
+impl Related<super::proposal_tally_audit::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProposalTallyAudit.def()
+    }
+}
+
 impl Related<super::vote::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Vote.def()
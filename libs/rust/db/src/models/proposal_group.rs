@@ -45,6 +45,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Dao,
+    MappingProposalDecision,
     UserProposalGroupLastRead,
 }
 
@@ -68,6 +69,9 @@ impl RelationTrait for Relation {
                 .from(Column::DaoId)
                 .to(super::dao::Column::Id)
                 .into(),
+            Self::MappingProposalDecision => {
+                Entity::has_many(super::mapping_proposal_decision::Entity).into()
+            }
             Self::UserProposalGroupLastRead => {
                 Entity::has_many(super::user_proposal_group_last_read::Entity).into()
             }
@@ -81,6 +85,12 @@ impl Related<super::dao::Entity> for Entity {
     }
 }
 
+impl Related<super::mapping_proposal_decision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MappingProposalDecision.def()
+    }
+}
+
 impl Related<super::user_proposal_group_last_read::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserProposalGroupLastRead.def()
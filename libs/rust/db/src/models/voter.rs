@@ -19,6 +19,12 @@ pub struct Model {
     pub ens: Option<String>,
     pub avatar: Option<String>,
     pub updated_at: DateTime,
+    pub ens_verified: bool,
+    pub url: Option<String>,
+    pub twitter: Option<String>,
+    pub discourse: Option<String>,
+    pub description: Option<String>,
+    pub ens_refreshed_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -28,6 +34,12 @@ pub enum Column {
     Ens,
     Avatar,
     UpdatedAt,
+    EnsVerified,
+    Url,
+    Twitter,
+    Discourse,
+    Description,
+    EnsRefreshedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -57,6 +69,12 @@ impl ColumnTrait for Column {
             Self::Ens => ColumnType::Text.def().null(),
             Self::Avatar => ColumnType::Text.def().null(),
             Self::UpdatedAt => ColumnType::DateTime.def(),
+            Self::EnsVerified => ColumnType::Boolean.def(),
+            Self::Url => ColumnType::Text.def().null(),
+            Self::Twitter => ColumnType::Text.def().null(),
+            Self::Discourse => ColumnType::Text.def().null(),
+            Self::Description => ColumnType::Text.def().null(),
+            Self::EnsRefreshedAt => ColumnType::DateTime.def().null(),
         }
     }
 }
@@ -21,6 +21,7 @@ pub struct Model {
     pub timestamp: DateTime,
     pub block: i32,
     pub txid: Option<String>,
+    pub log_index: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -32,6 +33,7 @@ pub enum Column {
     Timestamp,
     Block,
     Txid,
+    LogIndex,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -62,6 +64,7 @@ impl ColumnTrait for Column {
             Self::Timestamp => ColumnType::DateTime.def(),
             Self::Block => ColumnType::Integer.def(),
             Self::Txid => ColumnType::Text.def().null(),
+            Self::LogIndex => ColumnType::Integer.def(),
         }
     }
 }
@@ -21,6 +21,8 @@ pub struct Model {
     pub timestamp: DateTime,
     pub block: i32,
     pub txid: Option<String>,
+    pub log_index: i32,
+    pub previous_voting_power: Option<f64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -32,6 +34,8 @@ pub enum Column {
     Timestamp,
     Block,
     Txid,
+    LogIndex,
+    PreviousVotingPower,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -62,6 +66,8 @@ impl ColumnTrait for Column {
             Self::Timestamp => ColumnType::DateTime.def(),
             Self::Block => ColumnType::Integer.def(),
             Self::Txid => ColumnType::Text.def().null(),
+            Self::LogIndex => ColumnType::Integer.def(),
+            Self::PreviousVotingPower => ColumnType::Double.def().null(),
         }
     }
 }
@@ -0,0 +1,272 @@
+//! Point-in-time voting power reads over `voting_power_timeseries`,
+//! `delegation` and `delegation_flow`.
+//!
+//! Addresses are matched exactly as stored (checksummed by the indexer).
+
+use sea_orm::{
+    ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement, Value,
+    prelude::{DateTime, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// A point in a DAO's history: a block number or a block timestamp.
+/// Both are inclusive, i.e. events in that block (or at that time) count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum At {
+    Block(i32),
+    Time(DateTime),
+}
+
+impl At {
+    /// Condition selecting rows at or before this point, bound to `$placeholder`.
+    fn condition(&self, placeholder: usize) -> String {
+        match self {
+            At::Block(_) => format!("block <= ${placeholder}"),
+            At::Time(_) => format!("timestamp <= ${placeholder}"),
+        }
+    }
+
+    fn value(&self) -> Value {
+        match *self {
+            At::Block(block) => block.into(),
+            At::Time(timestamp) => timestamp.into(),
+        }
+    }
+}
+
+/// Width of the buckets returned by [`voting_power_series`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bucket {
+    Hour,
+    Day,
+    Week,
+}
+
+impl Bucket {
+    fn unit(&self) -> &'static str {
+        match self {
+            Bucket::Hour => "hour",
+            Bucket::Day => "day",
+            Bucket::Week => "week",
+        }
+    }
+}
+
+/// Voting power of a voter as set by its latest event at or before a point.
+#[derive(Debug, Clone, PartialEq, FromQueryResult, Serialize, Deserialize)]
+pub struct VoterVotingPower {
+    pub voter: String,
+    pub voting_power: f64,
+    pub block: i32,
+    pub timestamp: DateTime,
+}
+
+/// Voting power at the end of a bucket starting at `bucket_start`.
+#[derive(Debug, Clone, PartialEq, FromQueryResult, Serialize, Deserialize)]
+pub struct VotingPowerBucket {
+    pub bucket_start: DateTime,
+    pub voting_power: f64,
+}
+
+/// A delegator and the delegation that made it one.
+#[derive(Debug, Clone, PartialEq, FromQueryResult, Serialize, Deserialize)]
+pub struct Delegator {
+    pub delegator: String,
+    pub block: i32,
+    pub timestamp: DateTime,
+}
+
+/// Voting power moved between two delegates by a delegation change
+/// (`kind = "delegate"`) or by a token transfer between their delegators
+/// (`kind = "transfer"`). A missing delegate means undelegated tokens.
+#[derive(Debug, Clone, PartialEq, FromQueryResult, Serialize, Deserialize)]
+pub struct DelegationFlow {
+    pub kind: String,
+    pub delegator: String,
+    pub from_delegate: Option<String>,
+    pub to_delegate: Option<String>,
+    pub amount: f64,
+    pub block: i32,
+    pub log_index: i32,
+    pub timestamp: DateTime,
+    pub txid: Option<String>,
+}
+
+/// Voting power of `voter` at `at`, or 0 if it had no events by then.
+pub async fn voting_power_at<C: ConnectionTrait>(
+    db: &C,
+    dao_id: Uuid,
+    voter: &str,
+    at: At,
+) -> Result<f64, DbErr> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                SELECT voting_power
+                FROM voting_power_timeseries
+                WHERE dao_id = $1 AND voter = $2 AND {}
+                ORDER BY timestamp DESC, block DESC, log_index DESC
+                LIMIT 1
+                "#,
+                at.condition(3)
+            ),
+            vec![dao_id.into(), voter.into(), at.value()],
+        ))
+        .await?;
+
+    row.map(|row| row.try_get::<f64>("", "voting_power"))
+        .transpose()
+        .map(|vp| vp.unwrap_or(0.0))
+}
+
+/// The `limit` voters with the most voting power at `at`, largest first.
+/// Voters whose voting power had dropped to zero are left out.
+pub async fn top_delegates_at<C: ConnectionTrait>(
+    db: &C,
+    dao_id: Uuid,
+    at: At,
+    limit: u32,
+) -> Result<Vec<VoterVotingPower>, DbErr> {
+    VoterVotingPower::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"
+            SELECT voter, voting_power, block, timestamp
+            FROM (
+                SELECT DISTINCT ON (voter) voter, voting_power, block, timestamp
+                FROM voting_power_timeseries
+                WHERE dao_id = $1 AND {}
+                ORDER BY voter, block DESC, log_index DESC
+            ) latest
+            WHERE voting_power > 0
+            ORDER BY voting_power DESC, voter
+            LIMIT $3
+            "#,
+            at.condition(2)
+        ),
+        vec![dao_id.into(), at.value(), i64::from(limit).into()],
+    ))
+    .all(db)
+    .await
+}
+
+/// Voting power of `voter` over `range`, one value per bucket: the voting
+/// power at the end of the bucket (or at the end of `range` for the last
+/// one), carried forward through buckets without events. Buckets are
+/// aligned to the start of the hour, day or week containing `range.start`.
+pub async fn voting_power_series<C: ConnectionTrait>(
+    db: &C,
+    dao_id: Uuid,
+    voter: &str,
+    range: Range<DateTime>,
+    bucket: Bucket,
+) -> Result<Vec<VotingPowerBucket>, DbErr> {
+    if range.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let unit = bucket.unit();
+    VotingPowerBucket::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"
+            SELECT b.bucket_start, COALESCE(v.voting_power, 0.0) AS voting_power
+            FROM generate_series(
+                date_trunc('{unit}', $3::timestamp),
+                $4::timestamp,
+                interval '1 {unit}'
+            ) AS b(bucket_start)
+            LEFT JOIN LATERAL (
+                SELECT voting_power
+                FROM voting_power_timeseries
+                WHERE dao_id = $1
+                  AND voter = $2
+                  AND timestamp < LEAST(b.bucket_start + interval '1 {unit}', $4::timestamp)
+                ORDER BY timestamp DESC, block DESC, log_index DESC
+                LIMIT 1
+            ) v ON TRUE
+            WHERE b.bucket_start < $4::timestamp
+            ORDER BY b.bucket_start
+            "#
+        ),
+        vec![
+            dao_id.into(),
+            voter.into(),
+            range.start.into(),
+            range.end.into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
+
+/// Delegators whose latest delegation at `at` points to `delegate`, oldest
+/// delegation first.
+pub async fn delegators_of<C: ConnectionTrait>(
+    db: &C,
+    dao_id: Uuid,
+    delegate: &str,
+    at: At,
+) -> Result<Vec<Delegator>, DbErr> {
+    Delegator::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"
+            SELECT d.delegator, d.block, d.timestamp
+            FROM (
+                SELECT DISTINCT delegator
+                FROM delegation
+                WHERE dao_id = $1 AND delegate = $2
+            ) candidates
+            CROSS JOIN LATERAL (
+                SELECT delegator, delegate, block, timestamp
+                FROM delegation
+                WHERE dao_id = $1 AND delegator = candidates.delegator AND {}
+                ORDER BY block DESC, timestamp DESC, log_index DESC
+                LIMIT 1
+            ) d
+            WHERE d.delegate = $2
+            ORDER BY d.block, d.delegator
+            "#,
+            at.condition(3)
+        ),
+        vec![dao_id.into(), delegate.into(), at.value()],
+    ))
+    .all(db)
+    .await
+}
+
+/// Flows into and out of `delegate` within `range`, oldest first. Inflows
+/// have `to_delegate == delegate`, outflows `from_delegate == delegate`.
+pub async fn delegation_flows<C: ConnectionTrait>(
+    db: &C,
+    dao_id: Uuid,
+    delegate: &str,
+    range: Range<DateTime>,
+) -> Result<Vec<DelegationFlow>, DbErr> {
+    DelegationFlow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        SELECT kind, delegator, from_delegate, to_delegate, amount, block, log_index, timestamp, txid
+        FROM (
+            SELECT * FROM delegation_flow
+            WHERE dao_id = $1 AND to_delegate = $2 AND timestamp >= $3 AND timestamp < $4
+            UNION
+            SELECT * FROM delegation_flow
+            WHERE dao_id = $1 AND from_delegate = $2 AND timestamp >= $3 AND timestamp < $4
+        ) flows
+        ORDER BY block, log_index, kind
+        "#,
+        vec![
+            dao_id.into(),
+            delegate.into(),
+            range.start.into(),
+            range.end.into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
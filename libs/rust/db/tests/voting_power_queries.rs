@@ -0,0 +1,385 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use proposalsapp_db::{
+    models::{dao, delegation, delegation_flow, voting_power_timeseries},
+    voting_power::{
+        At, Bucket, delegation_flows, delegators_of, top_delegates_at, voting_power_at,
+        voting_power_series,
+    },
+};
+use sea_orm::{ActiveValue::NotSet, DatabaseConnection, EntityTrait, Set, prelude::Uuid};
+use serial_test::serial;
+use test_support::{DOCKER_AVAILABLE, TEST_RUNTIME};
+use tokio::sync::OnceCell;
+
+const DAO_ID: Uuid = Uuid::from_u128(30);
+const VOTER_A: &str = "0x000000000000000000000000000000000000000A";
+const VOTER_B: &str = "0x000000000000000000000000000000000000000B";
+const VOTER_C: &str = "0x000000000000000000000000000000000000000C";
+const DELEGATOR_1: &str = "0x00000000000000000000000000000000000000D1";
+const DELEGATOR_2: &str = "0x00000000000000000000000000000000000000D2";
+const DELEGATOR_3: &str = "0x00000000000000000000000000000000000000D3";
+
+static SEEDED: OnceCell<()> = OnceCell::const_new();
+
+/// The shared database, seeded once with the voting power the tests query.
+async fn test_db() -> Result<&'static DatabaseConnection> {
+    let db = test_support::test_db().await;
+    SEEDED.get_or_try_init(|| seed_voting_power(db)).await?;
+    Ok(db)
+}
+
+fn day(d: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, d)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+fn vp_row(
+    voter: &str,
+    block: i32,
+    log_index: i32,
+    timestamp: NaiveDateTime,
+    voting_power: f64,
+) -> voting_power_timeseries::ActiveModel {
+    voting_power_timeseries::ActiveModel {
+        id: NotSet,
+        voter: Set(voter.to_string()),
+        voting_power: Set(voting_power),
+        dao_id: Set(DAO_ID),
+        timestamp: Set(timestamp),
+        block: Set(block),
+        txid: Set(Some(format!("0xvp{block}{log_index}{voter}"))),
+        log_index: Set(log_index),
+        previous_voting_power: NotSet,
+    }
+}
+
+fn delegation_row(
+    delegator: &str,
+    delegate: &str,
+    block: i32,
+    timestamp: NaiveDateTime,
+) -> delegation::ActiveModel {
+    delegation::ActiveModel {
+        id: NotSet,
+        delegator: Set(delegator.to_string()),
+        delegate: Set(delegate.to_string()),
+        dao_id: Set(DAO_ID),
+        timestamp: Set(timestamp),
+        block: Set(block),
+        txid: Set(Some(format!("0xdel{block}{delegator}"))),
+        from_delegate: NotSet,
+        log_index: NotSet,
+        weight: NotSet,
+    }
+}
+
+fn flow_row(
+    kind: &str,
+    delegator: &str,
+    from_delegate: Option<&str>,
+    to_delegate: Option<&str>,
+    amount: f64,
+    block: i32,
+    timestamp: NaiveDateTime,
+) -> delegation_flow::ActiveModel {
+    delegation_flow::ActiveModel {
+        id: NotSet,
+        dao_id: Set(DAO_ID),
+        kind: Set(kind.to_string()),
+        delegator: Set(delegator.to_string()),
+        from_delegate: Set(from_delegate.map(str::to_string)),
+        to_delegate: Set(to_delegate.map(str::to_string)),
+        amount: Set(amount),
+        block: Set(block),
+        log_index: Set(0),
+        timestamp: Set(timestamp),
+        txid: Set(Some(format!("0xflow{block}{kind}"))),
+    }
+}
+
+async fn seed_voting_power(db: &DatabaseConnection) -> Result<()> {
+    dao::Entity::insert(dao::ActiveModel {
+        id: Set(DAO_ID),
+        name: Set("Voting Power DAO".to_string()),
+        slug: Set("voting-power-dao".to_string()),
+        picture: Set("https://example.com/dao.png".to_string()),
+    })
+    .exec(db)
+    .await
+    .context("failed to insert dao")?;
+
+    voting_power_timeseries::Entity::insert_many([
+        vp_row(VOTER_A, 100, 0, day(1, 0), 10.0),
+        vp_row(VOTER_A, 100, 2, day(1, 0), 15.0),
+        vp_row(VOTER_B, 110, 0, day(2, 0), 20.0),
+        vp_row(VOTER_A, 120, 0, day(3, 0), 5.0),
+        vp_row(VOTER_C, 130, 0, day(4, 0), 30.0),
+        vp_row(VOTER_B, 140, 0, day(5, 0), 0.0),
+    ])
+    .exec(db)
+    .await
+    .context("failed to insert voting power")?;
+
+    delegation::Entity::insert_many([
+        delegation_row(DELEGATOR_1, VOTER_A, 100, day(1, 0)),
+        delegation_row(DELEGATOR_2, VOTER_A, 105, day(1, 12)),
+        delegation_row(DELEGATOR_3, VOTER_B, 108, day(1, 18)),
+        delegation_row(DELEGATOR_2, VOTER_B, 115, day(2, 12)),
+        delegation_row(DELEGATOR_3, VOTER_A, 125, day(3, 12)),
+    ])
+    .exec(db)
+    .await
+    .context("failed to insert delegations")?;
+
+    delegation_flow::Entity::insert_many([
+        flow_row(
+            "delegate",
+            DELEGATOR_1,
+            None,
+            Some(VOTER_A),
+            10.0,
+            100,
+            day(1, 0),
+        ),
+        flow_row(
+            "delegate",
+            DELEGATOR_2,
+            None,
+            Some(VOTER_A),
+            5.0,
+            105,
+            day(1, 12),
+        ),
+        flow_row(
+            "delegate",
+            DELEGATOR_2,
+            Some(VOTER_A),
+            Some(VOTER_B),
+            5.0,
+            115,
+            day(2, 12),
+        ),
+        flow_row(
+            "transfer",
+            DELEGATOR_1,
+            Some(VOTER_A),
+            Some(VOTER_C),
+            2.0,
+            118,
+            day(2, 18),
+        ),
+        flow_row(
+            "delegate",
+            DELEGATOR_3,
+            Some(VOTER_B),
+            Some(VOTER_A),
+            7.0,
+            125,
+            day(3, 12),
+        ),
+    ])
+    .exec(db)
+    .await
+    .context("failed to insert delegation flows")?;
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_voting_power_at_block_and_time() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        assert_eq!(
+            voting_power_at(db, DAO_ID, VOTER_A, At::Block(99)).await?,
+            0.0
+        );
+        // The last event in a block wins
+        assert_eq!(
+            voting_power_at(db, DAO_ID, VOTER_A, At::Block(100)).await?,
+            15.0
+        );
+        assert_eq!(
+            voting_power_at(db, DAO_ID, VOTER_A, At::Block(120)).await?,
+            5.0
+        );
+        assert_eq!(
+            voting_power_at(db, DAO_ID, VOTER_A, At::Time(day(2, 12))).await?,
+            15.0
+        );
+        assert_eq!(
+            voting_power_at(db, DAO_ID, "0xunknown", At::Block(200)).await?,
+            0.0
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_top_delegates_at() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        let top = top_delegates_at(db, DAO_ID, At::Block(125), 10).await?;
+        let top: Vec<(&str, f64)> = top
+            .iter()
+            .map(|d| (d.voter.as_str(), d.voting_power))
+            .collect();
+        assert_eq!(top, vec![(VOTER_B, 20.0), (VOTER_A, 5.0)]);
+
+        // Voter B dropped to zero by block 140 and is left out
+        let top = top_delegates_at(db, DAO_ID, At::Time(day(6, 0)), 10).await?;
+        let top: Vec<(&str, f64)> = top
+            .iter()
+            .map(|d| (d.voter.as_str(), d.voting_power))
+            .collect();
+        assert_eq!(top, vec![(VOTER_C, 30.0), (VOTER_A, 5.0)]);
+
+        let top = top_delegates_at(db, DAO_ID, At::Block(200), 1).await?;
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].voter, VOTER_C);
+        assert_eq!(top[0].block, 130);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_voting_power_series_carries_values_forward() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        let series =
+            voting_power_series(db, DAO_ID, VOTER_A, day(1, 0)..day(4, 0), Bucket::Day).await?;
+        let series: Vec<(NaiveDateTime, f64)> = series
+            .into_iter()
+            .map(|b| (b.bucket_start, b.voting_power))
+            .collect();
+        assert_eq!(
+            series,
+            vec![(day(1, 0), 15.0), (day(2, 0), 15.0), (day(3, 0), 5.0)]
+        );
+
+        // Buckets are aligned to the start of the day and the last one ends
+        // at the end of the range
+        let series =
+            voting_power_series(db, DAO_ID, VOTER_B, day(1, 6)..day(2, 6), Bucket::Day).await?;
+        let series: Vec<(NaiveDateTime, f64)> = series
+            .into_iter()
+            .map(|b| (b.bucket_start, b.voting_power))
+            .collect();
+        assert_eq!(series, vec![(day(1, 0), 0.0), (day(2, 0), 20.0)]);
+
+        let series =
+            voting_power_series(db, DAO_ID, VOTER_A, day(4, 0)..day(4, 0), Bucket::Hour).await?;
+        assert!(series.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_delegators_of_follows_redelegations() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        let delegators = |rows: Vec<proposalsapp_db::voting_power::Delegator>| {
+            rows.into_iter()
+                .map(|d| (d.delegator, d.block))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            delegators(delegators_of(db, DAO_ID, VOTER_A, At::Block(110)).await?),
+            vec![
+                (DELEGATOR_1.to_string(), 100),
+                (DELEGATOR_2.to_string(), 105)
+            ]
+        );
+        // Delegator 2 moved to B at 115, delegator 3 moved to A at 125
+        assert_eq!(
+            delegators(delegators_of(db, DAO_ID, VOTER_A, At::Block(130)).await?),
+            vec![
+                (DELEGATOR_1.to_string(), 100),
+                (DELEGATOR_3.to_string(), 125)
+            ]
+        );
+        assert_eq!(
+            delegators(delegators_of(db, DAO_ID, VOTER_B, At::Time(day(3, 0))).await?),
+            vec![
+                (DELEGATOR_3.to_string(), 108),
+                (DELEGATOR_2.to_string(), 115)
+            ]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_delegation_flows_in_and_out() -> Result<()> {
+    if !*DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        let flows = delegation_flows(db, DAO_ID, VOTER_A, day(1, 6)..day(4, 0)).await?;
+        let flows: Vec<(&str, Option<&str>, Option<&str>, f64)> = flows
+            .iter()
+            .map(|f| {
+                (
+                    f.kind.as_str(),
+                    f.from_delegate.as_deref(),
+                    f.to_delegate.as_deref(),
+                    f.amount,
+                )
+            })
+            .collect();
+        assert_eq!(
+            flows,
+            vec![
+                ("delegate", None, Some(VOTER_A), 5.0),
+                ("delegate", Some(VOTER_A), Some(VOTER_B), 5.0),
+                ("transfer", Some(VOTER_A), Some(VOTER_C), 2.0),
+                ("delegate", Some(VOTER_B), Some(VOTER_A), 7.0),
+            ]
+        );
+
+        let flows = delegation_flows(db, DAO_ID, VOTER_C, day(1, 0)..day(2, 0)).await?;
+        assert!(flows.is_empty());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,113 @@
+//! Postgres for integration tests: one container per test binary, migrated
+//! with the TypeScript migrations of `@proposalsapp/db`.
+
+use anyhow::{Context, Result, anyhow};
+use once_cell::sync::Lazy;
+use sea_orm::{Database, DatabaseConnection};
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+use testcontainers::{
+    ContainerAsync, GenericImage, ImageExt,
+    core::{IntoContainerPort, WaitFor},
+    runners::AsyncRunner,
+};
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::OnceCell;
+
+const POSTGRES_IMAGE: &str = "pgvector/pgvector";
+const POSTGRES_TAG: &str = "pg16";
+const POSTGRES_PORT: u16 = 5432;
+const POSTGRES_USER: &str = "postgres";
+const POSTGRES_PASSWORD: &str = "postgres";
+const POSTGRES_DB: &str = "proposalsapp_test";
+
+struct TestContext {
+    db: DatabaseConnection,
+    _container: ContainerAsync<GenericImage>,
+}
+
+pub static DOCKER_AVAILABLE: Lazy<bool> = Lazy::new(|| {
+    Command::new("docker")
+        .arg("info")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+});
+pub static TEST_RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime")
+});
+static TEST_CONTEXT: OnceCell<TestContext> = OnceCell::const_new();
+
+/// A database with all migrations, shared by the tests of a file.
+pub async fn test_db() -> &'static DatabaseConnection {
+    let context = TEST_CONTEXT
+        .get_or_init(|| async {
+            init_context()
+                .await
+                .expect("failed to initialize test context")
+        })
+        .await;
+    &context.db
+}
+
+async fn init_context() -> Result<TestContext> {
+    let container = GenericImage::new(POSTGRES_IMAGE, POSTGRES_TAG)
+        .with_exposed_port(POSTGRES_PORT.tcp())
+        .with_wait_for(WaitFor::message_on_stdout(
+            "database system is ready to accept connections",
+        ))
+        .with_env_var("POSTGRES_PASSWORD", POSTGRES_PASSWORD)
+        .with_env_var("POSTGRES_USER", POSTGRES_USER)
+        .with_env_var("POSTGRES_DB", POSTGRES_DB)
+        .start()
+        .await
+        .context("failed to start postgres container")?;
+
+    let host = "127.0.0.1";
+    let port = container.get_host_port_ipv4(POSTGRES_PORT).await?;
+    let database_url =
+        format!("postgres://{POSTGRES_USER}:{POSTGRES_PASSWORD}@{host}:{port}/{POSTGRES_DB}");
+
+    run_migrations(&database_url).context("failed running migrations")?;
+
+    let db = Database::connect(&database_url)
+        .await
+        .context("failed to connect to postgres")?;
+
+    Ok(TestContext {
+        db,
+        _container: container,
+    })
+}
+
+fn repo_root() -> Result<PathBuf> {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .ancestors()
+        .nth(3)
+        .context("failed to resolve repo root")?;
+    Ok(root.to_path_buf())
+}
+
+fn run_migrations(database_url: &str) -> Result<()> {
+    let root: &Path = &repo_root()?;
+    let status = Command::new("pnpm")
+        .args(["--filter", "@proposalsapp/db", "db:migrate"])
+        .current_dir(root)
+        .env("DATABASE_URL", database_url)
+        .status()
+        .context("failed to run pnpm command")?;
+
+    if !status.success() {
+        return Err(anyhow!("db:migrate failed with status {:?}", status.code()));
+    }
+
+    Ok(())
+}
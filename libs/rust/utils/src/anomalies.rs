@@ -0,0 +1,492 @@
+use crate::outbox::{GovernanceEvent, OutboxConsumer, StoredEvent, publish};
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use proposalsapp_db::models::{delegation, proposal, vote, voting_power_timeseries};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, QueryFilter,
+    Statement, TransactionTrait, prelude::Uuid,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{fmt, time::Duration};
+use tracing::{info, instrument};
+
+/// Name the detector stores its outbox offset under.
+pub const CONSUMER_NAME: &str = "anomalies";
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Kinds of anomalies, stored as `governance_anomaly.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AnomalyKind {
+    /// A delegate's voting power changed a lot during an active proposal.
+    /// `amount` is the change.
+    VotingPowerSwing,
+    /// A vote holding a large share of the total delegated voting power.
+    /// `amount` is its voting power.
+    WhaleVote,
+    /// Many delegations to one delegate right before a proposal's snapshot.
+    /// `amount` is the delegated weight, when known.
+    DelegationBurst,
+}
+
+impl AnomalyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyKind::VotingPowerSwing => "VOTING_POWER_SWING",
+            AnomalyKind::WhaleVote => "WHALE_VOTE",
+            AnomalyKind::DelegationBurst => "DELEGATION_BURST",
+        }
+    }
+}
+
+impl fmt::Display for AnomalyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnomalyThresholds {
+    /// Change of a delegate's voting power during an active proposal,
+    /// relative to what it had before.
+    pub voting_power_swing: f64,
+    /// Swings smaller than this share of the total delegated voting power
+    /// are ignored, however large relative to the delegate.
+    pub min_swing_share: f64,
+    /// Share of the total delegated voting power of a single vote.
+    pub whale_vote_share: f64,
+    /// Delegators delegating to one delegate within `delegation_burst_window`
+    /// before a proposal's snapshot.
+    pub delegation_burst_count: i64,
+    pub delegation_burst_window: Duration,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            voting_power_swing: 0.5,
+            min_swing_share: 0.001,
+            whale_vote_share: 0.05,
+            delegation_burst_count: 20,
+            delegation_burst_window: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl AnomalyThresholds {
+    /// Whether voting power going from `previous` to `current` is a swing,
+    /// with `total` the delegated voting power at the time, 0 when unknown.
+    pub fn is_swing(&self, previous: f64, current: f64, total: f64) -> bool {
+        let change = (current - previous).abs();
+        if total > 0.0 && change < self.min_swing_share * total {
+            return false;
+        }
+        if previous > 0.0 {
+            change / previous >= self.voting_power_swing
+        } else {
+            // Without a total, any voting power out of nothing would count
+            total > 0.0 && change > 0.0
+        }
+    }
+}
+
+struct Anomaly {
+    dao_id: Uuid,
+    proposal_id: Option<Uuid>,
+    kind: AnomalyKind,
+    address: String,
+    block: Option<i32>,
+    amount: f64,
+    details: Value,
+    dedupe_key: String,
+}
+
+/// Detects anomalies around live votes from indexed voting power changes,
+/// delegations and votes, records each once in `governance_anomaly` and
+/// publishes it as [`GovernanceEvent::AnomalyDetected`].
+#[derive(Clone)]
+pub struct AnomalyDetector {
+    db: DatabaseConnection,
+    thresholds: AnomalyThresholds,
+}
+
+impl AnomalyDetector {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            thresholds: AnomalyThresholds::default(),
+        }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: AnomalyThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Tail governance events for anomalies, forever.
+    pub async fn run(&self) -> Result<()> {
+        OutboxConsumer::new(self.db.clone(), CONSUMER_NAME)
+            .run(|event| {
+                let detector = self.clone();
+                async move { detector.handle_event(&event).await.map(|_| ()) }
+            })
+            .await
+    }
+
+    /// Check what `event` describes for anomalies. Returns how many were
+    /// recorded; ones recorded before are skipped.
+    #[instrument(skip_all, fields(event_id = event.id))]
+    pub async fn handle_event(&self, event: &StoredEvent) -> Result<usize> {
+        match &event.event {
+            GovernanceEvent::VotingPowerChanged { timeseries_id, .. } => {
+                self.check_voting_power_swing(*timeseries_id).await
+            }
+            GovernanceEvent::VoteCast { vote_id, .. } => self.check_whale_vote(*vote_id).await,
+            GovernanceEvent::DelegationChanged { delegation_id, .. } => {
+                self.check_delegation(*delegation_id).await
+            }
+            // Delegations indexed before the proposal are only checked now
+            GovernanceEvent::ProposalCreated { proposal_id, .. } => {
+                let Some(proposal) = proposal::Entity::find_by_id(*proposal_id)
+                    .one(&self.db)
+                    .await
+                    .context("Failed to fetch proposal")?
+                    .filter(|proposal| !proposal.marked_spam)
+                else {
+                    return Ok(0);
+                };
+                self.check_delegation_bursts(&proposal, None).await
+            }
+            GovernanceEvent::ProposalStateChanged { .. }
+            | GovernanceEvent::TopicCreated { .. }
+            | GovernanceEvent::PostEdited { .. }
+            | GovernanceEvent::AnomalyDetected { .. } => Ok(0),
+        }
+    }
+
+    async fn check_voting_power_swing(&self, timeseries_id: Uuid) -> Result<usize> {
+        let Some(row) = voting_power_timeseries::Entity::find_by_id(timeseries_id)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch voting power change")?
+        else {
+            return Ok(0);
+        };
+        let Some(previous) = row.previous_voting_power else {
+            return Ok(0);
+        };
+
+        let total = self.total_delegated_at(row.dao_id, row.timestamp).await?;
+        if !self.thresholds.is_swing(previous, row.voting_power, total) {
+            return Ok(0);
+        }
+
+        let proposals = proposal::Entity::find()
+            .filter(proposal::Column::DaoId.eq(row.dao_id))
+            .filter(proposal::Column::MarkedSpam.eq(false))
+            .filter(proposal::Column::StartAt.lte(row.timestamp))
+            .filter(proposal::Column::EndAt.gt(row.timestamp))
+            .all(&self.db)
+            .await
+            .context("Failed to fetch active proposals")?;
+
+        let mut recorded = 0;
+        for proposal in proposals {
+            let kind = AnomalyKind::VotingPowerSwing;
+            let anomaly = Anomaly {
+                dao_id: row.dao_id,
+                proposal_id: Some(proposal.id),
+                kind,
+                address: row.voter.clone(),
+                block: Some(row.block),
+                amount: row.voting_power - previous,
+                details: json!({
+                    "previous_voting_power": previous,
+                    "voting_power": row.voting_power,
+                    "total_delegated_vp": total,
+                    "txid": row.txid,
+                }),
+                dedupe_key: format!("{kind}:{}:{}", row.id, proposal.id),
+            };
+            if self.record(anomaly).await? {
+                recorded += 1;
+            }
+        }
+        Ok(recorded)
+    }
+
+    async fn check_whale_vote(&self, vote_id: Uuid) -> Result<usize> {
+        let Some((vote, Some(proposal))) = vote::Entity::find_by_id(vote_id)
+            .find_also_related(proposal::Entity)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch vote")?
+        else {
+            return Ok(0);
+        };
+        if proposal.marked_spam {
+            return Ok(0);
+        }
+
+        // Prefer the total the proposal was created with
+        let total = match proposal
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata["total_delegated_vp"].as_f64())
+            .filter(|total| *total > 0.0)
+        {
+            Some(total) => total,
+            None => {
+                self.total_delegated_at(proposal.dao_id, proposal.start_at)
+                    .await?
+            }
+        };
+        if total <= 0.0 {
+            return Ok(0);
+        }
+        let share = vote.voting_power / total;
+        if share < self.thresholds.whale_vote_share {
+            return Ok(0);
+        }
+
+        let kind = AnomalyKind::WhaleVote;
+        let anomaly = Anomaly {
+            dao_id: vote.dao_id,
+            proposal_id: Some(proposal.id),
+            kind,
+            address: vote.voter_address.clone(),
+            block: vote.block_created_at,
+            amount: vote.voting_power,
+            details: json!({
+                "share": share,
+                "total_delegated_vp": total,
+                "choice": vote.choice,
+                "txid": vote.txid,
+            }),
+            dedupe_key: format!("{kind}:{}", vote.id),
+        };
+        Ok(usize::from(self.record(anomaly).await?))
+    }
+
+    async fn check_delegation(&self, delegation_id: Uuid) -> Result<usize> {
+        let Some(delegation) = delegation::Entity::find_by_id(delegation_id)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch delegation")?
+        else {
+            return Ok(0);
+        };
+        if delegation.delegate == ZERO_ADDRESS {
+            return Ok(0);
+        }
+
+        // Proposals whose snapshot is within the burst window after it
+        let window = chrono::Duration::from_std(self.thresholds.delegation_burst_window)?;
+        let proposals = proposal::Entity::find()
+            .filter(proposal::Column::DaoId.eq(delegation.dao_id))
+            .filter(proposal::Column::MarkedSpam.eq(false))
+            .filter(proposal::Column::StartAt.gte(delegation.timestamp))
+            .filter(proposal::Column::StartAt.lt(delegation.timestamp + window))
+            .all(&self.db)
+            .await
+            .context("Failed to fetch upcoming proposals")?;
+
+        let mut recorded = 0;
+        for proposal in proposals {
+            recorded += self
+                .check_delegation_bursts(&proposal, Some(&delegation.delegate))
+                .await?;
+        }
+        Ok(recorded)
+    }
+
+    /// Record delegates of `delegate`, or any, that enough delegators
+    /// delegated to within the burst window before the snapshot of
+    /// `proposal`.
+    async fn check_delegation_bursts(
+        &self,
+        proposal: &proposal::Model,
+        delegate: Option<&str>,
+    ) -> Result<usize> {
+        let window = chrono::Duration::from_std(self.thresholds.delegation_burst_window)?;
+        let bursts = self
+            .db
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT
+                    delegate,
+                    COUNT(DISTINCT delegator) AS delegators,
+                    COALESCE(SUM(weight), 0) AS weight,
+                    MIN(block) AS first_block,
+                    MAX(block) AS last_block
+                FROM delegation
+                WHERE dao_id = $1
+                    AND timestamp > $2 AND timestamp <= $3
+                    AND delegate <> $4
+                    AND ($5::text IS NULL OR delegate = $5)
+                GROUP BY delegate
+                HAVING COUNT(DISTINCT delegator) >= $6
+                "#,
+                vec![
+                    proposal.dao_id.into(),
+                    (proposal.start_at - window).into(),
+                    proposal.start_at.into(),
+                    ZERO_ADDRESS.into(),
+                    delegate.map(str::to_string).into(),
+                    self.thresholds.delegation_burst_count.into(),
+                ],
+            ))
+            .await
+            .context("Failed to count delegations before snapshot")?;
+
+        let mut recorded = 0;
+        for burst in bursts {
+            let delegate: String = burst.try_get("", "delegate")?;
+            let delegators: i64 = burst.try_get("", "delegators")?;
+            let weight: f64 = burst.try_get("", "weight")?;
+            let first_block: i32 = burst.try_get("", "first_block")?;
+            let last_block: i32 = burst.try_get("", "last_block")?;
+
+            let kind = AnomalyKind::DelegationBurst;
+            let dedupe_key = format!("{kind}:{}:{delegate}", proposal.id);
+            let anomaly = Anomaly {
+                dao_id: proposal.dao_id,
+                proposal_id: Some(proposal.id),
+                kind,
+                address: delegate,
+                block: Some(last_block),
+                amount: weight,
+                details: json!({
+                    "delegators": delegators,
+                    "first_block": first_block,
+                    "snapshot_block": proposal.block_start_at,
+                    "window_secs": self.thresholds.delegation_burst_window.as_secs(),
+                }),
+                dedupe_key,
+            };
+            if self.record(anomaly).await? {
+                recorded += 1;
+            }
+        }
+        Ok(recorded)
+    }
+
+    /// Total delegated voting power of a DAO as of `timestamp`, or 0 when no
+    /// snapshot is that old.
+    async fn total_delegated_at(&self, dao_id: Uuid, timestamp: NaiveDateTime) -> Result<f64> {
+        let row = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT total_voting_power
+                FROM delegated_voting_power_snapshot
+                WHERE dao_id = $1 AND timestamp <= $2
+                ORDER BY timestamp DESC, block DESC
+                LIMIT 1
+                "#,
+                vec![dao_id.into(), timestamp.into()],
+            ))
+            .await
+            .context("Failed to query delegated voting power snapshot")?;
+
+        Ok(row
+            .map(|row| row.try_get::<f64>("", "total_voting_power"))
+            .transpose()
+            .context("Failed to read total delegated voting power")?
+            .unwrap_or(0.0))
+    }
+
+    /// Store `anomaly` and publish it, unless it was recorded before.
+    async fn record(&self, anomaly: Anomaly) -> Result<bool> {
+        let txn = self
+            .db
+            .begin()
+            .await
+            .context("Failed to start anomaly transaction")?;
+
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                INSERT INTO governance_anomaly
+                    (dao_id, proposal_id, kind, address, block, amount, details, dedupe_key)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (dedupe_key) DO NOTHING
+                RETURNING id
+                "#,
+                vec![
+                    anomaly.dao_id.into(),
+                    anomaly.proposal_id.into(),
+                    anomaly.kind.as_str().into(),
+                    anomaly.address.clone().into(),
+                    anomaly.block.into(),
+                    anomaly.amount.into(),
+                    anomaly.details.into(),
+                    anomaly.dedupe_key.clone().into(),
+                ],
+            ))
+            .await
+            .context("Failed to record anomaly")?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let anomaly_id: Uuid = row.try_get("", "id")?;
+
+        publish(
+            &txn,
+            anomaly.dao_id,
+            [GovernanceEvent::AnomalyDetected {
+                anomaly_id,
+                kind: anomaly.kind,
+                proposal_id: anomaly.proposal_id,
+            }],
+        )
+        .await?;
+        txn.commit().await.context("Failed to commit anomaly")?;
+
+        info!(
+            anomaly_id = %anomaly_id,
+            kind = %anomaly.kind,
+            address = %anomaly.address,
+            proposal_id = ?anomaly.proposal_id,
+            amount = anomaly.amount,
+            "Anomaly detected"
+        );
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_swing() {
+        let thresholds = AnomalyThresholds::default();
+
+        assert!(thresholds.is_swing(1_000.0, 1_600.0, 100_000.0));
+        assert!(thresholds.is_swing(1_000.0, 400.0, 100_000.0));
+        assert!(!thresholds.is_swing(1_000.0, 1_400.0, 100_000.0));
+        // Large relative to the delegate, negligible for the DAO
+        assert!(!thresholds.is_swing(10.0, 50.0, 100_000.0));
+        // Voting power out of nothing only counts against a known total
+        assert!(thresholds.is_swing(0.0, 500.0, 100_000.0));
+        assert!(!thresholds.is_swing(0.0, 500.0, 0.0));
+        assert!(thresholds.is_swing(1_000.0, 2_000.0, 0.0));
+    }
+
+    #[test]
+    fn test_kind_matches_serialization() {
+        for kind in [
+            AnomalyKind::VotingPowerSwing,
+            AnomalyKind::WhaleVote,
+            AnomalyKind::DelegationBurst,
+        ] {
+            assert_eq!(serde_json::to_value(kind).unwrap(), json!(kind.as_str()));
+        }
+    }
+}
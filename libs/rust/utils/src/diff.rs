@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+
+/// Line pairs above which the middle of two texts is shown as replaced
+/// rather than diffed, to bound memory on very long bodies.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DiffType {
+    Deletion,
+    Insertion,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffNode {
+    pub content: String,
+    pub diff_type: DiffType,
+    pub children: Vec<DiffNode>,
+}
+
+impl DiffNode {
+    pub fn new(content: String, diff_type: DiffType) -> Self {
+        Self {
+            content,
+            diff_type,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn add_child(&mut self, child: DiffNode) {
+        self.children.push(child);
+    }
+
+    pub fn get_before_content(&self) -> String {
+        match self.diff_type {
+            DiffType::Deletion | DiffType::Unchanged => {
+                let mut content = self.content.clone();
+                for child in &self.children {
+                    content.push_str(&child.get_before_content());
+                }
+                content
+            }
+            DiffType::Insertion => String::new(),
+        }
+    }
+
+    pub fn get_after_content(&self) -> String {
+        match self.diff_type {
+            DiffType::Insertion | DiffType::Unchanged => {
+                let mut content = self.content.clone();
+                for child in &self.children {
+                    content.push_str(&child.get_after_content());
+                }
+                content
+            }
+            DiffType::Deletion => String::new(),
+        }
+    }
+
+    /// Whether any child was deleted or inserted.
+    pub fn has_changes(&self) -> bool {
+        self.diff_type != DiffType::Unchanged || self.children.iter().any(DiffNode::has_changes)
+    }
+
+    /// Append a change, merging it into the last child when they're of the
+    /// same type.
+    fn push(&mut self, content: &str, diff_type: DiffType) {
+        match self.children.last_mut() {
+            Some(last) if last.diff_type == diff_type => last.content.push_str(content),
+            _ => self.add_child(DiffNode::new(content.to_string(), diff_type)),
+        }
+    }
+}
+
+/// Line diff of two markdown texts, in the same shape as the Discourse
+/// revision diffs: a root whose children are unchanged, deleted and inserted
+/// runs of lines. Lines keep their newlines, so the before and after content
+/// of the result are exactly `before` and `after`.
+pub fn diff_lines(before: &str, after: &str) -> DiffNode {
+    let before_lines: Vec<&str> = before.split_inclusive('\n').collect();
+    let after_lines: Vec<&str> = after.split_inclusive('\n').collect();
+
+    let prefix = before_lines
+        .iter()
+        .zip(&after_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = before_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(after_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let before_middle = &before_lines[prefix..before_lines.len() - suffix];
+    let after_middle = &after_lines[prefix..after_lines.len() - suffix];
+
+    let mut root = DiffNode::new(String::new(), DiffType::Unchanged);
+    for line in &before_lines[..prefix] {
+        root.push(line, DiffType::Unchanged);
+    }
+
+    if before_middle.len().saturating_mul(after_middle.len()) > MAX_DIFF_CELLS {
+        for line in before_middle {
+            root.push(line, DiffType::Deletion);
+        }
+        for line in after_middle {
+            root.push(line, DiffType::Insertion);
+        }
+    } else {
+        diff_middle(&mut root, before_middle, after_middle);
+    }
+
+    for line in &before_lines[before_lines.len() - suffix..] {
+        root.push(line, DiffType::Unchanged);
+    }
+    root
+}
+
+/// Longest common subsequence diff of the lines that differ.
+fn diff_middle(root: &mut DiffNode, before: &[&str], after: &[&str]) {
+    let width = after.len() + 1;
+    // lcs[i * width + j] is the LCS length of before[i..] and after[j..]
+    let mut lcs = vec![0u32; (before.len() + 1) * width];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lcs[i * width + j] = if before[i] == after[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() && j < after.len() {
+        if before[i] == after[j] {
+            root.push(before[i], DiffType::Unchanged);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            root.push(before[i], DiffType::Deletion);
+            i += 1;
+        } else {
+            root.push(after[j], DiffType::Insertion);
+            j += 1;
+        }
+    }
+    for line in &before[i..] {
+        root.push(line, DiffType::Deletion);
+    }
+    for line in &after[j..] {
+        root.push(line, DiffType::Insertion);
+    }
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+
+    fn changes(node: &DiffNode) -> Vec<(DiffType, &str)> {
+        node.children
+            .iter()
+            .map(|child| (child.diff_type.clone(), child.content.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let before = "# Temp check\nFund the grants program.\nBudget: 1M ARB\n\nThanks!";
+        let after =
+            "# Proposal\nFund the grants program.\nBudget: 2M ARB\nDuration: 6 months\n\nThanks!";
+
+        let diff = diff_lines(before, after);
+        assert_eq!(
+            changes(&diff),
+            vec![
+                (DiffType::Deletion, "# Temp check\n"),
+                (DiffType::Insertion, "# Proposal\n"),
+                (DiffType::Unchanged, "Fund the grants program.\n"),
+                (DiffType::Deletion, "Budget: 1M ARB\n"),
+                (DiffType::Insertion, "Budget: 2M ARB\nDuration: 6 months\n"),
+                (DiffType::Unchanged, "\nThanks!"),
+            ]
+        );
+        assert!(diff.has_changes());
+        assert_eq!(diff.get_before_content(), before);
+        assert_eq!(diff.get_after_content(), after);
+    }
+
+    #[test]
+    fn test_diff_lines_edge_cases() {
+        let same = diff_lines("a\nb", "a\nb");
+        assert!(!same.has_changes());
+        assert_eq!(changes(&same), vec![(DiffType::Unchanged, "a\nb")]);
+
+        let added = diff_lines("", "a\n");
+        assert_eq!(changes(&added), vec![(DiffType::Insertion, "a\n")]);
+
+        // A newline added at the end changes the last line
+        let newline = diff_lines("a\nb", "a\nb\n");
+        assert_eq!(newline.get_before_content(), "a\nb");
+        assert_eq!(newline.get_after_content(), "a\nb\n");
+        assert!(newline.has_changes());
+    }
+}
@@ -0,0 +1,360 @@
+use crate::types::JobData;
+use anyhow::{Context, Result};
+use proposalsapp_db::models::job_queue;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, Statement};
+use serde::de::DeserializeOwned;
+use std::{future::Future, time::Duration};
+use tracing::{debug, error, info, instrument, warn};
+
+pub const PENDING: &str = "PENDING";
+pub const RUNNING: &str = "RUNNING";
+pub const COMPLETED: &str = "COMPLETED";
+/// Jobs that failed `max_attempts` times, or whose data can't be read.
+/// They stay in the table for inspection and are never claimed again.
+pub const DEAD: &str = "DEAD";
+
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// A claimed job. It's leased to the worker that claimed it until it
+/// completes, fails, or the lease runs out and another worker claims it.
+#[derive(Debug, Clone)]
+pub struct Job<T> {
+    pub id: i32,
+    /// Claims so far, including this one.
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub data: T,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkerConfig {
+    /// Recorded on claimed jobs as `locked_by`.
+    pub name: String,
+    /// How long a job stays claimed; jobs that run longer may run twice.
+    pub lease: Duration,
+    /// Wait before looking for jobs again when there are none.
+    pub poll_interval: Duration,
+}
+
+impl WorkerConfig {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            lease: Duration::from_secs(5 * 60),
+            poll_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Delay before retrying a job after its `attempts`-th failure: 30 seconds,
+/// doubling per attempt, capped at an hour.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+    FIRST_RETRY_DELAY
+        .saturating_mul(2u32.pow(exponent))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Add a job, unless one with the same dedupe key is already pending or
+/// running. Returns the id of the new job, if any. Takes any connection so
+/// jobs can be enqueued in the transaction that stores what they're about.
+#[instrument(name = "job_queue_enqueue", skip_all, fields(job_type = %T::job_type()))]
+pub async fn enqueue<T: JobData, C: ConnectionTrait>(conn: &C, data: &T) -> Result<Option<i32>> {
+    let dedupe_key = data.dedupe_key();
+    let row = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO job_queue (type, data, dedupe_key)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (type, dedupe_key)
+                WHERE dedupe_key IS NOT NULL AND status IN ('PENDING', 'RUNNING')
+                DO NOTHING
+            RETURNING id
+            "#,
+            vec![
+                T::job_type().to_string().into(),
+                serde_json::to_value(data)
+                    .context("Failed to serialize job data")?
+                    .into(),
+                dedupe_key.clone().into(),
+            ],
+        ))
+        .await
+        .context("Failed to enqueue job")?;
+
+    let id = row
+        .map(|row| row.try_get::<i32>("", "id"))
+        .transpose()
+        .context("Failed to read enqueued job id")?;
+    match id {
+        Some(id) => debug!(job_id = id, "Job enqueued"),
+        None => debug!(dedupe_key, "Job already queued"),
+    }
+    Ok(id)
+}
+
+/// Add a job unless one with the same dedupe key was ever enqueued, whatever
+/// its status. For jobs that must run at most once, like notifications.
+#[instrument(name = "job_queue_enqueue_once", skip_all, fields(job_type = %T::job_type()))]
+pub async fn enqueue_once<T: JobData, C: ConnectionTrait>(
+    conn: &C,
+    data: &T,
+) -> Result<Option<i32>> {
+    let dedupe_key = data
+        .dedupe_key()
+        .context("Jobs enqueued once need a dedupe key")?;
+    let row = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO job_queue (type, data, dedupe_key)
+            SELECT $1, $2, $3
+            WHERE NOT EXISTS (
+                SELECT 1 FROM job_queue WHERE type = $1 AND dedupe_key = $3
+            )
+            ON CONFLICT (type, dedupe_key)
+                WHERE dedupe_key IS NOT NULL AND status IN ('PENDING', 'RUNNING')
+                DO NOTHING
+            RETURNING id
+            "#,
+            vec![
+                T::job_type().to_string().into(),
+                serde_json::to_value(data)
+                    .context("Failed to serialize job data")?
+                    .into(),
+                dedupe_key.clone().into(),
+            ],
+        ))
+        .await
+        .context("Failed to enqueue job")?;
+
+    let id = row
+        .map(|row| row.try_get::<i32>("", "id"))
+        .transpose()
+        .context("Failed to read enqueued job id")?;
+    match id {
+        Some(id) => debug!(job_id = id, "Job enqueued"),
+        None => debug!(dedupe_key, "Job enqueued before"),
+    }
+    Ok(id)
+}
+
+/// Claim the next due job of type `T` for `lease`. Pending jobs and running
+/// jobs whose lease expired are due; jobs locked by a concurrent claim are
+/// skipped rather than waited on.
+#[instrument(name = "job_queue_claim", skip(conn, lease), fields(job_type = %T::job_type()))]
+pub async fn claim<T: JobData + DeserializeOwned, C: ConnectionTrait>(
+    conn: &C,
+    worker: &str,
+    lease: Duration,
+) -> Result<Option<Job<T>>> {
+    let job_type = T::job_type().to_string();
+
+    // Expired leases of jobs out of attempts mean their worker died mid-run
+    // every time
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        UPDATE job_queue SET
+            status = 'DEAD',
+            last_error = COALESCE(last_error, 'Lease expired'),
+            locked_until = NULL,
+            updated_at = NOW()
+        WHERE type = $1
+            AND status = 'RUNNING'
+            AND locked_until <= NOW()
+            AND attempts >= max_attempts
+        "#,
+        vec![job_type.clone().into()],
+    ))
+    .await
+    .context("Failed to dead-letter expired jobs")?;
+
+    loop {
+        let claimed = job_queue::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                WITH next AS (
+                    SELECT id FROM job_queue
+                    WHERE type = $1
+                        AND (
+                            (status = 'PENDING' AND run_at <= NOW())
+                            OR (status = 'RUNNING' AND locked_until <= NOW() AND attempts < max_attempts)
+                        )
+                    ORDER BY run_at, id
+                    LIMIT 1
+                    FOR UPDATE SKIP LOCKED
+                )
+                UPDATE job_queue SET
+                    status = 'RUNNING',
+                    attempts = job_queue.attempts + 1,
+                    locked_until = NOW() + make_interval(secs => $2),
+                    locked_by = $3,
+                    updated_at = NOW()
+                FROM next
+                WHERE job_queue.id = next.id
+                RETURNING job_queue.*
+                "#,
+                vec![
+                    job_type.clone().into(),
+                    lease.as_secs_f64().into(),
+                    worker.into(),
+                ],
+            ))
+            .one(conn)
+            .await
+            .context("Failed to claim job")?;
+
+        let Some(claimed) = claimed else {
+            return Ok(None);
+        };
+
+        match serde_json::from_value(claimed.data) {
+            Ok(data) => {
+                return Ok(Some(Job {
+                    id: claimed.id,
+                    attempts: claimed.attempts,
+                    max_attempts: claimed.max_attempts,
+                    data,
+                }));
+            }
+            Err(e) => {
+                let error = anyhow::Error::new(e).context("Failed to parse job data");
+                error!(job_id = claimed.id, error = %error, "Dead-lettering unreadable job");
+                settle(conn, claimed.id, claimed.attempts, DEAD, None, &error).await?;
+            }
+        }
+    }
+}
+
+/// Mark a job done. Returns false when its lease expired and another worker
+/// claimed it in the meantime.
+pub async fn complete<T, C: ConnectionTrait>(conn: &C, job: &Job<T>) -> Result<bool> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE job_queue SET
+                status = 'COMPLETED',
+                locked_until = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND status = 'RUNNING' AND attempts = $2
+            "#,
+            vec![job.id.into(), job.attempts.into()],
+        ))
+        .await
+        .context("Failed to complete job")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record a failed attempt: the job is retried after [`retry_delay`], or
+/// dead-lettered once it's out of attempts. Returns false when its lease
+/// expired and another worker claimed it in the meantime.
+pub async fn fail<T, C: ConnectionTrait>(
+    conn: &C,
+    job: &Job<T>,
+    error: &anyhow::Error,
+) -> Result<bool> {
+    if job.attempts >= job.max_attempts {
+        settle(conn, job.id, job.attempts, DEAD, None, error).await
+    } else {
+        let delay = retry_delay(job.attempts);
+        settle(conn, job.id, job.attempts, PENDING, Some(delay), error).await
+    }
+}
+
+async fn settle<C: ConnectionTrait>(
+    conn: &C,
+    id: i32,
+    attempts: i32,
+    status: &str,
+    retry_in: Option<Duration>,
+    error: &anyhow::Error,
+) -> Result<bool> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE job_queue SET
+                status = $3,
+                run_at = COALESCE(NOW() + make_interval(secs => $4), run_at),
+                last_error = $5,
+                locked_until = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND status = 'RUNNING' AND attempts = $2
+            "#,
+            vec![
+                id.into(),
+                attempts.into(),
+                status.into(),
+                retry_in.map(|delay| delay.as_secs_f64()).into(),
+                format!("{error:#}").into(),
+            ],
+        ))
+        .await
+        .context("Failed to record job failure")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim and handle jobs of type `T` one at a time, forever. Errors from the
+/// handler fail the job; database errors end the worker so its supervisor
+/// can restart it.
+#[instrument(name = "job_queue_worker", skip_all, fields(worker = %config.name, job_type = %T::job_type()))]
+pub async fn run_worker<T, F, Fut>(
+    db: &DatabaseConnection,
+    config: &WorkerConfig,
+    mut handler: F,
+) -> Result<()>
+where
+    T: JobData + DeserializeOwned + Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    info!("Starting job worker");
+
+    loop {
+        let Some(job) = claim::<T, _>(db, &config.name, config.lease).await? else {
+            tokio::time::sleep(config.poll_interval).await;
+            continue;
+        };
+
+        let settled = match handler(job.data.clone()).await {
+            Ok(()) => {
+                debug!(job_id = job.id, attempts = job.attempts, "Job completed");
+                complete(db, &job).await?
+            }
+            Err(e) => {
+                if job.attempts >= job.max_attempts {
+                    error!(job_id = job.id, attempts = job.attempts, error = %e, "Job failed, dead-lettered");
+                } else {
+                    warn!(job_id = job.id, attempts = job.attempts, error = %e, "Job failed, will retry");
+                }
+                fail(db, &job, &e).await?
+            }
+        };
+        if !settled {
+            warn!(
+                job_id = job.id,
+                "Job lease expired before it was settled, it may run again"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(5), Duration::from_secs(8 * 60));
+        assert_eq!(retry_delay(8), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(i32::MAX), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(0), FIRST_RETRY_DELAY);
+    }
+}
@@ -1,2 +1,8 @@
+pub mod anomalies;
+pub mod diff;
+pub mod job_queue;
+pub mod notifications;
+pub mod outbox;
+pub mod public_http;
 pub mod test_utils;
 pub mod types;
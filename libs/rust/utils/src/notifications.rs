@@ -0,0 +1,293 @@
+use crate::{
+    job_queue::enqueue_once,
+    outbox::{GovernanceEvent, OutboxConsumer, StoredEvent},
+    types::{
+        EndingProposalNotification, JobData, NewDiscussionNotification, NewProposalNotification,
+        ProposalEndedNotification, VotingStartedNotification,
+    },
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use proposalsapp_db::models::{
+    dao, dao_discourse, discourse_topic, job_queue, proposal, sea_orm_active_enums::ProposalState,
+    user,
+};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, prelude::Uuid,
+};
+use std::{collections::HashSet, time::Duration};
+use tracing::{debug, info, instrument};
+
+/// Name the producer stores its outbox offset under.
+pub const CONSUMER_NAME: &str = "notifications";
+
+/// Active proposals are notified as ending once they end within this.
+pub const ENDING_SOON_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+const ENDING_SOON_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Discourse categories holding governance discussions, by DAO slug. Topics
+/// of DAOs not listed are all notified.
+const GOVERNANCE_CATEGORIES: &[(&str, &[i32])] =
+    &[("arbitrum", &[7, 8, 9]), ("uniswap", &[5, 8, 9, 10])];
+
+/// Detects notifiable moments and enqueues a notification job for each user
+/// who opted in to its kind of email:
+/// - a new proposal, and voting starting on one created pending, for
+///   `email_settings_new_proposals`
+/// - a proposal ending within [`ENDING_SOON_WINDOW`], and a proposal ending,
+///   for `email_settings_ending_proposals`
+/// - a new topic in a governance category, for
+///   `email_settings_new_discussions`
+#[derive(Clone)]
+pub struct NotificationProducer {
+    db: DatabaseConnection,
+}
+
+impl NotificationProducer {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Tail governance events and scan for proposals ending soon, forever.
+    pub async fn run(&self) -> Result<()> {
+        let consumer = OutboxConsumer::new(self.db.clone(), CONSUMER_NAME);
+        tokio::select! {
+            result = consumer.run(|event| {
+                let producer = self.clone();
+                async move { producer.handle_event(&event).await.map(|_| ()) }
+            }) => result,
+            result = self.run_ending_soon() => result,
+        }
+    }
+
+    async fn run_ending_soon(&self) -> Result<()> {
+        let mut interval = tokio::time::interval(ENDING_SOON_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let queued = self.queue_ending_soon().await?;
+            if queued > 0 {
+                info!(queued, "Queued ending proposal notifications");
+            }
+        }
+    }
+
+    /// Enqueue the notifications following from `event`. Returns how many
+    /// were enqueued; users already notified are skipped.
+    #[instrument(skip_all, fields(event_id = event.id))]
+    pub async fn handle_event(&self, event: &StoredEvent) -> Result<usize> {
+        match &event.event {
+            GovernanceEvent::ProposalCreated { proposal_id, .. } => {
+                let Some(proposal) = self.notifiable_proposal(*proposal_id).await? else {
+                    return Ok(0);
+                };
+                self.notify(user::Column::EmailSettingsNewProposals, |user_id| {
+                    NewProposalNotification {
+                        user_id,
+                        target_id: proposal.id,
+                        dao_id: proposal.dao_id,
+                    }
+                })
+                .await
+            }
+            GovernanceEvent::ProposalStateChanged {
+                proposal_id,
+                from_state,
+                to_state,
+            } => {
+                let started =
+                    *to_state == ProposalState::Active && *from_state != ProposalState::Active;
+                let ended = *from_state == ProposalState::Active && has_ended(to_state);
+                if !started && !ended {
+                    return Ok(0);
+                }
+                let Some(proposal) = self.notifiable_proposal(*proposal_id).await? else {
+                    return Ok(0);
+                };
+
+                if started {
+                    self.notify(user::Column::EmailSettingsNewProposals, |user_id| {
+                        VotingStartedNotification {
+                            user_id,
+                            target_id: proposal.id,
+                            dao_id: proposal.dao_id,
+                        }
+                    })
+                    .await
+                } else {
+                    self.notify(user::Column::EmailSettingsEndingProposals, |user_id| {
+                        ProposalEndedNotification {
+                            user_id,
+                            target_id: proposal.id,
+                            dao_id: proposal.dao_id,
+                            result: to_state.clone(),
+                        }
+                    })
+                    .await
+                }
+            }
+            GovernanceEvent::TopicCreated { topic_id, .. } => {
+                self.notify_new_discussion(*topic_id).await
+            }
+            GovernanceEvent::VoteCast { .. }
+            | GovernanceEvent::DelegationChanged { .. }
+            | GovernanceEvent::VotingPowerChanged { .. }
+            | GovernanceEvent::PostEdited { .. }
+            | GovernanceEvent::AnomalyDetected { .. } => Ok(0),
+        }
+    }
+
+    /// Enqueue ending notifications for active proposals ending within
+    /// [`ENDING_SOON_WINDOW`]. Meant to run periodically.
+    #[instrument(skip_all)]
+    pub async fn queue_ending_soon(&self) -> Result<usize> {
+        let now = Utc::now().naive_utc();
+        let window = chrono::Duration::from_std(ENDING_SOON_WINDOW)?;
+        let proposals = proposal::Entity::find()
+            .filter(proposal::Column::ProposalState.eq(ProposalState::Active))
+            .filter(proposal::Column::MarkedSpam.eq(false))
+            .filter(proposal::Column::EndAt.gt(now))
+            .filter(proposal::Column::EndAt.lte(now + window))
+            .all(&self.db)
+            .await
+            .context("Failed to fetch proposals ending soon")?;
+
+        let mut queued = 0;
+        for proposal in proposals {
+            queued += self
+                .notify(user::Column::EmailSettingsEndingProposals, |user_id| {
+                    EndingProposalNotification {
+                        user_id,
+                        target_id: proposal.id,
+                        dao_id: proposal.dao_id,
+                    }
+                })
+                .await?;
+        }
+        Ok(queued)
+    }
+
+    async fn notifiable_proposal(&self, proposal_id: Uuid) -> Result<Option<proposal::Model>> {
+        let proposal = proposal::Entity::find_by_id(proposal_id)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch proposal")?;
+        Ok(proposal.filter(|proposal| !proposal.marked_spam))
+    }
+
+    async fn notify_new_discussion(&self, topic_id: Uuid) -> Result<usize> {
+        let Some(topic) = discourse_topic::Entity::find_by_id(topic_id)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch topic")?
+        else {
+            return Ok(0);
+        };
+        let Some((_, Some(dao))) = dao_discourse::Entity::find_by_id(topic.dao_discourse_id)
+            .find_also_related(dao::Entity)
+            .one(&self.db)
+            .await
+            .context("Failed to fetch DAO of forum")?
+        else {
+            return Ok(0);
+        };
+        if topic.restricted {
+            debug!(topic_id = %topic.id, "Topic is in a restricted category");
+            return Ok(0);
+        }
+        if !is_governance_category(&dao.slug, topic.category_id) {
+            debug!(topic_id = %topic.id, category_id = topic.category_id, "Topic not in a governance category");
+            return Ok(0);
+        }
+
+        self.notify(user::Column::EmailSettingsNewDiscussions, |user_id| {
+            NewDiscussionNotification {
+                user_id,
+                target_id: topic.id,
+                dao_id: dao.id,
+            }
+        })
+        .await
+    }
+
+    /// Enqueue the job `notification` makes for every user with `setting`
+    /// on, unless one was enqueued for them before.
+    async fn notify<T: JobData>(
+        &self,
+        setting: user::Column,
+        notification: impl Fn(String) -> T,
+    ) -> Result<usize> {
+        let user_ids: Vec<String> = user::Entity::find()
+            .select_only()
+            .column(user::Column::Id)
+            .filter(setting.eq(true))
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .context("Failed to fetch users to notify")?;
+        let jobs: Vec<T> = user_ids.into_iter().map(notification).collect();
+
+        // Skip users notified before, rather than trying to enqueue for
+        // everyone again on every scan
+        let keys: Vec<String> = jobs.iter().filter_map(JobData::dedupe_key).collect();
+        let enqueued: HashSet<String> = job_queue::Entity::find()
+            .select_only()
+            .column(job_queue::Column::DedupeKey)
+            .filter(job_queue::Column::Type.eq(T::job_type().to_string()))
+            .filter(job_queue::Column::DedupeKey.is_in(keys))
+            .into_tuple::<Option<String>>()
+            .all(&self.db)
+            .await
+            .context("Failed to fetch enqueued notifications")?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut queued = 0;
+        for job in jobs {
+            if job.dedupe_key().is_some_and(|key| enqueued.contains(&key)) {
+                continue;
+            }
+            if enqueue_once(&self.db, &job).await?.is_some() {
+                queued += 1;
+            }
+        }
+
+        if queued > 0 {
+            debug!(job_type = %T::job_type(), queued, "Notifications enqueued");
+        }
+        Ok(queued)
+    }
+}
+
+/// States an active proposal ends in. Only leaving ACTIVE counts, so
+/// proposals canceled before they started never end.
+fn has_ended(state: &ProposalState) -> bool {
+    !matches!(
+        state,
+        ProposalState::Pending
+            | ProposalState::Active
+            | ProposalState::Hidden
+            | ProposalState::Unknown
+    )
+}
+
+fn is_governance_category(dao_slug: &str, category_id: i32) -> bool {
+    GOVERNANCE_CATEGORIES
+        .iter()
+        .find(|(slug, _)| *slug == dao_slug)
+        .is_none_or(|(_, categories)| categories.contains(&category_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_governance_category() {
+        assert!(is_governance_category("arbitrum", 7));
+        assert!(!is_governance_category("arbitrum", 5));
+        assert!(is_governance_category("uniswap", 5));
+        assert!(is_governance_category("other", 1));
+    }
+}
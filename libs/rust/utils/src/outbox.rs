@@ -0,0 +1,312 @@
+use crate::anomalies::AnomalyKind;
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDateTime;
+use proposalsapp_db::models::{
+    governance_event, governance_event_consumer, sea_orm_active_enums::ProposalState,
+};
+use sea_orm::{
+    ActiveValue::NotSet, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, Set,
+    Statement, prelude::Uuid, sqlx::postgres::PgListener,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{future::Future, time::Duration};
+use tracing::{debug, info, instrument, warn};
+
+/// Channel notified when events are published. Notifications only wake
+/// consumers up; the events themselves are read from the outbox.
+pub const CHANNEL: &str = "governance_events";
+
+const BATCH_SIZE: i64 = 500;
+
+/// Consumers poll this often even without notifications, as events held
+/// back by an older running transaction aren't notified again once it ends.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Events are kept this long, and longer while a consumer hasn't handled
+/// them.
+pub const RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event_type", content = "payload", rename_all = "snake_case")]
+pub enum GovernanceEvent {
+    ProposalCreated {
+        proposal_id: Uuid,
+        governor_id: Uuid,
+        external_id: String,
+    },
+    ProposalStateChanged {
+        proposal_id: Uuid,
+        from_state: ProposalState,
+        to_state: ProposalState,
+    },
+    VoteCast {
+        vote_id: Uuid,
+        proposal_id: Uuid,
+        voter_address: String,
+    },
+    DelegationChanged {
+        delegation_id: Uuid,
+        delegator: String,
+        delegate: String,
+        from_delegate: Option<String>,
+    },
+    VotingPowerChanged {
+        timeseries_id: Uuid,
+        voter: String,
+        block: i32,
+    },
+    TopicCreated {
+        topic_id: Uuid,
+        dao_discourse_id: Uuid,
+        external_id: i32,
+    },
+    PostEdited {
+        post_id: Uuid,
+        dao_discourse_id: Uuid,
+        external_id: i32,
+        topic_external_id: i32,
+        version: i32,
+    },
+    AnomalyDetected {
+        anomaly_id: Uuid,
+        kind: AnomalyKind,
+        proposal_id: Option<Uuid>,
+    },
+}
+
+/// An event read from the outbox.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub xact_id: i64,
+    pub dao_id: Uuid,
+    pub event: GovernanceEvent,
+    pub created_at: NaiveDateTime,
+}
+
+impl TryFrom<governance_event::Model> for StoredEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(model: governance_event::Model) -> Result<Self> {
+        let event = serde_json::from_value(json!({
+            "event_type": model.event_type,
+            "payload": model.payload,
+        }))
+        .with_context(|| format!("Failed to parse governance event {}", model.id))?;
+
+        Ok(Self {
+            id: model.id,
+            xact_id: model.xact_id,
+            dao_id: model.dao_id,
+            event,
+            created_at: model.created_at,
+        })
+    }
+}
+
+/// Write events of `dao_id` to the outbox and notify consumers. Meant to run
+/// in the transaction of the change the events describe, so they're only
+/// published if it commits.
+pub async fn publish<C: ConnectionTrait>(
+    conn: &C,
+    dao_id: Uuid,
+    events: impl IntoIterator<Item = GovernanceEvent>,
+) -> Result<()> {
+    let mut models = Vec::new();
+    for event in events {
+        let Value::Object(mut fields) =
+            serde_json::to_value(&event).context("Failed to serialize governance event")?
+        else {
+            bail!("Governance event didn't serialize to an object");
+        };
+        models.push(governance_event::ActiveModel {
+            id: NotSet,
+            xact_id: NotSet,
+            event_type: Set(fields
+                .remove("event_type")
+                .and_then(|event_type| event_type.as_str().map(str::to_string))
+                .context("Governance event has no type")?),
+            dao_id: Set(dao_id),
+            payload: Set(fields.remove("payload").unwrap_or(Value::Null)),
+            created_at: NotSet,
+        });
+    }
+    if models.is_empty() {
+        return Ok(());
+    }
+
+    let count = models.len();
+    governance_event::Entity::insert_many(models)
+        .exec(conn)
+        .await
+        .context("Failed to write governance events")?;
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_notify($1, $2)",
+        vec![CHANNEL.into(), dao_id.to_string().into()],
+    ))
+    .await
+    .context("Failed to notify governance event consumers")?;
+
+    debug!(dao_id = %dao_id, count, "Governance events published");
+    Ok(())
+}
+
+/// Delete events older than `retention` that every consumer has handled, and
+/// return how many were deleted. The offset of a retired consumer holds back
+/// pruning until it's deleted; consumers that start later begin at the oldest
+/// event kept.
+#[instrument(name = "outbox_prune", skip_all)]
+pub async fn prune<C: ConnectionTrait>(conn: &C, retention: Duration) -> Result<u64> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            DELETE FROM governance_event e
+            WHERE e.created_at < timezone('utc', now()) - make_interval(secs => $1)
+                AND NOT EXISTS (
+                    SELECT 1 FROM governance_event_consumer c
+                    WHERE (c.xact_id, c.event_id) < (e.xact_id, e.id)
+                )
+            "#,
+            vec![retention.as_secs_f64().into()],
+        ))
+        .await
+        .context("Failed to prune governance events")?;
+
+    let pruned = result.rows_affected();
+    if pruned > 0 {
+        info!(pruned, "Governance events pruned");
+    }
+    Ok(pruned)
+}
+
+/// Tails the outbox from an offset stored under its name, delivering every
+/// event at least once: the offset only moves past events once they're
+/// handled.
+///
+/// Events are read in (transaction, id) order and only from transactions
+/// older than the oldest one still running, since ids are taken before
+/// commit and a transaction that started earlier may commit later. A long
+/// running writer holds back delivery until it ends.
+#[derive(Clone)]
+pub struct OutboxConsumer {
+    db: DatabaseConnection,
+    name: String,
+}
+
+impl OutboxConsumer {
+    /// A consumer that hasn't stored an offset yet starts from the first
+    /// event.
+    pub fn new(db: DatabaseConnection, name: impl Into<String>) -> Self {
+        Self {
+            db,
+            name: name.into(),
+        }
+    }
+
+    /// Up to a batch of events after the stored offset.
+    pub async fn next_batch(&self) -> Result<Vec<StoredEvent>> {
+        let offset = governance_event_consumer::Entity::find_by_id(self.name.clone())
+            .one(&self.db)
+            .await
+            .context("Failed to fetch consumer offset")?;
+        let (xact_id, event_id) = offset.map_or((0, 0), |offset| (offset.xact_id, offset.event_id));
+
+        governance_event::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT * FROM governance_event
+                WHERE (xact_id, id) > ($1, $2)
+                    AND xact_id < pg_snapshot_xmin(pg_current_snapshot())::text::bigint
+                ORDER BY xact_id, id
+                LIMIT $3
+                "#,
+                vec![xact_id.into(), event_id.into(), BATCH_SIZE.into()],
+            ))
+            .all(&self.db)
+            .await
+            .context("Failed to fetch governance events")?
+            .into_iter()
+            .map(StoredEvent::try_from)
+            .collect()
+    }
+
+    /// Move the offset past `event`.
+    pub async fn commit(&self, event: &StoredEvent) -> Result<()> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                INSERT INTO governance_event_consumer (name, xact_id, event_id, updated_at)
+                VALUES ($1, $2, $3, timezone('utc', now()))
+                ON CONFLICT (name) DO UPDATE SET
+                    xact_id = EXCLUDED.xact_id,
+                    event_id = EXCLUDED.event_id,
+                    updated_at = timezone('utc', now())
+                "#,
+                vec![
+                    self.name.clone().into(),
+                    event.xact_id.into(),
+                    event.id.into(),
+                ],
+            ))
+            .await
+            .context("Failed to store consumer offset")?;
+        Ok(())
+    }
+
+    /// Hand every event to `handler` in order, forever, waking up on
+    /// notifications. Returns the handler's error once events up to the
+    /// failed one are committed, so the failed event is delivered again when
+    /// the consumer restarts.
+    #[instrument(name = "outbox_consumer", skip_all, fields(consumer = %self.name))]
+    pub async fn run<F, Fut>(&self, mut handler: F) -> Result<()>
+    where
+        F: FnMut(StoredEvent) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut listener = PgListener::connect_with(self.db.get_postgres_connection_pool())
+            .await
+            .context("Failed to connect the outbox listener")?;
+        listener
+            .listen(CHANNEL)
+            .await
+            .with_context(|| format!("Failed to listen on {CHANNEL}"))?;
+        info!("Tailing governance events");
+
+        loop {
+            loop {
+                let batch = self.next_batch().await?;
+                let Some(last) = batch.last().cloned() else {
+                    break;
+                };
+
+                let mut handled: Option<&StoredEvent> = None;
+                for event in &batch {
+                    if let Err(e) = handler(event.clone()).await {
+                        if let Some(handled) = handled {
+                            self.commit(handled).await?;
+                        }
+                        return Err(
+                            e.context(format!("Failed to handle governance event {}", event.id))
+                        );
+                    }
+                    handled = Some(event);
+                }
+                self.commit(&last).await?;
+                debug!(count = batch.len(), "Governance events handled");
+            }
+
+            match tokio::time::timeout(POLL_INTERVAL, listener.try_recv()).await {
+                Ok(Ok(Some(_))) | Err(_) => {}
+                // The listener reconnects on the next call, and the poll
+                // catches up on anything missed in between
+                Ok(Ok(None)) => warn!("Outbox listener connection lost, reconnecting"),
+                Ok(Err(e)) => return Err(e).context("Failed to receive notification"),
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use reqwest::{
+    Client, Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect::Policy,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+/// Client for fetching URLs that come from users or on-chain records. Every
+/// redirect is checked with [`is_public_web_url`], at most `max_redirects`
+/// are followed, and hosts only resolve to public addresses.
+pub fn public_client(timeout: Duration, max_redirects: usize) -> Result<Client> {
+    Client::builder()
+        .timeout(timeout)
+        .redirect(Policy::custom(move |attempt| {
+            if attempt.previous().len() >= max_redirects {
+                attempt.error("Too many redirects")
+            } else if is_public_web_url(attempt.url().as_str()) {
+                attempt.follow()
+            } else {
+                attempt.stop()
+            }
+        }))
+        .dns_resolver(PublicResolver)
+        .build()
+        .context("Failed to build public HTTP client")
+}
+
+/// Resolves hosts to their public addresses only, so that no redirect can
+/// reach an internal service through a name pointing at it.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is routable on the internet.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // Shared address space, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Whether `url` is an http(s) link to a named public website. IP literals
+/// and single-label hosts such as `localhost` are refused outright.
+pub fn is_public_web_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https")
+            && url.host_str().is_some_and(|host| {
+                host.contains('.') && host.trim_matches(['[', ']']).parse::<IpAddr>().is_err()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_web_url() {
+        assert!(is_public_web_url("https://bit.ly/3abcdef"));
+        assert!(!is_public_web_url(
+            "http://169.254.169.254/latest/meta-data"
+        ));
+        assert!(!is_public_web_url("http://[::1]/t/1"));
+        assert!(!is_public_web_url("http://localhost:3000/t/1"));
+        assert!(!is_public_web_url("ftp://forum.example.org/t/1"));
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        let ip = |ip: &str| is_public_ip(ip.parse().unwrap());
+        assert!(ip("104.18.2.1"));
+        assert!(ip("2606:4700::1"));
+        assert!(!ip("127.0.0.1"));
+        assert!(!ip("10.0.0.5"));
+        assert!(!ip("169.254.169.254"));
+        assert!(!ip("100.100.0.1"));
+        assert!(!ip("::1"));
+        assert!(!ip("fd00::1"));
+        assert!(!ip("::ffff:192.168.1.1"));
+    }
+}
@@ -1,3 +1,4 @@
+use proposalsapp_db::models::sea_orm_active_enums::ProposalState;
 use sea_orm::prelude::Uuid;
 use serde::{Deserialize, Serialize};
 
@@ -11,18 +12,18 @@ pub enum ProposalGroupItem {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TopicItem {
     pub name: String,
-    #[serde(rename = "externalId")]
+    #[serde(rename = "externalId", alias = "external_id")]
     pub external_id: String,
-    #[serde(rename = "daoDiscourseId")]
+    #[serde(rename = "daoDiscourseId", alias = "dao_discourse_id")]
     pub dao_discourse_id: Uuid,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProposalItem {
     pub name: String,
-    #[serde(rename = "governorId")]
+    #[serde(rename = "governorId", alias = "governor_id")]
     pub governor_id: Uuid,
-    #[serde(rename = "externalId")]
+    #[serde(rename = "externalId", alias = "external_id")]
     pub external_id: String,
 }
 
@@ -30,14 +31,26 @@ pub struct ProposalItem {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum JobType {
     MapperNewProposalDiscussion,
+    MapperNewProposal,
     MapperNewSnapshotProposal,
+    NotificationNewProposal,
+    NotificationVotingStarted,
+    NotificationEndingProposal,
+    NotificationProposalEnded,
+    NotificationNewDiscussion,
 }
 
 impl std::fmt::Display for JobType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             JobType::MapperNewProposalDiscussion => write!(f, "MAPPER_NEW_PROPOSAL_DISCUSSION"),
+            JobType::MapperNewProposal => write!(f, "MAPPER_NEW_PROPOSAL"),
             JobType::MapperNewSnapshotProposal => write!(f, "MAPPER_NEW_SNAPSHOT_PROPOSAL"),
+            JobType::NotificationNewProposal => write!(f, "NOTIFICATION_NEW_PROPOSAL"),
+            JobType::NotificationVotingStarted => write!(f, "NOTIFICATION_VOTING_STARTED"),
+            JobType::NotificationEndingProposal => write!(f, "NOTIFICATION_ENDING_PROPOSAL"),
+            JobType::NotificationProposalEnded => write!(f, "NOTIFICATION_PROPOSAL_ENDED"),
+            JobType::NotificationNewDiscussion => write!(f, "NOTIFICATION_NEW_DISCUSSION"),
         }
     }
 }
@@ -48,34 +61,166 @@ impl std::str::FromStr for JobType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "MAPPER_NEW_PROPOSAL_DISCUSSION" => Ok(JobType::MapperNewProposalDiscussion),
+            "MAPPER_NEW_PROPOSAL" => Ok(JobType::MapperNewProposal),
             "MAPPER_NEW_SNAPSHOT_PROPOSAL" => Ok(JobType::MapperNewSnapshotProposal),
+            "NOTIFICATION_NEW_PROPOSAL" => Ok(JobType::NotificationNewProposal),
+            "NOTIFICATION_VOTING_STARTED" => Ok(JobType::NotificationVotingStarted),
+            "NOTIFICATION_ENDING_PROPOSAL" => Ok(JobType::NotificationEndingProposal),
+            "NOTIFICATION_PROPOSAL_ENDED" => Ok(JobType::NotificationProposalEnded),
+            "NOTIFICATION_NEW_DISCUSSION" => Ok(JobType::NotificationNewDiscussion),
             _ => Err(anyhow::anyhow!("Unknown job type: {}", s)),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscussionJobData {
     pub discourse_topic_id: Uuid,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A newly indexed proposal, of any governor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProposalJobData {
     pub proposal_id: Uuid,
 }
 
+/// Notification jobs carry the `user_notification` row the email service
+/// records once it sent them: the job type maps to its `type`, and the
+/// target is a proposal, or a topic for new discussions. Each is enqueued at
+/// most once per user and target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewProposalNotification {
+    pub user_id: String,
+    pub target_id: Uuid,
+    pub dao_id: Uuid,
+}
+
+impl NewProposalNotification {
+    pub const NOTIFICATION_TYPE: &str = "new_proposal";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VotingStartedNotification {
+    pub user_id: String,
+    pub target_id: Uuid,
+    pub dao_id: Uuid,
+}
+
+impl VotingStartedNotification {
+    pub const NOTIFICATION_TYPE: &str = "voting_started";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndingProposalNotification {
+    pub user_id: String,
+    pub target_id: Uuid,
+    pub dao_id: Uuid,
+}
+
+impl EndingProposalNotification {
+    pub const NOTIFICATION_TYPE: &str = "ending_proposal";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalEndedNotification {
+    pub user_id: String,
+    pub target_id: Uuid,
+    pub dao_id: Uuid,
+    /// The state the proposal ended in.
+    pub result: ProposalState,
+}
+
+impl ProposalEndedNotification {
+    pub const NOTIFICATION_TYPE: &str = "proposal_ended";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewDiscussionNotification {
+    pub user_id: String,
+    pub target_id: Uuid,
+    pub dao_id: Uuid,
+}
+
+impl NewDiscussionNotification {
+    pub const NOTIFICATION_TYPE: &str = "new_discussion";
+}
+
 pub trait JobData: Serialize {
     fn job_type() -> JobType;
+
+    /// Jobs of the same type with the same key aren't queued twice while
+    /// one of them is pending or running.
+    fn dedupe_key(&self) -> Option<String> {
+        None
+    }
 }
 
 impl JobData for DiscussionJobData {
     fn job_type() -> JobType {
         JobType::MapperNewProposalDiscussion
     }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(self.discourse_topic_id.to_string())
+    }
 }
 
 impl JobData for ProposalJobData {
     fn job_type() -> JobType {
-        JobType::MapperNewSnapshotProposal
+        JobType::MapperNewProposal
+    }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(self.proposal_id.to_string())
+    }
+}
+
+impl JobData for NewProposalNotification {
+    fn job_type() -> JobType {
+        JobType::NotificationNewProposal
+    }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(format!("{}:{}", self.target_id, self.user_id))
+    }
+}
+
+impl JobData for VotingStartedNotification {
+    fn job_type() -> JobType {
+        JobType::NotificationVotingStarted
+    }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(format!("{}:{}", self.target_id, self.user_id))
+    }
+}
+
+impl JobData for EndingProposalNotification {
+    fn job_type() -> JobType {
+        JobType::NotificationEndingProposal
+    }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(format!("{}:{}", self.target_id, self.user_id))
+    }
+}
+
+impl JobData for ProposalEndedNotification {
+    fn job_type() -> JobType {
+        JobType::NotificationProposalEnded
+    }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(format!("{}:{}", self.target_id, self.user_id))
+    }
+}
+
+impl JobData for NewDiscussionNotification {
+    fn job_type() -> JobType {
+        JobType::NotificationNewDiscussion
+    }
+
+    fn dedupe_key(&self) -> Option<String> {
+        Some(format!("{}:{}", self.target_id, self.user_id))
     }
 }
@@ -0,0 +1,281 @@
+use anyhow::{Context, Result};
+use chrono::{Duration as ChronoDuration, NaiveDateTime, Utc};
+use proposalsapp_db::models::{
+    dao, dao_governor, delegated_voting_power_snapshot, delegation, governance_anomaly,
+    governance_event, proposal, sea_orm_active_enums::ProposalState, vote, voting_power_timeseries,
+};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, prelude::Uuid,
+    sea_query::OnConflict,
+};
+use serde_json::json;
+use serial_test::serial;
+use utils::{
+    anomalies::{AnomalyDetector, AnomalyKind},
+    outbox::{GovernanceEvent, StoredEvent},
+};
+
+const DAO_ID: Uuid = Uuid::from_u128(1);
+const GOVERNOR_ID: Uuid = Uuid::from_u128(2);
+const PROPOSAL_ID: Uuid = Uuid::from_u128(3);
+const TOTAL_DELEGATED_VP: f64 = 1_000_000.0;
+
+/// The shared database without anomalies, with a proposal starting at
+/// `start_at` and a known total delegated voting power.
+async fn test_db(start_at: NaiveDateTime) -> Result<&'static DatabaseConnection> {
+    let db = test_support::test_db().await;
+    governance_anomaly::Entity::delete_many().exec(db).await?;
+    governance_event::Entity::delete_many().exec(db).await?;
+    vote::Entity::delete_many().exec(db).await?;
+    proposal::Entity::delete_many().exec(db).await?;
+    delegation::Entity::delete_many().exec(db).await?;
+    voting_power_timeseries::Entity::delete_many()
+        .exec(db)
+        .await?;
+    delegated_voting_power_snapshot::Entity::delete_many()
+        .exec(db)
+        .await?;
+
+    dao::Entity::insert(dao::ActiveModel {
+        id: Set(DAO_ID),
+        name: Set("Test DAO".to_string()),
+        slug: Set("test-dao".to_string()),
+        picture: Set("https://example.com/dao.png".to_string()),
+    })
+    .on_conflict(OnConflict::column(dao::Column::Id).do_nothing().to_owned())
+    .do_nothing()
+    .exec(db)
+    .await
+    .context("failed to insert dao")?;
+    dao_governor::Entity::insert(dao_governor::ActiveModel {
+        id: Set(GOVERNOR_ID),
+        dao_id: Set(DAO_ID),
+        name: Set("Test Governor".to_string()),
+        r#type: Set("TEST_GOVERNOR".to_string()),
+        portal_url: Set(None),
+    })
+    .on_conflict(
+        OnConflict::column(dao_governor::Column::Id)
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await
+    .context("failed to insert dao_governor")?;
+
+    let long_ago = start_at - ChronoDuration::days(30);
+    delegated_voting_power_snapshot::Entity::insert(delegated_voting_power_snapshot::ActiveModel {
+        id: Set(Uuid::from_u128(4)),
+        dao_id: Set(DAO_ID),
+        block: Set(1),
+        timestamp: Set(long_ago),
+        total_voting_power: Set(TOTAL_DELEGATED_VP),
+        created_at: Set(long_ago),
+    })
+    .exec(db)
+    .await
+    .context("failed to insert delegated voting power snapshot")?;
+
+    proposal::Entity::insert(proposal::ActiveModel {
+        id: Set(PROPOSAL_ID),
+        external_id: Set("42".to_string()),
+        name: Set("Fund the thing".to_string()),
+        body: Set("Let's fund the thing".to_string()),
+        url: Set("https://example.com/proposal/42".to_string()),
+        discussion_url: Set(None),
+        choices: Set(json!(["For", "Against", "Abstain"])),
+        quorum: Set(100.0),
+        proposal_state: Set(ProposalState::Active),
+        marked_spam: Set(false),
+        created_at: Set(start_at - ChronoDuration::days(1)),
+        start_at: Set(start_at),
+        end_at: Set(start_at + ChronoDuration::days(7)),
+        block_created_at: Set(None),
+        txid: Set(None),
+        metadata: Set(Some(json!({ "total_delegated_vp": TOTAL_DELEGATED_VP }))),
+        dao_id: Set(DAO_ID),
+        author: Set(Some("0xauthor".to_string())),
+        governor_id: Set(GOVERNOR_ID),
+        block_start_at: Set(Some(1_000)),
+        block_end_at: Set(None),
+    })
+    .exec(db)
+    .await
+    .context("failed to insert proposal")?;
+
+    Ok(db)
+}
+
+fn stored(event: GovernanceEvent) -> StoredEvent {
+    StoredEvent {
+        id: 1,
+        xact_id: 1,
+        dao_id: DAO_ID,
+        event,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+async fn anomalies(db: &DatabaseConnection) -> Result<Vec<governance_anomaly::Model>> {
+    Ok(governance_anomaly::Entity::find()
+        .order_by_asc(governance_anomaly::Column::DetectedAt)
+        .order_by_asc(governance_anomaly::Column::Kind)
+        .all(db)
+        .await?)
+}
+
+#[test]
+#[serial]
+fn test_swings_and_whale_votes_are_recorded_once() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let now = Utc::now().naive_utc();
+        let db = test_db(now - ChronoDuration::days(1)).await?;
+        let detector = AnomalyDetector::new(db.clone());
+
+        // Doubling is a swing, a 10% change isn't
+        for (id, voter, previous, current) in [
+            (10, "0xswing", 10_000.0, 20_000.0),
+            (11, "0xsteady", 10_000.0, 11_000.0),
+        ] {
+            voting_power_timeseries::Entity::insert(voting_power_timeseries::ActiveModel {
+                id: Set(Uuid::from_u128(id)),
+                voter: Set(voter.to_string()),
+                voting_power: Set(current),
+                dao_id: Set(DAO_ID),
+                timestamp: Set(now),
+                block: Set(2_000),
+                txid: Set(Some(format!("0xtx{id}"))),
+                log_index: Set(0),
+                previous_voting_power: Set(Some(previous)),
+            })
+            .exec(db)
+            .await?;
+            let event = stored(GovernanceEvent::VotingPowerChanged {
+                timeseries_id: Uuid::from_u128(id),
+                voter: voter.to_string(),
+                block: 2_000,
+            });
+            let expected = usize::from(voter == "0xswing");
+            assert_eq!(detector.handle_event(&event).await?, expected);
+            assert_eq!(detector.handle_event(&event).await?, 0);
+        }
+
+        // 10% of the delegated voting power is a whale vote, 1% isn't
+        for (id, voter, voting_power) in [(20, "0xwhale", 100_000.0), (21, "0xfish", 10_000.0)] {
+            vote::Entity::insert(vote::ActiveModel {
+                id: Set(Uuid::from_u128(id)),
+                voter_address: Set(voter.to_string()),
+                choice: Set(json!(0)),
+                voting_power: Set(voting_power),
+                reason: Set(None),
+                created_at: Set(now),
+                block_created_at: Set(Some(2_001)),
+                txid: Set(None),
+                proposal_external_id: Set("42".to_string()),
+                proposal_id: Set(PROPOSAL_ID),
+                dao_id: Set(DAO_ID),
+                governor_id: Set(GOVERNOR_ID),
+            })
+            .exec(db)
+            .await?;
+            let event = stored(GovernanceEvent::VoteCast {
+                vote_id: Uuid::from_u128(id),
+                proposal_id: PROPOSAL_ID,
+                voter_address: voter.to_string(),
+            });
+            let expected = usize::from(voter == "0xwhale");
+            assert_eq!(detector.handle_event(&event).await?, expected);
+        }
+
+        let recorded = anomalies(db).await?;
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].kind, AnomalyKind::VotingPowerSwing.as_str());
+        assert_eq!(recorded[0].address, "0xswing");
+        assert_eq!(recorded[0].proposal_id, Some(PROPOSAL_ID));
+        assert_eq!(recorded[0].block, Some(2_000));
+        assert_eq!(recorded[0].amount, 10_000.0);
+        assert_eq!(recorded[1].kind, AnomalyKind::WhaleVote.as_str());
+        assert_eq!(recorded[1].address, "0xwhale");
+        assert_eq!(recorded[1].details["share"], json!(0.1));
+
+        // Each anomaly is published for notification
+        let published = governance_event::Entity::find()
+            .filter(governance_event::Column::EventType.eq("anomaly_detected"))
+            .all(db)
+            .await?;
+        assert_eq!(published.len(), 2);
+        assert_eq!(
+            published[0].payload["anomaly_id"],
+            json!(recorded[0].id.to_string())
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_delegation_bursts_before_snapshot_are_recorded() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let start_at = Utc::now().naive_utc() + ChronoDuration::hours(1);
+        let db = test_db(start_at).await?;
+        let detector = AnomalyDetector::new(db.clone());
+
+        let mut delegation_ids = Vec::new();
+        for i in 0..20u128 {
+            let id = Uuid::from_u128(100 + i);
+            delegation::Entity::insert(delegation::ActiveModel {
+                id: Set(id),
+                delegator: Set(format!("0xdelegator{i}")),
+                delegate: Set("0xcandidate".to_string()),
+                dao_id: Set(DAO_ID),
+                timestamp: Set(start_at - ChronoDuration::hours(2)),
+                block: Set(900 + i as i32),
+                txid: Set(Some(format!("0xtx{i}"))),
+                from_delegate: Set(None),
+                log_index: Set(0),
+                weight: Set(Some(1_000.0)),
+            })
+            .exec(db)
+            .await?;
+            delegation_ids.push(id);
+        }
+
+        // Delegations indexed before the proposal are checked once it's created
+        let created = stored(GovernanceEvent::ProposalCreated {
+            proposal_id: PROPOSAL_ID,
+            governor_id: GOVERNOR_ID,
+            external_id: "42".to_string(),
+        });
+        assert_eq!(detector.handle_event(&created).await?, 1);
+
+        let delegated = stored(GovernanceEvent::DelegationChanged {
+            delegation_id: delegation_ids[19],
+            delegator: "0xdelegator19".to_string(),
+            delegate: "0xcandidate".to_string(),
+            from_delegate: None,
+        });
+        assert_eq!(detector.handle_event(&delegated).await?, 0);
+
+        let recorded = anomalies(db).await?;
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].kind, AnomalyKind::DelegationBurst.as_str());
+        assert_eq!(recorded[0].address, "0xcandidate");
+        assert_eq!(recorded[0].amount, 20_000.0);
+        assert_eq!(recorded[0].block, Some(919));
+        assert_eq!(recorded[0].details["delegators"], json!(20));
+
+        Ok(())
+    })
+}
@@ -0,0 +1,308 @@
+use anyhow::{Context, Result, anyhow};
+use proposalsapp_db::models::job_queue;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, prelude::Uuid,
+};
+use serial_test::serial;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use utils::{
+    job_queue::{self as queue, Job, WorkerConfig},
+    types::{JobData, ProposalJobData},
+};
+
+const LEASE: Duration = Duration::from_secs(60);
+
+/// The shared database, with the queue emptied by the previous test.
+async fn test_db() -> Result<&'static DatabaseConnection> {
+    let db = test_support::test_db().await;
+    job_queue::Entity::delete_many()
+        .exec(db)
+        .await
+        .context("failed to clear job_queue")?;
+    Ok(db)
+}
+
+fn proposal_job(id: u128) -> ProposalJobData {
+    ProposalJobData {
+        proposal_id: Uuid::from_u128(id),
+    }
+}
+
+async fn stored_job(db: &DatabaseConnection, id: i32) -> Result<job_queue::Model> {
+    job_queue::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .context("missing job_queue record")
+}
+
+async fn expire_leases(db: &DatabaseConnection) -> Result<()> {
+    db.execute_unprepared(
+        "UPDATE job_queue SET locked_until = NOW() - INTERVAL '1 second' WHERE status = 'RUNNING'",
+    )
+    .await?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_enqueue_dedupes_queued_jobs() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        let id = queue::enqueue(db, &proposal_job(1))
+            .await?
+            .context("first job not enqueued")?;
+        assert_eq!(queue::enqueue(db, &proposal_job(1)).await?, None);
+        assert!(queue::enqueue(db, &proposal_job(2)).await?.is_some());
+
+        let stored = stored_job(db, id).await?;
+        assert_eq!(stored.r#type, ProposalJobData::job_type().to_string());
+        assert_eq!(stored.status, queue::PENDING);
+        assert_eq!(stored.dedupe_key, Some(Uuid::from_u128(1).to_string()));
+
+        // Still deduped while running, queued again once done
+        let job = queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+            .await?
+            .context("no job claimed")?;
+        assert_eq!(job.id, id);
+        assert_eq!(job.data.proposal_id, Uuid::from_u128(1));
+        assert_eq!(queue::enqueue(db, &proposal_job(1)).await?, None);
+
+        assert!(queue::complete(db, &job).await?);
+        assert!(queue::enqueue(db, &proposal_job(1)).await?.is_some());
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_enqueue_once_dedupes_past_jobs() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        assert!(queue::enqueue_once(db, &proposal_job(1)).await?.is_some());
+        assert_eq!(queue::enqueue_once(db, &proposal_job(1)).await?, None);
+
+        // Unlike enqueue, completed jobs are never queued again
+        let job = queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+            .await?
+            .context("no job claimed")?;
+        assert!(queue::complete(db, &job).await?);
+        assert_eq!(queue::enqueue_once(db, &proposal_job(1)).await?, None);
+        assert!(queue::enqueue(db, &proposal_job(1)).await?.is_some());
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_claim_leases_jobs() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        for id in 1..=3 {
+            queue::enqueue(db, &proposal_job(id)).await?;
+        }
+
+        // Concurrent claims never get the same job
+        let claims = concurrent_claims(db, 5).await?;
+        let mut claimed: Vec<u128> = claims
+            .iter()
+            .flatten()
+            .map(|job| job.data.proposal_id.as_u128())
+            .collect();
+        claimed.sort();
+        assert_eq!(claimed, vec![1, 2, 3]);
+
+        let first = claims.into_iter().flatten().next().unwrap();
+        let stored = stored_job(db, first.id).await?;
+        assert_eq!(stored.status, queue::RUNNING);
+        assert_eq!(stored.attempts, 1);
+        assert!(stored.locked_by.is_some());
+        assert!(stored.locked_until.is_some());
+
+        // Once a lease runs out, the job can be claimed again, and the
+        // worker that lost it can no longer settle it
+        expire_leases(db).await?;
+        let reclaimed = queue::claim::<ProposalJobData, _>(db, "worker-b", LEASE)
+            .await?
+            .context("expired job not reclaimed")?;
+        assert_eq!(reclaimed.attempts, 2);
+
+        let lost = Job {
+            attempts: 1,
+            ..reclaimed.clone()
+        };
+        assert!(!queue::complete(db, &lost).await?);
+        assert!(queue::complete(db, &reclaimed).await?);
+
+        Ok(())
+    })
+}
+
+async fn concurrent_claims(
+    db: &'static DatabaseConnection,
+    count: usize,
+) -> Result<Vec<Option<Job<ProposalJobData>>>> {
+    let handles: Vec<_> = (0..count)
+        .map(|worker| {
+            tokio::spawn(async move {
+                queue::claim::<ProposalJobData, _>(db, &format!("worker-{worker}"), LEASE).await
+            })
+        })
+        .collect();
+
+    let mut claims = Vec::new();
+    for handle in handles {
+        claims.push(handle.await??);
+    }
+    Ok(claims)
+}
+
+#[test]
+#[serial]
+fn test_failed_jobs_back_off_then_dead_letter() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        let id = queue::enqueue(db, &proposal_job(1))
+            .await?
+            .context("job not enqueued")?;
+        db.execute_unprepared("UPDATE job_queue SET max_attempts = 2")
+            .await?;
+
+        let job = queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+            .await?
+            .context("no job claimed")?;
+        assert!(queue::fail(db, &job, &anyhow!("RPC unavailable")).await?);
+
+        let stored = stored_job(db, id).await?;
+        assert_eq!(stored.status, queue::PENDING);
+        assert_eq!(stored.last_error.as_deref(), Some("RPC unavailable"));
+        assert!(stored.run_at > stored.updated_at);
+        assert!(stored.locked_until.is_none());
+
+        // Not due until the backoff has passed
+        assert!(
+            queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+                .await?
+                .is_none()
+        );
+
+        db.execute_unprepared("UPDATE job_queue SET run_at = NOW()")
+            .await?;
+        let job = queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+            .await?
+            .context("retry not claimed")?;
+        assert_eq!(job.attempts, 2);
+        assert!(queue::fail(db, &job, &anyhow!("RPC unavailable")).await?);
+
+        let stored = stored_job(db, id).await?;
+        assert_eq!(stored.status, queue::DEAD);
+        db.execute_unprepared("UPDATE job_queue SET run_at = NOW()")
+            .await?;
+        assert!(
+            queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+                .await?
+                .is_none()
+        );
+
+        // Jobs whose data doesn't parse are dead-lettered when claimed
+        db.execute_unprepared(&format!(
+            "INSERT INTO job_queue (type, data) VALUES ('{}', '{{\"proposal_id\": 1}}')",
+            ProposalJobData::job_type()
+        ))
+        .await?;
+        assert!(
+            queue::claim::<ProposalJobData, _>(db, "worker-a", LEASE)
+                .await?
+                .is_none()
+        );
+        let dead = job_queue::Entity::find()
+            .filter(job_queue::Column::Status.eq(queue::DEAD))
+            .all(db)
+            .await?;
+        assert_eq!(dead.len(), 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_run_worker_handles_jobs() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+
+        for id in 1..=2 {
+            queue::enqueue(db, &proposal_job(id)).await?;
+        }
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let config = WorkerConfig {
+            poll_interval: Duration::from_millis(50),
+            ..WorkerConfig::new("test-worker")
+        };
+        let worker = tokio::spawn(async move {
+            queue::run_worker(db, &config, |job: ProposalJobData| {
+                let sender = sender.clone();
+                async move {
+                    sender.send(job.proposal_id.as_u128())?;
+                    Ok(())
+                }
+            })
+            .await
+        });
+
+        let mut handled = Vec::new();
+        for _ in 0..2 {
+            let id = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+                .await
+                .context("worker didn't handle the job in time")?
+                .context("worker stopped")?;
+            handled.push(id);
+        }
+        handled.sort();
+        assert_eq!(handled, vec![1, 2]);
+
+        // The second job may still be settling
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        worker.abort();
+
+        let completed = job_queue::Entity::find()
+            .filter(job_queue::Column::Status.eq(queue::COMPLETED))
+            .all(db)
+            .await?;
+        assert_eq!(completed.len(), 2);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,322 @@
+use anyhow::{Context, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use proposalsapp_db::models::{
+    dao, dao_discourse, dao_governor, discourse_topic, job_queue, proposal,
+    sea_orm_active_enums::ProposalState, user,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    prelude::Uuid, sea_query::OnConflict,
+};
+use serde_json::json;
+use serial_test::serial;
+use utils::{
+    notifications::NotificationProducer,
+    outbox::{GovernanceEvent, StoredEvent},
+    types::{JobType, ProposalEndedNotification},
+};
+
+const DAO_ID: Uuid = Uuid::from_u128(1);
+const ARBITRUM_ID: Uuid = Uuid::from_u128(2);
+const GOVERNOR_ID: Uuid = Uuid::from_u128(3);
+const DAO_DISCOURSE_ID: Uuid = Uuid::from_u128(4);
+const PROPOSAL_ID: Uuid = Uuid::from_u128(5);
+
+/// The shared database with an empty queue, a pending proposal, and users
+/// opted in to one kind of email each.
+async fn test_db() -> Result<&'static DatabaseConnection> {
+    let db = test_support::test_db().await;
+    job_queue::Entity::delete_many().exec(db).await?;
+    discourse_topic::Entity::delete_many().exec(db).await?;
+    proposal::Entity::delete_many().exec(db).await?;
+    user::Entity::delete_many().exec(db).await?;
+
+    for (id, slug) in [(DAO_ID, "test-dao"), (ARBITRUM_ID, "arbitrum")] {
+        dao::Entity::insert(dao::ActiveModel {
+            id: Set(id),
+            name: Set(format!("DAO {slug}")),
+            slug: Set(slug.to_string()),
+            picture: Set("https://example.com/dao.png".to_string()),
+        })
+        .on_conflict(OnConflict::column(dao::Column::Id).do_nothing().to_owned())
+        .do_nothing()
+        .exec(db)
+        .await
+        .context("failed to insert dao")?;
+    }
+    dao_governor::Entity::insert(dao_governor::ActiveModel {
+        id: Set(GOVERNOR_ID),
+        dao_id: Set(DAO_ID),
+        name: Set("Test Governor".to_string()),
+        r#type: Set("TEST_GOVERNOR".to_string()),
+        portal_url: Set(None),
+    })
+    .on_conflict(
+        OnConflict::column(dao_governor::Column::Id)
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await
+    .context("failed to insert dao_governor")?;
+    dao_discourse::Entity::insert(dao_discourse::ActiveModel {
+        id: Set(DAO_DISCOURSE_ID),
+        dao_id: Set(ARBITRUM_ID),
+        discourse_base_url: Set("https://forum.arbitrum.foundation".to_string()),
+        verification_topic_id: Set(None),
+        post_high_water_id: Set(None),
+    })
+    .on_conflict(
+        OnConflict::column(dao_discourse::Column::Id)
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await
+    .context("failed to insert dao_discourse")?;
+
+    let now = Utc::now().naive_utc();
+    proposal::Entity::insert(proposal::ActiveModel {
+        id: Set(PROPOSAL_ID),
+        external_id: Set("42".to_string()),
+        name: Set("Fund the thing".to_string()),
+        body: Set("Let's fund the thing".to_string()),
+        url: Set("https://example.com/proposal/42".to_string()),
+        discussion_url: Set(None),
+        choices: Set(json!(["For", "Against", "Abstain"])),
+        quorum: Set(100.0),
+        proposal_state: Set(ProposalState::Pending),
+        marked_spam: Set(false),
+        created_at: Set(now),
+        start_at: Set(now + ChronoDuration::days(1)),
+        end_at: Set(now + ChronoDuration::days(8)),
+        block_created_at: Set(None),
+        txid: Set(None),
+        metadata: Set(None),
+        dao_id: Set(DAO_ID),
+        author: Set(Some("0xauthor".to_string())),
+        governor_id: Set(GOVERNOR_ID),
+        block_start_at: Set(None),
+        block_end_at: Set(None),
+    })
+    .exec(db)
+    .await
+    .context("failed to insert proposal")?;
+
+    for (id, proposals, discussions, ending) in [
+        ("proposals", true, false, false),
+        ("discussions", false, true, false),
+        ("ending", false, false, true),
+        ("none", false, false, false),
+    ] {
+        user::Entity::insert(user::ActiveModel {
+            id: Set(id.to_string()),
+            name: Set(id.to_string()),
+            email: Set(format!("{id}@example.com")),
+            email_verified: Set(true),
+            image: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+            email_settings_new_proposals: Set(proposals),
+            email_settings_new_discussions: Set(discussions),
+            is_onboarded: Set(true),
+            email_settings_ending_proposals: Set(ending),
+        })
+        .exec(db)
+        .await
+        .context("failed to insert user")?;
+    }
+
+    Ok(db)
+}
+
+fn stored(dao_id: Uuid, event: GovernanceEvent) -> StoredEvent {
+    StoredEvent {
+        id: 1,
+        xact_id: 1,
+        dao_id,
+        event,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+fn state_changed(from_state: ProposalState, to_state: ProposalState) -> StoredEvent {
+    stored(
+        DAO_ID,
+        GovernanceEvent::ProposalStateChanged {
+            proposal_id: PROPOSAL_ID,
+            from_state,
+            to_state,
+        },
+    )
+}
+
+async fn insert_topic(
+    db: &DatabaseConnection,
+    external_id: i32,
+    category_id: i32,
+    restricted: bool,
+) -> Result<Uuid> {
+    let now = Utc::now().naive_utc();
+    let topic = discourse_topic::ActiveModel {
+        id: Set(Uuid::from_u128(100 + external_id as u128)),
+        external_id: Set(external_id),
+        title: Set(format!("Topic {external_id}")),
+        fancy_title: Set(format!("Topic {external_id}")),
+        slug: Set(format!("topic-{external_id}")),
+        posts_count: Set(1),
+        reply_count: Set(0),
+        created_at: Set(now),
+        last_posted_at: Set(now),
+        bumped_at: Set(now),
+        pinned: Set(false),
+        pinned_globally: Set(false),
+        visible: Set(true),
+        closed: Set(false),
+        archived: Set(false),
+        views: Set(0),
+        like_count: Set(0),
+        category_id: Set(category_id),
+        dao_discourse_id: Set(DAO_DISCOURSE_ID),
+        restricted: Set(restricted),
+    }
+    .insert(db)
+    .await
+    .context("failed to insert topic")?;
+    Ok(topic.id)
+}
+
+/// Queued jobs as (type, user id) pairs, in the order they were enqueued.
+async fn queued(db: &DatabaseConnection) -> Result<Vec<(String, String)>> {
+    let jobs = job_queue::Entity::find()
+        .order_by_asc(job_queue::Column::Id)
+        .all(db)
+        .await?;
+    Ok(jobs
+        .into_iter()
+        .map(|job| {
+            (
+                job.r#type,
+                job.data["user_id"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect())
+}
+
+fn job(job_type: JobType, user_id: &str) -> (String, String) {
+    (job_type.to_string(), user_id.to_string())
+}
+
+#[test]
+#[serial]
+fn test_proposal_lifecycle_notifies_opted_in_users_once() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let producer = NotificationProducer::new(db.clone());
+
+        let created = stored(
+            DAO_ID,
+            GovernanceEvent::ProposalCreated {
+                proposal_id: PROPOSAL_ID,
+                governor_id: GOVERNOR_ID,
+                external_id: "42".to_string(),
+            },
+        );
+        assert_eq!(producer.handle_event(&created).await?, 1);
+        // Replayed events notify no one twice
+        assert_eq!(producer.handle_event(&created).await?, 0);
+
+        let started = state_changed(ProposalState::Pending, ProposalState::Active);
+        assert_eq!(producer.handle_event(&started).await?, 1);
+
+        // Not ending soon yet, then ending within a day
+        assert_eq!(producer.queue_ending_soon().await?, 0);
+        proposal::Entity::update_many()
+            .col_expr(
+                proposal::Column::ProposalState,
+                ProposalState::Active.into(),
+            )
+            .col_expr(
+                proposal::Column::EndAt,
+                (Utc::now().naive_utc() + ChronoDuration::hours(6)).into(),
+            )
+            .filter(proposal::Column::Id.eq(PROPOSAL_ID))
+            .exec(db)
+            .await?;
+        assert_eq!(producer.queue_ending_soon().await?, 1);
+        assert_eq!(producer.queue_ending_soon().await?, 0);
+
+        let ended = state_changed(ProposalState::Active, ProposalState::Succeeded);
+        assert_eq!(producer.handle_event(&ended).await?, 1);
+        // Leaving a state the proposal wasn't voted in isn't ending
+        let canceled = state_changed(ProposalState::Pending, ProposalState::Canceled);
+        assert_eq!(producer.handle_event(&canceled).await?, 0);
+
+        assert_eq!(
+            queued(db).await?,
+            vec![
+                job(JobType::NotificationNewProposal, "proposals"),
+                job(JobType::NotificationVotingStarted, "proposals"),
+                job(JobType::NotificationEndingProposal, "ending"),
+                job(JobType::NotificationProposalEnded, "ending"),
+            ]
+        );
+
+        let ended_job = job_queue::Entity::find()
+            .filter(job_queue::Column::Type.eq(JobType::NotificationProposalEnded.to_string()))
+            .one(db)
+            .await?
+            .context("missing ended notification")?;
+        let data: ProposalEndedNotification = serde_json::from_value(ended_job.data)?;
+        assert_eq!(data.target_id, PROPOSAL_ID);
+        assert_eq!(data.dao_id, DAO_ID);
+        assert_eq!(data.result, ProposalState::Succeeded);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_new_discussions_in_governance_categories_notify() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let producer = NotificationProducer::new(db.clone());
+
+        // Topics in restricted categories stay private even in a governance category
+        for (external_id, category_id, restricted, expected) in
+            [(1, 7, false, 1), (2, 1, false, 0), (3, 7, true, 0)]
+        {
+            let topic_id = insert_topic(db, external_id, category_id, restricted).await?;
+            let event = stored(
+                ARBITRUM_ID,
+                GovernanceEvent::TopicCreated {
+                    topic_id,
+                    dao_discourse_id: DAO_DISCOURSE_ID,
+                    external_id,
+                },
+            );
+            assert_eq!(producer.handle_event(&event).await?, expected);
+            assert_eq!(producer.handle_event(&event).await?, 0);
+        }
+
+        assert_eq!(
+            queued(db).await?,
+            vec![job(JobType::NotificationNewDiscussion, "discussions")]
+        );
+
+        Ok(())
+    })
+}
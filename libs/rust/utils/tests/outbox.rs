@@ -0,0 +1,218 @@
+use anyhow::{Context, Result, anyhow};
+use proposalsapp_db::models::{dao, governance_event, governance_event_consumer};
+use sea_orm::{
+    DatabaseConnection, EntityTrait, Set, TransactionTrait, prelude::Uuid, sea_query::OnConflict,
+};
+use serial_test::serial;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use utils::outbox::{GovernanceEvent, OutboxConsumer, StoredEvent, prune, publish};
+
+const DAO_ID: Uuid = Uuid::from_u128(1);
+
+/// The shared database, with an empty outbox and a DAO to publish events of.
+async fn test_db() -> Result<&'static DatabaseConnection> {
+    let db = test_support::test_db().await;
+    governance_event::Entity::delete_many().exec(db).await?;
+    governance_event_consumer::Entity::delete_many()
+        .exec(db)
+        .await?;
+
+    dao::Entity::insert(dao::ActiveModel {
+        id: Set(DAO_ID),
+        name: Set("Test DAO".to_string()),
+        slug: Set("test-dao".to_string()),
+        picture: Set("https://example.com/dao.png".to_string()),
+    })
+    .on_conflict(OnConflict::column(dao::Column::Id).do_nothing().to_owned())
+    .do_nothing()
+    .exec(db)
+    .await
+    .context("failed to insert dao")?;
+    Ok(db)
+}
+
+fn topic_created(external_id: i32) -> GovernanceEvent {
+    GovernanceEvent::TopicCreated {
+        topic_id: Uuid::from_u128(external_id as u128),
+        dao_discourse_id: Uuid::from_u128(2),
+        external_id,
+    }
+}
+
+fn external_ids(events: &[StoredEvent]) -> Vec<i32> {
+    events
+        .iter()
+        .map(|event| match event.event {
+            GovernanceEvent::TopicCreated { external_id, .. } => external_id,
+            _ => panic!("unexpected event {:?}", event.event),
+        })
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_events_are_published_with_their_transaction() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let consumer = OutboxConsumer::new(db.clone(), "test");
+
+        let txn = db.begin().await?;
+        publish(&txn, DAO_ID, [topic_created(1)]).await?;
+        txn.rollback().await?;
+        assert!(consumer.next_batch().await?.is_empty());
+
+        let txn = db.begin().await?;
+        publish(&txn, DAO_ID, [topic_created(2), topic_created(3)]).await?;
+        txn.commit().await?;
+
+        let batch = consumer.next_batch().await?;
+        assert_eq!(external_ids(&batch), vec![2, 3]);
+        assert_eq!(batch[0].dao_id, DAO_ID);
+        let stored = governance_event::Entity::find_by_id(batch[0].id)
+            .one(db)
+            .await?
+            .context("missing governance_event record")?;
+        assert_eq!(stored.event_type, "topic_created");
+        assert_eq!(stored.payload["external_id"], 2);
+
+        // Uncommitted offsets redeliver, committed ones don't, and each
+        // consumer has its own
+        assert_eq!(consumer.next_batch().await?, batch);
+        consumer.commit(batch.last().unwrap()).await?;
+        assert!(consumer.next_batch().await?.is_empty());
+        let other = OutboxConsumer::new(db.clone(), "other");
+        assert_eq!(other.next_batch().await?, batch);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_events_committed_out_of_order_are_not_skipped() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let consumer = OutboxConsumer::new(db.clone(), "test");
+
+        // The earlier transaction takes the lower id but commits last
+        let earlier = db.begin().await?;
+        publish(&earlier, DAO_ID, [topic_created(1)]).await?;
+        let later = db.begin().await?;
+        publish(&later, DAO_ID, [topic_created(2)]).await?;
+        later.commit().await?;
+
+        assert!(consumer.next_batch().await?.is_empty());
+
+        earlier.commit().await?;
+        let batch = consumer.next_batch().await?;
+        assert_eq!(external_ids(&batch), vec![1, 2]);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_run_delivers_events_at_least_once() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let consumer = OutboxConsumer::new(db.clone(), "test");
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let failures = Arc::new(Mutex::new(vec![3]));
+
+        let run = {
+            let consumer = consumer.clone();
+            let failures = Arc::clone(&failures);
+            tokio::spawn(async move {
+                consumer
+                    .run(|event| {
+                        let sender = sender.clone();
+                        let failures = Arc::clone(&failures);
+                        async move {
+                            let id = external_ids(std::slice::from_ref(&event))[0];
+                            sender.send(id)?;
+                            let mut failures = failures.lock().unwrap();
+                            if let Some(position) = failures.iter().position(|&f| f == id) {
+                                failures.remove(position);
+                                return Err(anyhow!("handler failed on {id}"));
+                            }
+                            Ok(())
+                        }
+                    })
+                    .await
+            })
+        };
+
+        // Woken up by the notification rather than the poll interval
+        publish(db, DAO_ID, [topic_created(1), topic_created(2)]).await?;
+        for expected in [1, 2] {
+            let id = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+                .await
+                .context("event not delivered in time")?;
+            assert_eq!(id, Some(expected));
+        }
+
+        publish(db, DAO_ID, [topic_created(3), topic_created(4)]).await?;
+        let result = tokio::time::timeout(Duration::from_secs(5), run).await??;
+        assert!(result.is_err());
+        assert_eq!(receiver.recv().await, Some(3));
+
+        // Restarted, it resumes at the event that failed
+        let batch = consumer.next_batch().await?;
+        assert_eq!(external_ids(&batch), vec![3, 4]);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn test_prune_keeps_recent_and_unhandled_events() -> Result<()> {
+    if !*test_support::DOCKER_AVAILABLE {
+        eprintln!("Docker unavailable; skipping integration test.");
+        return Ok(());
+    }
+
+    test_support::TEST_RUNTIME.block_on(async {
+        let db = test_db().await?;
+        let fast = OutboxConsumer::new(db.clone(), "fast");
+        let slow = OutboxConsumer::new(db.clone(), "slow");
+
+        let txn = db.begin().await?;
+        publish(&txn, DAO_ID, [topic_created(1), topic_created(2)]).await?;
+        txn.commit().await?;
+        let batch = fast.next_batch().await?;
+        fast.commit(&batch[1]).await?;
+        slow.commit(&batch[0]).await?;
+
+        // Nothing is old enough yet
+        assert_eq!(prune(db, Duration::from_secs(3600)).await?, 0);
+
+        // Only the event both consumers handled goes
+        assert_eq!(prune(db, Duration::ZERO).await?, 1);
+        let kept = governance_event::Entity::find().all(db).await?;
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, batch[1].id);
+        assert_eq!(external_ids(&slow.next_batch().await?), vec![2]);
+        Ok(())
+    })
+}
@@ -0,0 +1,33 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.voter
+      ADD COLUMN IF NOT EXISTS ens_verified BOOLEAN NOT NULL DEFAULT FALSE,
+      ADD COLUMN IF NOT EXISTS url TEXT,
+      ADD COLUMN IF NOT EXISTS twitter TEXT,
+      ADD COLUMN IF NOT EXISTS discourse TEXT,
+      ADD COLUMN IF NOT EXISTS description TEXT,
+      ADD COLUMN IF NOT EXISTS ens_refreshed_at TIMESTAMP
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_voter_ens_refreshed_at
+      ON public.voter (ens_refreshed_at NULLS FIRST)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP INDEX IF EXISTS public.idx_voter_ens_refreshed_at`.execute(db);
+
+  await sql`
+    ALTER TABLE public.voter
+      DROP COLUMN IF EXISTS ens_verified,
+      DROP COLUMN IF EXISTS url,
+      DROP COLUMN IF EXISTS twitter,
+      DROP COLUMN IF EXISTS discourse,
+      DROP COLUMN IF EXISTS description,
+      DROP COLUMN IF EXISTS ens_refreshed_at
+  `.execute(db);
+}
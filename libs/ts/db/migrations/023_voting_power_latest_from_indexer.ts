@@ -0,0 +1,94 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * voting_power_latest is now maintained by the indexer in the same
+ * transaction as each voting_power_timeseries batch, ordered by
+ * (block, log_index). The insert trigger never saw upserted rows and could
+ * not order events within a block, so it is dropped, and the table is
+ * rebuilt from the timeseries so rows it missed are not left stale.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.voting_power_timeseries
+      ADD COLUMN IF NOT EXISTS log_index INTEGER NOT NULL DEFAULT 0
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.voting_power_latest
+      ADD COLUMN IF NOT EXISTS log_index INTEGER NOT NULL DEFAULT 0
+  `.execute(db);
+
+  await sql`DROP TRIGGER IF EXISTS maintain_voting_power_latest ON public.voting_power_timeseries`.execute(
+    db
+  );
+  await sql`DROP FUNCTION IF EXISTS update_voting_power_latest()`.execute(db);
+
+  // Used to rebuild and verify voting_power_latest from the timeseries
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_voting_power_timeseries_dao_voter_block_log
+      ON public.voting_power_timeseries (dao_id, voter, block DESC, log_index DESC)
+  `.execute(db);
+
+  await sql`
+    DELETE FROM public.voting_power_latest l
+    WHERE NOT EXISTS (
+      SELECT 1 FROM public.voting_power_timeseries t
+      WHERE t.voter = l.voter AND t.dao_id = l.dao_id
+    )
+  `.execute(db);
+
+  await sql`
+    INSERT INTO public.voting_power_latest
+      (voter, voting_power, dao_id, timestamp, block, log_index, txid)
+    SELECT DISTINCT ON (dao_id, voter)
+      voter, voting_power, dao_id, timestamp, block, log_index, txid
+    FROM public.voting_power_timeseries
+    ORDER BY dao_id, voter, block DESC, log_index DESC
+    ON CONFLICT (voter, dao_id) DO UPDATE SET
+      voting_power = EXCLUDED.voting_power,
+      timestamp = EXCLUDED.timestamp,
+      block = EXCLUDED.block,
+      log_index = EXCLUDED.log_index,
+      txid = EXCLUDED.txid
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP INDEX IF EXISTS public.idx_voting_power_timeseries_dao_voter_block_log`.execute(
+    db
+  );
+
+  await sql`
+    CREATE OR REPLACE FUNCTION update_voting_power_latest()
+    RETURNS TRIGGER AS $$
+    BEGIN
+      INSERT INTO public.voting_power_latest (voter, voting_power, dao_id, timestamp, block, txid)
+      VALUES (NEW.voter, NEW.voting_power, NEW.dao_id, NEW.timestamp, NEW.block, NEW.txid)
+      ON CONFLICT (voter, dao_id)
+      DO UPDATE SET
+        voting_power = EXCLUDED.voting_power,
+        timestamp = EXCLUDED.timestamp,
+        block = EXCLUDED.block,
+        txid = EXCLUDED.txid
+      WHERE
+        EXCLUDED.timestamp > voting_power_latest.timestamp
+        OR (EXCLUDED.timestamp = voting_power_latest.timestamp AND EXCLUDED.block > voting_power_latest.block)
+        OR (EXCLUDED.timestamp = voting_power_latest.timestamp
+            AND EXCLUDED.block = voting_power_latest.block
+            AND EXCLUDED.txid IS NOT NULL
+            AND voting_power_latest.txid IS NULL);
+      RETURN NEW;
+    END;
+    $$ LANGUAGE plpgsql;
+  `.execute(db);
+
+  await sql`
+    CREATE TRIGGER maintain_voting_power_latest
+    AFTER INSERT ON public.voting_power_timeseries
+    FOR EACH ROW EXECUTE FUNCTION update_voting_power_latest();
+  `.execute(db);
+
+  await sql`ALTER TABLE public.voting_power_latest DROP COLUMN IF EXISTS log_index`.execute(db);
+  await sql`ALTER TABLE public.voting_power_timeseries DROP COLUMN IF EXISTS log_index`.execute(db);
+}
@@ -0,0 +1,38 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Running total of delegated voting power per DAO, one row per block in
+ * which a (non-excluded) voter's voting power changed. Maintained by the
+ * indexer alongside voting_power_timeseries; the first write for a DAO
+ * backfills its history.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.delegated_voting_power_snapshot (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      block INTEGER NOT NULL,
+      timestamp TIMESTAMP NOT NULL,
+      total_voting_power DOUBLE PRECISION NOT NULL,
+      created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      CONSTRAINT delegated_voting_power_snapshot_dao_block_unique UNIQUE (dao_id, block)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegated_voting_power_snapshot_dao_timestamp
+      ON public.delegated_voting_power_snapshot (dao_id, timestamp DESC, block DESC)
+  `.execute(db);
+
+  // Used to recompute snapshots from the first block touched by a batch
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_voting_power_timeseries_dao_block
+      ON public.voting_power_timeseries (dao_id, block)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP INDEX IF EXISTS public.idx_voting_power_timeseries_dao_block`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.delegated_voting_power_snapshot`.execute(db);
+}
@@ -0,0 +1,32 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Indexes for the point-in-time voting power queries in proposalsapp-db
+ * (voting power at a time, voting power series, delegators at a block).
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  // Latest row per voter at or before a timestamp
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_voting_power_timeseries_dao_voter_timestamp
+      ON public.voting_power_timeseries (dao_id, voter, timestamp DESC, block DESC, log_index DESC)
+  `.execute(db);
+
+  // Latest delegation per delegator at or before a block
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegation_dao_delegator_block
+      ON public.delegation (dao_id, delegator, block DESC)
+  `.execute(db);
+
+  // Everyone who ever delegated to a delegate
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegation_dao_delegate_delegator
+      ON public.delegation (dao_id, delegate, delegator)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP INDEX IF EXISTS public.idx_delegation_dao_delegate_delegator`.execute(db);
+  await sql`DROP INDEX IF EXISTS public.idx_delegation_dao_delegator_block`.execute(db);
+  await sql`DROP INDEX IF EXISTS public.idx_voting_power_timeseries_dao_voter_timestamp`.execute(db);
+}
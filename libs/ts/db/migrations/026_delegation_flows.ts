@@ -0,0 +1,157 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Delegation flow tracking: DelegateChanged now records the previous
+ * delegate, token transfers are indexed to maintain delegator balances, and
+ * delegation_flow records how much voting power moved between delegates
+ * through delegations and transfers.
+ *
+ * Flows are synced from the rows that haven't been turned into flows yet
+ * rather than only from blocks above a cursor, so delegations and transfers
+ * indexed late (backfills, replays) still get their flows. Unprocessed
+ * delegations have no weight yet; transfers get a flag.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.delegation
+      ADD COLUMN IF NOT EXISTS from_delegate TEXT,
+      ADD COLUMN IF NOT EXISTS log_index INTEGER,
+      ADD COLUMN IF NOT EXISTS weight DOUBLE PRECISION
+  `.execute(db);
+
+  // The log index of delegations indexed so far wasn't recorded, and
+  // rindexer doesn't keep the raw events to recover it from. They get
+  // negative placeholders instead: distinct within a block, so no two
+  // delegations of a transaction share a flow or tie when ordered, and below
+  // every real log index, so they count as coming before the block's
+  // transfers. Delegations of the same delegator in one block are ordered by
+  // transaction hash, which is arbitrary. Re-indexing a delegation stores
+  // its real log index.
+  await sql`
+    UPDATE public.delegation d
+    SET log_index = placeholder.log_index
+    FROM (
+      SELECT
+        id,
+        -ROW_NUMBER() OVER (
+          PARTITION BY dao_id, block
+          ORDER BY txid DESC, delegator DESC
+        )::INTEGER AS log_index
+      FROM public.delegation
+      WHERE log_index IS NULL
+    ) placeholder
+    WHERE d.id = placeholder.id
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.delegation
+      ALTER COLUMN log_index SET NOT NULL
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegation_flow_pending
+      ON public.delegation (dao_id, block)
+      WHERE weight IS NULL
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.token_transfer (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      from_address TEXT NOT NULL,
+      to_address TEXT NOT NULL,
+      amount DOUBLE PRECISION NOT NULL,
+      block INTEGER NOT NULL,
+      log_index INTEGER NOT NULL,
+      timestamp TIMESTAMP NOT NULL,
+      txid TEXT NOT NULL,
+      flow_synced BOOLEAN NOT NULL DEFAULT false,
+      CONSTRAINT token_transfer_dao_txid_log_index_unique UNIQUE (dao_id, txid, log_index)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_token_transfer_dao_from_block
+      ON public.token_transfer (dao_id, from_address, block, log_index)
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_token_transfer_dao_to_block
+      ON public.token_transfer (dao_id, to_address, block, log_index)
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_token_transfer_dao_block
+      ON public.token_transfer (dao_id, block)
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_token_transfer_flow_pending
+      ON public.token_transfer (dao_id, block)
+      WHERE NOT flow_synced
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.token_balance (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      account TEXT NOT NULL,
+      balance DOUBLE PRECISION NOT NULL,
+      block INTEGER NOT NULL,
+      updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      CONSTRAINT token_balance_dao_account_unique UNIQUE (dao_id, account)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.delegation_flow (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      kind TEXT NOT NULL CHECK (kind IN ('delegate', 'transfer')),
+      delegator TEXT NOT NULL,
+      from_delegate TEXT,
+      to_delegate TEXT,
+      amount DOUBLE PRECISION NOT NULL,
+      block INTEGER NOT NULL,
+      log_index INTEGER NOT NULL,
+      timestamp TIMESTAMP NOT NULL,
+      txid TEXT,
+      CONSTRAINT delegation_flow_dao_txid_log_index_kind_unique UNIQUE (dao_id, txid, log_index, kind)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegation_flow_dao_to_delegate_timestamp
+      ON public.delegation_flow (dao_id, to_delegate, timestamp DESC)
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegation_flow_dao_from_delegate_timestamp
+      ON public.delegation_flow (dao_id, from_delegate, timestamp DESC)
+  `.execute(db);
+
+  // Last block whose delegations and transfers have been turned into flows
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.delegation_flow_sync_state (
+      dao_id UUID PRIMARY KEY REFERENCES public.dao(id) ON DELETE CASCADE,
+      last_block INTEGER NOT NULL,
+      updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+    )
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.delegation_flow_sync_state`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.delegation_flow`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.token_balance`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.token_transfer`.execute(db);
+  await sql`DROP INDEX IF EXISTS public.idx_delegation_flow_pending`.execute(db);
+
+  await sql`
+    ALTER TABLE public.delegation
+      DROP COLUMN IF EXISTS from_delegate,
+      DROP COLUMN IF EXISTS log_index,
+      DROP COLUMN IF EXISTS weight
+  `.execute(db);
+}
@@ -0,0 +1,21 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Keep DelegateVotesChanged previousBalance on voting_power_timeseries so
+ * each event can be checked against the voter's previous stored balance.
+ * Rows indexed before this migration have no previous balance.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.voting_power_timeseries
+      ADD COLUMN IF NOT EXISTS previous_voting_power DOUBLE PRECISION
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.voting_power_timeseries
+      DROP COLUMN IF EXISTS previous_voting_power
+  `.execute(db);
+}
@@ -0,0 +1,35 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Dead-letter store for indexer events whose handler failed to persist them.
+ * The payload holds the raw log (topics and data) plus its position so the
+ * event can be decoded and replayed later.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.failed_event (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      handler TEXT NOT NULL,
+      payload JSONB NOT NULL,
+      block_number BIGINT NOT NULL,
+      transaction_hash TEXT NOT NULL,
+      log_index INTEGER NOT NULL,
+      error TEXT NOT NULL,
+      attempts INTEGER NOT NULL DEFAULT 1,
+      next_attempt_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+      created_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+      updated_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+      CONSTRAINT failed_event_handler_tx_log_index_unique UNIQUE (handler, transaction_hash, log_index)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_failed_event_next_attempt_at
+      ON public.failed_event (next_attempt_at)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.failed_event`.execute(db);
+}
@@ -0,0 +1,39 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * State of the governor backfiller: how far each governor's ProposalCreated
+ * logs have been scanned, and a record of every proposal or set of votes it
+ * healed.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.governor_backfill_state (
+      governor_id UUID PRIMARY KEY REFERENCES public.dao_governor(id) ON DELETE CASCADE,
+      last_scanned_block BIGINT NOT NULL,
+      updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.governor_backfill_heal (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      governor_id UUID NOT NULL REFERENCES public.dao_governor(id) ON DELETE CASCADE,
+      proposal_external_id TEXT NOT NULL,
+      kind TEXT NOT NULL CHECK (kind IN ('proposal', 'votes')),
+      healed_count INTEGER NOT NULL,
+      details JSONB NOT NULL DEFAULT '{}',
+      created_at TIMESTAMP NOT NULL DEFAULT NOW()
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_governor_backfill_heal_governor_created_at
+      ON public.governor_backfill_heal (governor_id, created_at DESC)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.governor_backfill_heal`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.governor_backfill_state`.execute(db);
+}
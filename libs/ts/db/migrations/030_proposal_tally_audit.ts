@@ -0,0 +1,42 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Discrepancies found by the tally auditor between a proposal's stored vote
+ * totals, quorum or state and the governor contract. A discrepancy stays
+ * open, updated on every audit that still sees it, until it resolves or is
+ * auto-corrected.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.proposal_tally_audit (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      proposal_id UUID NOT NULL REFERENCES public.proposal(id) ON DELETE CASCADE,
+      governor_id UUID NOT NULL REFERENCES public.dao_governor(id) ON DELETE CASCADE,
+      field TEXT NOT NULL CHECK (field IN ('for_votes', 'against_votes', 'abstain_votes', 'quorum', 'state')),
+      severity TEXT NOT NULL CHECK (severity IN ('info', 'warning', 'critical')),
+      stored_value TEXT NOT NULL,
+      onchain_value TEXT NOT NULL,
+      corrected BOOLEAN NOT NULL DEFAULT FALSE,
+      first_seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      last_seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      resolved_at TIMESTAMP
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_proposal_tally_audit_open_unique
+      ON public.proposal_tally_audit (proposal_id, field)
+      WHERE resolved_at IS NULL
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_proposal_tally_audit_governor_open
+      ON public.proposal_tally_audit (governor_id, severity)
+      WHERE resolved_at IS NULL
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.proposal_tally_audit`.execute(db);
+}
@@ -0,0 +1,32 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Every change of a proposal's state, with where it came from: an indexed
+ * event, the state scheduler, the Snapshot indexer or a manual correction.
+ * The first row of a proposal has no from_state.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.proposal_state_history (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      proposal_id UUID NOT NULL REFERENCES public.proposal(id) ON DELETE CASCADE,
+      from_state public.proposal_state,
+      to_state public.proposal_state NOT NULL,
+      source TEXT NOT NULL CHECK (source IN ('event', 'poller', 'snapshot', 'manual')),
+      correction BOOLEAN NOT NULL DEFAULT FALSE,
+      block_number BIGINT,
+      txid TEXT,
+      created_at TIMESTAMP NOT NULL DEFAULT NOW()
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_proposal_state_history_proposal
+      ON public.proposal_state_history (proposal_id, created_at)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.proposal_state_history`.execute(db);
+}
@@ -0,0 +1,30 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Versions of a proposal's content: title, body, choices, dates and
+ * discussion link. A revision is added whenever the content hash changes, so
+ * edits made to Snapshot proposals before voting starts aren't lost.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.proposal_revision (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      proposal_id UUID NOT NULL REFERENCES public.proposal(id) ON DELETE CASCADE,
+      revision INTEGER NOT NULL,
+      name TEXT NOT NULL,
+      body TEXT NOT NULL,
+      choices JSONB NOT NULL,
+      discussion_url TEXT,
+      start_at TIMESTAMP NOT NULL,
+      end_at TIMESTAMP NOT NULL,
+      content_hash TEXT NOT NULL,
+      created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      UNIQUE (proposal_id, revision)
+    )
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.proposal_revision`.execute(db);
+}
@@ -0,0 +1,50 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Lets workers lease jobs. A claimed job is RUNNING until locked_until, after
+ * which another worker may claim it again. Failed jobs go back to PENDING
+ * with run_at pushed back, or to DEAD once max_attempts is reached. Jobs with
+ * a dedupe_key are only enqueued once while PENDING or RUNNING.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.job_queue
+      ADD COLUMN IF NOT EXISTS attempts INTEGER NOT NULL DEFAULT 0,
+      ADD COLUMN IF NOT EXISTS max_attempts INTEGER NOT NULL DEFAULT 5,
+      ADD COLUMN IF NOT EXISTS run_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      ADD COLUMN IF NOT EXISTS locked_until TIMESTAMP,
+      ADD COLUMN IF NOT EXISTS locked_by TEXT,
+      ADD COLUMN IF NOT EXISTS last_error TEXT,
+      ADD COLUMN IF NOT EXISTS dedupe_key TEXT,
+      ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+  `.execute(db);
+
+  await sql`
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_job_queue_dedupe_key
+      ON public.job_queue (type, dedupe_key)
+      WHERE dedupe_key IS NOT NULL AND status IN ('PENDING', 'RUNNING')
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_job_queue_claim
+      ON public.job_queue (type, run_at)
+      WHERE status IN ('PENDING', 'RUNNING')
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP INDEX IF EXISTS public.idx_job_queue_claim`.execute(db);
+  await sql`DROP INDEX IF EXISTS public.idx_job_queue_dedupe_key`.execute(db);
+  await sql`
+    ALTER TABLE public.job_queue
+      DROP COLUMN IF EXISTS attempts,
+      DROP COLUMN IF EXISTS max_attempts,
+      DROP COLUMN IF EXISTS run_at,
+      DROP COLUMN IF EXISTS locked_until,
+      DROP COLUMN IF EXISTS locked_by,
+      DROP COLUMN IF EXISTS last_error,
+      DROP COLUMN IF EXISTS dedupe_key,
+      DROP COLUMN IF EXISTS updated_at
+  `.execute(db);
+}
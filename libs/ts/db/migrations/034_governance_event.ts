@@ -0,0 +1,55 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Outbox of governance events, written in the transaction of the change they
+ * describe, with a NOTIFY on the governance_events channel. Consumers read
+ * events in (xact_id, id) order, only up to the oldest transaction still
+ * running, so events committed out of id order aren't skipped.
+ * governance_event_consumer stores each consumer's offset. Events are pruned
+ * once every consumer handled them and they're older than the retention of
+ * the Rust outbox.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.governance_event (
+      id BIGSERIAL PRIMARY KEY,
+      xact_id BIGINT NOT NULL DEFAULT (pg_current_xact_id()::text::bigint),
+      event_type TEXT NOT NULL CHECK (event_type IN (
+        'proposal_created',
+        'proposal_state_changed',
+        'vote_cast',
+        'delegation_changed',
+        'topic_created',
+        'post_edited'
+      )),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      payload JSONB NOT NULL,
+      created_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now())
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_governance_event_position
+      ON public.governance_event (xact_id, id)
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_governance_event_created_at
+      ON public.governance_event (created_at)
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.governance_event_consumer (
+      name TEXT PRIMARY KEY,
+      xact_id BIGINT NOT NULL DEFAULT 0,
+      event_id BIGINT NOT NULL DEFAULT 0,
+      updated_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now())
+    )
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.governance_event_consumer`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.governance_event`.execute(db);
+}
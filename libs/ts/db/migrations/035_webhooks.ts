@@ -0,0 +1,61 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Outbound webhooks. A webhook_subscription receives the events listed in
+ * event_types (all of them when empty) for its DAO (every DAO when dao_id is
+ * null), and vote.cast only for the voters in tracked_voters. Each event is
+ * delivered once per subscription as a webhook_delivery, which is retried with
+ * backoff and keeps the outcome of its last attempt. Subscriptions failing
+ * too many attempts in a row, for long enough, are disabled.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.webhook_subscription (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      url TEXT NOT NULL,
+      secret TEXT NOT NULL,
+      dao_id UUID REFERENCES public.dao(id) ON DELETE CASCADE,
+      event_types JSONB NOT NULL DEFAULT '[]'::jsonb,
+      tracked_voters JSONB NOT NULL DEFAULT '[]'::jsonb,
+      enabled BOOLEAN NOT NULL DEFAULT TRUE,
+      consecutive_failures INTEGER NOT NULL DEFAULT 0,
+      failing_since TIMESTAMP,
+      disabled_at TIMESTAMP,
+      created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.webhook_delivery (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      subscription_id UUID NOT NULL REFERENCES public.webhook_subscription(id) ON DELETE CASCADE,
+      event_type TEXT NOT NULL,
+      event_key TEXT NOT NULL,
+      payload JSONB NOT NULL,
+      status TEXT NOT NULL DEFAULT 'PENDING'
+        CHECK (status IN ('PENDING', 'DELIVERED', 'FAILED')),
+      attempts INTEGER NOT NULL DEFAULT 0,
+      next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      locked_until TIMESTAMP,
+      response_status INTEGER,
+      last_error TEXT,
+      delivered_at TIMESTAMP,
+      created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      UNIQUE (subscription_id, event_key)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_webhook_delivery_due
+      ON public.webhook_delivery (next_attempt_at)
+      WHERE status = 'PENDING'
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.webhook_delivery`.execute(db);
+  await sql`DROP TABLE IF EXISTS public.webhook_subscription`.execute(db);
+}
@@ -0,0 +1,20 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Looks up jobs by dedupe_key whatever their status, for jobs that must only
+ * ever be enqueued once, like per-user notifications.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_job_queue_dedupe_key_history
+      ON public.job_queue (type, dedupe_key)
+      WHERE dedupe_key IS NOT NULL
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP INDEX IF EXISTS public.idx_job_queue_dedupe_key_history`.execute(
+    db
+  );
+}
@@ -0,0 +1,77 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Anomalies detected around live votes: large voting power swings of a
+ * delegate during a proposal, votes holding a large share of the delegated
+ * voting power, and bursts of delegations to one delegate right before a
+ * proposal's snapshot. Each is recorded once per dedupe_key, with its amounts
+ * in details, and published as an anomaly_detected governance event. Voting
+ * power changes are published too, for the detector to consume.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.governance_anomaly (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      proposal_id UUID REFERENCES public.proposal(id) ON DELETE CASCADE,
+      kind TEXT NOT NULL CHECK (kind IN (
+        'VOTING_POWER_SWING',
+        'WHALE_VOTE',
+        'DELEGATION_BURST'
+      )),
+      address TEXT NOT NULL,
+      block INTEGER,
+      amount DOUBLE PRECISION NOT NULL,
+      details JSONB NOT NULL DEFAULT '{}'::jsonb,
+      dedupe_key TEXT NOT NULL UNIQUE,
+      detected_at TIMESTAMP NOT NULL DEFAULT NOW()
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_governance_anomaly_dao_detected
+      ON public.governance_anomaly (dao_id, detected_at DESC)
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_governance_anomaly_proposal
+      ON public.governance_anomaly (proposal_id)
+      WHERE proposal_id IS NOT NULL
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.governance_event
+      DROP CONSTRAINT IF EXISTS governance_event_event_type_check,
+      ADD CONSTRAINT governance_event_event_type_check CHECK (event_type IN (
+        'proposal_created',
+        'proposal_state_changed',
+        'vote_cast',
+        'delegation_changed',
+        'voting_power_changed',
+        'topic_created',
+        'post_edited',
+        'anomaly_detected'
+      ))
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`
+    DELETE FROM public.governance_event
+      WHERE event_type IN ('voting_power_changed', 'anomaly_detected')
+  `.execute(db);
+  await sql`
+    ALTER TABLE public.governance_event
+      DROP CONSTRAINT IF EXISTS governance_event_event_type_check,
+      ADD CONSTRAINT governance_event_event_type_check CHECK (event_type IN (
+        'proposal_created',
+        'proposal_state_changed',
+        'vote_cast',
+        'delegation_changed',
+        'topic_created',
+        'post_edited'
+      ))
+  `.execute(db);
+  await sql`DROP TABLE IF EXISTS public.governance_anomaly`.execute(db);
+}
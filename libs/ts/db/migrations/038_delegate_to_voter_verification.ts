@@ -0,0 +1,34 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Records every check of a delegate_to_voter proof: the proof checked, whether
+ * it proved the voter claimed the delegate profile for the claimed period, by
+ * which method (an EIP-191 or EIP-712 signature of the voter, or an EIP-1271
+ * smart contract wallet accepting it), and why not otherwise. The latest
+ * record decides delegate_to_voter.verified.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.delegate_to_voter_verification (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      delegate_to_voter_id UUID NOT NULL REFERENCES public.delegate_to_voter(id) ON DELETE CASCADE,
+      proof JSONB,
+      verified BOOLEAN NOT NULL,
+      method TEXT CHECK (method IN ('EIP191', 'EIP712', 'EIP1271')),
+      error TEXT,
+      checked_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now())
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegate_to_voter_verification_latest
+      ON public.delegate_to_voter_verification (delegate_to_voter_id, checked_at DESC)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.delegate_to_voter_verification`.execute(
+    db
+  );
+}
@@ -0,0 +1,55 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Ownership checks of the Discourse accounts delegates link to. Each link gets
+ * a challenge nonce, which the account owner puts in their profile bio or in a
+ * post in the forum's verification topic, optionally with a signature of the
+ * delegate's voter address. Every change of outcome is recorded with its
+ * evidence; the latest record decides delegate_to_discourse_user.verified.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.delegate_to_discourse_user
+      ADD COLUMN IF NOT EXISTS challenge TEXT UNIQUE,
+      ADD COLUMN IF NOT EXISTS challenge_issued_at TIMESTAMP
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.dao_discourse
+      ADD COLUMN IF NOT EXISTS verification_topic_id INTEGER
+  `.execute(db);
+
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.delegate_to_discourse_user_verification (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      delegate_to_discourse_user_id UUID NOT NULL REFERENCES public.delegate_to_discourse_user(id) ON DELETE CASCADE,
+      challenge TEXT NOT NULL,
+      verified BOOLEAN NOT NULL,
+      method TEXT CHECK (method IN ('PROFILE_BIO', 'TOPIC_POST')),
+      evidence JSONB,
+      error TEXT,
+      checked_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now())
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_delegate_to_discourse_user_verification_latest
+      ON public.delegate_to_discourse_user_verification (delegate_to_discourse_user_id, checked_at DESC)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`DROP TABLE IF EXISTS public.delegate_to_discourse_user_verification`.execute(
+    db
+  );
+  await sql`
+    ALTER TABLE public.dao_discourse
+      DROP COLUMN IF EXISTS verification_topic_id
+  `.execute(db);
+  await sql`
+    ALTER TABLE public.delegate_to_discourse_user
+      DROP COLUMN IF EXISTS challenge,
+      DROP COLUMN IF EXISTS challenge_issued_at
+  `.execute(db);
+}
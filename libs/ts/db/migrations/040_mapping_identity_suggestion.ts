@@ -0,0 +1,36 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Voter and Discourse account pairings the mapping agent suggests from indexed
+ * identity data, with the evidence behind each score. Suggestions stay pending
+ * until someone accepts or rejects them on the delegate mapping page; only
+ * pending ones are rescored.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    CREATE TABLE IF NOT EXISTS public.mapping_identity_suggestion (
+      id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+      dao_id UUID NOT NULL REFERENCES public.dao(id) ON DELETE CASCADE,
+      voter_id UUID NOT NULL REFERENCES public.voter(id) ON DELETE CASCADE,
+      discourse_user_id UUID NOT NULL REFERENCES public.discourse_user(id) ON DELETE CASCADE,
+      score DOUBLE PRECISION NOT NULL,
+      evidence JSONB NOT NULL DEFAULT '[]'::jsonb,
+      status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'rejected')),
+      created_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+      updated_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+      UNIQUE (dao_id, voter_id, discourse_user_id)
+    )
+  `.execute(db);
+
+  await sql`
+    CREATE INDEX IF NOT EXISTS idx_mapping_identity_suggestion_review
+    ON public.mapping_identity_suggestion (dao_id, status, score DESC)
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`
+    DROP TABLE IF EXISTS public.mapping_identity_suggestion
+  `.execute(db);
+}
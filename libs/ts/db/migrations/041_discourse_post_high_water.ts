@@ -0,0 +1,21 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Newest post id the Discourse indexer has taken from a forum's latest posts
+ * feed. Recent updates tail the feed down to this id instead of re-crawling
+ * every bumped topic.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.dao_discourse
+      ADD COLUMN IF NOT EXISTS post_high_water_id INTEGER
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.dao_discourse
+      DROP COLUMN IF EXISTS post_high_water_id
+  `.execute(db);
+}
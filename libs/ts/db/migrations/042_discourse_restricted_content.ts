@@ -0,0 +1,41 @@
+import { type Kysely, sql } from 'kysely';
+import { type DB } from '../src';
+
+/**
+ * Marks Discourse content from read-restricted categories. Only forums indexed
+ * with API credentials can see such categories; their topics and posts inherit
+ * the flag so public pages and notifications can leave them out.
+ */
+export async function up(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.discourse_category
+      ADD COLUMN IF NOT EXISTS restricted BOOLEAN NOT NULL DEFAULT false
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.discourse_topic
+      ADD COLUMN IF NOT EXISTS restricted BOOLEAN NOT NULL DEFAULT false
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.discourse_post
+      ADD COLUMN IF NOT EXISTS restricted BOOLEAN NOT NULL DEFAULT false
+  `.execute(db);
+}
+
+export async function down(db: Kysely<DB>): Promise<void> {
+  await sql`
+    ALTER TABLE public.discourse_post
+      DROP COLUMN IF EXISTS restricted
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.discourse_topic
+      DROP COLUMN IF EXISTS restricted
+  `.execute(db);
+
+  await sql`
+    ALTER TABLE public.discourse_category
+      DROP COLUMN IF EXISTS restricted
+  `.execute(db);
+}
@@ -72,6 +72,8 @@ export interface DaoDiscourse {
   daoId: string;
   discourseBaseUrl: string;
   id: Generated<string>;
+  postHighWaterId: number | null;
+  verificationTopicId: number | null;
 }
 
 export interface DaoGovernor {
@@ -88,6 +90,8 @@ export interface Delegate {
 }
 
 export interface DelegateToDiscourseUser {
+  challenge: string | null;
+  challengeIssuedAt: Timestamp | null;
   createdAt: Generated<Timestamp>;
   delegateId: string;
   discourseUserId: string;
@@ -98,6 +102,17 @@ export interface DelegateToDiscourseUser {
   verified: Generated<boolean>;
 }
 
+export interface DelegateToDiscourseUserVerification {
+  challenge: string;
+  checkedAt: Generated<Timestamp>;
+  delegateToDiscourseUserId: string;
+  error: string | null;
+  evidence: Json | null;
+  id: Generated<string>;
+  method: string | null;
+  verified: boolean;
+}
+
 export interface DelegateToVoter {
   createdAt: Generated<Timestamp>;
   delegateId: string;
@@ -109,14 +124,56 @@ export interface DelegateToVoter {
   voterId: string;
 }
 
+export interface DelegateToVoterVerification {
+  checkedAt: Generated<Timestamp>;
+  delegateToVoterId: string;
+  error: string | null;
+  id: Generated<string>;
+  method: string | null;
+  proof: Json | null;
+  verified: boolean;
+}
+
+export interface DelegatedVotingPowerSnapshot {
+  block: number;
+  createdAt: Generated<Timestamp>;
+  daoId: string;
+  id: Generated<string>;
+  timestamp: Timestamp;
+  totalVotingPower: number;
+}
+
 export interface Delegation {
   block: number;
   daoId: string;
   delegate: string;
   delegator: string;
+  fromDelegate: string | null;
   id: Generated<string>;
+  logIndex: Generated<number>;
   timestamp: Generated<Timestamp>;
   txid: string | null;
+  weight: number | null;
+}
+
+export interface DelegationFlow {
+  amount: number;
+  block: number;
+  daoId: string;
+  delegator: string;
+  fromDelegate: string | null;
+  id: Generated<string>;
+  kind: string;
+  logIndex: number;
+  timestamp: Timestamp;
+  toDelegate: string | null;
+  txid: string | null;
+}
+
+export interface DelegationFlowSyncState {
+  daoId: string;
+  lastBlock: number;
+  updatedAt: Generated<Timestamp>;
 }
 
 export interface DiscourseCategory {
@@ -128,6 +185,7 @@ export interface DiscourseCategory {
   id: Generated<string>;
   name: string;
   postCount: number;
+  restricted: Generated<boolean>;
   slug: string;
   textColor: string;
   topicCount: number;
@@ -161,6 +219,7 @@ export interface DiscoursePost {
   reads: number;
   replyCount: number;
   replyToPostNumber: number | null;
+  restricted: Generated<boolean>;
   score: number;
   topicId: number;
   topicSlug: string;
@@ -211,6 +270,7 @@ export interface DiscourseTopic {
   pinnedGlobally: boolean;
   postsCount: number;
   replyCount: number;
+  restricted: Generated<boolean>;
   slug: string;
   title: string;
   views: number;
@@ -234,12 +294,91 @@ export interface DiscourseUser {
   username: string;
 }
 
+export interface FailedEvent {
+  attempts: Generated<number>;
+  blockNumber: Int8;
+  createdAt: Generated<Timestamp>;
+  error: string;
+  handler: string;
+  id: Generated<string>;
+  logIndex: number;
+  nextAttemptAt: Generated<Timestamp>;
+  payload: Json;
+  transactionHash: string;
+  updatedAt: Generated<Timestamp>;
+}
+
+export interface GovernanceAnomaly {
+  address: string;
+  amount: number;
+  block: number | null;
+  daoId: string;
+  dedupeKey: string;
+  details: Generated<Json>;
+  detectedAt: Generated<Timestamp>;
+  id: Generated<string>;
+  kind: string;
+  proposalId: string | null;
+}
+
+export interface GovernanceEvent {
+  createdAt: Generated<Timestamp>;
+  daoId: string;
+  eventType: string;
+  id: Generated<Int8>;
+  payload: Json;
+  xactId: Generated<Int8>;
+}
+
+export interface GovernanceEventConsumer {
+  eventId: Generated<Int8>;
+  name: string;
+  updatedAt: Generated<Timestamp>;
+  xactId: Generated<Int8>;
+}
+
+export interface GovernorBackfillHeal {
+  createdAt: Generated<Timestamp>;
+  details: Generated<Json>;
+  governorId: string;
+  healedCount: number;
+  id: Generated<string>;
+  kind: string;
+  proposalExternalId: string;
+}
+
+export interface GovernorBackfillState {
+  governorId: string;
+  lastScannedBlock: Int8;
+  updatedAt: Generated<Timestamp>;
+}
+
 export interface JobQueue {
+  attempts: Generated<number>;
   createdAt: Generated<Timestamp>;
   data: Json;
+  dedupeKey: string | null;
   id: Generated<number>;
+  lastError: string | null;
+  lockedBy: string | null;
+  lockedUntil: Timestamp | null;
+  maxAttempts: Generated<number>;
+  runAt: Generated<Timestamp>;
   status: Generated<string>;
   type: string;
+  updatedAt: Generated<Timestamp>;
+}
+
+export interface MappingIdentitySuggestion {
+  createdAt: Generated<Timestamp>;
+  daoId: string;
+  discourseUserId: string;
+  evidence: Generated<Json>;
+  id: Generated<string>;
+  score: number;
+  status: Generated<string>;
+  updatedAt: Generated<Timestamp>;
+  voterId: string;
 }
 
 export interface Proposal {
@@ -274,6 +413,46 @@ export interface ProposalGroup {
   name: string;
 }
 
+export interface ProposalRevision {
+  body: string;
+  choices: Json;
+  contentHash: string;
+  createdAt: Generated<Timestamp>;
+  discussionUrl: string | null;
+  endAt: Timestamp;
+  id: Generated<string>;
+  name: string;
+  proposalId: string;
+  revision: number;
+  startAt: Timestamp;
+}
+
+export interface ProposalStateHistory {
+  blockNumber: Int8 | null;
+  correction: Generated<boolean>;
+  createdAt: Generated<Timestamp>;
+  fromState: ProposalState | null;
+  id: Generated<string>;
+  proposalId: string;
+  source: string;
+  toState: ProposalState;
+  txid: string | null;
+}
+
+export interface ProposalTallyAudit {
+  corrected: Generated<boolean>;
+  field: string;
+  firstSeenAt: Generated<Timestamp>;
+  governorId: string;
+  id: Generated<string>;
+  lastSeenAt: Generated<Timestamp>;
+  onchainValue: string;
+  proposalId: string;
+  resolvedAt: Timestamp | null;
+  severity: string;
+  storedValue: string;
+}
+
 export interface RindexerInternalLatestBlock {
   block: Numeric | null;
   network: string;
@@ -339,6 +518,11 @@ export interface RindexerInternalRindexerArbTokenDelegateVotesChanged {
   network: string;
 }
 
+export interface RindexerInternalRindexerArbTokenTransfer {
+  lastSyncedBlock: Numeric | null;
+  network: string;
+}
+
 export interface RindexerInternalRindexerLastKnownIndexesDroppingSql {
   key: number;
   value: string;
@@ -379,6 +563,11 @@ export interface RindexerInternalRindexerUniTokenDelegateVotesChanged {
   network: string;
 }
 
+export interface RindexerInternalRindexerUniTokenTransfer {
+  lastSyncedBlock: Numeric | null;
+  network: string;
+}
+
 export interface Session {
   createdAt: Timestamp;
   expiresAt: Timestamp;
@@ -408,6 +597,28 @@ export interface SnapshotSyncState {
   updatedAt: Generated<Timestamp>;
 }
 
+export interface TokenBalance {
+  account: string;
+  balance: number;
+  block: number;
+  daoId: string;
+  id: Generated<string>;
+  updatedAt: Generated<Timestamp>;
+}
+
+export interface TokenTransfer {
+  amount: number;
+  block: number;
+  daoId: string;
+  flowSynced: Generated<boolean>;
+  fromAddress: string;
+  id: Generated<string>;
+  logIndex: number;
+  timestamp: Timestamp;
+  toAddress: string;
+  txid: string;
+}
+
 export interface User {
   createdAt: Timestamp;
   email: string;
@@ -465,15 +676,22 @@ export interface Vote {
 export interface Voter {
   address: string;
   avatar: string | null;
+  description: string | null;
+  discourse: string | null;
   ens: string | null;
+  ensRefreshedAt: Timestamp | null;
+  ensVerified: Generated<boolean>;
   id: Generated<string>;
+  twitter: string | null;
   updatedAt: Generated<Timestamp>;
+  url: string | null;
 }
 
 export interface VotingPowerLatest {
   block: number;
   daoId: string;
   id: Generated<string>;
+  logIndex: Generated<number>;
   timestamp: Timestamp;
   txid: string | null;
   voter: string;
@@ -484,12 +702,46 @@ export interface VotingPowerTimeseries {
   block: number;
   daoId: string;
   id: Generated<string>;
+  logIndex: Generated<number>;
+  previousVotingPower: number | null;
   timestamp: Generated<Timestamp>;
   txid: string | null;
   voter: string;
   votingPower: number;
 }
 
+export interface WebhookDelivery {
+  attempts: Generated<number>;
+  createdAt: Generated<Timestamp>;
+  deliveredAt: Timestamp | null;
+  eventKey: string;
+  eventType: string;
+  id: Generated<string>;
+  lastError: string | null;
+  lockedUntil: Timestamp | null;
+  nextAttemptAt: Generated<Timestamp>;
+  payload: Json;
+  responseStatus: number | null;
+  status: Generated<string>;
+  subscriptionId: string;
+  updatedAt: Generated<Timestamp>;
+}
+
+export interface WebhookSubscription {
+  consecutiveFailures: Generated<number>;
+  createdAt: Generated<Timestamp>;
+  daoId: string | null;
+  disabledAt: Timestamp | null;
+  enabled: Generated<boolean>;
+  eventTypes: Generated<Json>;
+  failingSince: Timestamp | null;
+  id: Generated<string>;
+  secret: string;
+  trackedVoters: Generated<Json>;
+  updatedAt: Generated<Timestamp>;
+  url: string;
+}
+
 export interface DB {
   account: Account;
   dao: Dao;
@@ -497,17 +749,32 @@ export interface DB {
   daoGovernor: DaoGovernor;
   delegate: Delegate;
   delegateToDiscourseUser: DelegateToDiscourseUser;
+  delegateToDiscourseUserVerification: DelegateToDiscourseUserVerification;
   delegateToVoter: DelegateToVoter;
+  delegateToVoterVerification: DelegateToVoterVerification;
+  delegatedVotingPowerSnapshot: DelegatedVotingPowerSnapshot;
   delegation: Delegation;
+  delegationFlow: DelegationFlow;
+  delegationFlowSyncState: DelegationFlowSyncState;
   discourseCategory: DiscourseCategory;
   discoursePost: DiscoursePost;
   discoursePostLike: DiscoursePostLike;
   discoursePostRevision: DiscoursePostRevision;
   discourseTopic: DiscourseTopic;
   discourseUser: DiscourseUser;
+  failedEvent: FailedEvent;
+  governanceAnomaly: GovernanceAnomaly;
+  governanceEvent: GovernanceEvent;
+  governanceEventConsumer: GovernanceEventConsumer;
+  governorBackfillHeal: GovernorBackfillHeal;
+  governorBackfillState: GovernorBackfillState;
   jobQueue: JobQueue;
+  mappingIdentitySuggestion: MappingIdentitySuggestion;
   proposal: Proposal;
   proposalGroup: ProposalGroup;
+  proposalRevision: ProposalRevision;
+  proposalStateHistory: ProposalStateHistory;
+  proposalTallyAudit: ProposalTallyAudit;
   'rindexerInternal.latestBlock': RindexerInternalLatestBlock;
   'rindexerInternal.rindexerArbitrumCoreGovernorProposalCreated': RindexerInternalRindexerArbitrumCoreGovernorProposalCreated;
   'rindexerInternal.rindexerArbitrumCoreGovernorProposalExecuted': RindexerInternalRindexerArbitrumCoreGovernorProposalExecuted;
@@ -521,6 +788,7 @@ export interface DB {
   'rindexerInternal.rindexerArbitrumTreasuryGovernorVoteCast': RindexerInternalRindexerArbitrumTreasuryGovernorVoteCast;
   'rindexerInternal.rindexerArbTokenDelegateChanged': RindexerInternalRindexerArbTokenDelegateChanged;
   'rindexerInternal.rindexerArbTokenDelegateVotesChanged': RindexerInternalRindexerArbTokenDelegateVotesChanged;
+  'rindexerInternal.rindexerArbTokenTransfer': RindexerInternalRindexerArbTokenTransfer;
   'rindexerInternal.rindexerLastKnownIndexesDroppingSql': RindexerInternalRindexerLastKnownIndexesDroppingSql;
   'rindexerInternal.rindexerLastKnownRelationshipDroppingSql': RindexerInternalRindexerLastKnownRelationshipDroppingSql;
   'rindexerInternal.rindexerLastRunMigrationsSql': RindexerInternalRindexerLastRunMigrationsSql;
@@ -529,9 +797,12 @@ export interface DB {
   'rindexerInternal.rindexerUniGovernorVoteCast': RindexerInternalRindexerUniGovernorVoteCast;
   'rindexerInternal.rindexerUniTokenDelegateChanged': RindexerInternalRindexerUniTokenDelegateChanged;
   'rindexerInternal.rindexerUniTokenDelegateVotesChanged': RindexerInternalRindexerUniTokenDelegateVotesChanged;
+  'rindexerInternal.rindexerUniTokenTransfer': RindexerInternalRindexerUniTokenTransfer;
   session: Session;
   snapshotMessage: SnapshotMessage;
   snapshotSyncState: SnapshotSyncState;
+  tokenBalance: TokenBalance;
+  tokenTransfer: TokenTransfer;
   user: User;
   userNotification: UserNotification;
   userProposalGroupLastRead: UserProposalGroupLastRead;
@@ -540,4 +811,6 @@ export interface DB {
   voter: Voter;
   votingPowerLatest: VotingPowerLatest;
   votingPowerTimeseries: VotingPowerTimeseries;
+  webhookDelivery: WebhookDelivery;
+  webhookSubscription: WebhookSubscription;
 }
@@ -29,8 +29,12 @@ export { default as DeleteAccountTemplate } from '../emails/delete-account';
 export { default as NewProposalEmailTemplate } from '../emails/new-proposal';
 export { default as NewDiscussionEmailTemplate } from '../emails/new-discussion';
 export { default as EndingProposalEmailTemplate } from '../emails/ending-proposal';
+export { default as VotingStartedEmailTemplate } from '../emails/voting-started';
+export { default as ProposalEndedEmailTemplate } from '../emails/proposal-ended';
 
 // Export email template prop types
 export type { NewProposalEmailProps } from '../emails/new-proposal';
 export type { NewDiscussionEmailProps } from '../emails/new-discussion';
 export type { EndingProposalEmailProps } from '../emails/ending-proposal';
+export type { VotingStartedEmailProps } from '../emails/voting-started';
+export type { ProposalEndedEmailProps } from '../emails/proposal-ended';